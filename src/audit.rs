@@ -0,0 +1,94 @@
+//! Append-only log of mutating xlaude operations, stored as JSONL next to the state file.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+const HISTORY_FILE: &str = "history.jsonl";
+
+static SOURCE: OnceLock<&'static str> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub action: String,
+    // Usually the "{repo_name}/{worktree_name}" key; empty for global operations
+    pub target: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub user: String,
+    // Which front-end performed the operation ("cli" or "dashboard")
+    pub source: String,
+    pub pid: u32,
+}
+
+/// Mark every entry recorded by this process as coming from `source`.
+pub fn set_source(source: &'static str) {
+    let _ = SOURCE.set(source);
+}
+
+pub fn history_path() -> Result<PathBuf> {
+    let state_path = crate::state::get_state_path()?;
+    Ok(state_path.with_file_name(HISTORY_FILE))
+}
+
+/// Append an entry to the audit log. Failures only emit a warning so that
+/// logging can never block the operation being logged.
+pub fn record(action: &str, target: &str, detail: Option<String>) {
+    let entry = AuditEntry {
+        timestamp: Utc::now(),
+        action: action.to_string(),
+        target: target.to_string(),
+        detail,
+        user: std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .unwrap_or_else(|_| "unknown".to_string()),
+        source: SOURCE.get().copied().unwrap_or("cli").to_string(),
+        pid: std::process::id(),
+    };
+
+    if let Err(err) = append(&entry) {
+        estatus!("⚠️  Failed to write audit log: {err:#}");
+    }
+}
+
+fn append(entry: &AuditEntry) -> Result<()> {
+    let path = history_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create data directory")?;
+    }
+    let mut line = serde_json::to_string(entry).context("Failed to serialize audit entry")?;
+    line.push('\n');
+
+    // A single O_APPEND write keeps concurrent writers from interleaving lines
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    file.write_all(line.as_bytes())
+        .context("Failed to append audit entry")?;
+    Ok(())
+}
+
+/// Read all entries in chronological order, skipping malformed lines.
+pub fn read_entries() -> Result<Vec<AuditEntry>> {
+    let path = history_path()?;
+    let file = match fs::File::open(&path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => {
+            return Err(err).with_context(|| format!("Failed to open {}", path.display()));
+        }
+    };
+
+    Ok(BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect())
+}
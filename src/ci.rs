@@ -0,0 +1,167 @@
+//! CI status of a worktree branch, read through the GitHub CLI.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::forge;
+use crate::tools::{self, Tool};
+use crate::utils::{RunPolicy, run_command};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckState {
+    Pass,
+    Fail,
+    Pending,
+    Skipping,
+    Cancel,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CiCheck {
+    pub name: String,
+    pub state: CheckState,
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CiStatus {
+    // "pr" when the checks of an open pull request were found, "runs" when
+    // falling back to the latest workflow runs of the branch
+    pub source: &'static str,
+    pub checks: Vec<CiCheck>,
+    // Failures win over pending checks, which win over passes
+    pub overall: Option<CheckState>,
+}
+
+impl CiStatus {
+    fn new(source: &'static str, checks: Vec<CiCheck>) -> Self {
+        let has = |state| checks.iter().any(|c: &CiCheck| c.state == state);
+        let overall = if checks.is_empty() {
+            None
+        } else if has(CheckState::Fail) {
+            Some(CheckState::Fail)
+        } else if has(CheckState::Pending) {
+            Some(CheckState::Pending)
+        } else if has(CheckState::Cancel) {
+            Some(CheckState::Cancel)
+        } else {
+            Some(CheckState::Pass)
+        };
+        Self {
+            source,
+            checks,
+            overall,
+        }
+    }
+
+    pub fn count(&self, state: CheckState) -> usize {
+        self.checks.iter().filter(|c| c.state == state).count()
+    }
+}
+
+#[derive(Deserialize)]
+struct PrCheck {
+    name: String,
+    bucket: CheckState,
+    #[serde(default)]
+    link: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WorkflowRun {
+    workflow_name: String,
+    status: String,
+    #[serde(default)]
+    conclusion: String,
+    #[serde(default)]
+    url: Option<String>,
+    head_sha: String,
+}
+
+/// Read the checks of the pull request for `branch`, or of the latest workflow
+/// runs on it when there is no pull request.
+pub fn fetch(path: &Path, branch: &str) -> Result<CiStatus> {
+    forge::require()?;
+    // `gh pr checks` exits non-zero while checks fail or are pending, so the
+    // output is parsed whenever there is some
+    let output = gh(
+        path,
+        &["pr", "checks", branch, "--json", "name,bucket,link"],
+    )?;
+    if let Ok(checks) = serde_json::from_slice::<Vec<PrCheck>>(&output.stdout) {
+        let checks = checks
+            .into_iter()
+            .map(|c| CiCheck {
+                name: c.name,
+                state: c.bucket,
+                url: c.link.filter(|l| !l.is_empty()),
+            })
+            .collect();
+        return Ok(CiStatus::new("pr", checks));
+    }
+
+    let output = gh(
+        path,
+        &[
+            "run",
+            "list",
+            "--branch",
+            branch,
+            "--limit",
+            "20",
+            "--json",
+            "workflowName,status,conclusion,url,headSha",
+        ],
+    )?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "gh run list failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let runs: Vec<WorkflowRun> =
+        serde_json::from_slice(&output.stdout).context("gh returned invalid JSON")?;
+
+    // Runs are newest first; keep the latest commit's run of each workflow
+    let head = runs.first().map(|r| r.head_sha.clone());
+    let mut checks: Vec<CiCheck> = Vec::new();
+    for run in runs
+        .into_iter()
+        .filter(|r| Some(&r.head_sha) == head.as_ref())
+    {
+        if checks.iter().any(|c| c.name == run.workflow_name) {
+            continue;
+        }
+        checks.push(CiCheck {
+            state: run_state(&run.status, &run.conclusion),
+            name: run.workflow_name,
+            url: run.url,
+        });
+    }
+    Ok(CiStatus::new("runs", checks))
+}
+
+fn run_state(status: &str, conclusion: &str) -> CheckState {
+    if status != "completed" {
+        return CheckState::Pending;
+    }
+    match conclusion {
+        "success" => CheckState::Pass,
+        "cancelled" => CheckState::Cancel,
+        "skipped" | "neutral" => CheckState::Skipping,
+        _ => CheckState::Fail,
+    }
+}
+
+fn gh(path: &Path, args: &[&str]) -> Result<std::process::Output> {
+    run_command(
+        tools::command(Tool::Gh).current_dir(path).args(args),
+        RunPolicy::network(),
+    )
+    .context("Failed to run gh; is the GitHub CLI installed?")
+}
@@ -1,32 +1,82 @@
 use chrono::{DateTime, Utc};
 use std::fs;
 use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 #[derive(Debug)]
 pub struct SessionInfo {
     pub last_user_message: String,
     pub last_timestamp: Option<DateTime<Utc>>,
+    pub user_message_count: usize,
+    // Sum of the `costUSD` fields Claude writes on assistant entries, when present
+    pub cost_usd: f64,
 }
 
-pub fn get_claude_sessions(project_path: &Path) -> Vec<SessionInfo> {
-    // Get home directory
-    let Ok(home) = std::env::var("HOME") else {
-        return vec![];
-    };
+/// Directory holding one session directory per project Claude was run in.
+pub fn projects_dir() -> Option<PathBuf> {
+    let home = std::env::var("HOME").ok()?;
+    Some(Path::new(&home).join(".claude").join("projects"))
+}
 
-    // Construct path to Claude projects directory
-    let claude_projects_dir = Path::new(&home).join(".claude").join("projects");
+/// Resolve the directory where Claude stores session logs for a project.
+fn project_sessions_dir(project_path: &Path) -> Option<PathBuf> {
+    let claude_projects_dir = projects_dir()?;
 
     // Get canonical path of the project
-    let Ok(canonical_path) = project_path.canonicalize() else {
-        return vec![];
-    };
+    let canonical_path = project_path.canonicalize().ok()?;
 
     // Convert path to Claude's format (replace / with -)
     let encoded_path = canonical_path.to_string_lossy().replace('/', "-");
 
-    let project_dir = claude_projects_dir.join(&encoded_path);
+    Some(claude_projects_dir.join(&encoded_path))
+}
+
+/// Return the most recent modification time of any Claude session log for the project.
+/// Only file metadata is read, so this is cheap enough for shell prompts.
+pub fn last_session_write(project_path: &Path) -> Option<SystemTime> {
+    let project_dir = project_sessions_dir(project_path)?;
+    fs::read_dir(project_dir)
+        .ok()?
+        .flatten()
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("jsonl"))
+        })
+        .filter_map(|entry| entry.metadata().ok()?.modified().ok())
+        .max()
+}
+
+/// The project a session directory belongs to, from the `cwd` Claude records
+/// on log entries. The directory name itself cannot be decoded reliably, as
+/// dashes in the original path are indistinguishable from separators.
+pub fn recorded_project_path(sessions_dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(sessions_dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("jsonl"))
+        })
+        .find_map(|path| {
+            let file = fs::File::open(path).ok()?;
+            BufReader::new(file)
+                .lines()
+                .map_while(Result::ok)
+                .find_map(|line| {
+                    let json = serde_json::from_str::<serde_json::Value>(&line).ok()?;
+                    json.get("cwd")?.as_str().map(PathBuf::from)
+                })
+        })
+}
+
+pub fn get_claude_sessions(project_path: &Path) -> Vec<SessionInfo> {
+    let Some(project_dir) = project_sessions_dir(project_path) else {
+        return vec![];
+    };
 
     // List session files (.jsonl files)
     let mut sessions = vec![];
@@ -40,15 +90,22 @@ pub fn get_claude_sessions(project_path: &Path) -> Vec<SessionInfo> {
                 // Read session data from the file
                 let mut last_user_message = String::new();
                 let mut last_timestamp = None;
+                let mut user_message_count = 0;
+                let mut cost_usd = 0.0;
 
                 if let Ok(file) = fs::File::open(entry.path()) {
                     let reader = BufReader::new(file);
                     let mut user_messages = Vec::new();
 
                     for line in reader.lines().map_while(Result::ok) {
-                        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&line)
-                            && json.get("type").and_then(|t| t.as_str()) == Some("user")
-                        {
+                        let Ok(json) = serde_json::from_str::<serde_json::Value>(&line) else {
+                            continue;
+                        };
+                        cost_usd += json
+                            .get("costUSD")
+                            .and_then(serde_json::Value::as_f64)
+                            .unwrap_or_default();
+                        if json.get("type").and_then(|t| t.as_str()) == Some("user") {
                             // Extract timestamp
                             if let Some(ts_str) = json.get("timestamp").and_then(|t| t.as_str())
                                 && let Ok(ts) = DateTime::parse_from_rfc3339(ts_str)
@@ -95,6 +152,7 @@ pub fn get_claude_sessions(project_path: &Path) -> Vec<SessionInfo> {
                     if let Some(msg) = user_messages.last() {
                         last_user_message.clone_from(msg);
                     }
+                    user_message_count = user_messages.len();
                 }
 
                 // Only add sessions with user messages
@@ -102,6 +160,8 @@ pub fn get_claude_sessions(project_path: &Path) -> Vec<SessionInfo> {
                     sessions.push(SessionInfo {
                         last_user_message,
                         last_timestamp,
+                        user_message_count,
+                        cost_usd,
                     });
                 }
             }
@@ -13,9 +13,10 @@ pub struct CodexSession {
     pub cwd: PathBuf,
     pub last_timestamp: Option<DateTime<Utc>>,
     pub last_user_message: Option<String>,
+    pub user_message_count: usize,
 }
 
-fn sessions_root() -> Option<PathBuf> {
+pub fn sessions_root() -> Option<PathBuf> {
     if let Ok(dir) = std::env::var("XLAUDE_CODEX_SESSIONS_DIR") {
         return Some(PathBuf::from(dir));
     }
@@ -127,6 +128,7 @@ fn parse_session_file(path: &Path) -> Result<Option<CodexSession>> {
 
     let mut last_user_message = None;
     let mut last_timestamp = start_timestamp;
+    let mut user_message_count = 0;
 
     for line in lines {
         let Ok(value) = serde_json::from_str::<Value>(&line) else {
@@ -169,6 +171,7 @@ fn parse_session_file(path: &Path) -> Result<Option<CodexSession>> {
             && !msg.trim().is_empty()
         {
             last_user_message = Some(msg);
+            user_message_count += 1;
         }
     }
 
@@ -177,6 +180,7 @@ fn parse_session_file(path: &Path) -> Result<Option<CodexSession>> {
         cwd,
         last_timestamp,
         last_user_message,
+        user_message_count,
     }))
 }
 
@@ -243,6 +247,18 @@ fn iterate_session_files(descending: bool) -> Result<Vec<PathBuf>> {
     Ok(result)
 }
 
+/// Every readable session file with the directory it was recorded in.
+/// Files that are not Codex sessions are skipped.
+pub fn all_session_files() -> Result<Vec<(PathBuf, PathBuf)>> {
+    Ok(iterate_session_files(false)?
+        .into_iter()
+        .filter_map(|file| {
+            let session = parse_session_file(&file).ok()??;
+            Some((file, session.cwd))
+        })
+        .collect())
+}
+
 fn matches_worktree(session_path: &Path, target_canonical: &Path, fallback: &Path) -> bool {
     session_path
         .canonicalize()
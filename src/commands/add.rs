@@ -3,6 +3,7 @@ use chrono::Utc;
 use colored::Colorize;
 use std::fs;
 
+use crate::audit;
 use crate::git::{get_current_branch, get_repo_name, is_in_worktree};
 use crate::state::{WorktreeInfo, XlaudeState};
 use crate::utils::sanitize_branch_name;
@@ -21,7 +22,7 @@ pub fn handle_add(name: Option<String>) -> Result<()> {
 
     // Use provided name or default to sanitized branch name
     let worktree_name = match name {
-        Some(n) => n,
+        Some(n) => sanitize_branch_name(&n),
         None => sanitize_branch_name(&current_branch),
     };
 
@@ -60,7 +61,7 @@ pub fn handle_add(name: Option<String>) -> Result<()> {
         );
     }
 
-    println!(
+    status!(
         "{} Adding worktree '{}' to xlaude management...",
         "➕".green(),
         worktree_name.cyan()
@@ -68,18 +69,20 @@ pub fn handle_add(name: Option<String>) -> Result<()> {
 
     // Add to state
     state.worktrees.insert(
-        key,
+        key.clone(),
         WorktreeInfo {
             name: worktree_name.clone(),
             branch: current_branch,
             path: current_dir.clone(),
             repo_name,
             created_at: Utc::now(),
+            ..Default::default()
         },
     );
     state.save()?;
+    audit::record("add", &key, Some(current_dir.display().to_string()));
 
-    println!(
+    status!(
         "{} Worktree '{}' added successfully",
         "✅".green(),
         worktree_name.cyan()
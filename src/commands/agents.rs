@@ -0,0 +1,73 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::time::{Duration, Instant};
+
+use crate::input::get_command_arg;
+use crate::sessions;
+use crate::state::XlaudeState;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub fn handle_kill(name: Option<String>) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let key = state.resolve_key(get_command_arg(name)?)?;
+    let info = state.worktrees.get(&key).context("Worktree not found")?;
+
+    let running = sessions::running_in(&info.path);
+    if running.is_empty() {
+        status!(
+            "{} No agent is running in '{}'",
+            "💡".cyan(),
+            info.name.cyan()
+        );
+        return Ok(());
+    }
+
+    for session in &running {
+        if sessions::terminate(session)? {
+            status!("{} Stopped {}", "✅".green(), session.describe());
+        } else {
+            status!(
+                "{} {} had already exited, dropped its record",
+                "💡".cyan(),
+                session.describe()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Block until every agent tracked in the worktree has exited.
+pub fn handle_wait(name: Option<String>, timeout: Option<u64>) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let key = state.resolve_key(get_command_arg(name)?)?;
+    let info = state.worktrees.get(&key).context("Worktree not found")?;
+
+    let running = sessions::running_in(&info.path);
+    if running.is_empty() {
+        status!(
+            "{} No agent is running in '{}'",
+            "💡".cyan(),
+            info.name.cyan()
+        );
+        return Ok(());
+    }
+    for session in &running {
+        status!("{} Waiting for {}", "⏳".yellow(), session.describe());
+    }
+
+    let deadline = timeout.map(|secs| Instant::now() + Duration::from_secs(secs));
+    while !sessions::running_in(&info.path).is_empty() {
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            anyhow::bail!(
+                "Timed out after {}s waiting for the agent in '{}'",
+                timeout.unwrap_or_default(),
+                info.name
+            );
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+
+    status!("{} Agent in '{}' finished", "✅".green(), info.name.cyan());
+    Ok(())
+}
@@ -0,0 +1,155 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::fs;
+use std::path::Path;
+
+use crate::audit;
+use crate::error::XlaudeError;
+use crate::git::{apply_patch, execute_git, git_output_bytes, resolve_base_ref};
+use crate::state::{WorktreeInfo, XlaudeState};
+
+pub fn handle_apply(source: String, destination: String) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let (source_key, source_info) = find_worktree(&state, &source)?;
+    let (destination_key, destination_info) = find_worktree(&state, &destination)?;
+    if source_key == destination_key {
+        anyhow::bail!("Source and destination are the same worktree");
+    }
+
+    let src = source_info.path.to_string_lossy().to_string();
+    let dst = destination_info.path.to_string_lossy().to_string();
+
+    if !execute_git(&["-C", &dst, "status", "--porcelain"])?.is_empty() {
+        return Err(XlaudeError::Refused(format!(
+            "Worktree '{}' has uncommitted changes. Commit or stash them before applying",
+            destination_info.name
+        ))
+        .into());
+    }
+
+    let base = resolve_base_ref(&source_info.path)
+        .context("Could not determine the base branch of the source worktree")?;
+    let merge_base = execute_git(&["-C", &src, "merge-base", &base, "HEAD"])?;
+    let commits = execute_git(&[
+        "-C",
+        &src,
+        "rev-list",
+        "--reverse",
+        "--no-merges",
+        &format!("{merge_base}..HEAD"),
+    ])?;
+    let commits: Vec<&str> = commits.lines().collect();
+
+    status!(
+        "{} Applying changes from '{}' to '{}' (relative to {})",
+        "🔀".cyan(),
+        source_info.name.cyan(),
+        destination_info.name.cyan(),
+        base
+    );
+
+    // Worktrees share one object database, so the commits can be picked directly
+    if !commits.is_empty() {
+        let mut args = vec!["-C", dst.as_str(), "cherry-pick"];
+        args.extend(&commits);
+        if execute_git(&args).is_err() {
+            report_conflicts(&dst);
+            return Err(XlaudeError::Vcs(format!(
+                "Cherry-pick stopped on conflicts in '{}'. Resolve them and run 'git cherry-pick --continue', or 'git cherry-pick --abort'",
+                destination_info.name
+            ))
+            .into());
+        }
+        status!(
+            "{} Cherry-picked {} commit{}",
+            "✅".green(),
+            commits.len(),
+            if commits.len() == 1 { "" } else { "s" }
+        );
+    }
+
+    let diff = git_output_bytes(&source_info.path, &["diff", "--binary", "HEAD"])?;
+    if !diff.is_empty() {
+        if apply_patch(&destination_info.path, &diff, &["--3way"]).is_err() {
+            report_conflicts(&dst);
+            return Err(XlaudeError::Vcs(format!(
+                "Uncommitted changes did not apply cleanly to '{}'. Resolve the conflicts in the files above",
+                destination_info.name
+            ))
+            .into());
+        }
+        // --3way stages what it applies; leave the changes uncommitted and unstaged
+        execute_git(&["-C", &dst, "reset", "--quiet"])?;
+        status!("{} Applied uncommitted changes", "✅".green());
+    }
+
+    let skipped = copy_untracked(&source_info.path, &destination_info.path)?;
+    if !skipped.is_empty() {
+        status!(
+            "{} Untracked files already present in '{}' were left alone:",
+            "⚠️".yellow(),
+            destination_info.name
+        );
+        for file in &skipped {
+            println!("    {file}");
+        }
+    }
+
+    audit::record(
+        "apply",
+        &destination_key,
+        Some(format!("from {source_key} ({} commits)", commits.len())),
+    );
+    status!(
+        "{} Changes from '{}' applied to '{}'",
+        "✅".green(),
+        source_info.name.cyan(),
+        destination_info.name.cyan()
+    );
+    Ok(())
+}
+
+fn find_worktree(state: &XlaudeState, name: &str) -> Result<(String, WorktreeInfo)> {
+    state
+        .find_by_name(name)
+        .map(|(k, w)| (k.clone(), w.clone()))
+        .ok_or_else(|| XlaudeError::NotFound(format!("Worktree '{name}' not found")).into())
+}
+
+fn report_conflicts(dir: &str) {
+    let conflicted =
+        execute_git(&["-C", dir, "diff", "--name-only", "--diff-filter=U"]).unwrap_or_default();
+    if conflicted.is_empty() {
+        return;
+    }
+    status!("{} Conflicts in:", "⚠️".yellow());
+    for file in conflicted.lines() {
+        println!("    {}", file.red());
+    }
+}
+
+/// Copy untracked files that do not exist in the destination. Returns the
+/// ones that were skipped because they do.
+fn copy_untracked(source: &Path, destination: &Path) -> Result<Vec<String>> {
+    let untracked = git_output_bytes(
+        source,
+        &["ls-files", "--others", "--exclude-standard", "-z"],
+    )?;
+    let mut skipped = Vec::new();
+
+    for file in untracked.split(|b| *b == 0).filter(|f| !f.is_empty()) {
+        let relative = std::str::from_utf8(file).context("Non UTF-8 file name")?;
+        let target = destination.join(relative);
+        if target.exists() {
+            skipped.push(relative.to_string());
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        fs::copy(source.join(relative), &target)
+            .with_context(|| format!("Failed to copy {relative}"))?;
+    }
+    Ok(skipped)
+}
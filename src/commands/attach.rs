@@ -0,0 +1,63 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::error::XlaudeError;
+use crate::input::{get_command_arg, smart_select};
+use crate::state::{WorktreeInfo, XlaudeState};
+use crate::tmux::TmuxManager;
+
+pub fn handle_attach(name: Option<String>) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let tmux = TmuxManager::new()?;
+
+    let session_of = |info: &WorktreeInfo| TmuxManager::session_name(&info.repo_name, &info.name);
+
+    let worktree_info = if let Some(n) = get_command_arg(name)? {
+        // Find worktree by name across all projects
+        state
+            .find_by_name(&n)
+            .map(|(_, w)| w.clone())
+            .ok_or_else(|| XlaudeError::NotFound(format!("Worktree '{n}' not found")))?
+    } else {
+        // Only offer worktrees that have a running session
+        let mut running: Vec<WorktreeInfo> = state
+            .worktrees
+            .values()
+            .filter(|info| tmux.has_session(&session_of(info)))
+            .cloned()
+            .collect();
+        if running.is_empty() {
+            anyhow::bail!(
+                "No agent sessions are running. Start one with 'xlaude open --new-window'"
+            );
+        }
+        running.sort_by(|a, b| (&a.repo_name, &a.name).cmp(&(&b.repo_name, &b.name)));
+
+        let selection = smart_select("Select a session to attach", &running, |info| {
+            format!("{}/{}", info.repo_name, info.name)
+        })?;
+        match selection {
+            Some(idx) => running[idx].clone(),
+            None => anyhow::bail!(
+                "Interactive selection not available in non-interactive mode. Please specify a worktree name."
+            ),
+        }
+    };
+
+    let session = session_of(&worktree_info);
+    if !tmux.has_session(&session) {
+        anyhow::bail!(
+            "No tmux session for '{}'. Start one with 'xlaude open {} --new-window'",
+            worktree_info.name,
+            worktree_info.name
+        );
+    }
+
+    status!(
+        "{} Attaching to '{}/{}'...",
+        "🔗".green(),
+        worktree_info.repo_name,
+        worktree_info.name.cyan()
+    );
+    tmux.attach_session(&session)
+}
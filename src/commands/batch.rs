@@ -0,0 +1,199 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::error::XlaudeError;
+use crate::git::{execute_git, get_repo_name};
+use crate::state::XlaudeState;
+use crate::utils::{
+    execute_in_dir, generate_random_name, name_from_prompt, sanitize_branch_name, unique_name,
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BatchTask {
+    // Repository path, or the name of a repository that already has managed worktrees
+    repo: Option<String>,
+    name: Option<String>,
+    prompt: Option<String>,
+    agent: Option<String>,
+    #[serde(default = "default_open")]
+    open: bool,
+}
+
+fn default_open() -> bool {
+    true
+}
+
+#[derive(Debug, Default, Serialize)]
+struct BatchResult {
+    line: usize,
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    repo: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<PathBuf>,
+    opened: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Read newline-delimited JSON tasks from stdin and create or open a worktree
+/// for each, printing one JSON result per task.
+pub fn handle_batch(new_window: bool) -> Result<()> {
+    let exe = std::env::current_exe().context("Failed to locate xlaude binary")?;
+    let (mut total, mut failed) = (0usize, 0usize);
+
+    for (index, line) in std::io::stdin().lock().lines().enumerate() {
+        let line = line.context("Failed to read tasks from stdin")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        total += 1;
+
+        let mut result = BatchResult {
+            line: index + 1,
+            ..BatchResult::default()
+        };
+        let outcome = serde_json::from_str::<BatchTask>(&line)
+            .context("Invalid task")
+            .and_then(|task| run_task(&exe, task, new_window, &mut result));
+        if let Err(err) = outcome {
+            failed += 1;
+            result.status = "failed";
+            result.error = Some(format!("{err:#}"));
+        }
+        println!("{}", serde_json::to_string(&result)?);
+    }
+
+    if failed > 0 {
+        anyhow::bail!("{failed} of {total} batch tasks failed");
+    }
+    Ok(())
+}
+
+fn run_task(exe: &Path, task: BatchTask, new_window: bool, result: &mut BatchResult) -> Result<()> {
+    let repo_dir = resolve_repo(task.repo.as_deref())?;
+    let repo_name = execute_in_dir(&repo_dir, get_repo_name)?;
+    let name = match task.name {
+        Some(name) => sanitize_branch_name(&name),
+        None => {
+            let state = XlaudeState::load()?;
+            let is_taken = |name: &str| {
+                state
+                    .worktrees
+                    .contains_key(&XlaudeState::make_key(&repo_name, name))
+                    || execute_git(&[
+                        "-C",
+                        &repo_dir.to_string_lossy(),
+                        "show-ref",
+                        "--verify",
+                        "--quiet",
+                        &format!("refs/heads/{name}"),
+                    ])
+                    .is_ok()
+            };
+            // Tasks are easier to tell apart by what they were asked to do
+            match task
+                .prompt
+                .as_deref()
+                .and_then(|prompt| name_from_prompt(&state.names, prompt))
+            {
+                Some(slug) => unique_name(&slug, is_taken),
+                None => sanitize_branch_name(&generate_random_name(&state.names, is_taken)?),
+            }
+        }
+    };
+    result.repo = Some(repo_name.clone());
+    result.name = Some(name.clone());
+
+    let key = XlaudeState::make_key(&repo_name, &name);
+    if XlaudeState::load()?.worktrees.contains_key(&key) {
+        result.status = "exists";
+    } else {
+        run_xlaude(exe, &repo_dir, &["create", &name])?;
+        result.status = "created";
+    }
+    result.path = XlaudeState::load()?
+        .worktrees
+        .get(&key)
+        .map(|info| info.path.clone());
+
+    if !task.open {
+        return Ok(());
+    }
+
+    let mut args = vec!["open", name.as_str()];
+    if let Some(agent) = task.agent.as_deref() {
+        args.extend(["--agent", agent]);
+    }
+    if let Some(prompt) = task.prompt.as_deref() {
+        args.extend(["--type-text", prompt]);
+    }
+    if new_window {
+        args.push("--new-window");
+        run_xlaude(exe, &repo_dir, &args)?;
+    } else {
+        // Agents run in the background, like those launched from the dashboard
+        xlaude_command(exe, &repo_dir, &args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("Failed to launch agent")?;
+    }
+    result.opened = true;
+    Ok(())
+}
+
+/// A repo is either a directory, or the name of a repository registered with
+/// `xlaude clone` or whose worktrees are managed (its checkout lives next to them).
+fn resolve_repo(repo: Option<&str>) -> Result<PathBuf> {
+    let Some(repo) = repo else {
+        return std::env::current_dir().context("Failed to get current directory");
+    };
+
+    let path = Path::new(repo);
+    if path.is_dir() {
+        return Ok(path.to_path_buf());
+    }
+
+    let state = XlaudeState::load()?;
+    if let Some(path) = state.repos.get(repo).and_then(|r| r.path.clone())
+        && path.is_dir()
+    {
+        return Ok(path);
+    }
+    state
+        .worktrees
+        .values()
+        .filter(|info| info.repo_name == repo)
+        .map(|info| info.main_repo_path())
+        .find(|candidate| candidate.is_dir())
+        .ok_or_else(|| XlaudeError::NotFound(format!("Unknown repository '{repo}'")).into())
+}
+
+fn xlaude_command(exe: &Path, dir: &Path, args: &[&str]) -> Command {
+    let mut cmd = Command::new(exe);
+    cmd.args(args)
+        .current_dir(dir)
+        .env("XLAUDE_NON_INTERACTIVE", "1")
+        .env("XLAUDE_NO_AUTO_OPEN", "1")
+        .stdin(Stdio::null());
+    cmd
+}
+
+fn run_xlaude(exe: &Path, dir: &Path, args: &[&str]) -> Result<()> {
+    let output = xlaude_command(exe, dir, args)
+        .output()
+        .with_context(|| format!("Failed to run xlaude {}", args[0]))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = stderr.trim().trim_start_matches("Error: ");
+        anyhow::bail!("xlaude {} failed: {message}", args[0]);
+    }
+    Ok(())
+}
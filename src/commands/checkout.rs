@@ -4,18 +4,27 @@ use std::path::{Path, PathBuf};
 use anyhow::{Context, Result, bail};
 use chrono::Utc;
 use colored::Colorize;
-
-use crate::commands::open::handle_open;
-use crate::git::{execute_git, get_repo_name, update_submodules};
-use crate::input::{get_command_arg, smart_confirm};
+use serde::Deserialize;
+
+use crate::audit;
+use crate::commands::open::{OpenOptions, handle_open};
+use crate::error::XlaudeError;
+use crate::forge;
+use crate::git::{execute_git, fork_point, get_repo_name, resolve_base_ref, update_submodules};
+use crate::input::{get_command_arg, smart_confirm, smart_fuzzy_select};
+use crate::output::{self, Elapsed};
 use crate::state::{WorktreeInfo, XlaudeState};
-use crate::utils::sanitize_branch_name;
+use crate::tools::{self, Tool};
+use crate::utils::{RunPolicy, ensure_online, run_command, sanitize_branch_name};
 
 pub fn handle_checkout(target: Option<String>) -> Result<()> {
-    let raw_target = get_command_arg(target)?
+    let raw_target = match get_command_arg(target)?
         .map(|s| s.trim().to_string())
         .filter(|s| !s.is_empty())
-        .context("Please provide a branch name or pull request number")?;
+    {
+        Some(target) => target,
+        None => pick_pull_request()?.to_string(),
+    };
 
     let checkout_target = CheckoutTarget::parse(&raw_target)?;
     let repo_root_str = execute_git(&["rev-parse", "--show-toplevel"])?
@@ -28,13 +37,13 @@ pub fn handle_checkout(target: Option<String>) -> Result<()> {
     let worktree_name = sanitize_branch_name(&branch_name);
 
     if let Some(existing) = find_existing_worktree(&repo_name, &branch_name)? {
-        println!(
+        status!(
             "{} Worktree for {} already exists at {}",
             "⚠️".yellow(),
             checkout_target.describe().cyan(),
             existing.path.display()
         );
-        println!(
+        status!(
             "  {} To open it manually, run: {} {}",
             "💡".cyan(),
             "xlaude open".cyan(),
@@ -47,7 +56,7 @@ pub fn handle_checkout(target: Option<String>) -> Result<()> {
         )?;
 
         if should_open {
-            handle_open(Some(existing.name.clone()))?;
+            handle_open(Some(existing.name.clone()), OpenOptions::default())?;
             return Ok(());
         }
 
@@ -60,21 +69,22 @@ pub fn handle_checkout(target: Option<String>) -> Result<()> {
 
     ensure_branch_ready(&checkout_target, &branch_name)?;
 
-    println!(
+    status!(
         "{} Checking out {} into worktree '{}'...",
         "✨".green(),
         checkout_target.describe().cyan(),
         worktree_name.cyan()
     );
 
-    let created_path = create_worktree(&repo_root, &repo_name, &branch_name, &worktree_name)?;
+    let (created_path, created_in) =
+        create_worktree(&repo_root, &repo_name, &branch_name, &worktree_name)?;
 
-    println!(
-        "{} Worktree created at: {}",
+    status!(
+        "{} Worktree created at: {}{created_in}",
         "✅".green(),
         created_path.display()
     );
-    println!(
+    status!(
         "  {} To open it later, run: {} {}",
         "💡".cyan(),
         "xlaude open".cyan(),
@@ -84,6 +94,104 @@ pub fn handle_checkout(target: Option<String>) -> Result<()> {
     Ok(())
 }
 
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OpenPullRequest {
+    number: u64,
+    title: String,
+    #[serde(default)]
+    head_ref_name: String,
+    #[serde(default)]
+    author: PullRequestAuthor,
+}
+
+#[derive(Clone, Default, Deserialize)]
+struct PullRequestAuthor {
+    #[serde(default)]
+    login: String,
+}
+
+/// Let the user pick one of the repository's open pull requests.
+fn pick_pull_request() -> Result<u64> {
+    if std::env::var("XLAUDE_NON_INTERACTIVE").is_ok() {
+        bail!("Please provide a branch name or pull request number");
+    }
+    forge::require().context("Pass a branch name or pull request number instead")?;
+
+    let output = run_command(
+        tools::command(Tool::Gh).args([
+            "pr",
+            "list",
+            "--state",
+            "open",
+            "--limit",
+            "100",
+            "--json",
+            "number,title,headRefName,author",
+        ]),
+        RunPolicy::network(),
+    )
+    .context("Failed to run gh; pass a branch name or pull request number instead")?;
+    if !output.status.success() {
+        bail!(
+            "Failed to list pull requests: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let prs: Vec<OpenPullRequest> =
+        serde_json::from_slice(&output.stdout).context("gh returned invalid JSON")?;
+    if prs.is_empty() {
+        bail!("No open pull requests, pass a branch name instead");
+    }
+
+    let selection = smart_fuzzy_select("Check out a pull request", &prs, |pr| {
+        format!(
+            "#{} {} (@{}, {})",
+            pr.number, pr.title, pr.author.login, pr.head_ref_name
+        )
+    })?;
+    match selection {
+        Some(index) => Ok(prs[index].number),
+        None => Err(XlaudeError::Cancelled.into()),
+    }
+}
+
+/// Check out a pull request into its own worktree, reusing the worktree when
+/// one already exists. Returns the worktree name.
+pub fn checkout_pull_request(pr_number: u64) -> Result<String> {
+    let target = CheckoutTarget::PullRequest(pr_number);
+    let repo_root = PathBuf::from(execute_git(&["rev-parse", "--show-toplevel"])?.trim());
+    let repo_name = get_repo_name().context("Not in a git repository")?;
+    let branch_name = target.branch_name();
+
+    if let Some(existing) = find_existing_worktree(&repo_name, &branch_name)? {
+        status!(
+            "{} Reusing worktree '{}' for {}",
+            "♻️".cyan(),
+            existing.name.cyan(),
+            target.describe()
+        );
+        return Ok(existing.name.clone());
+    }
+
+    ensure_branch_ready(&target, &branch_name)?;
+    let worktree_name = sanitize_branch_name(&branch_name);
+    status!(
+        "{} Checking out {} into worktree '{}'...",
+        "✨".green(),
+        target.describe().cyan(),
+        worktree_name.cyan()
+    );
+    let (created_path, created_in) =
+        create_worktree(&repo_root, &repo_name, &branch_name, &worktree_name)?;
+    status!(
+        "{} Worktree created at: {}{created_in}",
+        "✅".green(),
+        created_path.display()
+    );
+    Ok(worktree_name)
+}
+
 fn find_existing_worktree(repo_name: &str, branch_name: &str) -> Result<Option<ExistingWorktree>> {
     let state = XlaudeState::load()?;
     Ok(state
@@ -105,8 +213,9 @@ fn ensure_branch_available(branch_name: &str) -> Result<()> {
     if branch_exists(branch_name) {
         return Ok(());
     }
+    ensure_online(&format!("Fetching branch '{branch_name}'"))?;
 
-    println!(
+    status!(
         "{} Branch '{}' not found locally. Attempting to fetch from origin...",
         "🌐".blue(),
         branch_name.cyan()
@@ -114,6 +223,7 @@ fn ensure_branch_available(branch_name: &str) -> Result<()> {
 
     ensure_origin_remote()?;
     let fetch_spec = format!("{branch_name}:{branch_name}");
+    let _step = output::step(format!("Fetching {branch_name}"));
     execute_git(&["fetch", "origin", &fetch_spec])
         .with_context(|| format!("Failed to fetch branch '{branch_name}' from origin"))?;
 
@@ -126,13 +236,14 @@ fn ensure_branch_available(branch_name: &str) -> Result<()> {
 
 fn fetch_pull_request(pr_number: u64, branch_name: &str) -> Result<()> {
     ensure_origin_remote()?;
-    println!(
+    status!(
         "{} Fetching pull request #{} from origin...",
         "🌐".blue(),
         pr_number
     );
 
     let fetch_ref = format!("pull/{pr_number}/head:refs/heads/{branch_name}");
+    let _step = output::step(format!("Fetching pull request #{pr_number}"));
     execute_git(&["fetch", "origin", &fetch_ref])
         .with_context(|| format!("Failed to fetch pull request #{pr_number} from origin"))?;
 
@@ -154,7 +265,7 @@ fn create_worktree(
     repo_name: &str,
     branch_name: &str,
     worktree_name: &str,
-) -> Result<PathBuf> {
+) -> Result<(PathBuf, Elapsed)> {
     let repo_root_str = repo_root
         .to_str()
         .context("Repository path contains invalid UTF-8")?;
@@ -193,6 +304,7 @@ fn create_worktree(
         .to_str()
         .context("Worktree path contains invalid UTF-8")?;
 
+    let step = output::step(format!("Creating worktree '{worktree_name}'"));
     execute_git(&[
         "-C",
         repo_root_str,
@@ -202,9 +314,13 @@ fn create_worktree(
         branch_name,
     ])
     .context("Failed to create worktree")?;
+    let created_in = step.finish();
 
-    if let Err(e) = update_submodules(&worktree_path) {
-        println!(
+    let step = output::step("Updating submodules");
+    let updated = update_submodules(&worktree_path);
+    let submodules_in = step.finish();
+    if let Err(e) = updated {
+        status!(
             "{} Warning: Failed to update submodules: {}",
             "⚠️".yellow(),
             e
@@ -212,7 +328,7 @@ fn create_worktree(
     } else {
         let gitmodules = worktree_path.join(".gitmodules");
         if gitmodules.exists() {
-            println!("{} Updated submodules", "📦".green());
+            status!("{} Updated submodules{submodules_in}", "📦".green());
         }
     }
 
@@ -220,22 +336,36 @@ fn create_worktree(
     if claude_local.exists() {
         let target = worktree_path.join("CLAUDE.local.md");
         fs::copy(&claude_local, &target).context("Failed to copy CLAUDE.local.md")?;
-        println!("{} Copied CLAUDE.local.md to worktree", "📄".green());
+        status!("{} Copied CLAUDE.local.md to worktree", "📄".green());
     }
 
+    let base_branch = resolve_base_ref(&worktree_path);
     state.worktrees.insert(
-        key,
+        key.clone(),
         WorktreeInfo {
             name: worktree_name.to_string(),
             branch: branch_name.to_string(),
             path: worktree_path.clone(),
             repo_name: repo_name.to_string(),
             created_at: Utc::now(),
+            base_commit: base_branch
+                .as_deref()
+                .and_then(|base| fork_point(&worktree_path, branch_name, base)),
+            base_branch,
+            ..Default::default()
         },
     );
     state.save()?;
+    audit::record(
+        "checkout",
+        &key,
+        Some(format!(
+            "branch {branch_name} at {}",
+            worktree_path.display()
+        )),
+    );
 
-    Ok(worktree_path)
+    Ok((worktree_path, created_in))
 }
 
 fn list_worktrees_for_repo(repo_root: &Path) -> Result<Vec<PathBuf>> {
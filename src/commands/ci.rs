@@ -0,0 +1,83 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::time::Duration;
+
+use crate::ci::{self, CheckState, CiStatus};
+use crate::input::get_command_arg;
+use crate::state::XlaudeState;
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(10);
+
+pub fn handle_ci(name: Option<String>, watch: bool) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let key = state.resolve_key(get_command_arg(name)?)?;
+    let info = &state.worktrees[&key];
+
+    let status = loop {
+        let status = ci::fetch(&info.path, &info.branch)?;
+        let pending = status.count(CheckState::Pending);
+        if !watch || pending == 0 {
+            break status;
+        }
+        status!(
+            "{} {} of {} check{} pending...",
+            "⏳".yellow(),
+            pending,
+            status.checks.len(),
+            if status.checks.len() == 1 { "" } else { "s" }
+        );
+        std::thread::sleep(WATCH_INTERVAL);
+    };
+
+    print_status(&info.branch, &status);
+
+    if status.overall == Some(CheckState::Fail) {
+        let failed = status.count(CheckState::Fail);
+        anyhow::bail!(
+            "{failed} check{} failed on '{}'",
+            if failed == 1 { "" } else { "s" },
+            info.branch
+        );
+    }
+    Ok(())
+}
+
+fn print_status(branch: &str, status: &CiStatus) {
+    if status.checks.is_empty() {
+        status!(
+            "{} No CI checks found for '{}'",
+            "📭".yellow(),
+            branch.cyan()
+        );
+        return;
+    }
+
+    let source = if status.source == "pr" {
+        "pull request checks"
+    } else {
+        "latest workflow runs"
+    };
+    status!("{} CI for '{}' ({source})", "🔎".cyan(), branch.cyan());
+
+    let width = status
+        .checks
+        .iter()
+        .map(|c| c.name.chars().count())
+        .max()
+        .unwrap_or(0);
+    for check in &status.checks {
+        let state = match check.state {
+            CheckState::Pass => "✓ pass".green(),
+            CheckState::Fail => "✗ fail".red(),
+            CheckState::Pending => "… pending".yellow(),
+            CheckState::Skipping => "- skipped".bright_black(),
+            CheckState::Cancel => "✗ cancelled".bright_black(),
+        };
+        println!(
+            "  {:<11} {:<width$}  {}",
+            state,
+            check.name,
+            check.url.as_deref().unwrap_or_default().bright_black()
+        );
+    }
+}
@@ -1,24 +1,33 @@
 use anyhow::Result;
 use colored::Colorize;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::path::PathBuf;
 
-use crate::git::list_worktrees;
-use crate::state::XlaudeState;
+use crate::audit;
+use crate::git::{self, HeadState, list_worktrees};
+use crate::input::smart_select;
+use crate::output;
+use crate::state::{WorktreeInfo, XlaudeState};
 use crate::utils::execute_in_dir;
 
-pub fn handle_clean() -> Result<()> {
+pub fn handle_clean(repo: Option<String>) -> Result<()> {
     let mut state = XlaudeState::load()?;
 
     if state.worktrees.is_empty() {
-        println!("{} No worktrees in state", "✨".green());
+        status!("{} No worktrees in state", "✨".green());
         return Ok(());
     }
+    if let Some(repo) = &repo {
+        state.ensure_repo_known(repo)?;
+    }
+    let scope = Scope(repo.as_deref());
 
-    println!("{} Checking for invalid worktrees...", "🔍".cyan());
+    status!("{} Checking for invalid worktrees...", "🔍".cyan());
 
     // Collect all actual worktrees from all repositories
-    let actual_worktrees = collect_all_worktrees(&state)?;
+    let step = output::step("Scanning repositories");
+    let actual_worktrees = collect_all_worktrees(&state, scope)?;
+    drop(step);
 
     // Find and remove invalid worktrees
     let mut removed_count = 0;
@@ -26,8 +35,8 @@ pub fn handle_clean() -> Result<()> {
         .worktrees
         .iter()
         .filter_map(|(name, info)| {
-            if !actual_worktrees.contains(&info.path) {
-                println!(
+            if scope.contains(info) && !actual_worktrees.contains(&info.path) {
+                status!(
                     "  {} Found invalid worktree: {} ({})",
                     "❌".red(),
                     name.yellow(),
@@ -41,34 +50,267 @@ pub fn handle_clean() -> Result<()> {
         })
         .collect();
 
+    if repo.is_none() {
+        print_repo_summary(&state, &worktrees_to_remove);
+    }
+
     // Remove invalid worktrees from state
-    for name in worktrees_to_remove {
-        state.worktrees.remove(&name);
+    for name in &worktrees_to_remove {
+        state.worktrees.remove(name);
     }
 
     if removed_count > 0 {
         state.save()?;
-        println!(
+        for name in &worktrees_to_remove {
+            audit::record("clean", name, Some("worktree no longer exists".to_string()));
+        }
+        status!(
             "{} Removed {} invalid worktree{}",
             "✅".green(),
             removed_count,
             if removed_count == 1 { "" } else { "s" }
         );
     } else {
-        println!("{} All worktrees are valid", "✨".green());
+        status!("{} All worktrees are valid", "✨".green());
+    }
+
+    repair_branches(&mut state, scope)?;
+    resolve_duplicates(&mut state, scope)
+}
+
+/// Repository given to `--repo`, or every repository.
+#[derive(Clone, Copy)]
+struct Scope<'a>(Option<&'a str>);
+
+impl Scope<'_> {
+    fn contains(&self, info: &WorktreeInfo) -> bool {
+        self.0.is_none_or(|repo| info.repo_name == repo)
+    }
+}
+
+/// Worktrees checked and removed per repository, when there are several.
+fn print_repo_summary(state: &XlaudeState, removed: &[String]) {
+    let counts = state.repo_counts();
+    if counts.len() < 2 {
+        return;
     }
+    for (repo, count) in counts {
+        let removed = removed
+            .iter()
+            .filter(|key| state.worktrees[*key].repo_name == repo)
+            .count();
+        status!(
+            "  {} {}: {} checked, {} invalid",
+            "📦".blue(),
+            repo.bold(),
+            count,
+            removed
+        );
+    }
+}
 
+/// Point records at the branch their worktree actually has checked out, and
+/// report detached worktrees, which have no branch to record.
+fn repair_branches(state: &mut XlaudeState, scope: Scope) -> Result<()> {
+    let mut repaired = Vec::new();
+    for (key, info) in state.worktrees.iter_mut() {
+        if !scope.contains(info) || !info.path.exists() {
+            continue;
+        }
+        match git::checked_out_branch(&info.path) {
+            Some(branch) if branch != info.branch => {
+                status!(
+                    "  {} Updated branch of {}: {} → {}",
+                    "🔧".yellow(),
+                    key.cyan(),
+                    info.branch,
+                    branch.green()
+                );
+                repaired.push((key.clone(), format!("branch {} -> {branch}", info.branch)));
+                info.branch = branch;
+            }
+            Some(_) => {}
+            None => {
+                if git::head_state(&info.path, &info.branch) == Some(HeadState::Detached) {
+                    status!(
+                        "  {} {} is on a detached HEAD, check out a branch in {}",
+                        "⚠️".yellow(),
+                        key.cyan(),
+                        info.path.display()
+                    );
+                }
+            }
+        }
+    }
+
+    if !repaired.is_empty() {
+        state.save()?;
+        for (key, detail) in repaired {
+            audit::record("repair", &key, Some(detail));
+        }
+    }
     Ok(())
 }
 
-fn collect_all_worktrees(state: &XlaudeState) -> Result<HashSet<PathBuf>> {
+/// Entries sharing a directory (e.g. `add` and the auto-add of `open` under
+/// different names) or a branch of the same repository. Asks which one to
+/// keep and merges the others into it.
+fn resolve_duplicates(state: &mut XlaudeState, scope: Scope) -> Result<()> {
+    let groups = find_duplicates(state, scope);
+    if groups.is_empty() {
+        return Ok(());
+    }
+
+    let mut merged = Vec::new();
+    let mut unresolved = 0;
+    for (reason, mut keys) in groups {
+        // A group may already be resolved by merging an overlapping one
+        keys.retain(|key| state.worktrees.contains_key(key));
+        if keys.len() < 2 {
+            continue;
+        }
+        // The oldest entry is most likely the original
+        keys.sort_by_key(|key| state.worktrees[key].created_at);
+        status!(
+            "  {} {} {}",
+            "⚠️".yellow(),
+            keys.iter()
+                .map(|key| key.cyan().to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            reason
+        );
+
+        let mut choices: Vec<Option<String>> = keys.iter().cloned().map(Some).collect();
+        choices.push(None);
+        let selection = smart_select(
+            "Which entry should be kept?",
+            &choices,
+            |choice| match choice {
+                Some(key) => format!("Keep {key} and merge the others into it"),
+                None => "Leave as is".to_string(),
+            },
+        )?;
+        let Some(keep) = selection.and_then(|index| choices[index].clone()) else {
+            unresolved += 1;
+            continue;
+        };
+
+        for key in keys.iter().filter(|key| **key != keep) {
+            let Some(duplicate) = state.worktrees.remove(key) else {
+                continue;
+            };
+            let Some(kept) = state.worktrees.get_mut(&keep) else {
+                continue;
+            };
+            let (repo_name, kept_name) = (kept.repo_name.clone(), kept.name.clone());
+            let same_repo = duplicate.repo_name == repo_name;
+            let duplicate_name = duplicate.name.clone();
+            absorb(kept, duplicate);
+            if same_repo {
+                state.reparent_children(&repo_name, &duplicate_name, Some(&kept_name));
+                // The kept entry may have been stacked on its own duplicate
+                if let Some(kept) = state.worktrees.get_mut(&keep)
+                    && kept.parent.as_deref() == Some(kept_name.as_str())
+                {
+                    kept.parent = None;
+                }
+            }
+            merged.push((keep.clone(), key.clone()));
+        }
+    }
+
+    if !merged.is_empty() {
+        state.save()?;
+        for (keep, key) in &merged {
+            audit::record("merge", keep, Some(format!("duplicate {key}")));
+        }
+        status!(
+            "{} Merged {} duplicate entr{}",
+            "✅".green(),
+            merged.len(),
+            if merged.len() == 1 { "y" } else { "ies" }
+        );
+    }
+    if unresolved > 0 {
+        status!(
+            "{} Run 'xlaude clean' interactively to merge duplicate entries",
+            "💡".cyan()
+        );
+    }
+    Ok(())
+}
+
+/// Groups of keys claiming the same directory or branch, with the reason.
+fn find_duplicates(state: &XlaudeState, scope: Scope) -> Vec<(String, Vec<String>)> {
+    let mut by_path: BTreeMap<PathBuf, Vec<String>> = BTreeMap::new();
+    let mut by_branch: BTreeMap<(String, String), Vec<String>> = BTreeMap::new();
+    for (key, info) in state
+        .worktrees
+        .iter()
+        .filter(|(_, info)| scope.contains(info))
+    {
+        let path = info
+            .path
+            .canonicalize()
+            .unwrap_or_else(|_| info.path.clone());
+        by_path.entry(path).or_default().push(key.clone());
+        by_branch
+            .entry((info.repo_name.clone(), info.branch.clone()))
+            .or_default()
+            .push(key.clone());
+    }
+
+    let mut groups: Vec<(String, Vec<String>)> = by_path
+        .into_iter()
+        .filter(|(_, keys)| keys.len() > 1)
+        .map(|(path, keys)| (format!("share {}", path.display()), keys))
+        .collect();
+    for ((_, branch), keys) in by_branch {
+        // Entries sharing a directory share its branch too
+        let already_grouped = groups
+            .iter()
+            .any(|(_, group)| keys.iter().all(|key| group.contains(key)));
+        if keys.len() > 1 && !already_grouped {
+            groups.push((format!("share branch {branch}"), keys));
+        }
+    }
+    for (_, keys) in &mut groups {
+        keys.sort();
+    }
+    groups
+}
+
+/// Fold the metadata of a duplicate entry into the one being kept, which
+/// wins wherever both have a value.
+fn absorb(kept: &mut WorktreeInfo, duplicate: WorktreeInfo) {
+    kept.created_at = kept.created_at.min(duplicate.created_at);
+    kept.last_activity = kept.last_activity.max(duplicate.last_activity);
+    kept.description = kept.description.take().or(duplicate.description);
+    for tag in duplicate.tags {
+        if !kept.tags.contains(&tag) {
+            kept.tags.push(tag);
+        }
+    }
+    kept.pinned |= duplicate.pinned;
+    kept.lock = kept.lock.take().or(duplicate.lock);
+    kept.parent = kept.parent.take().or(duplicate.parent);
+    kept.merged_at = kept.merged_at.or(duplicate.merged_at);
+    kept.ttl_days = kept.ttl_days.or(duplicate.ttl_days);
+    for (key, value) in duplicate.env {
+        kept.env.entry(key).or_insert(value);
+    }
+}
+
+fn collect_all_worktrees(state: &XlaudeState, scope: Scope) -> Result<HashSet<PathBuf>> {
     let mut all_worktrees = HashSet::new();
 
     // Get unique repository paths
     let repo_paths: HashSet<_> = state
         .worktrees
         .values()
-        .filter_map(|info| info.path.parent().map(|p| p.join(&info.repo_name)))
+        .filter(|info| scope.contains(info))
+        .map(|info| info.main_repo_path())
         .collect();
 
     // Collect worktrees from each repository
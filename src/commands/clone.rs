@@ -0,0 +1,85 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+
+use crate::audit;
+use crate::commands::create::{CreateOptions, handle_create_in_dir_quiet};
+use crate::error::XlaudeError;
+use crate::git::{execute_git, extract_repo_name_from_url};
+use crate::output;
+use crate::state::XlaudeState;
+use crate::utils::ensure_online;
+
+pub fn handle_clone(url: String, dir: Option<PathBuf>, worktree: Option<String>) -> Result<()> {
+    let repo_name = extract_repo_name_from_url(&url)
+        .with_context(|| format!("Failed to get repository name from '{url}'"))?;
+    // Worktrees are created next to the checkout, so it goes in a directory of its own
+    let path = std::env::current_dir()?.join(dir.unwrap_or_else(|| PathBuf::from(&repo_name)));
+    if path.exists() {
+        return Err(XlaudeError::Refused(format!("{} already exists", path.display())).into());
+    }
+
+    let mut state = XlaudeState::load()?;
+    if state
+        .repos
+        .get(&repo_name)
+        .is_some_and(|r| r.path.is_some())
+        || state.worktrees.values().any(|w| w.repo_name == repo_name)
+    {
+        return Err(XlaudeError::Refused(format!(
+            "Repository '{repo_name}' is already managed by xlaude"
+        ))
+        .into());
+    }
+
+    if !Path::new(&url).exists() {
+        ensure_online("Cloning a remote repository")?;
+    }
+    status!("{} Cloning {} ...", "📦".cyan(), url.cyan());
+    let dir = path.to_string_lossy();
+    let step = output::step(format!("Cloning {repo_name}"));
+    execute_git(&["clone", &url, &dir]).context("Failed to clone repository")?;
+    let cloned_in = step.finish();
+
+    // `origin/HEAD` is set by clone and names the remote's default branch
+    let base_branch = execute_git(&[
+        "-C",
+        &dir,
+        "symbolic-ref",
+        "--short",
+        "refs/remotes/origin/HEAD",
+    ])
+    .ok()
+    .and_then(|head| head.strip_prefix("origin/").map(str::to_string));
+
+    let config = state.repos.entry(repo_name.clone()).or_default();
+    config.path = Some(path.clone());
+    if config.base_branch.is_none() {
+        config.base_branch = base_branch;
+    }
+    let base_branch = config.base_branch.clone();
+    state.save()?;
+    audit::record("clone", &repo_name, Some(url.clone()));
+
+    status!(
+        "{} Registered '{}' at {}{cloned_in}",
+        "✅".green(),
+        repo_name.cyan(),
+        path.display()
+    );
+    if let Some(base) = &base_branch {
+        status!("  {} Base branch: {}", "🌿".green(), base.cyan());
+    }
+
+    if worktree.is_some() {
+        handle_create_in_dir_quiet(worktree, Some(path), CreateOptions::default(), false)?;
+        return Ok(());
+    }
+
+    println!();
+    status!("{} Next steps:", "💡".cyan());
+    println!("  cd {}", path.display());
+    println!("  xlaude create <name>");
+    println!("  xlaude open <name>");
+    Ok(())
+}
@@ -1,61 +1,87 @@
 use anyhow::Result;
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
 use crate::claude::get_claude_sessions;
-use crate::state::{WorktreeInfo, XlaudeState};
+use crate::git::status_counts;
+use crate::sessions::{AgentStatus, RunningAgents};
+use crate::state::{WorktreeInfo, XlaudeState, get_cache_dir, get_state_path, peek_state_path};
 
-pub fn handle_complete_worktrees(format: &str) -> Result<()> {
-    // Silently load state, return empty on any error
-    let state = match XlaudeState::load() {
-        Ok(s) => s,
-        Err(_) => return Ok(()), // Silent failure for completions
-    };
+// Sessions, agents and changed files move without the state file changing,
+// so cached entries are only reused for a short while
+const CACHE_TTL: Duration = Duration::from_secs(60);
+const CACHE_FILE: &str = "completions.json";
 
-    if state.worktrees.is_empty() {
-        return Ok(());
-    }
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct CompletionEntry {
+    pub(crate) name: String,
+    // Repository-qualified name, accepted wherever a worktree name is
+    key: String,
+    repo_name: String,
+    branch: String,
+    pub(crate) path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    sessions: usize,
+    pub(crate) agent: AgentStatus,
+    // `git status` markers as in `xlaude prompt`, empty when clean
+    pub(crate) dirty: String,
+}
 
-    // Collect all worktrees and sort them
-    // Primary sort: by repository name
-    // Secondary sort: by worktree name within same repository
-    let mut all_worktrees: Vec<&WorktreeInfo> = state.worktrees.values().collect();
-    all_worktrees.sort_by(|a, b| match a.repo_name.cmp(&b.repo_name) {
-        std::cmp::Ordering::Equal => a.name.cmp(&b.name),
-        other => other,
-    });
+/// Entries computed from a given version of the state file.
+#[derive(Debug, Serialize, Deserialize)]
+struct CompletionCache {
+    // Several state files (`--state`) share the cache directory
+    state_path: PathBuf,
+    state_modified: SystemTime,
+    state_len: u64,
+    generated_at: SystemTime,
+    entries: Vec<CompletionEntry>,
+}
+
+pub fn handle_complete_worktrees(format: &str) -> Result<()> {
+    // Completions fail silently, an error would end up in the user's prompt
+    let Some(entries) = entries() else {
+        return Ok(());
+    };
 
     match format {
-        "simple" => {
-            // Simple format: just worktree names, one per line, sorted
-            for info in &all_worktrees {
-                println!("{}", info.name);
-            }
-        }
         "detailed" => {
-            // Detailed format: name<TAB>repo<TAB>path<TAB>sessions
+            // Detailed format:
+            // name<TAB>repo<TAB>path<TAB>sessions<TAB>description<TAB>agent<TAB>dirty
             // Used by shell completions for rich descriptions
-            for info in &all_worktrees {
-                let session_count = count_sessions_safe(&info.path);
-                let session_text = match session_count {
+            for entry in &entries {
+                let session_text = match entry.sessions {
                     0 => "no sessions".to_string(),
                     1 => "1 session".to_string(),
                     n => format!("{} sessions", n),
                 };
+                let description = entry
+                    .description
+                    .as_deref()
+                    .unwrap_or_default()
+                    .replace(['\t', '\n'], " ");
 
                 // Use tab separator for easy parsing
                 println!(
-                    "{}\t{}\t{}\t{}",
-                    info.name,
-                    info.repo_name,
-                    info.path.display(),
-                    session_text
+                    "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                    entry.name,
+                    entry.repo_name,
+                    entry.path.display(),
+                    session_text,
+                    description,
+                    entry.agent.as_str(),
+                    entry.dirty
                 );
             }
         }
+        "json" => println!("{}", serde_json::to_string(&entries)?),
+        // Simple format, also used for unknown formats: just worktree names
         _ => {
-            // Unknown format, fall back to simple
-            for info in &all_worktrees {
-                println!("{}", info.name);
+            for entry in &entries {
+                println!("{}", entry.name);
             }
         }
     }
@@ -63,7 +89,115 @@ pub fn handle_complete_worktrees(format: &str) -> Result<()> {
     Ok(())
 }
 
-// Safe wrapper for counting sessions that won't fail
-fn count_sessions_safe(worktree_path: &Path) -> usize {
-    get_claude_sessions(worktree_path).len()
+/// Completion entries, from the cache when the state file has not changed
+/// since it was written.
+fn entries() -> Option<Vec<CompletionEntry>> {
+    let state_path = get_state_path().ok()?;
+    let metadata = fs::metadata(&state_path).ok()?;
+    let (state_modified, state_len) = (metadata.modified().ok()?, metadata.len());
+
+    if let Some(cache) = read_cache()
+        && cache.state_path == state_path
+        && cache.state_modified == state_modified
+        && cache.state_len == state_len
+        && cache
+            .generated_at
+            .elapsed()
+            .is_ok_and(|age| age < CACHE_TTL)
+    {
+        return Some(cache.entries);
+    }
+    refresh_cache(state_path, state_modified, state_len)
+}
+
+/// Rebuild the cache for the current state file, e.g. from the dashboard so
+/// `xlaude prompt` stays current.
+pub(crate) fn refresh() {
+    if let Ok(state_path) = get_state_path()
+        && let Ok(metadata) = fs::metadata(&state_path)
+        && let Ok(state_modified) = metadata.modified()
+    {
+        refresh_cache(state_path, state_modified, metadata.len());
+    }
+}
+
+/// The cached entry of the worktree containing `dir` and when it was
+/// cached. Only reads the cache: state is not loaded, let alone migrated or
+/// written, and git is not run, so shell prompts stay fast.
+pub(crate) fn cached_entry(dir: &Path) -> Option<(CompletionEntry, SystemTime)> {
+    let cache = read_cache()?;
+    if cache.state_path != peek_state_path().ok()? {
+        return None;
+    }
+    let canonical = fs::canonicalize(dir).ok();
+    let generated_at = cache.generated_at;
+    cache
+        .entries
+        .into_iter()
+        .filter(|entry| {
+            dir.starts_with(&entry.path)
+                || canonical
+                    .as_deref()
+                    .is_some_and(|dir| dir.starts_with(&entry.path))
+        })
+        .max_by_key(|entry| entry.path.components().count())
+        .map(|entry| (entry, generated_at))
+}
+
+fn read_cache() -> Option<CompletionCache> {
+    let bytes = fs::read(get_cache_dir().ok()?.join(CACHE_FILE)).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+fn refresh_cache(
+    state_path: PathBuf,
+    state_modified: SystemTime,
+    state_len: u64,
+) -> Option<Vec<CompletionEntry>> {
+    let cache_path = get_cache_dir().ok()?.join(CACHE_FILE);
+    let state = XlaudeState::load().ok()?;
+    let cache = CompletionCache {
+        state_path,
+        state_modified,
+        state_len,
+        generated_at: SystemTime::now(),
+        entries: collect_entries(&state),
+    };
+    if let Ok(bytes) = serde_json::to_vec(&cache)
+        && let Some(dir) = cache_path.parent()
+        && fs::create_dir_all(dir).is_ok()
+    {
+        let _ = fs::write(&cache_path, bytes);
+    }
+    Some(cache.entries)
+}
+
+fn collect_entries(state: &XlaudeState) -> Vec<CompletionEntry> {
+    // Primary sort: by repository name
+    // Secondary sort: most recently active first within same repository
+    let mut all_worktrees: Vec<&WorktreeInfo> = state.worktrees.values().collect();
+    all_worktrees.sort_by(|a, b| {
+        a.repo_name
+            .cmp(&b.repo_name)
+            .then_with(|| b.recent_activity().cmp(&a.recent_activity()))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    let running = RunningAgents::detect();
+    all_worktrees
+        .into_iter()
+        .map(|info| CompletionEntry {
+            name: info.name.clone(),
+            key: XlaudeState::make_key(&info.repo_name, &info.name),
+            repo_name: info.repo_name.clone(),
+            branch: info.branch.clone(),
+            path: info.path.clone(),
+            description: info.description.clone(),
+            sessions: get_claude_sessions(&info.path).len(),
+            agent: running.status(info),
+            dirty: status_counts(&info.path)
+                .map(|counts| counts.markers())
+                .unwrap_or_default(),
+        })
+        .collect()
 }
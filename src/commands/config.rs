@@ -1,34 +1,316 @@
 use std::fs;
-use std::process::Command;
 
 use anyhow::{Context, Result, anyhow, bail};
+use colored::Colorize;
 
-pub fn handle_config() -> Result<()> {
-    let editor = std::env::var("EDITOR")
-        .context("EDITOR environment variable is not set; please export your preferred editor")?;
+use crate::audit;
+use crate::error::XlaudeError;
+use crate::launcher;
+use crate::state::XlaudeState;
+use crate::tools::Tool;
+
+const KNOWN_KEYS: &str = "editor, shell, agent, default_ttl_days, stale_after_days, \
+    trash_retention_days, dashboard_refresh_secs, forge_checks, offline, agents.<name>, prompt_templates.<name>, dashboard_peers.<name>, repos.<repo>.base_branch, tools.git, tools.gh, tools.tmux, tools.env.<NAME>";
+
+/// A setting that `config get/set/unset` can address, named after its field
+/// in the state file.
+enum ConfigKey {
+    Editor,
+    Shell,
+    Agent,
+    DefaultTtlDays,
+    StaleAfterDays,
+    TrashRetentionDays,
+    DashboardRefreshSecs,
+    ForgeChecks,
+    Offline,
+    NamedAgent(String),
+    PromptTemplate(String),
+    DashboardPeer(String),
+    BaseBranch(String),
+    Tool(Tool),
+    ToolEnv(String),
+}
+
+impl ConfigKey {
+    fn parse(key: &str) -> Result<Self> {
+        let parsed = match key.split_once('.') {
+            None => match key {
+                "editor" => Some(Self::Editor),
+                "shell" => Some(Self::Shell),
+                "agent" => Some(Self::Agent),
+                "default_ttl_days" => Some(Self::DefaultTtlDays),
+                "stale_after_days" => Some(Self::StaleAfterDays),
+                "trash_retention_days" => Some(Self::TrashRetentionDays),
+                "dashboard_refresh_secs" => Some(Self::DashboardRefreshSecs),
+                "forge_checks" => Some(Self::ForgeChecks),
+                "offline" => Some(Self::Offline),
+                _ => None,
+            },
+            Some((_, "")) => None,
+            Some(("agents", name)) => Some(Self::NamedAgent(name.to_string())),
+            Some(("prompt_templates", name)) => Some(Self::PromptTemplate(name.to_string())),
+            Some(("dashboard_peers", name)) => Some(Self::DashboardPeer(name.to_string())),
+            Some(("repos", rest)) => rest
+                .strip_suffix(".base_branch")
+                .filter(|repo| !repo.is_empty())
+                .map(|repo| Self::BaseBranch(repo.to_string())),
+            Some(("tools", "git")) => Some(Self::Tool(Tool::Git)),
+            Some(("tools", "gh")) => Some(Self::Tool(Tool::Gh)),
+            Some(("tools", "tmux")) => Some(Self::Tool(Tool::Tmux)),
+            Some(("tools", rest)) => rest
+                .strip_prefix("env.")
+                .filter(|name| !name.is_empty())
+                .map(|name| Self::ToolEnv(name.to_string())),
+            Some(_) => None,
+        };
+        parsed.ok_or_else(|| anyhow!("Unknown config key '{key}'. Known keys: {KNOWN_KEYS}"))
+    }
+
+    fn get(&self, state: &XlaudeState) -> Option<String> {
+        match self {
+            Self::Editor => state.editor.clone(),
+            Self::Shell => state.shell.clone(),
+            Self::Agent => state.agent.clone(),
+            Self::DefaultTtlDays => state.default_ttl_days.map(|days| days.to_string()),
+            Self::StaleAfterDays => state.stale_after_days.map(|days| days.to_string()),
+            Self::TrashRetentionDays => state.trash_retention_days.map(|days| days.to_string()),
+            Self::DashboardRefreshSecs => state.dashboard_refresh_secs.map(|secs| secs.to_string()),
+            Self::ForgeChecks => state.forge_checks.map(|enabled| enabled.to_string()),
+            Self::Offline => state.offline.map(|enabled| enabled.to_string()),
+            Self::NamedAgent(name) => state.agents.get(name).cloned(),
+            Self::PromptTemplate(name) => state.prompt_templates.get(name).cloned(),
+            Self::DashboardPeer(name) => state.dashboard_peers.get(name).cloned(),
+            Self::BaseBranch(repo) => state.repos.get(repo)?.base_branch.clone(),
+            Self::Tool(tool) => tool_program(state, *tool).clone(),
+            Self::ToolEnv(name) => state.tools.env.get(name).cloned(),
+        }
+    }
+
+    /// Store `value`, or remove the setting when it is `None`.
+    fn set(&self, state: &mut XlaudeState, value: Option<String>) -> Result<()> {
+        // Command lines are split like a shell when they are used
+        if let Some(command) = &value
+            && matches!(
+                self,
+                Self::Editor | Self::Shell | Self::Agent | Self::NamedAgent(_)
+            )
+        {
+            shell_words::split(command)
+                .map_err(|e| anyhow!("Failed to parse command: {command} ({e})"))?;
+        }
+        match self {
+            Self::Editor => state.editor = value,
+            Self::Shell => state.shell = value,
+            Self::Agent => state.agent = value,
+            Self::DefaultTtlDays => state.default_ttl_days = value.map(parse_days).transpose()?,
+            Self::StaleAfterDays => state.stale_after_days = value.map(parse_days).transpose()?,
+            Self::TrashRetentionDays => {
+                state.trash_retention_days = value.map(parse_days).transpose()?;
+            }
+            Self::DashboardRefreshSecs => {
+                state.dashboard_refresh_secs = value.map(parse_seconds).transpose()?;
+            }
+            Self::ForgeChecks => state.forge_checks = value.map(parse_bool).transpose()?,
+            Self::Offline => state.offline = value.map(parse_bool).transpose()?,
+            Self::NamedAgent(name) => set_entry(&mut state.agents, name, value),
+            Self::PromptTemplate(name) => set_entry(&mut state.prompt_templates, name, value),
+            Self::DashboardPeer(name) => {
+                if let Some(url) = &value
+                    && !url.starts_with("http://")
+                    && !url.starts_with("https://")
+                {
+                    bail!("Dashboard peer must be an http:// or https:// URL, got '{url}'");
+                }
+                set_entry(&mut state.dashboard_peers, name, value);
+            }
+            Self::BaseBranch(repo) => {
+                state.repos.entry(repo.clone()).or_default().base_branch = value;
+                state.repos.retain(|_, config| !config.is_empty());
+            }
+            Self::Tool(tool) => *tool_program_mut(state, *tool) = value,
+            Self::ToolEnv(name) => set_entry(&mut state.tools.env, name, value),
+        }
+        Ok(())
+    }
+}
+
+fn parse_days(value: String) -> Result<u32> {
+    value
+        .parse()
+        .map_err(|_| anyhow!("Expected a number of days, got '{value}'"))
+}
+
+fn parse_seconds(value: String) -> Result<u32> {
+    value
+        .parse()
+        .map_err(|_| anyhow!("Expected a number of seconds, got '{value}'"))
+}
+
+fn parse_bool(value: String) -> Result<bool> {
+    value
+        .parse()
+        .map_err(|_| anyhow!("Expected true or false, got '{value}'"))
+}
+
+fn tool_program(state: &XlaudeState, tool: Tool) -> &Option<String> {
+    match tool {
+        Tool::Git => &state.tools.git,
+        Tool::Gh => &state.tools.gh,
+        Tool::Tmux => &state.tools.tmux,
+    }
+}
+
+fn tool_program_mut(state: &mut XlaudeState, tool: Tool) -> &mut Option<String> {
+    match tool {
+        Tool::Git => &mut state.tools.git,
+        Tool::Gh => &mut state.tools.gh,
+        Tool::Tmux => &mut state.tools.tmux,
+    }
+}
+
+fn set_entry(
+    map: &mut std::collections::BTreeMap<String, String>,
+    name: &str,
+    value: Option<String>,
+) {
+    match value {
+        Some(value) => map.insert(name.to_string(), value),
+        None => map.remove(name),
+    };
+}
+
+/// Every configured setting as `(key, value)`, in `KNOWN_KEYS` order.
+fn configured(state: &XlaudeState) -> Vec<(String, String)> {
+    let mut settings = Vec::new();
+    for (key, value) in [
+        ("editor", &state.editor),
+        ("shell", &state.shell),
+        ("agent", &state.agent),
+    ] {
+        if let Some(value) = value {
+            settings.push((key.to_string(), value.clone()));
+        }
+    }
+    for (key, days) in [
+        ("default_ttl_days", state.default_ttl_days),
+        ("stale_after_days", state.stale_after_days),
+        ("trash_retention_days", state.trash_retention_days),
+    ] {
+        if let Some(days) = days {
+            settings.push((key.to_string(), days.to_string()));
+        }
+    }
+    if let Some(secs) = state.dashboard_refresh_secs {
+        settings.push(("dashboard_refresh_secs".to_string(), secs.to_string()));
+    }
+    for (key, enabled) in [
+        ("forge_checks", state.forge_checks),
+        ("offline", state.offline),
+    ] {
+        if let Some(enabled) = enabled {
+            settings.push((key.to_string(), enabled.to_string()));
+        }
+    }
+    for (prefix, map) in [
+        ("agents", &state.agents),
+        ("prompt_templates", &state.prompt_templates),
+        ("dashboard_peers", &state.dashboard_peers),
+    ] {
+        settings.extend(
+            map.iter()
+                .map(|(name, value)| (format!("{prefix}.{name}"), value.clone())),
+        );
+    }
+    for (repo, config) in &state.repos {
+        if let Some(base) = &config.base_branch {
+            settings.push((format!("repos.{repo}.base_branch"), base.clone()));
+        }
+    }
+    for (key, tool) in [
+        ("tools.git", Tool::Git),
+        ("tools.gh", Tool::Gh),
+        ("tools.tmux", Tool::Tmux),
+    ] {
+        if let Some(program) = tool_program(state, tool) {
+            settings.push((key.to_string(), program.clone()));
+        }
+    }
+    settings.extend(
+        state
+            .tools
+            .env
+            .iter()
+            .map(|(name, value)| (format!("tools.env.{name}"), value.clone())),
+    );
+    settings
+}
 
-    let parts = shell_words::split(&editor)
-        .map_err(|e| anyhow!("Failed to parse EDITOR command: {editor} ({e})"))?;
+pub fn handle_config_get(key: String) -> Result<()> {
+    let state = XlaudeState::load()?;
+    match ConfigKey::parse(&key)?.get(&state) {
+        Some(value) => {
+            println!("{value}");
+            Ok(())
+        }
+        None => Err(XlaudeError::NotFound(format!("'{key}' is not set")).into()),
+    }
+}
 
-    if parts.is_empty() {
-        bail!("EDITOR command is empty");
+pub fn handle_config_set(key: String, value: String) -> Result<()> {
+    let config_key = ConfigKey::parse(&key)?;
+    let value = value.trim().to_string();
+    if value.is_empty() {
+        bail!("Value cannot be empty, use 'xlaude config unset {key}' to remove it");
     }
 
+    let mut state = XlaudeState::load()?;
+    config_key.set(&mut state, Some(value.clone()))?;
+    state.save()?;
+    audit::record("config", &key, Some(value.clone()));
+
+    status!("{} {} = {}", "✅".green(), key.cyan(), value);
+    Ok(())
+}
+
+pub fn handle_config_unset(key: String) -> Result<()> {
+    let config_key = ConfigKey::parse(&key)?;
+    let mut state = XlaudeState::load()?;
+    if config_key.get(&state).is_none() {
+        status!("{} '{}' is not set", "💡".cyan(), key);
+        return Ok(());
+    }
+    config_key.set(&mut state, None)?;
+    state.save()?;
+    audit::record("config", &key, Some("unset".to_string()));
+
+    status!("{} Removed {}", "✅".green(), key.cyan());
+    Ok(())
+}
+
+pub fn handle_config_list() -> Result<()> {
+    let state = XlaudeState::load()?;
+    for (key, value) in configured(&state) {
+        println!("{key}={value}");
+    }
+    Ok(())
+}
+
+/// Open the state file in `$VISUAL` or `$EDITOR`, falling back to the platform's
+/// terminal editor.
+pub fn handle_config() -> Result<()> {
+    let editor = launcher::terminal_editor_command();
+
     let state_path = crate::state::get_state_path()?;
     if let Some(parent) = state_path.parent() {
         fs::create_dir_all(parent)
             .with_context(|| format!("Failed to create config directory: {}", parent.display()))?;
     }
 
-    let mut cmd = Command::new(&parts[0]);
-    if parts.len() > 1 {
-        cmd.args(&parts[1..]);
-    }
-    cmd.arg(&state_path);
-
-    let status = cmd
+    let status = launcher::editor(&editor, &state_path)
+        .with_context(|| format!("Invalid editor command: {editor}"))?
         .status()
-        .with_context(|| format!("Failed to launch editor: {}", parts[0]))?;
+        .with_context(|| format!("Failed to launch editor: {editor}"))?;
 
     if !status.success() {
         bail!(
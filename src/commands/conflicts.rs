@@ -0,0 +1,49 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::conflicts::find_overlaps;
+use crate::state::XlaudeState;
+
+pub fn handle_conflicts(repo: Option<String>, json: bool) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let overlaps = find_overlaps(
+        state
+            .worktrees
+            .values()
+            .filter(|info| repo.as_ref().is_none_or(|repo| &info.repo_name == repo)),
+    );
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&overlaps)?);
+        return Ok(());
+    }
+
+    if overlaps.is_empty() {
+        status!("{} No worktrees change the same files", "✅".green());
+        return Ok(());
+    }
+
+    let mut current_repo = None;
+    for overlap in &overlaps {
+        if current_repo != Some(&overlap.repo_name) {
+            if current_repo.is_some() {
+                println!();
+            }
+            status!("{} {}", "📦".blue(), overlap.repo_name.bold());
+            current_repo = Some(&overlap.repo_name);
+        }
+        status!(
+            "  {} {} {} {} ({} file{})",
+            "⚠️".yellow(),
+            overlap.worktrees[0].cyan(),
+            "↔".bright_black(),
+            overlap.worktrees[1].cyan(),
+            overlap.files.len(),
+            if overlap.files.len() == 1 { "" } else { "s" }
+        );
+        for file in &overlap.files {
+            println!("      {}", file.bright_black());
+        }
+    }
+    Ok(())
+}
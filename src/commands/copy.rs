@@ -0,0 +1,174 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use colored::Colorize;
+use std::fs;
+use std::path::Path;
+
+use crate::audit;
+use crate::commands::create::new_worktree_location;
+use crate::error::XlaudeError;
+use crate::git::{
+    StatusCounts, apply_patch, execute_git, git_output_bytes, status_counts, update_submodules,
+};
+use crate::output;
+use crate::state::{WorktreeInfo, XlaudeState};
+use crate::utils::sanitize_branch_name;
+
+pub fn handle_copy(source: String, new_branch: String) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let (source_key, source_info) = state
+        .find_by_name(&source)
+        .map(|(k, w)| (k.clone(), w.clone()))
+        .ok_or_else(|| XlaudeError::NotFound(format!("Worktree '{source}' not found")))?;
+
+    let repo_name = source_info.repo_name.clone();
+    let worktree_name = sanitize_branch_name(&new_branch);
+    let worktrees_dir = source_info
+        .path
+        .parent()
+        .context("Failed to get parent directory")?;
+    let (key, worktree_path) =
+        new_worktree_location(&state, &repo_name, &worktree_name, worktrees_dir)?;
+
+    status!(
+        "{} Copying worktree '{}' to new branch '{}'...",
+        "✨".green(),
+        source_info.name.cyan(),
+        new_branch.cyan()
+    );
+
+    // Branch from the source's current commit, not its branch tip elsewhere
+    let source_dir = source_info.path.to_string_lossy().to_string();
+    let target_dir = worktree_path.to_string_lossy().to_string();
+    let base_commit = execute_git(&["-C", &source_dir, "rev-parse", "HEAD"])
+        .context("Failed to resolve the source's HEAD")?;
+    let step = output::step(format!("Creating worktree '{worktree_name}'"));
+    execute_git(&[
+        "-C",
+        &source_dir,
+        "worktree",
+        "add",
+        "-b",
+        &new_branch,
+        &target_dir,
+        &base_commit,
+    ])
+    .context("Failed to create worktree")?;
+    let created_in = step.finish();
+
+    let finished = finish_copy(&source_info, &worktree_path).and_then(|counts| {
+        let mut state = XlaudeState::load()?;
+        state.worktrees.insert(
+            key.clone(),
+            WorktreeInfo {
+                name: worktree_name.clone(),
+                branch: new_branch.clone(),
+                path: worktree_path.clone(),
+                repo_name,
+                created_at: Utc::now(),
+                description: source_info.description.clone(),
+                tags: source_info.tags.clone(),
+                base_branch: Some(source_info.branch.clone()),
+                base_commit: Some(base_commit),
+                ..Default::default()
+            },
+        );
+        state.save()?;
+        Ok(counts)
+    });
+    let counts = match finished {
+        Ok(counts) => counts,
+        Err(err) => {
+            // Leave nothing behind that would block a second attempt
+            let removed = execute_git(&[
+                "-C",
+                &source_dir,
+                "worktree",
+                "remove",
+                "--force",
+                &target_dir,
+            ])
+            .and_then(|_| execute_git(&["-C", &source_dir, "branch", "-D", &new_branch]));
+            if let Err(cleanup) = removed {
+                estatus!(
+                    "{} Failed to remove the partial copy at {}: {cleanup:#}",
+                    "⚠️".yellow(),
+                    worktree_path.display()
+                );
+            }
+            return Err(err);
+        }
+    };
+    audit::record("copy", &key, Some(format!("from {source_key}")));
+
+    if !counts.is_clean() {
+        status!(
+            "{} Copied uncommitted changes: {} staged, {} modified, {} untracked",
+            "📄".green(),
+            counts.staged,
+            counts.unstaged,
+            counts.untracked
+        );
+    }
+    status!(
+        "{} Worktree created at: {}{created_in}",
+        "✅".green(),
+        worktree_path.display()
+    );
+    status!(
+        "  {} To open it, run: {} {}",
+        "💡".cyan(),
+        "xlaude open".cyan(),
+        worktree_name.cyan()
+    );
+    Ok(())
+}
+
+/// Everything after `git worktree add`: submodules and the source's
+/// uncommitted changes. Returns what was copied, counted in the source.
+fn finish_copy(source_info: &WorktreeInfo, worktree_path: &Path) -> Result<StatusCounts> {
+    let step = output::step("Updating submodules");
+    let updated = update_submodules(worktree_path);
+    drop(step);
+    if let Err(e) = updated {
+        status!(
+            "{} Warning: Failed to update submodules: {}",
+            "⚠️".yellow(),
+            e
+        );
+    }
+
+    let counts = status_counts(&source_info.path)?;
+    copy_uncommitted_changes(&source_info.path, worktree_path)?;
+    Ok(counts)
+}
+
+/// Reproduce staged, unstaged and untracked changes of `source` in `target`.
+fn copy_uncommitted_changes(source: &Path, target: &Path) -> Result<()> {
+    let staged = git_output_bytes(source, &["diff", "--binary", "--cached"])?;
+    let unstaged = git_output_bytes(source, &["diff", "--binary"])?;
+    let untracked = git_output_bytes(
+        source,
+        &["ls-files", "--others", "--exclude-standard", "-z"],
+    )?;
+
+    // Staged changes go to the index too, so the copy has the same staging state
+    if !staged.is_empty() {
+        apply_patch(target, &staged, &["--index"]).context("Failed to apply staged changes")?;
+    }
+    if !unstaged.is_empty() {
+        apply_patch(target, &unstaged, &[]).context("Failed to apply unstaged changes")?;
+    }
+
+    for file in untracked.split(|b| *b == 0).filter(|f| !f.is_empty()) {
+        let relative = Path::new(std::str::from_utf8(file).context("Non UTF-8 file name")?);
+        let destination = target.join(relative);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        fs::copy(source.join(relative), &destination)
+            .with_context(|| format!("Failed to copy {}", relative.display()))?;
+    }
+    Ok(())
+}
@@ -2,32 +2,50 @@ use anyhow::{Context, Result};
 use chrono::Utc;
 use colored::Colorize;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::commands::open::handle_open;
+use crate::audit;
+use crate::commands::open::{OpenOptions, handle_open};
+use crate::error::XlaudeError;
 use crate::git::{
-    execute_git, extract_repo_name_from_url, get_repo_name, list_worktrees, update_submodules,
+    execute_git, fork_point, get_repo_name, get_repo_name_at, list_worktrees, local_default_branch,
+    matches_base_branch, update_submodules,
 };
 use crate::input::{get_command_arg, smart_confirm};
-use crate::state::{WorktreeInfo, XlaudeState};
-use crate::utils::{generate_random_name, sanitize_branch_name};
+use crate::output;
+use crate::state::{WorktreeInfo, XlaudeState, configured_base_branch};
+use crate::utils::{generate_random_name, name_from_prompt, sanitize_branch_name, unique_name};
 
-pub fn handle_create(name: Option<String>) -> Result<()> {
-    handle_create_in_dir(name, None)
+#[derive(Debug, Default, Clone)]
+pub struct CreateOptions {
+    /// Stack the new branch on this worktree's branch and record it as its parent
+    pub from_worktree: Option<String>,
+    /// Agent prompt; names the worktree when no name is given and is sent to
+    /// the agent when the worktree is opened right away
+    pub type_text: Option<String>,
+    /// Days until the worktree is flagged as expired
+    pub ttl_days: Option<u32>,
 }
 
-pub fn handle_create_in_dir(name: Option<String>, repo_path: Option<PathBuf>) -> Result<()> {
-    handle_create_in_dir_quiet(name, repo_path, false)?;
+pub fn handle_create(name: Option<String>, options: CreateOptions) -> Result<()> {
+    handle_create_in_dir_quiet(name, None, options, false)?;
     Ok(())
 }
 
-// Create worktree quietly without prompting for open, returns the created worktree name
+// Create worktree quietly without prompting for open, returns the created worktree name.
 pub fn handle_create_in_dir_quiet(
     name: Option<String>,
     repo_path: Option<PathBuf>,
+    options: CreateOptions,
     quiet: bool,
 ) -> Result<String> {
+    let CreateOptions {
+        from_worktree,
+        type_text: prompt,
+        ttl_days,
+    } = options;
     // Helper to execute git in the right directory using git -C
+    let repo_dir = repo_path.clone().unwrap_or_else(|| PathBuf::from("."));
     let exec_git = |args: &[&str]| -> Result<String> {
         if let Some(ref path) = repo_path {
             // Use git -C to execute in specified directory
@@ -41,78 +59,95 @@ pub fn handle_create_in_dir_quiet(
 
     // Get repo name from the target directory
     let repo_name = if let Some(ref path) = repo_path {
-        // Get repo name from the specified path using git -C
-        let output = execute_git(&["-C", path.to_str().unwrap(), "remote", "get-url", "origin"])?;
-        if let Some(name) = extract_repo_name_from_url(&output) {
-            name
-        } else {
-            // Fallback to directory name
-            path.file_name()
-                .and_then(|n| n.to_str())
-                .map(String::from)
-                .context("Failed to get repository name")?
-        }
+        get_repo_name_at(path)?
     } else {
         get_repo_name().context("Not in a git repository")?
     };
 
+    let parent = match &from_worktree {
+        Some(parent_name) => {
+            let state = XlaudeState::load()?;
+            let parent = state
+                .worktrees
+                .get(&XlaudeState::make_key(&repo_name, parent_name))
+                .cloned()
+                .ok_or_else(|| {
+                    XlaudeError::NotFound(format!(
+                        "Worktree '{parent_name}' not found in repository '{repo_name}'"
+                    ))
+                })?;
+            Some(parent)
+        }
+        None => None,
+    };
+
     // Only check base branch if no repo_path is provided (i.e., running from CLI in current directory)
-    // Clients that pass repo_path are expected to enforce their own branch safety checks
-    if repo_path.is_none() {
+    // Clients that pass repo_path are expected to enforce their own branch safety checks.
+    // Stacked worktrees start from their parent, so the current branch does not matter
+    if repo_path.is_none() && parent.is_none() {
         let current_branch = exec_git(&["branch", "--show-current"])?;
-        let default_branch = exec_git(&["symbolic-ref", "refs/remotes/origin/HEAD"])
-            .ok()
-            .and_then(|s| s.strip_prefix("refs/remotes/origin/").map(String::from))
-            .unwrap_or_else(|| "main".to_string());
+        let default_branch = local_default_branch(&repo_dir).unwrap_or_else(|| "main".to_string());
 
-        let base_branches = ["main", "master", "develop", &default_branch];
-        if !base_branches.contains(&current_branch.as_str()) {
-            anyhow::bail!(
-                "Must be on a base branch (main, master, or develop) to create a new worktree. Current branch: {}",
-                current_branch
-            );
+        if let Some(pattern) = configured_base_branch(&repo_name) {
+            if !matches_base_branch(&pattern, &current_branch) {
+                anyhow::bail!(
+                    "Must be on the base branch configured for '{}' ({}) to create a new worktree. Current branch: {}",
+                    repo_name,
+                    pattern,
+                    current_branch
+                );
+            }
+        } else {
+            let base_branches = ["main", "master", "develop", &default_branch];
+            if !base_branches.contains(&current_branch.as_str()) {
+                anyhow::bail!(
+                    "Must be on a base branch (main, master, or develop) to create a new worktree. Current branch: {}",
+                    current_branch
+                );
+            }
         }
     }
 
+    let worktrees_dir = match repo_path {
+        Some(ref path) => path.parent().unwrap().to_path_buf(),
+        None => std::env::current_dir()?.parent().unwrap().to_path_buf(),
+    };
+    let state = XlaudeState::load()?;
+    // Generated names must not clash with a tracked worktree, a branch or a directory
+    let is_taken = |name: &str| {
+        let dir_name = sanitize_branch_name(name);
+        state
+            .worktrees
+            .contains_key(&XlaudeState::make_key(&repo_name, &dir_name))
+            || exec_git(&[
+                "show-ref",
+                "--verify",
+                "--quiet",
+                &format!("refs/heads/{name}"),
+            ])
+            .is_ok()
+            || worktrees_dir
+                .join(format!("{repo_name}-{dir_name}"))
+                .exists()
+    };
+
     // Get name from CLI args or pipe, generate if not provided
     let branch_name = match get_command_arg(name)? {
         Some(n) => n,
-        None => generate_random_name()?,
+        None => match prompt
+            .as_deref()
+            .and_then(|prompt| name_from_prompt(&state.names, prompt))
+        {
+            Some(slug) => unique_name(&slug, is_taken),
+            None => generate_random_name(&state.names, is_taken)?,
+        },
     };
 
     // Sanitize the branch name for use in directory names
     let worktree_name = sanitize_branch_name(&branch_name);
 
-    // Check if a worktree with this name already exists in xlaude state
-    let state = XlaudeState::load()?;
-    let key = XlaudeState::make_key(&repo_name, &worktree_name);
-    if state.worktrees.contains_key(&key) {
-        anyhow::bail!(
-            "A worktree named '{}' already exists for repository '{}' (tracked by xlaude). Please choose a different name.",
-            worktree_name,
-            repo_name
-        );
-    }
-
-    // Check if the worktree directory will be created
-    let worktree_dir_path = if let Some(ref path) = repo_path {
-        path.parent()
-            .unwrap()
-            .join(format!("{repo_name}-{worktree_name}"))
-    } else {
-        std::env::current_dir()?
-            .parent()
-            .unwrap()
-            .join(format!("{repo_name}-{worktree_name}"))
-    };
-
-    // Check if the directory already exists
-    if worktree_dir_path.exists() {
-        anyhow::bail!(
-            "Directory '{}' already exists. Please choose a different name or remove the existing directory.",
-            worktree_dir_path.display()
-        );
-    }
+    let (_, worktree_dir_path) =
+        new_worktree_location(&state, &repo_name, &worktree_name, &worktrees_dir)?;
 
     // Check if a git worktree already exists at this path
     // Need to run git worktree list in the correct directory
@@ -143,6 +178,24 @@ pub fn handle_create_in_dir_quiet(
         );
     }
 
+    // Branch the new one starts from, also recorded for an existing branch
+    let base_branch = if let Some(parent) = &parent {
+        Some(parent.branch.clone())
+    } else if repo_path.is_some() {
+        let default_branch = local_default_branch(&repo_dir).unwrap_or_else(|| "main".to_string());
+        // Bare clones have no remote-tracking branches, only the local one
+        let remote_branch = format!("origin/{default_branch}");
+        if exec_git(&["rev-parse", "--verify", "-q", &remote_branch]).is_ok() {
+            Some(remote_branch)
+        } else {
+            Some(default_branch)
+        }
+    } else {
+        exec_git(&["branch", "--show-current"])
+            .ok()
+            .filter(|branch| !branch.is_empty())
+    };
+
     // Check if the branch already exists
     let branch_already_exists = exec_git(&[
         "show-ref",
@@ -153,7 +206,7 @@ pub fn handle_create_in_dir_quiet(
 
     if branch_already_exists {
         if !quiet {
-            println!(
+            status!(
                 "{} Creating worktree '{}' from existing branch '{}'...",
                 "✨".green(),
                 worktree_name.cyan(),
@@ -162,7 +215,7 @@ pub fn handle_create_in_dir_quiet(
         }
     } else {
         if !quiet {
-            println!(
+            status!(
                 "{} Creating worktree '{}' with new branch '{}'...",
                 "✨".green(),
                 worktree_name.cyan(),
@@ -172,20 +225,14 @@ pub fn handle_create_in_dir_quiet(
 
         // When repo_path is provided, create branch from the default branch
         // Otherwise create from current branch
-        if repo_path.is_some() {
-            // Get the default branch
-            let default_branch = exec_git(&["symbolic-ref", "refs/remotes/origin/HEAD"])
-                .ok()
-                .and_then(|s| s.strip_prefix("refs/remotes/origin/").map(String::from))
-                .unwrap_or_else(|| "main".to_string());
-
-            // Create branch from the default branch
-            exec_git(&[
-                "branch",
-                &branch_name,
-                &format!("origin/{}", default_branch),
-            ])
-            .context("Failed to create branch from default branch")?;
+        if let Some(parent) = &parent {
+            exec_git(&["branch", &branch_name, &parent.branch])
+                .with_context(|| format!("Failed to create branch from '{}'", parent.branch))?;
+        } else if let Some(start) = &base_branch
+            && repo_path.is_some()
+        {
+            exec_git(&["branch", &branch_name, start])
+                .context("Failed to create branch from default branch")?;
         } else {
             // Create branch from current branch (original behavior for CLI)
             exec_git(&["branch", &branch_name]).context("Failed to create branch")?;
@@ -194,8 +241,10 @@ pub fn handle_create_in_dir_quiet(
 
     // Create worktree with sanitized directory name
     let worktree_dir = format!("../{repo_name}-{worktree_name}");
+    let step = output::step(format!("Creating worktree '{worktree_name}'"));
     exec_git(&["worktree", "add", &worktree_dir, &branch_name])
         .context("Failed to create worktree")?;
+    let created_in = step.finish();
 
     // Get absolute path
     let worktree_path = if let Some(ref path) = repo_path {
@@ -210,9 +259,12 @@ pub fn handle_create_in_dir_quiet(
     };
 
     // Update submodules if they exist
-    if let Err(e) = update_submodules(&worktree_path) {
+    let step = output::step("Updating submodules");
+    let updated = update_submodules(&worktree_path);
+    let submodules_in = step.finish();
+    if let Err(e) = updated {
         if !quiet {
-            println!(
+            status!(
                 "{} Warning: Failed to update submodules: {}",
                 "⚠️".yellow(),
                 e
@@ -222,7 +274,7 @@ pub fn handle_create_in_dir_quiet(
         // Check if submodules were actually updated
         let gitmodules = worktree_path.join(".gitmodules");
         if gitmodules.exists() && !quiet {
-            println!("{} Updated submodules", "📦".green());
+            status!("{} Updated submodules{submodules_in}", "📦".green());
         }
     }
 
@@ -236,7 +288,7 @@ pub fn handle_create_in_dir_quiet(
         let target_path = worktree_path.join("CLAUDE.local.md");
         fs::copy(claude_local_md, &target_path).context("Failed to copy CLAUDE.local.md")?;
         if !quiet {
-            println!("{} Copied CLAUDE.local.md to worktree", "📄".green());
+            status!("{} Copied CLAUDE.local.md to worktree", "📄".green());
         }
     }
 
@@ -244,20 +296,32 @@ pub fn handle_create_in_dir_quiet(
     let mut state = XlaudeState::load()?;
     let key = XlaudeState::make_key(&repo_name, &worktree_name);
     state.worktrees.insert(
-        key,
+        key.clone(),
         WorktreeInfo {
             name: worktree_name.clone(),
             branch: branch_name.clone(),
             path: worktree_path.clone(),
             repo_name,
             created_at: Utc::now(),
+            parent: parent.as_ref().map(|p| p.name.clone()),
+            ttl_days,
+            base_commit: base_branch
+                .as_deref()
+                .and_then(|base| fork_point(&worktree_path, &branch_name, base)),
+            base_branch,
+            ..Default::default()
         },
     );
     state.save()?;
+    let mut detail = format!("branch {branch_name} at {}", worktree_path.display());
+    if let Some(parent) = &parent {
+        detail.push_str(&format!(" on {}", parent.name));
+    }
+    audit::record("create", &key, Some(detail));
 
     if !quiet {
-        println!(
-            "{} Worktree created at: {}",
+        status!(
+            "{} Worktree created at: {}{created_in}",
             "✅".green(),
             worktree_path.display()
         );
@@ -269,7 +333,7 @@ pub fn handle_create_in_dir_quiet(
         let should_open = if std::env::var("XLAUDE_TEST_MODE").is_ok()
             || std::env::var("XLAUDE_NO_AUTO_OPEN").is_ok()
         {
-            println!(
+            status!(
                 "  {} To open it, run: {} {}",
                 "💡".cyan(),
                 "xlaude open".cyan(),
@@ -281,9 +345,15 @@ pub fn handle_create_in_dir_quiet(
         };
 
         if should_open {
-            handle_open(Some(worktree_name.clone()))?;
+            handle_open(
+                Some(worktree_name.clone()),
+                OpenOptions {
+                    type_text: prompt,
+                    ..OpenOptions::default()
+                },
+            )?;
         } else if std::env::var("XLAUDE_NON_INTERACTIVE").is_err() {
-            println!(
+            status!(
                 "  {} To open it later, run: {} {}",
                 "💡".cyan(),
                 "xlaude open".cyan(),
@@ -294,3 +364,31 @@ pub fn handle_create_in_dir_quiet(
 
     Ok(worktree_name)
 }
+
+/// Key and directory of a new worktree `worktree_name` of `repo_name`, placed
+/// in `worktrees_dir`. Fails if xlaude already tracks that name or the
+/// directory exists.
+pub(crate) fn new_worktree_location(
+    state: &XlaudeState,
+    repo_name: &str,
+    worktree_name: &str,
+    worktrees_dir: &Path,
+) -> Result<(String, PathBuf)> {
+    let key = XlaudeState::make_key(repo_name, worktree_name);
+    if state.worktrees.contains_key(&key) {
+        anyhow::bail!(
+            "A worktree named '{}' already exists for repository '{}' (tracked by xlaude). Please choose a different name.",
+            worktree_name,
+            repo_name
+        );
+    }
+
+    let path = worktrees_dir.join(format!("{repo_name}-{worktree_name}"));
+    if path.exists() {
+        anyhow::bail!(
+            "Directory '{}' already exists. Please choose a different name or remove the existing directory.",
+            path.display()
+        );
+    }
+    Ok((key, path))
+}
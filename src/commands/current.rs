@@ -0,0 +1,21 @@
+use anyhow::Result;
+
+use crate::state::XlaudeState;
+
+/// Print the managed worktree containing the current directory, matched by
+/// path so subdirectories resolve too. Only the state file is read, so this is
+/// cheap enough for shell prompts. Outside a managed worktree nothing is
+/// printed on stdout and the exit code is 3.
+pub fn handle_current(path: bool) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let key = state.resolve_key(None)?;
+    let info = &state.worktrees[&key];
+
+    if path {
+        println!("{}", info.path.display());
+    } else {
+        // Same tab-separated layout as `list --format vim`, key first
+        println!("{key}\t{}\t{}", info.branch, info.path.display());
+    }
+    Ok(())
+}
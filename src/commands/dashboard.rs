@@ -1,7 +1,174 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::fs::File;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
 
-use crate::dashboard;
+use crate::dashboard::{self, DashboardInstance};
+use crate::utils::{is_offline, terminate_process};
 
-pub fn handle_dashboard(addr: Option<String>, no_browser: bool) -> Result<()> {
-    dashboard::run_dashboard(addr, !no_browser)
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(5);
+const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+pub fn handle_dashboard(
+    addr: Option<String>,
+    no_browser: bool,
+    peers: Vec<String>,
+    daemon: bool,
+) -> Result<()> {
+    if daemon {
+        return start_daemon(addr, no_browser, peers);
+    }
+    dashboard::run_dashboard(addr, !no_browser, peers)
+}
+
+fn start_daemon(addr: Option<String>, no_browser: bool, peers: Vec<String>) -> Result<()> {
+    if let Some(existing) = DashboardInstance::running()? {
+        anyhow::bail!(
+            "A dashboard is already running for this state at {} (pid {}). Stop it with 'xlaude dashboard stop'",
+            existing.url(),
+            existing.pid
+        );
+    }
+
+    let log_path = DashboardInstance::log_path()?;
+    let log = File::create(&log_path)
+        .with_context(|| format!("Failed to create {}", log_path.display()))?;
+
+    let exe = std::env::current_exe().context("Failed to locate xlaude binary")?;
+    let mut cmd = Command::new(exe);
+    cmd.args(["dashboard", "--no-browser"]);
+    if let Some(addr) = &addr {
+        cmd.args(["--addr", addr]);
+    }
+    for peer in &peers {
+        cmd.args(["--peer", peer]);
+    }
+    cmd.stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log);
+    // Detach from the terminal's process group so Ctrl+C does not reach it
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+
+    let mut child = cmd.spawn().context("Failed to start dashboard")?;
+    let deadline = Instant::now() + STARTUP_TIMEOUT;
+    loop {
+        if let Some(instance) = DashboardInstance::running()?
+            && instance.pid == child.id()
+        {
+            status!(
+                "{} Dashboard running in the background at {} (pid {})",
+                "🚀".green(),
+                instance.url().cyan(),
+                instance.pid
+            );
+            println!("  {} {}", "Logs:".bright_black(), log_path.display());
+            if !no_browser
+                && !is_offline()
+                && let Err(err) = webbrowser::open(&instance.url())
+            {
+                estatus!("⚠️  Unable to open browser automatically: {err}");
+            }
+            return Ok(());
+        }
+        if let Some(status) = child.try_wait()? {
+            anyhow::bail!(
+                "Dashboard exited during startup ({status}), see {}",
+                log_path.display()
+            );
+        }
+        if Instant::now() > deadline {
+            anyhow::bail!(
+                "Dashboard did not start within {}s, see {}",
+                STARTUP_TIMEOUT.as_secs(),
+                log_path.display()
+            );
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+pub fn handle_dashboard_stop() -> Result<()> {
+    let Some(instance) = DashboardInstance::running()? else {
+        status!("{} No dashboard is running", "💡".cyan());
+        return Ok(());
+    };
+
+    terminate_process(instance.pid)?;
+    let deadline = Instant::now() + STOP_TIMEOUT;
+    // `running` also clears the record once the server stops answering
+    while DashboardInstance::running()?.is_some() {
+        if Instant::now() > deadline {
+            anyhow::bail!(
+                "Dashboard (pid {}) is still running after {}s",
+                instance.pid,
+                STOP_TIMEOUT.as_secs()
+            );
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+
+    status!("{} Stopped dashboard (pid {})", "✅".green(), instance.pid);
+    Ok(())
+}
+
+pub fn handle_dashboard_status() -> Result<()> {
+    let Some(instance) = DashboardInstance::running()? else {
+        status!("{} No dashboard is running", "💡".cyan());
+        return Ok(());
+    };
+    status!(
+        "{} Dashboard running at {} (pid {}, since {})",
+        "✅".green(),
+        instance.url().cyan(),
+        instance.pid,
+        instance
+            .started_at
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M:%S")
+    );
+
+    // A listening port alone does not mean the dashboard can serve requests
+    let health = instance
+        .health()
+        .with_context(|| format!("Dashboard at {} is not healthy", instance.url()))?;
+    println!("  {} {}", "Version:".bright_black(), health.version);
+    println!(
+        "  {} {}",
+        "Uptime:".bright_black(),
+        format_uptime(health.uptime_secs)
+    );
+    println!(
+        "  {} {}",
+        "State:".bright_black(),
+        health.state_path.display()
+    );
+    println!("  {} {}", "Worktrees:".bright_black(), health.worktrees);
+    println!(
+        "  {} {}",
+        "Last refresh:".bright_black(),
+        health.last_payload_built_at.map_or_else(
+            || "not yet requested".to_string(),
+            |at| at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        )
+    );
+    Ok(())
+}
+
+fn format_uptime(secs: u64) -> String {
+    let (days, hours, minutes) = (secs / 86400, secs / 3600 % 24, secs / 60 % 60);
+    match (days, hours, minutes) {
+        (0, 0, 0) => format!("{secs}s"),
+        (0, 0, _) => format!("{minutes}m"),
+        (0, _, _) => format!("{hours}h {minutes}m"),
+        _ => format!("{days}d {hours}h"),
+    }
 }
@@ -1,11 +1,20 @@
 use anyhow::{Context, Result};
+use chrono::Utc;
 use colored::Colorize;
+use std::fs;
 
-use crate::git::{execute_git, has_unpushed_commits, is_working_tree_clean};
+use crate::audit;
+use crate::error::XlaudeError;
+use crate::git::{self, HeadState, execute_git, has_unpushed_commits, is_working_tree_clean};
 use crate::input::{get_command_arg, smart_confirm};
-use crate::state::{WorktreeInfo, XlaudeState};
+use crate::merged;
+use crate::sessions::{self, AgentSession};
+use crate::state::{TrashedBranch, TrashedWorktree, WorktreeInfo, XlaudeState, get_state_path};
 use crate::utils::execute_in_dir;
 
+// Saved uncommitted changes of deleted worktrees, next to the state file
+const TRASH_DIR: &str = "trash";
+
 /// Represents the result of various checks performed before deletion
 struct DeletionChecks {
     has_uncommitted_changes: bool,
@@ -28,44 +37,84 @@ impl DeletionChecks {
 struct DeletionConfig {
     is_interactive: bool,
     worktree_exists: bool,
+    // The current directory is the worktree or one of its subdirectories
     is_current_directory: bool,
+    // Unmerged branches are force deleted without asking, after `--force`
+    // or a confirmed batch summary
+    force_delete_confirmed: bool,
+    keep_branch: bool,
+    // Captured before the worktree directory is removed
+    head_state: Option<HeadState>,
 }
 
 impl DeletionConfig {
-    fn from_env(worktree_info: &WorktreeInfo) -> Result<Self> {
+    fn from_env(worktree_info: &WorktreeInfo, force: bool, keep_branch: bool) -> Result<Self> {
         let current_dir = std::env::current_dir()?;
+        let current_dir = fs::canonicalize(&current_dir).unwrap_or(current_dir);
+        let worktree_dir =
+            fs::canonicalize(&worktree_info.path).unwrap_or_else(|_| worktree_info.path.clone());
 
         Ok(Self {
             is_interactive: std::env::var("XLAUDE_NON_INTERACTIVE").is_err(),
             worktree_exists: worktree_info.path.exists(),
-            is_current_directory: current_dir == worktree_info.path,
+            is_current_directory: current_dir.starts_with(&worktree_dir),
+            force_delete_confirmed: force,
+            keep_branch,
+            head_state: git::head_state(&worktree_info.path, &worktree_info.branch),
         })
     }
 }
 
-pub fn handle_delete(name: Option<String>) -> Result<()> {
+pub fn handle_delete(
+    names: Vec<String>,
+    repo: Option<String>,
+    merged: bool,
+    kill_session: bool,
+    force: bool,
+    keep_branch: bool,
+) -> Result<()> {
+    // `--force` skips every prompt, including the one for running agents
+    let kill_session = kill_session || force;
+    if names.len() > 1 || repo.is_some() || merged {
+        return delete_many(names, repo, merged, kill_session, force, keep_branch);
+    }
+
     let mut state = XlaudeState::load()?;
 
     // Get name from CLI args or pipe
-    let target_name = get_command_arg(name)?;
+    let target_name = get_command_arg(names.into_iter().next())?;
     let (key, worktree_info) = find_worktree_to_delete(&state, target_name)?;
-    let config = DeletionConfig::from_env(&worktree_info)?;
+    let config = DeletionConfig::from_env(&worktree_info, force, keep_branch)?;
+    // The automatic lock of a running agent is handled by the session check below
+    if worktree_info
+        .active_lock()
+        .is_some_and(|lock| lock.pid.is_none())
+    {
+        worktree_info.ensure_unlocked(force)?;
+    }
 
-    println!(
+    status!(
         "{} Checking worktree '{}'...",
         "🔍".yellow(),
         worktree_info.name.cyan()
     );
 
+    // Removing the directory under a running agent leaves it orphaned
+    let running = sessions::running_in(&worktree_info.path);
+    if !running.is_empty() && !stop_agent_sessions(&running, kill_session, &config)? {
+        status!("{} Cancelled", "❌".red());
+        return Err(XlaudeError::Cancelled.into());
+    }
+
     // Handle case where worktree directory doesn't exist
     if !config.worktree_exists {
-        if !handle_missing_worktree(&worktree_info, &config)? {
-            println!("{} Cancelled", "❌".red());
-            return Ok(());
+        if !force && !handle_missing_worktree(&worktree_info, &config)? {
+            status!("{} Cancelled", "❌".red());
+            return Err(XlaudeError::Cancelled.into());
         }
-    } else {
+    } else if !force {
         // Check branch status first (for output consistency)
-        println!(
+        status!(
             "{} Checking branch '{}'...",
             "🔍".yellow(),
             worktree_info.branch
@@ -75,19 +124,20 @@ pub fn handle_delete(name: Option<String>) -> Result<()> {
         let checks = perform_deletion_checks(&worktree_info)?;
 
         if !confirm_deletion(&worktree_info, &checks, &config)? {
-            println!("{} Cancelled", "❌".red());
-            return Ok(());
+            status!("{} Cancelled", "❌".red());
+            return Err(XlaudeError::Cancelled.into());
         }
     }
 
     // Execute deletion
-    perform_deletion(&worktree_info, &config)?;
+    let trashed = perform_deletion(&worktree_info, &config)?;
 
     // Update state
-    state.worktrees.remove(&key);
+    forget_worktree(&mut state, &key, &worktree_info, trashed);
     state.save()?;
+    record_deletion(&key, &worktree_info);
 
-    println!(
+    status!(
         "{} Worktree '{}' deleted successfully",
         "✅".green(),
         worktree_info.name.cyan()
@@ -95,6 +145,253 @@ pub fn handle_delete(name: Option<String>) -> Result<()> {
     Ok(())
 }
 
+/// A worktree selected for deletion together with what was found about it
+struct PlannedDeletion {
+    key: String,
+    info: WorktreeInfo,
+    config: DeletionConfig,
+    checks: DeletionChecks,
+    running: Vec<AgentSession>,
+}
+
+/// Delete several worktrees, selected by name or by `--repo`/`--merged`,
+/// behind a single confirmation
+fn delete_many(
+    names: Vec<String>,
+    repo: Option<String>,
+    merged: bool,
+    kill_session: bool,
+    force: bool,
+    keep_branch: bool,
+) -> Result<()> {
+    if merged {
+        merged::refresh()?;
+    }
+    let state = XlaudeState::load()?;
+    let selected = |info: &WorktreeInfo| {
+        repo.as_ref().is_none_or(|r| &info.repo_name == r) && (!merged || info.merged_at.is_some())
+    };
+
+    // Selectors narrow the named worktrees, or all of them when none are named
+    let mut candidates: Vec<(String, WorktreeInfo)> = Vec::new();
+    if names.is_empty() {
+        candidates.extend(
+            state
+                .worktrees
+                .iter()
+                .filter(|(_, w)| selected(w))
+                .map(|(k, w)| (k.clone(), w.clone())),
+        );
+        candidates.sort_by(|a, b| a.0.cmp(&b.0));
+    } else {
+        for name in names {
+            let (key, info) = find_worktree_to_delete(&state, Some(name))?;
+            if selected(&info) && !candidates.iter().any(|(k, _)| *k == key) {
+                candidates.push((key, info));
+            }
+        }
+    }
+
+    delete_candidates(state, candidates, kill_session, force, keep_branch)
+}
+
+/// Check the given worktrees, show one summary and delete them after a
+/// single confirmation.
+pub fn delete_candidates(
+    mut state: XlaudeState,
+    candidates: Vec<(String, WorktreeInfo)>,
+    kill_session: bool,
+    force: bool,
+    keep_branch: bool,
+) -> Result<()> {
+    let mut planned = Vec::new();
+    let mut skipped = Vec::new();
+    for (key, info) in candidates {
+        if let Some(lock) = info.active_lock().filter(|lock| lock.pid.is_none())
+            && !force
+        {
+            skipped.push((key, format!("locked ({})", lock.describe())));
+            continue;
+        }
+        let running = sessions::running_in(&info.path);
+        if !running.is_empty() && !kill_session {
+            skipped.push((
+                key,
+                "agent session running, pass --kill-session".to_string(),
+            ));
+            continue;
+        }
+
+        status!("{} Checking worktree '{}'...", "🔍".yellow(), key.cyan());
+        let checks = if info.path.exists() {
+            perform_deletion_checks(&info)?
+        } else {
+            let (branch_merged_via_git, branch_merged_via_pr) =
+                check_branch_merge_status(&info.main_repo_path(), &info.branch)?;
+            DeletionChecks {
+                has_uncommitted_changes: false,
+                has_unpushed_commits: false,
+                branch_merged_via_git,
+                branch_merged_via_pr,
+            }
+        };
+
+        let mut config = DeletionConfig::from_env(&info, force, keep_branch)?;
+        // The summary below covers unmerged branches too
+        config.force_delete_confirmed |= config.is_interactive;
+        planned.push(PlannedDeletion {
+            key,
+            info,
+            config,
+            checks,
+            running,
+        });
+    }
+
+    println!();
+    for (key, reason) in &skipped {
+        status!("{} Skipping '{}': {}", "⏭️ ".yellow(), key.cyan(), reason);
+    }
+    if planned.is_empty() {
+        status!("{} No worktrees to delete", "💡".cyan());
+        return Ok(());
+    }
+
+    status!("{} Worktrees to delete:", "🗑️ ".yellow());
+    for plan in &planned {
+        println!(
+            "  {} ({}){}",
+            plan.key.cyan(),
+            plan.info.branch,
+            describe_checks(plan)
+        );
+    }
+    let is_interactive = planned[0].config.is_interactive;
+    if planned[0].config.force_delete_confirmed
+        && !keep_branch
+        && planned.iter().any(|p| !p.checks.branch_is_merged())
+    {
+        status!(
+            "  {} Unmerged branches will be force deleted, restore them with {}",
+            "ℹ️".blue(),
+            "xlaude undo-branch".cyan()
+        );
+    }
+
+    // Pending work flips the default, as for a single worktree
+    let has_pending_work = planned.iter().any(|p| p.checks.has_pending_work());
+    let prompt = format!("Delete {} worktrees?", planned.len());
+    if !force && !smart_confirm(&prompt, !has_pending_work)? {
+        if has_pending_work && !is_interactive {
+            return Err(XlaudeError::Refused(
+                "Some worktrees have uncommitted changes or unpushed commits".to_string(),
+            )
+            .into());
+        }
+        status!("{} Cancelled", "❌".red());
+        return Err(XlaudeError::Cancelled.into());
+    }
+
+    let mut deleted = Vec::new();
+    let mut failed = 0;
+    for plan in planned {
+        println!();
+        let result = plan
+            .running
+            .iter()
+            .try_for_each(|session| sessions::terminate(session).map(|_| ()))
+            .and_then(|()| perform_deletion(&plan.info, &plan.config));
+        match result {
+            Ok(trashed) => {
+                forget_worktree(&mut state, &plan.key, &plan.info, trashed);
+                status!("{} Worktree '{}' deleted", "✅".green(), plan.key.cyan());
+                deleted.push((plan.key, plan.info));
+            }
+            Err(err) => {
+                failed += 1;
+                status!(
+                    "{} Failed to delete '{}': {err:#}",
+                    "❌".red(),
+                    plan.key.cyan()
+                );
+            }
+        }
+    }
+    // Keep the worktrees that were removed out of state even if others failed
+    state.save()?;
+    for (key, info) in &deleted {
+        record_deletion(key, info);
+    }
+
+    if failed > 0 {
+        anyhow::bail!("Failed to delete {failed} worktree(s)");
+    }
+    Ok(())
+}
+
+/// Short status suffix for a worktree in the batch summary
+fn describe_checks(plan: &PlannedDeletion) -> String {
+    let mut notes = Vec::new();
+    if !plan.config.worktree_exists {
+        notes.push("directory missing".yellow().to_string());
+    }
+    if plan.checks.has_uncommitted_changes {
+        notes.push("uncommitted changes".red().to_string());
+    }
+    if plan.checks.has_unpushed_commits {
+        notes.push("unpushed commits".red().to_string());
+    }
+    if !plan.checks.branch_is_merged() {
+        notes.push("not merged".yellow().to_string());
+    }
+    if !plan.running.is_empty() {
+        notes.push("agent will be stopped".yellow().to_string());
+    }
+
+    if notes.is_empty() {
+        String::new()
+    } else {
+        format!(" - {}", notes.join(", "))
+    }
+}
+
+/// What a deletion leaves in the trash
+struct Trashed {
+    worktree: TrashedWorktree,
+    branch: Option<TrashedBranch>,
+}
+
+/// Move a deleted worktree from the worktree list to the trash
+fn forget_worktree(
+    state: &mut XlaudeState,
+    key: &str,
+    worktree_info: &WorktreeInfo,
+    trashed: Trashed,
+) {
+    state.worktrees.remove(key);
+    state.trash.extend(trashed.branch);
+    state.trashed_worktrees.push(trashed.worktree);
+    state.purge_expired_trash();
+    // Worktrees stacked on the deleted one move down to its own parent
+    state.reparent_children(
+        &worktree_info.repo_name,
+        &worktree_info.name,
+        worktree_info.parent.as_deref(),
+    );
+}
+
+fn record_deletion(key: &str, worktree_info: &WorktreeInfo) {
+    audit::record(
+        "delete",
+        key,
+        Some(format!(
+            "branch {} at {}",
+            worktree_info.branch,
+            worktree_info.path.display()
+        )),
+    );
+}
+
 /// Find the worktree to delete based on the provided name or current directory
 fn find_worktree_to_delete(
     state: &XlaudeState,
@@ -103,41 +400,71 @@ fn find_worktree_to_delete(
     if let Some(n) = name {
         // Find worktree by name across all projects
         state
-            .worktrees
-            .iter()
-            .find(|(_, w)| w.name == n)
+            .find_by_name(&n)
             .map(|(k, w)| (k.clone(), w.clone()))
-            .context(format!("Worktree '{n}' not found"))
+            .ok_or_else(|| XlaudeError::NotFound(format!("Worktree '{n}' not found")).into())
     } else {
         // Find worktree by current directory
         find_current_worktree(state)
     }
 }
 
-/// Find the worktree that matches the current directory
+/// Find the worktree containing the current directory
 fn find_current_worktree(state: &XlaudeState) -> Result<(String, WorktreeInfo)> {
     let current_dir = std::env::current_dir()?;
-    let dir_name = current_dir
-        .file_name()
-        .and_then(|n| n.to_str())
-        .context("Failed to get current directory name")?;
-
     state
-        .worktrees
-        .iter()
-        .find(|(_, w)| w.path.file_name().and_then(|n| n.to_str()) == Some(dir_name))
+        .find_by_path(&current_dir)
         .map(|(k, w)| (k.clone(), w.clone()))
-        .context("Current directory is not a managed worktree")
+        .ok_or_else(|| {
+            XlaudeError::NotFound("Current directory is not a managed worktree".to_string()).into()
+        })
+}
+
+/// Stop agents still running in the worktree, asking first unless
+/// `--kill-session` was given. Returns false if the user declined.
+fn stop_agent_sessions(
+    running: &[AgentSession],
+    kill_session: bool,
+    config: &DeletionConfig,
+) -> Result<bool> {
+    for session in running {
+        status!(
+            "{} Agent '{}' (pid {}) is still running in this worktree",
+            "⚠️ ".yellow(),
+            session.program,
+            session.pid
+        );
+    }
+
+    if !kill_session {
+        if !config.is_interactive {
+            return Err(XlaudeError::Refused(
+                "An agent session is still running in this worktree. Stop it first or pass --kill-session"
+                    .to_string(),
+            )
+            .into());
+        }
+        if !smart_confirm("Stop the running agent session and continue?", false)? {
+            return Ok(false);
+        }
+    }
+
+    for session in running {
+        if sessions::terminate(session)? {
+            status!("{} Stopped agent (pid {})", "✅".green(), session.pid);
+        }
+    }
+    Ok(true)
 }
 
 /// Handle the case where worktree directory doesn't exist
 fn handle_missing_worktree(worktree_info: &WorktreeInfo, _config: &DeletionConfig) -> Result<bool> {
-    println!(
+    status!(
         "{} Worktree directory not found at {}",
         "⚠️ ".yellow(),
         worktree_info.path.display()
     );
-    println!(
+    status!(
         "  {} The worktree may have been manually deleted",
         "ℹ️".blue()
     );
@@ -152,7 +479,7 @@ fn perform_deletion_checks(worktree_info: &WorktreeInfo) -> Result<DeletionCheck
         let has_unpushed_commits = has_unpushed_commits();
 
         // Check branch merge status in main repo
-        let main_repo_path = get_main_repo_path(worktree_info)?;
+        let main_repo_path = worktree_info.main_repo_path();
         let (branch_merged_via_git, branch_merged_via_pr) =
             check_branch_merge_status(&main_repo_path, &worktree_info.branch)?;
 
@@ -171,57 +498,50 @@ fn check_branch_merge_status(
     branch: &str,
 ) -> Result<(bool, bool)> {
     execute_in_dir(main_repo_path, || {
-        // Check traditional git merge
-        let output = std::process::Command::new("git")
-            .args(["branch", "--merged"])
-            .output()
-            .context("Failed to check merged branches")?;
-
-        let merged_branches = String::from_utf8_lossy(&output.stdout);
-        let is_merged_git = merged_branches
-            .lines()
-            .any(|line| line.trim().trim_start_matches('*').trim() == branch);
+        // Check traditional git merge. `git branch` output marks branches
+        // checked out here or in other worktrees, refs are listed bare.
+        let merged_branches = execute_git(&[
+            "for-each-ref",
+            "--merged=HEAD",
+            "--format=%(refname:short)",
+            "refs/heads",
+        ])
+        .context("Failed to check merged branches")?;
+        let is_merged_git = merged_branches.lines().any(|line| line == branch);
 
         // Check if merged via PR (works for squash merge)
-        let is_merged_pr = check_branch_merged_via_pr(branch);
+        let is_merged_pr = merged::merged_pr_branches(main_repo_path).contains(branch);
 
         Ok((is_merged_git, is_merged_pr))
     })
 }
 
-/// Check if branch was merged via GitHub PR
-fn check_branch_merged_via_pr(branch: &str) -> bool {
-    std::process::Command::new("gh")
-        .args([
-            "pr", "list", "--state", "merged", "--head", branch, "--json", "number",
-        ])
-        .output()
-        .ok()
-        .filter(|output| output.status.success())
-        .and_then(|output| String::from_utf8(output.stdout).ok())
-        .and_then(|json| serde_json::from_str::<Vec<serde_json::Value>>(&json).ok())
-        .map(|prs| !prs.is_empty())
-        .unwrap_or(false)
-}
-
 /// Confirm deletion with the user based on checks
 fn confirm_deletion(
     worktree_info: &WorktreeInfo,
     checks: &DeletionChecks,
-    _config: &DeletionConfig,
+    config: &DeletionConfig,
 ) -> Result<bool> {
     // Show warnings for pending work
     if checks.has_pending_work() {
         show_pending_work_warnings(checks);
 
-        return smart_confirm("Are you sure you want to delete this worktree?", false);
+        let confirmed = smart_confirm("Are you sure you want to delete this worktree?", false)?;
+        if !confirmed && !config.is_interactive {
+            return Err(XlaudeError::Refused(format!(
+                "Worktree '{}' has uncommitted changes or unpushed commits",
+                worktree_info.name
+            ))
+            .into());
+        }
+        return Ok(confirmed);
     }
 
     // Show branch merge status
     if !checks.branch_is_merged() {
         show_unmerged_branch_warning(worktree_info);
     } else if checks.branch_merged_via_pr && !checks.branch_merged_via_git {
-        println!("  {} Branch was merged via PR", "ℹ️".blue());
+        status!("  {} Branch was merged via PR", "ℹ️".blue());
     }
 
     // Ask for confirmation
@@ -230,28 +550,29 @@ fn confirm_deletion(
 
 /// Show warnings for uncommitted changes or unpushed commits
 fn show_pending_work_warnings(checks: &DeletionChecks) {
-    println!();
+    status!("");
     if checks.has_uncommitted_changes {
-        println!("{} You have uncommitted changes", "⚠️ ".red());
+        status!("{} You have uncommitted changes", "⚠️ ".red());
     }
     if checks.has_unpushed_commits {
-        println!("{} You have unpushed commits", "⚠️ ".red());
+        status!("{} You have unpushed commits", "⚠️ ".red());
     }
 }
 
 /// Show warning for unmerged branch
 fn show_unmerged_branch_warning(worktree_info: &WorktreeInfo) {
-    println!(
+    status!(
         "{} Branch '{}' is not fully merged",
         "⚠️ ".yellow(),
         worktree_info.branch.cyan()
     );
-    println!("  {} No merged PR found for this branch", "ℹ️".blue());
+    status!("  {} No merged PR found for this branch", "ℹ️".blue());
 }
 
 /// Perform the actual deletion of worktree and branch
-fn perform_deletion(worktree_info: &WorktreeInfo, config: &DeletionConfig) -> Result<()> {
-    let main_repo_path = get_main_repo_path(worktree_info)?;
+fn perform_deletion(worktree_info: &WorktreeInfo, config: &DeletionConfig) -> Result<Trashed> {
+    let main_repo_path = worktree_info.main_repo_path();
+    let worktree = trash_worktree(worktree_info, config);
 
     // Change to main repo if we're deleting current directory
     if config.is_current_directory {
@@ -259,28 +580,78 @@ fn perform_deletion(worktree_info: &WorktreeInfo, config: &DeletionConfig) -> Re
             .context("Failed to change to main repository")?;
     }
 
-    execute_in_dir(&main_repo_path, || {
+    let branch = execute_in_dir(&main_repo_path, || {
         // Remove or prune worktree
         remove_worktree(worktree_info, config)?;
 
         // Delete branch
-        delete_branch(worktree_info, config)?;
+        delete_branch(worktree_info, config)
+    })?;
 
-        Ok(())
-    })
+    // Only this process moved; the calling shell still sits in the removed directory
+    if config.is_current_directory {
+        status!(
+            "{} Your shell was inside the deleted worktree, leave it with: cd {}",
+            "💡".cyan(),
+            shell_words::quote(&main_repo_path.display().to_string())
+        );
+    }
+    Ok(Trashed { worktree, branch })
+}
+
+/// Record the worktree's commit and save its uncommitted work before the
+/// directory goes away, so `xlaude trash restore` can bring it back.
+fn trash_worktree(worktree_info: &WorktreeInfo, config: &DeletionConfig) -> TrashedWorktree {
+    let deleted_at = Utc::now();
+    let mut trashed = TrashedWorktree {
+        info: worktree_info.clone(),
+        deleted_at,
+        head: None,
+        patch: None,
+    };
+    if !config.worktree_exists {
+        return trashed;
+    }
+
+    trashed.head = git::git_output_bytes(&worktree_info.path, &["rev-parse", "HEAD"])
+        .ok()
+        .map(|head| String::from_utf8_lossy(&head).trim().to_string());
+    let saved = git::diff_with_untracked(&worktree_info.path).and_then(|diff| {
+        if diff.is_empty() {
+            return Ok(None);
+        }
+        let dir = get_state_path()?.with_file_name(TRASH_DIR);
+        fs::create_dir_all(&dir).context("Failed to create trash directory")?;
+        let patch = dir.join(format!(
+            "{}-{}-{}.patch",
+            worktree_info.repo_name,
+            worktree_info.name,
+            deleted_at.format("%Y%m%dT%H%M%S")
+        ));
+        fs::write(&patch, diff).context("Failed to save uncommitted changes")?;
+        Ok(Some(patch))
+    });
+    match saved {
+        Ok(patch) => trashed.patch = patch,
+        Err(err) => estatus!(
+            "{} Uncommitted changes were not saved to the trash: {err:#}",
+            "⚠️".yellow()
+        ),
+    }
+    trashed
 }
 
 /// Remove the worktree from git
 fn remove_worktree(worktree_info: &WorktreeInfo, config: &DeletionConfig) -> Result<()> {
     if config.worktree_exists {
-        println!("{} Removing worktree...", "🗑️ ".yellow());
+        status!("{} Removing worktree...", "🗑️ ".yellow());
 
         // First attempt: try normal removal
         let result = execute_git(&["worktree", "remove", worktree_info.path.to_str().unwrap()]);
 
         // If failed, might be due to submodules - try with force flag
         if result.is_err() {
-            println!(
+            status!(
                 "{} Standard removal failed, trying force removal...",
                 "⚠️ ".yellow()
             );
@@ -293,15 +664,41 @@ fn remove_worktree(worktree_info: &WorktreeInfo, config: &DeletionConfig) -> Res
             .context("Failed to force remove worktree")?;
         }
     } else {
-        println!("{} Pruning non-existent worktree...", "🗑️ ".yellow());
+        status!("{} Pruning non-existent worktree...", "🗑️ ".yellow());
         execute_git(&["worktree", "prune"]).context("Failed to prune worktree")?;
     }
     Ok(())
 }
 
 /// Delete the branch from git
-fn delete_branch(worktree_info: &WorktreeInfo, config: &DeletionConfig) -> Result<()> {
-    println!(
+fn delete_branch(
+    worktree_info: &WorktreeInfo,
+    config: &DeletionConfig,
+) -> Result<Option<TrashedBranch>> {
+    if config.keep_branch {
+        status!("{} Branch '{}' kept", "ℹ️ ".blue(), worktree_info.branch);
+        return Ok(None);
+    }
+    let local = format!("refs/heads/{}", worktree_info.branch);
+    if execute_git(&["show-ref", "--verify", "--quiet", &local]).is_err() {
+        status!(
+            "{} Branch '{}' no longer exists, nothing to delete",
+            "ℹ️ ".blue(),
+            worktree_info.branch
+        );
+        return Ok(None);
+    }
+    // The branch was not what the worktree had checked out, so leave it alone
+    if config.head_state == Some(HeadState::Detached) {
+        status!(
+            "{} Worktree was on a detached HEAD, branch '{}' kept",
+            "ℹ️ ".blue(),
+            worktree_info.branch
+        );
+        return Ok(None);
+    }
+
+    status!(
         "{} Deleting branch '{}'...",
         "🗑️ ".yellow(),
         worktree_info.branch
@@ -309,35 +706,56 @@ fn delete_branch(worktree_info: &WorktreeInfo, config: &DeletionConfig) -> Resul
 
     // First try safe delete
     if execute_git(&["branch", "-d", &worktree_info.branch]).is_ok() {
-        println!("{} Branch deleted", "✅".green());
-        return Ok(());
+        status!("{} Branch deleted", "✅".green());
+        return Ok(None);
     }
 
     // Branch is not fully merged, ask for force delete
-    if !config.is_interactive {
-        println!("{} Branch kept (not fully merged)", "ℹ️ ".blue());
-        return Ok(());
+    if !config.is_interactive && !config.force_delete_confirmed {
+        status!("{} Branch kept (not fully merged)", "ℹ️ ".blue());
+        return Ok(None);
     }
 
-    let force_delete = smart_confirm("Branch is not fully merged. Force delete?", false)?;
+    let force_delete = config.force_delete_confirmed
+        || smart_confirm("Branch is not fully merged. Force delete?", false)?;
 
-    if force_delete {
-        execute_git(&["branch", "-D", &worktree_info.branch])
-            .context("Failed to force delete branch")?;
-        println!("{} Branch force deleted", "✅".green());
-    } else {
-        println!("{} Branch kept", "ℹ️ ".blue());
+    if !force_delete {
+        status!("{} Branch kept", "ℹ️ ".blue());
+        return Ok(None);
     }
 
-    Ok(())
-}
+    // Keep the commits reachable so `xlaude undo-branch` can bring the branch back
+    let trashed = trash_branch(worktree_info)?;
+    execute_git(&["branch", "-D", &worktree_info.branch])
+        .context("Failed to force delete branch")?;
+    status!("{} Branch force deleted", "✅".green());
+    status!(
+        "  {} Restore it with: {}",
+        "💡".cyan(),
+        format!("xlaude undo-branch {}", worktree_info.name).cyan()
+    );
 
-/// Get the path to the main repository from worktree info
-fn get_main_repo_path(worktree_info: &WorktreeInfo) -> Result<std::path::PathBuf> {
-    let parent = worktree_info
-        .path
-        .parent()
-        .context("Failed to get parent directory")?;
+    Ok(Some(trashed))
+}
 
-    Ok(parent.join(&worktree_info.repo_name))
+/// Tag the branch tip before it is force deleted
+fn trash_branch(worktree_info: &WorktreeInfo) -> Result<TrashedBranch> {
+    let commit = execute_git(&["rev-parse", &worktree_info.branch])
+        .context("Failed to resolve branch commit")?;
+    let deleted_at = Utc::now();
+    let tag = format!(
+        "xlaude/trash/{}/{}",
+        worktree_info.name,
+        deleted_at.format("%Y%m%d-%H%M%S")
+    );
+    execute_git(&["tag", &tag, &commit]).context("Failed to tag branch before deleting it")?;
+
+    Ok(TrashedBranch {
+        repo_name: worktree_info.repo_name.clone(),
+        name: worktree_info.name.clone(),
+        branch: worktree_info.branch.clone(),
+        commit,
+        tag,
+        deleted_at,
+    })
 }
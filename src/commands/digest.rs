@@ -0,0 +1,167 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Local, Utc};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::audit;
+use crate::claude::get_claude_sessions;
+use crate::codex;
+use crate::commands::stats::{is_creation, is_removal};
+use crate::git::{execute_git, resolve_base_ref};
+use crate::state::XlaudeState;
+
+#[derive(Debug, Default)]
+struct RepoDigest {
+    created: usize,
+    merged: usize,
+    deleted: usize,
+    branches: Vec<BranchDigest>,
+}
+
+#[derive(Debug)]
+struct BranchDigest {
+    name: String,
+    branch: String,
+    commits: usize,
+    sessions: usize,
+    cost_usd: f64,
+}
+
+pub fn handle_digest(since: String) -> Result<()> {
+    let start = Utc::now() - parse_since(&since)?;
+    let state = XlaudeState::load()?;
+    let mut repos: BTreeMap<String, RepoDigest> = BTreeMap::new();
+
+    for entry in audit::read_entries()? {
+        if entry.timestamp < start {
+            continue;
+        }
+        let Some((repo, _)) = entry.target.split_once('/') else {
+            continue;
+        };
+        let (created, merged, deleted) = match entry.action.as_str() {
+            action if is_creation(action) => (1, 0, 0),
+            "merged" => (0, 1, 0),
+            action if is_removal(action) => (0, 0, 1),
+            _ => continue,
+        };
+        let digest = repos.entry(repo.to_string()).or_default();
+        digest.created += created;
+        digest.merged += merged;
+        digest.deleted += deleted;
+    }
+
+    for info in state.worktrees.values() {
+        let claude_sessions: Vec<_> = get_claude_sessions(&info.path)
+            .into_iter()
+            .filter(|s| s.last_timestamp.is_some_and(|ts| ts >= start))
+            .collect();
+        let codex_sessions = codex::recent_sessions(&info.path, usize::MAX)
+            .map(|(sessions, _)| sessions)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|s| s.last_timestamp.is_some_and(|ts| ts >= start))
+            .count();
+
+        let branch = BranchDigest {
+            name: info.name.clone(),
+            branch: info.branch.clone(),
+            commits: commits_since(&info.path, start),
+            sessions: claude_sessions.len() + codex_sessions,
+            cost_usd: claude_sessions.iter().map(|s| s.cost_usd).sum(),
+        };
+        if branch.commits > 0 || branch.sessions > 0 {
+            repos
+                .entry(info.repo_name.clone())
+                .or_default()
+                .branches
+                .push(branch);
+        }
+    }
+
+    print!("{}", render_markdown(&repos, start));
+    Ok(())
+}
+
+/// Parse a look-back window such as `7d`, `2w` or `12h`.
+fn parse_since(value: &str) -> Result<Duration> {
+    let value = value.trim();
+    let unit = value.chars().last().unwrap_or_default();
+    let amount: i64 = value[..value.len().saturating_sub(unit.len_utf8())]
+        .parse()
+        .with_context(|| format!("Invalid --since '{value}', expected e.g. 7d, 2w or 12h"))?;
+    match unit {
+        'h' => Ok(Duration::hours(amount)),
+        'd' => Ok(Duration::days(amount)),
+        'w' => Ok(Duration::weeks(amount)),
+        _ => anyhow::bail!("Invalid --since '{value}', expected e.g. 7d, 2w or 12h"),
+    }
+}
+
+/// Commits on the worktree branch since `start` that are not on its base.
+fn commits_since(path: &std::path::Path, start: DateTime<Utc>) -> usize {
+    let (Some(dir), Some(base)) = (path.to_str(), resolve_base_ref(path)) else {
+        return 0;
+    };
+    let since = format!("--since={}", start.to_rfc3339());
+    let range = format!("{base}..HEAD");
+    execute_git(&["-C", dir, "rev-list", "--count", &since, &range])
+        .ok()
+        .and_then(|count| count.parse().ok())
+        .unwrap_or(0)
+}
+
+fn render_markdown(repos: &BTreeMap<String, RepoDigest>, start: DateTime<Utc>) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "# Agent work since {}\n",
+        start.with_timezone(&Local).format("%Y-%m-%d")
+    );
+    if repos.is_empty() {
+        out.push_str("No worktree activity.\n");
+        return out;
+    }
+
+    for (repo, digest) in repos {
+        let commits: usize = digest.branches.iter().map(|b| b.commits).sum();
+        let sessions: usize = digest.branches.iter().map(|b| b.sessions).sum();
+        let cost: f64 = digest.branches.iter().map(|b| b.cost_usd).sum();
+
+        let _ = writeln!(out, "## {repo}\n");
+        let _ = writeln!(
+            out,
+            "- Worktrees: {} created, {} merged, {} deleted",
+            digest.created, digest.merged, digest.deleted
+        );
+        let _ = writeln!(out, "- Commits: {commits}");
+        let _ = write!(out, "- Sessions: {sessions}");
+        if cost > 0.0 {
+            let _ = write!(out, " (${cost:.2})");
+        }
+        out.push('\n');
+
+        for branch in &digest.branches {
+            let _ = write!(
+                out,
+                "  - `{}` ({}): {} commit{}, {} session{}",
+                branch.branch,
+                branch.name,
+                branch.commits,
+                plural(branch.commits),
+                branch.sessions,
+                plural(branch.sessions)
+            );
+            if branch.cost_usd > 0.0 {
+                let _ = write!(out, ", ${:.2}", branch.cost_usd);
+            }
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 { "" } else { "s" }
+}
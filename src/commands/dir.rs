@@ -1,5 +1,6 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
 
+use crate::error::XlaudeError;
 use crate::input::{get_command_arg, smart_select};
 use crate::state::{WorktreeInfo, XlaudeState};
 
@@ -7,7 +8,10 @@ pub fn handle_dir(name: Option<String>) -> Result<()> {
     let state = XlaudeState::load()?;
 
     if state.worktrees.is_empty() {
-        anyhow::bail!("No worktrees found. Create one first with 'xlaude create'");
+        return Err(XlaudeError::NotFound(
+            "No worktrees found. Create one first with 'xlaude create'".to_string(),
+        )
+        .into());
     }
 
     // Get name from CLI args or pipe
@@ -17,11 +21,9 @@ pub fn handle_dir(name: Option<String>) -> Result<()> {
     let (_key, worktree_info) = if let Some(n) = target_name {
         // Find worktree by name across all projects
         state
-            .worktrees
-            .iter()
-            .find(|(_, w)| w.name == n)
+            .find_by_name(&n)
             .map(|(k, w)| (k.clone(), w.clone()))
-            .context(format!("Worktree '{n}' not found"))?
+            .ok_or_else(|| XlaudeError::NotFound(format!("Worktree '{n}' not found")))?
     } else {
         // Interactive selection - show repo/name format
         let worktree_list: Vec<(String, WorktreeInfo)> = state
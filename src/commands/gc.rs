@@ -0,0 +1,243 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::claude;
+use crate::codex;
+use crate::input::{smart_confirm, smart_select};
+use crate::state::{XlaudeState, get_state_path};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GcAction {
+    Archive,
+    Delete,
+}
+
+/// Session data left behind by a project that no longer exists.
+struct Orphan {
+    agent: &'static str,
+    // A Claude project directory or a single Codex session file
+    path: PathBuf,
+    // Location under the archive directory
+    archive_name: PathBuf,
+    project: PathBuf,
+    size: u64,
+}
+
+/// Find agent session files recorded in directories that are neither tracked
+/// nor present on disk, and archive or delete them.
+pub fn handle_gc(action: Option<GcAction>) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let orphans = find_orphans(&state)?;
+
+    if orphans.is_empty() {
+        status!("{} No session files from removed worktrees", "✨".green());
+        return Ok(());
+    }
+
+    for orphan in &orphans {
+        status!(
+            "  {} {} {} ({})",
+            "🗑️".yellow(),
+            orphan.agent.cyan(),
+            orphan.project.display(),
+            format_size(orphan.size)
+        );
+    }
+    let total: u64 = orphans.iter().map(|orphan| orphan.size).sum();
+    status!(
+        "{} {} session location{} from removed worktrees, {} in total",
+        "📦".cyan(),
+        orphans.len(),
+        if orphans.len() == 1 { "" } else { "s" },
+        format_size(total)
+    );
+
+    let archive_dir = get_state_path()?.with_file_name("archive");
+    let action = match action {
+        Some(action) => {
+            let prompt = match action {
+                GcAction::Archive => format!("Move them to {}?", archive_dir.display()),
+                GcAction::Delete => "Delete them permanently?".to_string(),
+            };
+            smart_confirm(&prompt, false)?.then_some(action)
+        }
+        None => {
+            let choices = [Some(GcAction::Archive), Some(GcAction::Delete), None];
+            let selection =
+                smart_select(
+                    "What should happen to them?",
+                    &choices,
+                    |choice| match choice {
+                        Some(GcAction::Archive) => format!("Archive to {}", archive_dir.display()),
+                        Some(GcAction::Delete) => "Delete".to_string(),
+                        None => "Keep".to_string(),
+                    },
+                )?;
+            selection.and_then(|index| choices[index])
+        }
+    };
+    let Some(action) = action else {
+        status!(
+            "{} Nothing changed. Run with --archive or --delete to clean up.",
+            "💡".cyan()
+        );
+        return Ok(());
+    };
+
+    for orphan in &orphans {
+        match action {
+            GcAction::Archive => {
+                let destination = archive_dir.join(&orphan.archive_name);
+                move_path(&orphan.path, &destination)
+            }
+            GcAction::Delete => remove_path(&orphan.path),
+        }
+        .with_context(|| format!("Failed to clean up {}", orphan.path.display()))?;
+    }
+
+    status!(
+        "{} {} {}",
+        "✅".green(),
+        match action {
+            GcAction::Archive => "Archived",
+            GcAction::Delete => "Deleted",
+        },
+        format_size(total)
+    );
+    Ok(())
+}
+
+fn find_orphans(state: &XlaudeState) -> Result<Vec<Orphan>> {
+    let is_orphaned = |project: &Path| {
+        !project.as_os_str().is_empty()
+            && !project.exists()
+            && !state.worktrees.values().any(|info| {
+                info.path == project
+                    || info
+                        .path
+                        .canonicalize()
+                        .is_ok_and(|canonical| canonical == project)
+            })
+    };
+
+    let mut orphans = Vec::new();
+    if let Some(projects_dir) = claude::projects_dir()
+        && let Ok(entries) = fs::read_dir(&projects_dir)
+    {
+        let mut dirs: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect();
+        dirs.sort();
+        for dir in dirs {
+            // Directories without a recorded project are left alone
+            let Some(project) = claude::recorded_project_path(&dir) else {
+                continue;
+            };
+            if is_orphaned(&project) {
+                orphans.push(Orphan {
+                    agent: "claude",
+                    archive_name: Path::new("claude").join(dir.file_name().unwrap_or_default()),
+                    size: disk_usage(&dir),
+                    path: dir,
+                    project,
+                });
+            }
+        }
+    }
+
+    if let Some(root) = codex::sessions_root() {
+        for (file, project) in codex::all_session_files()? {
+            if is_orphaned(&project) {
+                let relative = file.strip_prefix(&root).unwrap_or(&file).to_path_buf();
+                orphans.push(Orphan {
+                    agent: "codex",
+                    archive_name: Path::new("codex").join(relative),
+                    size: disk_usage(&file),
+                    path: file,
+                    project,
+                });
+            }
+        }
+    }
+
+    Ok(orphans)
+}
+
+fn disk_usage(path: &Path) -> u64 {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| disk_usage(&entry.path()))
+                .sum()
+        })
+        .unwrap_or_default()
+}
+
+fn remove_path(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+// The archive may live on another filesystem, where renaming fails
+fn move_path(from: &Path, to: &Path) -> std::io::Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    copy_path(from, to)?;
+    remove_path(from)
+}
+
+fn copy_path(from: &Path, to: &Path) -> std::io::Result<()> {
+    if !from.is_dir() {
+        return fs::copy(from, to).map(|_| ());
+    }
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        copy_path(&entry.path(), &to.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+}
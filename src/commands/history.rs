@@ -0,0 +1,50 @@
+use anyhow::Result;
+use chrono::Local;
+use colored::Colorize;
+
+use crate::audit;
+
+pub fn handle_history(filter: Option<String>, limit: usize, json: bool) -> Result<()> {
+    let mut entries = audit::read_entries()?;
+
+    if let Some(filter) = &filter {
+        entries.retain(|entry| entry.target.contains(filter.as_str()));
+    }
+    if limit != 0 && entries.len() > limit {
+        entries.drain(..entries.len() - limit);
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        status!("{} No recorded operations", "📭".yellow());
+        return Ok(());
+    }
+
+    for entry in &entries {
+        let mut line = format!(
+            "{} {:<10} {}",
+            entry
+                .timestamp
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+                .bright_black(),
+            entry.action.cyan(),
+            entry.target
+        );
+        if let Some(detail) = &entry.detail {
+            line.push_str(&format!(" {}", detail.bright_black()));
+        }
+        line.push_str(&format!(
+            " {}",
+            format!("({}@{})", entry.user, entry.source).dimmed()
+        ));
+        println!("{line}");
+    }
+
+    Ok(())
+}
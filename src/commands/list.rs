@@ -1,12 +1,23 @@
 use anyhow::Result;
 use chrono::{DateTime, Local, Utc};
+use clap::ValueEnum;
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::str::FromStr;
 
 use crate::claude::get_claude_sessions;
 use crate::codex;
-use crate::state::XlaudeState;
+use crate::forge;
+use crate::git::{self, BranchSummary, HeadState};
+use crate::merged;
+use crate::output;
+use crate::sessions::RunningAgents;
+use crate::staleness::{self, Staleness};
+use crate::state::{WorktreeInfo, XlaudeState};
+use crate::templates;
 
 #[derive(Debug, Serialize, Deserialize)]
 struct JsonSessionInfo {
@@ -16,16 +27,110 @@ struct JsonSessionInfo {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-struct JsonWorktreeInfo {
+pub struct JsonWorktreeInfo {
     name: String,
     branch: String,
     path: String,
     repo_name: String,
     created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    parent: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    merged_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ttl_days: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    staleness: Option<Staleness>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    head_state: Option<HeadState>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_activity: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    base_branch: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    base_commit: Option<String>,
+    // Only computed with --verbose, like the summary
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    base_moved: Option<usize>,
+    // Only computed with --verbose
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    summary: Option<BranchSummary>,
+    // An agent started by `open` or the dashboard is running in the worktree
+    running: bool,
     sessions: Vec<JsonSessionInfo>,
     codex_sessions: Vec<JsonCodexSessionInfo>,
 }
 
+/// Order of worktrees within each repository.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum ListSort {
+    #[default]
+    Name,
+    /// Most recently active first, using the cached last activity
+    Activity,
+}
+
+impl ListSort {
+    fn compare(self, a: &WorktreeInfo, b: &WorktreeInfo) -> Ordering {
+        match self {
+            Self::Name => a.name.cmp(&b.name),
+            Self::Activity => b
+                .recent_activity()
+                .cmp(&a.recent_activity())
+                .then_with(|| a.name.cmp(&b.name)),
+        }
+    }
+}
+
+/// Placeholders of `--format` templates. Values that need git or gh are only
+/// computed when the template uses them.
+const FORMAT_PLACEHOLDERS: &[&str] = &[
+    "repo",
+    "name",
+    "key",
+    "branch",
+    "path",
+    "status",
+    "ahead",
+    "behind",
+    "last_activity",
+    "pr_state",
+];
+
+/// Line-oriented output formats for other tools.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ListFormat {
+    /// `path<TAB>repo/name<TAB>branch<TAB>status` lines for fzf.vim, telescope
+    /// and quickfix lists
+    Vim,
+    /// One line per worktree with `{placeholder}`s filled in
+    Template(String),
+}
+
+impl FromStr for ListFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        if format == "vim" {
+            return Ok(Self::Vim);
+        }
+        if !format.contains('{') {
+            return Err(format!(
+                "expected 'vim' or a template such as '{{repo}}/{{name}} {{branch}}', placeholders: {}",
+                FORMAT_PLACEHOLDERS.join(", ")
+            ));
+        }
+        let unknown = templates::unknown_placeholders(format, FORMAT_PLACEHOLDERS);
+        if let Some(name) = unknown.first() {
+            return Err(format!(
+                "unknown placeholder '{{{name}}}', expected one of: {}",
+                FORMAT_PLACEHOLDERS.join(", ")
+            ));
+        }
+        Ok(Self::Template(format.to_string()))
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct JsonOutput {
     worktrees: Vec<JsonWorktreeInfo>,
@@ -74,68 +179,149 @@ fn format_message_preview(message: &str, limit: usize) -> String {
     truncated
 }
 
-pub fn handle_list(json: bool) -> Result<()> {
-    let state = XlaudeState::load()?;
+/// Compare every worktree against its base. Each one runs a few git
+/// commands, so they are spread across threads.
+fn branch_summaries(state: &XlaudeState) -> HashMap<PathBuf, BranchSummary> {
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = state
+            .worktrees
+            .values()
+            .map(|info| scope.spawn(move || (info.path.clone(), git::branch_summary(&info.path))))
+            .collect();
+        handles
+            .into_iter()
+            .filter_map(|handle| handle.join().ok())
+            .collect()
+    })
+}
+
+/// Where the worktree started, and whether the base has moved on since.
+fn print_base(info: &WorktreeInfo) {
+    let (Some(branch), Some(commit)) = (&info.base_branch, &info.base_commit) else {
+        return;
+    };
+    let moved = match info.base_moved() {
+        Some(0) => "up to date".green().to_string(),
+        Some(count) => format!(
+            "moved {count} commit{} since, needs rebase",
+            if count == 1 { "" } else { "s" }
+        )
+        .yellow()
+        .to_string(),
+        None => "no longer found".red().to_string(),
+    };
+    println!(
+        "      {} {} at {} ({moved})",
+        "Base:".bright_black(),
+        branch,
+        &commit[..commit.len().min(8)]
+    );
+}
+
+fn print_branch_summary(summary: &BranchSummary) {
+    if let Some(base) = &summary.base_ref {
+        println!(
+            "      {} {} ahead, {} behind {}",
+            "Commits:".bright_black(),
+            summary.ahead.to_string().green(),
+            summary.behind.to_string().red(),
+            base
+        );
+        println!(
+            "      {} {} file{}, {} {}",
+            "Changes:".bright_black(),
+            summary.files_changed,
+            if summary.files_changed == 1 { "" } else { "s" },
+            format!("+{}", summary.insertions).green(),
+            format!("-{}", summary.deletions).red()
+        );
+    }
+    if let Some(subject) = &summary.last_commit_subject {
+        println!(
+            "      {} {}",
+            "Last commit:".bright_black(),
+            format_message_preview(subject, 60)
+        );
+    }
+}
+
+pub fn handle_list(
+    json: bool,
+    tree: bool,
+    sort: ListSort,
+    verbose: bool,
+    format: Option<ListFormat>,
+    repo: Option<String>,
+) -> Result<()> {
+    let step = output::step("Checking merged branches");
+    let refreshed = merged::refresh_if_stale();
+    drop(step);
+    if let Err(err) = refreshed {
+        estatus!("{} Failed to check merged branches: {err:#}", "⚠️".yellow());
+    }
+    let mut state = XlaudeState::load()?;
+    // Never saved, so other repositories can simply be dropped
+    if let Some(repo) = &repo {
+        state.ensure_repo_known(repo)?;
+        state.worktrees.retain(|_, info| &info.repo_name == repo);
+    }
 
     if state.worktrees.is_empty() {
         if json {
             let output = JsonOutput { worktrees: vec![] };
             println!("{}", serde_json::to_string_pretty(&output)?);
-        } else {
-            println!("{} No active worktrees", "📭".yellow());
+        } else if format.is_none() {
+            status!("{} No active worktrees", "📭".yellow());
         }
         return Ok(());
     }
 
-    if json {
-        // JSON output
-        let mut worktrees = Vec::new();
+    if tree {
+        print_tree(&state);
+        print_cleanup_hint(&state);
+        return Ok(());
+    }
 
-        for info in state.worktrees.values() {
-            let claude_sessions = get_claude_sessions(&info.path);
-            let json_sessions: Vec<JsonSessionInfo> = claude_sessions
-                .into_iter()
-                .map(|session| JsonSessionInfo {
-                    last_user_message: session.last_user_message,
-                    last_timestamp: session.last_timestamp,
-                    time_ago: format_time_ago(session.last_timestamp),
-                })
-                .collect();
-
-            let (codex_sessions, _) = codex::recent_sessions(&info.path, usize::MAX)?;
-            let json_codex_sessions: Vec<JsonCodexSessionInfo> = codex_sessions
-                .into_iter()
-                .map(|session| JsonCodexSessionInfo {
-                    id: session.id,
-                    last_user_message: session.last_user_message,
-                    last_timestamp: session.last_timestamp,
-                    time_ago: format_time_ago(session.last_timestamp),
-                })
-                .collect();
-
-            worktrees.push(JsonWorktreeInfo {
-                name: info.name.clone(),
-                branch: info.branch.clone(),
-                path: info.path.display().to_string(),
-                repo_name: info.repo_name.clone(),
-                created_at: info.created_at,
-                sessions: json_sessions,
-                codex_sessions: json_codex_sessions,
-            });
+    let running = RunningAgents::detect();
+    match &format {
+        Some(ListFormat::Vim) => {
+            print_vim_lines(&state, &running, sort);
+            return Ok(());
+        }
+        Some(ListFormat::Template(template)) => {
+            print_template_lines(&state, &running, sort, template);
+            return Ok(());
         }
+        None => {}
+    }
+
+    let mut summaries = if verbose {
+        let _step = output::step(format!("Comparing {} worktrees", state.worktrees.len()));
+        branch_summaries(&state)
+    } else {
+        HashMap::new()
+    };
 
-        // Sort worktrees by repo name and then by name
-        worktrees.sort_by(|a, b| {
+    if json {
+        // JSON output
+        let mut worktrees = Vec::new();
+        let mut infos: Vec<&WorktreeInfo> = state.worktrees.values().collect();
+        infos.sort_by(|a, b| {
             a.repo_name
                 .cmp(&b.repo_name)
-                .then_with(|| a.name.cmp(&b.name))
+                .then_with(|| sort.compare(a, b))
         });
 
+        for info in infos {
+            let summary = summaries.remove(&info.path);
+            worktrees.push(json_worktree(&state, info, &running, summary)?);
+        }
+
         let output = JsonOutput { worktrees };
         println!("{}", serde_json::to_string_pretty(&output)?);
     } else {
         // Original colored output
-        println!("{} Active worktrees:", "📋".cyan());
+        status!("{} Active worktrees:", "📋".cyan());
         println!();
 
         // Group worktrees by repository
@@ -149,14 +335,20 @@ pub fn handle_list(json: bool) -> Result<()> {
 
         // Display grouped by repository
         for (repo_name, mut worktrees) in grouped {
-            println!("  {} {}", "📦".blue(), repo_name.bold());
+            status!("  {} {}", "📦".blue(), repo_name.bold());
 
-            // Sort worktrees within each repo by name
-            worktrees.sort_by_key(|w| &w.name);
+            worktrees.sort_by(|a, b| sort.compare(a, b));
 
             for info in worktrees {
                 println!("    {} {}", "•".green(), info.name.cyan());
                 println!("      {} {}", "Path:".bright_black(), info.path.display());
+                if let Some(head) = git::head_state(&info.path, &info.branch) {
+                    println!(
+                        "      {} {}",
+                        "Branch:".bright_black(),
+                        head.describe(&info.branch).yellow()
+                    );
+                }
                 println!(
                     "      {} {}",
                     "Created:".bright_black(),
@@ -164,6 +356,40 @@ pub fn handle_list(json: bool) -> Result<()> {
                         .with_timezone(&Local)
                         .format("%Y-%m-%d %H:%M:%S")
                 );
+                if let Some(summary) = summaries.get(&info.path) {
+                    print_base(info);
+                    print_branch_summary(summary);
+                }
+                if running.is_running(info) {
+                    let agents = running.in_worktree(info);
+                    let detail = if agents.is_empty() {
+                        "running in tmux".to_string()
+                    } else {
+                        agents
+                            .iter()
+                            .map(|agent| agent.describe())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    };
+                    println!("      {} {}", "Agent:".bright_black(), detail.green());
+                }
+                if let Some(lock) = info.active_lock() {
+                    println!("      {} {}", "Locked:".bright_black(), lock.describe());
+                }
+                if info.merged_at.is_some() {
+                    println!(
+                        "      {} {}",
+                        "Merged:".bright_black(),
+                        "branch merged, ready to clean up".green()
+                    );
+                }
+                if let Some(staleness) = staleness::check(&state, info) {
+                    println!(
+                        "      {} {}",
+                        "Stale:".bright_black(),
+                        staleness.describe().yellow()
+                    );
+                }
 
                 // Get Claude sessions for this worktree
                 let claude_sessions = get_claude_sessions(&info.path);
@@ -226,7 +452,273 @@ pub fn handle_list(json: bool) -> Result<()> {
             }
             println!();
         }
+        print_repo_summary(&state);
+        print_cleanup_hint(&state);
     }
 
     Ok(())
 }
+
+/// A worktree as reported by `list --json`, with its branch summary when one
+/// was computed.
+pub fn json_worktree(
+    state: &XlaudeState,
+    info: &WorktreeInfo,
+    running: &RunningAgents,
+    summary: Option<BranchSummary>,
+) -> Result<JsonWorktreeInfo> {
+    let claude_sessions = get_claude_sessions(&info.path);
+    let json_sessions: Vec<JsonSessionInfo> = claude_sessions
+        .into_iter()
+        .map(|session| JsonSessionInfo {
+            last_user_message: session.last_user_message,
+            last_timestamp: session.last_timestamp,
+            time_ago: format_time_ago(session.last_timestamp),
+        })
+        .collect();
+
+    let (codex_sessions, _) = codex::recent_sessions(&info.path, usize::MAX)?;
+    let json_codex_sessions: Vec<JsonCodexSessionInfo> = codex_sessions
+        .into_iter()
+        .map(|session| JsonCodexSessionInfo {
+            id: session.id,
+            last_user_message: session.last_user_message,
+            last_timestamp: session.last_timestamp,
+            time_ago: format_time_ago(session.last_timestamp),
+        })
+        .collect();
+
+    Ok(JsonWorktreeInfo {
+        name: info.name.clone(),
+        branch: info.branch.clone(),
+        path: info.path.display().to_string(),
+        repo_name: info.repo_name.clone(),
+        created_at: info.created_at,
+        parent: info.parent.clone(),
+        merged_at: info.merged_at,
+        ttl_days: info.ttl_days,
+        staleness: staleness::check(state, info),
+        head_state: git::head_state(&info.path, &info.branch),
+        last_activity: info.last_activity,
+        base_branch: info.base_branch.clone(),
+        base_commit: info.base_commit.clone(),
+        base_moved: summary.as_ref().and_then(|_| info.base_moved()),
+        summary,
+        running: running.is_running(info),
+        sessions: json_sessions,
+        codex_sessions: json_codex_sessions,
+    })
+}
+
+fn sorted_for_lines(state: &XlaudeState, sort: ListSort) -> Vec<&WorktreeInfo> {
+    let mut infos: Vec<&WorktreeInfo> = state.worktrees.values().collect();
+    infos.sort_by(|a, b| {
+        a.repo_name
+            .cmp(&b.repo_name)
+            .then_with(|| sort.compare(a, b))
+    });
+    infos
+}
+
+/// Comma-separated status flags shared by `--format vim` and `{status}`,
+/// `ok` when none apply.
+fn status_flags(state: &XlaudeState, info: &WorktreeInfo, running: &RunningAgents) -> String {
+    let mut flags = Vec::new();
+    if !info.path.exists() {
+        flags.push("missing");
+    } else {
+        match git::head_state(&info.path, &info.branch) {
+            Some(HeadState::Detached) => flags.push("detached"),
+            Some(HeadState::MissingBranch) => flags.push("branch-deleted"),
+            None => {}
+        }
+    }
+    if running.is_running(info) {
+        flags.push("running");
+    }
+    if info.merged_at.is_some() {
+        flags.push("merged");
+    }
+    match staleness::check(state, info) {
+        Some(Staleness::Expired { .. }) => flags.push("expired"),
+        Some(Staleness::Stale { .. }) => flags.push("stale"),
+        None => {}
+    }
+    if flags.is_empty() {
+        flags.push("ok");
+    }
+    flags.join(",")
+}
+
+/// One tab-separated line per worktree. The columns and status flags are
+/// documented as stable; new flags may be added, existing ones keep their
+/// meaning.
+fn print_vim_lines(state: &XlaudeState, running: &RunningAgents, sort: ListSort) {
+    for info in sorted_for_lines(state, sort) {
+        let fields = [
+            info.path.display().to_string(),
+            XlaudeState::make_key(&info.repo_name, &info.name),
+            info.branch.clone(),
+            status_flags(state, info, running),
+        ];
+        let fields: Vec<String> = fields
+            .iter()
+            .map(|field| field.replace(['\t', '\n'], " "))
+            .collect();
+        println!("{}", fields.join("\t"));
+    }
+}
+
+/// One line per worktree from a user template. Values that are unknown (no
+/// base to compare with, no pull request) render as empty strings.
+fn print_template_lines(
+    state: &XlaudeState,
+    running: &RunningAgents,
+    sort: ListSort,
+    template: &str,
+) {
+    for info in sorted_for_lines(state, sort) {
+        let mut counts = None;
+        let line = templates::render(template, FORMAT_PLACEHOLDERS, |key| match key {
+            "repo" => Some(info.repo_name.clone()),
+            "name" => Some(info.name.clone()),
+            "key" => Some(XlaudeState::make_key(&info.repo_name, &info.name)),
+            "branch" => Some(info.branch.clone()),
+            "path" => Some(info.path.display().to_string()),
+            "status" => Some(status_flags(state, info, running)),
+            "ahead" | "behind" => {
+                let (ahead, behind) = *counts
+                    .get_or_insert_with(|| {
+                        git::resolve_base_ref(&info.path)
+                            .and_then(|base| git::ahead_behind(&info.path, &base))
+                    })
+                    .as_ref()?;
+                Some(if key == "ahead" { ahead } else { behind }.to_string())
+            }
+            "last_activity" => Some(format_time_ago(Some(info.recent_activity()))),
+            "pr_state" => forge::pr_state(&info.path, &info.branch),
+            _ => None,
+        });
+        println!("{}", line.replace('\n', " "));
+    }
+}
+
+fn print_repo_summary(state: &XlaudeState) {
+    let counts = state.repo_counts();
+    if counts.len() < 2 {
+        return;
+    }
+    let repos: Vec<String> = counts
+        .iter()
+        .map(|(repo, count)| format!("{repo} ({count})"))
+        .collect();
+    status!(
+        "{} {} worktrees in {} repositories: {} (narrow with {})",
+        "📊".cyan(),
+        state.worktrees.len(),
+        counts.len(),
+        repos.join(", "),
+        "--repo <name>".cyan()
+    );
+}
+
+fn print_cleanup_hint(state: &XlaudeState) {
+    print_stale_hint(state);
+    let ready = merged::ready_to_clean(state);
+    if ready.is_empty() {
+        return;
+    }
+    let names: Vec<&str> = ready.iter().map(|w| w.name.as_str()).collect();
+    status!(
+        "{} {} worktree{} ready to clean: {} (remove with {})",
+        "💡".cyan(),
+        ready.len(),
+        if ready.len() == 1 { " is" } else { "s are" },
+        names.join(", ").cyan(),
+        "xlaude delete <name>".cyan()
+    );
+}
+
+fn print_stale_hint(state: &XlaudeState) {
+    let (mut expired, mut stale) = (0, 0);
+    for info in state.worktrees.values() {
+        match staleness::check(state, info) {
+            Some(Staleness::Expired { .. }) => expired += 1,
+            Some(Staleness::Stale { .. }) => stale += 1,
+            None => {}
+        }
+    }
+    if expired > 0 {
+        status!(
+            "{} {} worktree{} past {} TTL (remove with {})",
+            "⏰".yellow(),
+            expired,
+            if expired == 1 { " is" } else { "s are" },
+            if expired == 1 { "its" } else { "their" },
+            "xlaude prune".cyan()
+        );
+    }
+    if stale > 0 {
+        status!(
+            "{} {} worktree{} no recent activity (review with {})",
+            "💤".yellow(),
+            stale,
+            if stale == 1 { " has" } else { "s have" },
+            "xlaude prune --stale".cyan()
+        );
+    }
+}
+
+/// Print each repository's worktrees with stacked worktrees nested under
+/// their parents. A worktree whose parent is gone is shown at the top level.
+fn print_tree(state: &XlaudeState) {
+    let mut grouped: BTreeMap<&str, Vec<&WorktreeInfo>> = BTreeMap::new();
+    for info in state.worktrees.values() {
+        grouped.entry(&info.repo_name).or_default().push(info);
+    }
+
+    for (repo_name, mut worktrees) in grouped {
+        worktrees.sort_by_key(|w| &w.name);
+        status!("{} {}", "📦".blue(), repo_name.bold());
+
+        let names: Vec<&str> = worktrees.iter().map(|w| w.name.as_str()).collect();
+        let roots: Vec<&WorktreeInfo> = worktrees
+            .iter()
+            .copied()
+            .filter(|w| w.parent.as_deref().is_none_or(|p| !names.contains(&p)))
+            .collect();
+        for info in roots {
+            println!(
+                "  {} {}",
+                info.name.cyan(),
+                branch_label(info).bright_black()
+            );
+            print_children(&worktrees, info, "  ");
+        }
+        println!();
+    }
+}
+
+fn print_children(worktrees: &[&WorktreeInfo], parent: &WorktreeInfo, indent: &str) {
+    let children: Vec<&WorktreeInfo> = worktrees
+        .iter()
+        .copied()
+        .filter(|w| w.parent.as_deref() == Some(parent.name.as_str()))
+        .collect();
+    for (i, child) in children.iter().enumerate() {
+        let last = i + 1 == children.len();
+        println!(
+            "{indent}{} {} {}",
+            if last { "└─" } else { "├─" }.bright_black(),
+            child.name.cyan(),
+            branch_label(child).bright_black()
+        );
+        let nested = format!("{indent}{}", if last { "   " } else { "│  " });
+        print_children(worktrees, child, &nested);
+    }
+}
+
+fn branch_label(info: &WorktreeInfo) -> String {
+    git::head_state(&info.path, &info.branch)
+        .map_or_else(|| info.branch.clone(), |head| head.describe(&info.branch))
+}
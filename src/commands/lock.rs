@@ -0,0 +1,64 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use colored::Colorize;
+
+use crate::audit;
+use crate::input::get_command_arg;
+use crate::state::{WorktreeLock, XlaudeState};
+
+pub fn handle_lock(name: Option<String>, reason: Option<String>) -> Result<()> {
+    let mut state = XlaudeState::load()?;
+    let key = state.resolve_key(get_command_arg(name)?)?;
+    let info = state
+        .worktrees
+        .get_mut(&key)
+        .context("Worktree not found")?;
+
+    if let Some(lock) = info.active_lock()
+        && lock.pid.is_none()
+    {
+        status!(
+            "{} Worktree '{}' is already locked ({})",
+            "💡".cyan(),
+            info.name.cyan(),
+            lock.describe()
+        );
+        return Ok(());
+    }
+
+    info.lock = Some(WorktreeLock {
+        locked_at: Utc::now(),
+        reason: reason.clone(),
+        pid: None,
+    });
+    let name = info.name.clone();
+    state.save()?;
+    audit::record("lock", &key, reason);
+
+    status!("{} Locked worktree '{}'", "🔒".green(), name.cyan());
+    Ok(())
+}
+
+pub fn handle_unlock(name: Option<String>) -> Result<()> {
+    let mut state = XlaudeState::load()?;
+    let key = state.resolve_key(get_command_arg(name)?)?;
+    let info = state
+        .worktrees
+        .get_mut(&key)
+        .context("Worktree not found")?;
+
+    if info.lock.take().is_none() {
+        status!(
+            "{} Worktree '{}' is not locked",
+            "💡".cyan(),
+            info.name.cyan()
+        );
+        return Ok(());
+    }
+    let name = info.name.clone();
+    state.save()?;
+    audit::record("unlock", &key, None);
+
+    status!("{} Unlocked worktree '{}'", "🔓".green(), name.cyan());
+    Ok(())
+}
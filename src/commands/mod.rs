@@ -1,25 +1,89 @@
 pub mod add;
+pub mod agents;
+pub mod apply;
+pub mod attach;
+pub mod batch;
 pub mod checkout;
+pub mod ci;
 pub mod clean;
+pub mod clone;
 pub mod complete;
 pub mod config;
+pub mod conflicts;
+pub mod copy;
 pub mod create;
+pub mod current;
 pub mod dashboard;
 pub mod delete;
+pub mod digest;
 pub mod dir;
+pub mod gc;
+pub mod history;
 pub mod list;
+pub mod lock;
+pub mod move_worktree;
 pub mod open;
+pub mod prompt;
+pub mod prune;
+pub mod push;
+pub mod rebase;
+pub mod recreate;
 pub mod rename;
+pub mod repo;
+pub mod report;
+pub mod review;
+pub mod serve;
+pub mod service;
+pub mod snapshot;
+pub mod state;
+pub mod stats;
+pub mod sync;
+pub mod trash;
+pub mod undo_branch;
+pub mod watch;
 
 pub use add::handle_add;
+pub use agents::{handle_kill, handle_wait};
+pub use apply::handle_apply;
+pub use attach::handle_attach;
+pub use batch::handle_batch;
 pub use checkout::handle_checkout;
+pub use ci::handle_ci;
 pub use clean::handle_clean;
+pub use clone::handle_clone;
 pub use complete::handle_complete_worktrees;
-pub use config::handle_config;
-pub use create::handle_create;
-pub use dashboard::handle_dashboard;
+pub use config::{
+    handle_config, handle_config_get, handle_config_list, handle_config_set, handle_config_unset,
+};
+pub use conflicts::handle_conflicts;
+pub use copy::handle_copy;
+pub use create::{CreateOptions, handle_create};
+pub use current::handle_current;
+pub use dashboard::{handle_dashboard, handle_dashboard_status, handle_dashboard_stop};
 pub use delete::handle_delete;
+pub use digest::handle_digest;
 pub use dir::handle_dir;
-pub use list::handle_list;
-pub use open::handle_open;
-pub use rename::handle_rename;
+pub use gc::{GcAction, handle_gc};
+pub use history::handle_history;
+pub use list::{ListFormat, ListSort, handle_list};
+pub use lock::{handle_lock, handle_unlock};
+pub use move_worktree::handle_move;
+pub use open::{OpenOptions, handle_open};
+pub use prompt::handle_prompt;
+pub use prune::handle_prune;
+pub use push::handle_push;
+pub use rebase::handle_rebase;
+pub use recreate::handle_recreate;
+pub use rename::{handle_rename, handle_rename_repo};
+pub use repo::{handle_repo_list, handle_repo_set_base, handle_repo_unset_base};
+pub use report::handle_report;
+pub use review::handle_review;
+pub use serve::handle_serve;
+pub use service::{handle_service_install, handle_service_status, handle_service_uninstall};
+pub use snapshot::{handle_restore_snapshot, handle_snapshot, handle_snapshots};
+pub use state::{StateFormat, handle_state_export, handle_state_import};
+pub use stats::handle_stats;
+pub use sync::handle_sync;
+pub use trash::{handle_trash_empty, handle_trash_list, handle_trash_restore};
+pub use undo_branch::handle_undo_branch;
+pub use watch::handle_watch;
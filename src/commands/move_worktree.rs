@@ -0,0 +1,74 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::path::PathBuf;
+
+use crate::audit;
+use crate::error::XlaudeError;
+use crate::git::execute_git;
+use crate::sessions;
+use crate::state::XlaudeState;
+use crate::tmux::TmuxManager;
+
+pub fn handle_move(name: String, new_path: PathBuf) -> Result<()> {
+    let mut state = XlaudeState::load()?;
+    let key = state.resolve_key(Some(name))?;
+    let info = state.worktrees[&key].clone();
+    info.ensure_unlocked(false)?;
+
+    // An agent would be left running in a directory that no longer exists
+    if !sessions::running_in(&info.path).is_empty() {
+        return Err(XlaudeError::Refused(format!(
+            "An agent session is still running in '{}'. Stop it before moving the worktree",
+            info.name
+        ))
+        .into());
+    }
+
+    let new_path = std::env::current_dir()?.join(new_path);
+    if new_path.exists() {
+        return Err(XlaudeError::Refused(format!("{} already exists", new_path.display())).into());
+    }
+    if let Some(parent) = new_path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+
+    let old_dir = info.path.to_string_lossy();
+    let new_dir = new_path.to_string_lossy();
+    execute_git(&["-C", &old_dir, "worktree", "move", &old_dir, &new_dir])
+        .context("Failed to move worktree")?;
+
+    state
+        .worktrees
+        .get_mut(&key)
+        .context("Worktree disappeared from state")?
+        .path = new_path.clone();
+    state.save()?;
+    audit::record(
+        "move",
+        &key,
+        Some(format!("from {} to {}", info.path.display(), new_dir)),
+    );
+
+    // Windows opened later in an existing session should start in the new place
+    if let Ok(tmux) = TmuxManager::new() {
+        let session = TmuxManager::session_name(&info.repo_name, &info.name);
+        if tmux.has_session(&session)
+            && let Err(err) = tmux.set_session_dir(&session, &new_path)
+        {
+            status!(
+                "{} Failed to update tmux session '{}': {err:#}",
+                "⚠️".yellow(),
+                session
+            );
+        }
+    }
+
+    status!(
+        "{} Moved worktree '{}' to {}",
+        "✅".green(),
+        info.name.cyan(),
+        new_dir.cyan()
+    );
+    Ok(())
+}
@@ -1,16 +1,91 @@
 use anyhow::{Context, Result};
 use chrono::Utc;
 use colored::Colorize;
-use std::process::{Command, Stdio};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
 
-use crate::git::{get_current_branch, get_repo_name, is_base_branch, is_in_worktree};
+use crate::audit;
+use crate::commands::recreate::recreate;
+use crate::error::XlaudeError;
+use crate::git::{execute_git, get_current_branch, get_repo_name, is_base_branch, is_in_worktree};
 use crate::input::{drain_stdin, get_command_arg, is_piped_input, smart_confirm, smart_select};
-use crate::state::{WorktreeInfo, XlaudeState};
+use crate::sessions;
+use crate::state::{WorktreeInfo, WorktreeLock, XlaudeState};
+use crate::templates::TemplateContext;
+use crate::tmux::TmuxManager;
 use crate::utils::{prepare_agent_command, sanitize_branch_name};
+use crate::worktree_env;
+
+#[derive(Debug, Default, Clone)]
+pub struct OpenOptions {
+    /// Agent name from the registry; the global `agent` command when unset
+    pub agent: Option<String>,
+    /// Initial prompt handed to the agent as its last argument
+    pub type_text: Option<String>,
+    /// File whose contents are appended to the initial prompt
+    pub type_file: Option<PathBuf>,
+    /// Named entry of `prompt_templates`, rendered into the initial prompt
+    pub prompt_template: Option<String>,
+    /// Start the agent in a detached tmux session instead of the foreground
+    pub new_window: bool,
+    /// Print the directory and command line instead of running the agent
+    pub print_cmd: bool,
+    /// Open the managed worktree containing the current directory, matched
+    /// by path only and without prompting
+    pub here: bool,
+}
+
+/// How the agent for a worktree is started: its directory, the worktree's
+/// environment and the command line.
+#[derive(Debug, Serialize)]
+pub struct AgentInvocation {
+    pub cwd: PathBuf,
+    pub env: BTreeMap<String, String>,
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+/// Resolve the agent command for a worktree, including the initial prompt.
+fn agent_command(worktree_path: &Path, options: &OpenOptions) -> Result<(String, Vec<String>)> {
+    let (program, mut args) = prepare_agent_command(worktree_path, options.agent.as_deref())?;
+    if let Some(text) = options
+        .type_text
+        .as_deref()
+        .filter(|t| !t.trim().is_empty())
+    {
+        args.push(text.to_string());
+    }
+    Ok((program, args))
+}
+
+pub fn handle_open(name: Option<String>, mut options: OpenOptions) -> Result<()> {
+    // Read the prompt file before changing into the worktree, so relative paths
+    // resolve against the caller's directory
+    if let Some(file) = options.type_file.take() {
+        let content = fs::read_to_string(&file)
+            .with_context(|| format!("Failed to read prompt file {}", file.display()))?;
+        options.type_text = join_prompts(options.type_text.take(), Some(content));
+    }
 
-pub fn handle_open(name: Option<String>) -> Result<()> {
     let mut state = XlaudeState::load()?;
 
+    if options.here {
+        let current_dir = std::env::current_dir()?;
+        let info = state
+            .find_by_path(&current_dir)
+            .map(|(_, info)| info.clone())
+            .ok_or_else(|| {
+                XlaudeError::NotFound(format!(
+                    "{} is not inside a worktree managed by xlaude; add it with 'xlaude add'",
+                    current_dir.display()
+                ))
+            })?;
+        return open_worktree(&info, &options);
+    }
+
     // Check if current path is a worktree when no name is provided
     // Note: base branches (main/master/develop) are not considered worktrees
     // Skip this check if we have piped input waiting to be read
@@ -20,28 +95,33 @@ pub fn handle_open(name: Option<String>) -> Result<()> {
         if is_piped_input() && std::env::var("XLAUDE_TEST_MODE").is_err() {
             // There's piped input, so skip current worktree detection
         } else {
-            // Get current repository info
-            let repo_name = get_repo_name().context("Not in a git repository")?;
-            let current_branch = get_current_branch()?;
+            // Matched by path, so subdirectories and worktrees whose branch
+            // changed since they were added resolve too
             let current_dir = std::env::current_dir()?;
+            if let Some((_, info)) = state.find_by_path(&current_dir) {
+                if !options.print_cmd {
+                    status!(
+                        "{} Opening current worktree '{}/{}'...",
+                        "🚀".green(),
+                        info.repo_name,
+                        info.name.cyan()
+                    );
+                }
+                std::env::set_current_dir(&info.path).context("Failed to change directory")?;
+                return launch_agent(&info.path, &info.repo_name, &info.name, &options);
+            }
 
-            // Sanitize branch name for key lookup
+            let repo_name = get_repo_name().context("Not in a git repository")?;
+            let current_branch = get_current_branch()?;
+            let current_dir = PathBuf::from(execute_git(&["rev-parse", "--show-toplevel"])?);
             let worktree_name = sanitize_branch_name(&current_branch);
-
-            // Check if this worktree is already managed
             let key = XlaudeState::make_key(&repo_name, &worktree_name);
 
-            if state.worktrees.contains_key(&key) {
-                // Already managed, open directly
-                println!(
-                    "{} Opening current worktree '{}/{}'...",
-                    "🚀".green(),
-                    repo_name,
-                    worktree_name.cyan()
-                );
+            if options.print_cmd {
+                // Only show what would run, without adding the worktree
             } else {
                 // Not managed, ask if user wants to add it
-                println!(
+                status!(
                     "{} Current directory is a worktree but not managed by xlaude",
                     "ℹ️".blue()
                 );
@@ -64,7 +144,7 @@ pub fn handle_open(name: Option<String>) -> Result<()> {
                 }
 
                 // Add to state
-                println!(
+                status!(
                     "{} Adding worktree '{}' to xlaude management...",
                     "➕".green(),
                     worktree_name.cyan()
@@ -78,12 +158,14 @@ pub fn handle_open(name: Option<String>) -> Result<()> {
                         path: current_dir.clone(),
                         repo_name: repo_name.clone(),
                         created_at: Utc::now(),
+                        ..Default::default()
                     },
                 );
                 state.save()?;
+                audit::record("add", &key, Some(current_dir.display().to_string()));
 
-                println!("{} Worktree added successfully", "✅".green());
-                println!(
+                status!("{} Worktree added successfully", "✅".green());
+                status!(
                     "{} Opening worktree '{}/{}'...",
                     "🚀".green(),
                     repo_name,
@@ -92,30 +174,15 @@ pub fn handle_open(name: Option<String>) -> Result<()> {
             }
 
             // Launch agent in current directory
-            let (program, args) = prepare_agent_command(&current_dir)?;
-            let mut cmd = Command::new(&program);
-            cmd.args(&args);
-
-            cmd.envs(std::env::vars());
-
-            // If there's piped input, drain it and don't pass to Claude
-            if is_piped_input() {
-                drain_stdin()?;
-                cmd.stdin(Stdio::null());
-            }
-
-            let status = cmd.status().context("Failed to launch agent")?;
-
-            if !status.success() {
-                anyhow::bail!("Agent exited with error");
-            }
-
-            return Ok(());
+            return launch_agent(&current_dir, &repo_name, &worktree_name, &options);
         }
     }
 
     if state.worktrees.is_empty() {
-        anyhow::bail!("No worktrees found. Create one first with 'xlaude create'");
+        return Err(XlaudeError::NotFound(
+            "No worktrees found. Create one first with 'xlaude create'".to_string(),
+        )
+        .into());
     }
 
     // Get the name from CLI args or pipe
@@ -125,11 +192,9 @@ pub fn handle_open(name: Option<String>) -> Result<()> {
     let (_key, worktree_info) = if let Some(n) = target_name {
         // Find worktree by name across all projects
         state
-            .worktrees
-            .iter()
-            .find(|(_, w)| w.name == n)
+            .find_by_name(&n)
             .map(|(k, w)| (k.clone(), w.clone()))
-            .context(format!("Worktree '{n}' not found"))?
+            .ok_or_else(|| XlaudeError::NotFound(format!("Worktree '{n}' not found")))?
     } else {
         // Interactive selection - show repo/name format
         let worktree_list: Vec<(String, WorktreeInfo)> = state
@@ -150,25 +215,139 @@ pub fn handle_open(name: Option<String>) -> Result<()> {
         }
     };
 
+    open_worktree(&worktree_info, &options)
+}
+
+fn open_worktree(worktree_info: &WorktreeInfo, options: &OpenOptions) -> Result<()> {
+    let recreated;
+    let worktree_info = if worktree_info.path.exists() {
+        worktree_info
+    } else {
+        match handle_missing_worktree(worktree_info, options)? {
+            Some(info) => {
+                recreated = info;
+                &recreated
+            }
+            None => return Ok(()),
+        }
+    };
     let worktree_name = &worktree_info.name;
 
-    println!(
-        "{} Opening worktree '{}/{}'...",
-        "🚀".green(),
-        worktree_info.repo_name,
-        worktree_name.cyan()
-    );
+    if !options.print_cmd {
+        status!(
+            "{} Opening worktree '{}/{}'...",
+            "🚀".green(),
+            worktree_info.repo_name,
+            worktree_name.cyan()
+        );
+    }
 
     // Change to worktree directory and launch Claude
     std::env::set_current_dir(&worktree_info.path).context("Failed to change directory")?;
 
-    // Resolve global agent command
-    let (program, args) = prepare_agent_command(&worktree_info.path)?;
+    launch_agent(
+        &worktree_info.path,
+        &worktree_info.repo_name,
+        worktree_name,
+        options,
+    )
+}
+
+#[derive(Clone, Copy)]
+enum MissingAction {
+    Recreate,
+    Forget,
+    Cancel,
+}
+
+/// The worktree directory was removed behind xlaude's back. Offer to recreate
+/// it from the recorded branch or to drop the state entry, like `delete` does.
+/// Returns the recreated worktree, which may have moved, if it can be opened.
+fn handle_missing_worktree(
+    info: &WorktreeInfo,
+    options: &OpenOptions,
+) -> Result<Option<WorktreeInfo>> {
+    let key = XlaudeState::make_key(&info.repo_name, &info.name);
+    let missing = || {
+        XlaudeError::NotFound(format!(
+            "Worktree directory {} no longer exists; run 'xlaude recreate {key}' to add it \
+             again, or 'xlaude delete {key}' to forget it",
+            info.path.display()
+        ))
+    };
+    if options.print_cmd {
+        return Err(missing().into());
+    }
+
+    status!(
+        "{} Worktree directory not found at {}",
+        "⚠️ ".yellow(),
+        info.path.display()
+    );
+    status!(
+        "  {} The worktree may have been manually deleted",
+        "ℹ️".blue()
+    );
+    let choices = [
+        MissingAction::Recreate,
+        MissingAction::Forget,
+        MissingAction::Cancel,
+    ];
+    let selection = smart_select("What should happen?", &choices, |choice| match choice {
+        MissingAction::Recreate => format!("Recreate it from branch '{}'", info.branch),
+        MissingAction::Forget => "Remove it from xlaude management".to_string(),
+        MissingAction::Cancel => "Cancel".to_string(),
+    })?;
+    let Some(index) = selection else {
+        return Err(missing().into());
+    };
+
+    match choices[index] {
+        MissingAction::Recreate => recreate(&key).map(Some),
+        MissingAction::Forget => {
+            let mut state = XlaudeState::load()?;
+            state.worktrees.remove(&key);
+            state.save()?;
+            audit::record("clean", &key, Some("worktree no longer exists".to_string()));
+            status!("{} Removed '{}' from xlaude", "✅".green(), key.cyan());
+            Ok(None)
+        }
+        MissingAction::Cancel => {
+            status!("{} Cancelled", "❌".red());
+            Err(XlaudeError::Cancelled.into())
+        }
+    }
+}
+
+/// Start the agent in `path`, either in the foreground or, with
+/// `--new-window`, in a detached tmux session.
+fn launch_agent(
+    path: &Path,
+    repo_name: &str,
+    worktree_name: &str,
+    options: &OpenOptions,
+) -> Result<()> {
+    let AgentInvocation {
+        cwd: _,
+        env,
+        program,
+        args,
+    } = resolve_invocation(path, repo_name, worktree_name, options)?;
+    if options.print_cmd {
+        print_command(path, &env, &program, &args);
+        return Ok(());
+    }
+    let agent = options.agent.as_deref();
+    if options.new_window {
+        return launch_detached(path, repo_name, worktree_name, agent, &program, &args, &env);
+    }
+
     let mut cmd = Command::new(&program);
     cmd.args(&args);
 
-    // Inherit all environment variables
+    // Inherit all environment variables, then apply the worktree's own
     cmd.envs(std::env::vars());
+    cmd.envs(&env);
 
     // If there's piped input, drain it and don't pass to Claude
     if is_piped_input() {
@@ -176,11 +355,193 @@ pub fn handle_open(name: Option<String>) -> Result<()> {
         cmd.stdin(Stdio::null());
     }
 
-    let status = cmd.status().context("Failed to launch agent")?;
+    let status = run_agent(cmd, path, agent, &program)?;
 
     if !status.success() {
-        anyhow::bail!("Agent exited with error");
+        return Err(XlaudeError::Agent("Agent exited with error".to_string()).into());
+    }
+
+    Ok(())
+}
+
+/// Resolve what `open` runs in the worktree at `path`, rendering the prompt
+/// template if one was given.
+pub fn resolve_invocation(
+    path: &Path,
+    repo_name: &str,
+    worktree_name: &str,
+    options: &OpenOptions,
+) -> Result<AgentInvocation> {
+    let mut options = options.clone();
+    if let Some(template) = options.prompt_template.take() {
+        let context = TemplateContext {
+            path,
+            repo_name,
+            worktree_name,
+        };
+        let rendered = render_prompt_template(&template, &context)?;
+        options.type_text = join_prompts(Some(rendered), options.type_text.take());
+    }
+
+    let (program, args) = agent_command(path, &options)?;
+    Ok(AgentInvocation {
+        cwd: path.to_path_buf(),
+        env: worktree_env::for_path(path)?,
+        program,
+        args,
+    })
+}
+
+/// Print how the agent would be started, as shell commands that can be run
+/// as they are: the directory, the worktree's environment, then the agent.
+fn print_command(path: &Path, env: &BTreeMap<String, String>, program: &str, args: &[String]) {
+    println!("cd {}", shell_words::quote(&path.to_string_lossy()));
+    for (key, value) in env {
+        println!("export {key}={}", shell_words::quote(value));
+    }
+    println!(
+        "{}",
+        shell_words::join(std::iter::once(program).chain(args.iter().map(String::as_str)))
+    );
+}
+
+/// Combine prompt parts in order, separated by a blank line.
+fn join_prompts(first: Option<String>, second: Option<String>) -> Option<String> {
+    let parts: Vec<String> = [first, second]
+        .into_iter()
+        .flatten()
+        .map(|part| part.trim_end().to_string())
+        .filter(|part| !part.trim().is_empty())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("\n\n"))
+}
+
+fn render_prompt_template(name: &str, context: &TemplateContext) -> Result<String> {
+    let state = XlaudeState::load()?;
+    let template = state.prompt_templates.get(name).with_context(|| {
+        let available: Vec<&str> = state.prompt_templates.keys().map(String::as_str).collect();
+        if available.is_empty() {
+            format!("Unknown prompt template '{name}'. Define templates under 'prompt_templates' in the state file")
+        } else {
+            format!(
+                "Unknown prompt template '{name}'. Available templates: {}",
+                available.join(", ")
+            )
+        }
+    })?;
+    Ok(context.render(template))
+}
+
+fn launch_detached(
+    path: &Path,
+    repo_name: &str,
+    worktree_name: &str,
+    agent: Option<&str>,
+    program: &str,
+    args: &[String],
+    env: &BTreeMap<String, String>,
+) -> Result<()> {
+    ensure_lock_acknowledged(path)?;
+    if is_piped_input() {
+        drain_stdin()?;
     }
 
+    let tmux = TmuxManager::new()?;
+    let session = TmuxManager::session_name(repo_name, worktree_name);
+    let pid = tmux.create_session(&session, path, program, args, env)?;
+    // Nothing waits for a detached agent, so its lock lapses once the pid exits
+    sessions::register(pid, path, program, agent);
+    set_session_lock(path, pid, true);
+
+    status!(
+        "{} Agent started in tmux session '{}'",
+        "✅".green(),
+        session.cyan()
+    );
+    println!(
+        "  {} tmux attach -t {}",
+        "Attach with:".bright_black(),
+        session
+    );
     Ok(())
 }
+
+/// Ask before starting another agent in a locked worktree.
+fn ensure_lock_acknowledged(path: &Path) -> Result<()> {
+    let state = XlaudeState::load()?;
+    if let Some((_, info)) = state.find_by_path(path)
+        && let Some(lock) = info.active_lock()
+    {
+        status!(
+            "{} Worktree '{}' is locked ({})",
+            "🔒".yellow(),
+            info.name.cyan(),
+            lock.describe()
+        );
+        if !smart_confirm("Open it anyway?", false)? {
+            if std::env::var("XLAUDE_NON_INTERACTIVE").is_ok() {
+                return Err(
+                    XlaudeError::Refused(format!("Worktree '{}' is locked", info.name)).into(),
+                );
+            }
+            status!("{} Cancelled", "❌".red());
+            return Err(XlaudeError::Cancelled.into());
+        }
+    }
+    Ok(())
+}
+
+/// Run the agent to completion, tracking it in the session registry and
+/// holding the worktree lock so other commands can tell it is in use.
+fn run_agent(
+    mut cmd: Command,
+    path: &Path,
+    agent: Option<&str>,
+    program: &str,
+) -> Result<ExitStatus> {
+    ensure_lock_acknowledged(path)?;
+
+    let mut child = cmd
+        .spawn()
+        .map_err(|err| XlaudeError::Agent(format!("Failed to launch agent: {err}")))?;
+    sessions::register(child.id(), path, program, agent);
+    set_session_lock(path, child.id(), true);
+    let status = child.wait().context("Failed to wait for agent");
+    set_session_lock(path, child.id(), false);
+    sessions::unregister(child.id());
+    status
+}
+
+/// Take or release the automatic lock for `pid`. A manual lock, or one held
+/// by another live agent, is left in place. Either way the agent starting or
+/// exiting counts as activity.
+fn set_session_lock(path: &Path, pid: u32, locked: bool) {
+    let result = (|| -> Result<()> {
+        let mut state = XlaudeState::load()?;
+        let Some(key) = state.find_by_path(path).map(|(key, _)| key.clone()) else {
+            return Ok(());
+        };
+        let info = state
+            .worktrees
+            .get_mut(&key)
+            .context("Worktree not found")?;
+        info.last_activity = Some(Utc::now());
+
+        if locked {
+            if info.active_lock().is_none() {
+                info.lock = Some(WorktreeLock {
+                    locked_at: Utc::now(),
+                    reason: None,
+                    pid: Some(pid),
+                });
+            }
+        } else if info.lock.as_ref().is_some_and(|lock| lock.pid == Some(pid)) {
+            info.lock = None;
+        }
+        state.save()
+    })();
+
+    if let Err(err) = result {
+        estatus!("⚠️  Failed to update worktree lock: {err:#}");
+    }
+}
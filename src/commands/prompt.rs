@@ -0,0 +1,37 @@
+use anyhow::Result;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::commands::complete::cached_entry;
+use crate::sessions::AgentStatus;
+
+// Markers older than this are more likely wrong than helpful
+const MARKERS_MAX_AGE: Duration = Duration::from_secs(300);
+
+/// Runs on every prompt render, so it only reads the completion cache kept
+/// current by shell completions and a running dashboard.
+pub fn handle_prompt(path: Option<PathBuf>) -> Result<()> {
+    let cwd = match path {
+        Some(p) => p,
+        None => std::env::current_dir()?,
+    };
+
+    // Prompts must never fail loudly, so treat a missing cache as unmanaged.
+    let Some((entry, cached_at)) = cached_entry(&cwd) else {
+        return Ok(());
+    };
+
+    let mut segment = entry.name;
+    if cached_at.elapsed().is_ok_and(|age| age <= MARKERS_MAX_AGE) {
+        if entry.agent == AgentStatus::Running {
+            segment.push_str(" ●");
+        }
+        if !entry.dirty.is_empty() {
+            segment.push(' ');
+            segment.push_str(&entry.dirty);
+        }
+    }
+
+    println!("{segment}");
+    Ok(())
+}
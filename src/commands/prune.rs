@@ -0,0 +1,59 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::collections::BTreeMap;
+
+use crate::commands::delete::delete_candidates;
+use crate::staleness::{self, Staleness};
+use crate::state::XlaudeState;
+
+/// Offer to delete worktrees past their TTL, and with `stale` also those
+/// without recent activity.
+pub fn handle_prune(stale: bool, repo: Option<String>) -> Result<()> {
+    let state = XlaudeState::load()?;
+    if let Some(repo) = &repo {
+        state.ensure_repo_known(repo)?;
+    }
+
+    let mut candidates = Vec::new();
+    for (key, info) in &state.worktrees {
+        if repo.as_ref().is_some_and(|repo| &info.repo_name != repo) {
+            continue;
+        }
+        let Some(staleness) = staleness::check(&state, info) else {
+            continue;
+        };
+        if matches!(staleness, Staleness::Stale { .. }) && !stale {
+            continue;
+        }
+        status!("{} {}: {}", "⏰".yellow(), key.cyan(), staleness.describe());
+        candidates.push((key.clone(), info.clone()));
+    }
+
+    if candidates.is_empty() {
+        status!(
+            "{} No {} worktrees",
+            "✨".green(),
+            if stale { "expired or stale" } else { "expired" }
+        );
+        return Ok(());
+    }
+    candidates.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut per_repo: BTreeMap<&str, usize> = BTreeMap::new();
+    for (_, info) in &candidates {
+        *per_repo.entry(info.repo_name.as_str()).or_default() += 1;
+    }
+    if per_repo.len() > 1 {
+        for (repo, count) in &per_repo {
+            status!("  {} {}: {}", "📦".blue(), repo.bold(), count);
+        }
+        status!(
+            "{} Use {} to prune one repository at a time",
+            "💡".cyan(),
+            "--repo <name>".cyan()
+        );
+    }
+    println!();
+
+    delete_candidates(state, candidates, false, false, false)
+}
@@ -0,0 +1,59 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+
+use crate::error::XlaudeError;
+use crate::git::{compare_url, execute_git, remote_web_url, resolve_base_ref};
+use crate::input::get_command_arg;
+use crate::state::XlaudeState;
+use crate::tools::{self, Tool};
+use crate::utils::{ensure_online, execute_in_dir};
+
+pub fn handle_push(name: Option<String>, remote: String) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let key = state.resolve_key(get_command_arg(name)?)?;
+    let info = &state.worktrees[&key];
+    ensure_online("Pushing")?;
+
+    status!(
+        "{} Pushing '{}' to {}...",
+        "🚀".green(),
+        info.branch.cyan(),
+        remote.cyan()
+    );
+
+    execute_in_dir(&info.path, || {
+        // Let git's own progress and remote messages through
+        let status = tools::command(Tool::Git)
+            .args(["push", "-u", &remote, &info.branch])
+            .status()
+            .context("Failed to run git push")?;
+        if !status.success() {
+            return Err(
+                XlaudeError::Vcs(format!("Failed to push '{}' to {remote}", info.branch)).into(),
+            );
+        }
+        Ok(())
+    })?;
+
+    status!(
+        "{} Pushed '{}' and set its upstream to {}/{}",
+        "✅".green(),
+        info.branch.cyan(),
+        remote,
+        info.branch
+    );
+
+    let dir = info.path.to_string_lossy();
+    if let Ok(url) = execute_git(&["-C", &dir, "remote", "get-url", &remote])
+        && let Some(web_url) = remote_web_url(&url)
+    {
+        let base = resolve_base_ref(&info.path).unwrap_or_else(|| "main".to_string());
+        let base = base.strip_prefix(&format!("{remote}/")).unwrap_or(&base);
+        status!(
+            "  {} Open a pull request: {}",
+            "🔗".cyan(),
+            compare_url(&web_url, base, &info.branch)
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,250 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::collections::BTreeSet;
+
+use crate::error::XlaudeError;
+use crate::git::{self, ahead_behind, execute_git, fork_point, resolve_base_ref};
+use crate::input::get_command_arg;
+use crate::state::{WorktreeInfo, XlaudeState};
+use crate::utils::is_offline;
+
+enum Outcome {
+    Rebased(usize),
+    UpToDate,
+    Skipped(String),
+    Conflicts(Vec<String>),
+}
+
+/// Rebase one worktree, or with `all` every worktree of a repository, onto
+/// its freshly fetched base. Conflicting rebases are aborted and reported.
+pub fn handle_rebase(
+    name: Option<String>,
+    all: bool,
+    repo: Option<String>,
+    autostash: bool,
+) -> Result<()> {
+    let mut state = XlaudeState::load()?;
+    let keys = if all {
+        let repo = match repo {
+            Some(repo) => repo,
+            None => git::get_repo_name().context("Not in a git repository, use --repo")?,
+        };
+        state.ensure_repo_known(&repo)?;
+        stack_order(&state, &repo)
+    } else {
+        vec![state.resolve_key(get_command_arg(name)?)?]
+    };
+
+    fetch_bases(&state, &keys);
+
+    let mut results = Vec::new();
+    for key in &keys {
+        let info = &state.worktrees[key];
+        let Some(base) = base_of(&state, info) else {
+            results.push((key.clone(), None, Outcome::Skipped("no base branch".into())));
+            continue;
+        };
+        let outcome = rebase_one(info, &base, autostash);
+        if matches!(outcome, Outcome::Rebased(_) | Outcome::UpToDate)
+            && let Some(info) = state.worktrees.get_mut(key)
+        {
+            // The worktree now starts from the current tip of its base
+            info.base_commit = fork_point(&info.path, &info.branch, &base);
+            info.base_branch = Some(base.clone());
+        }
+        results.push((key.clone(), Some(base), outcome));
+    }
+    state.save()?;
+
+    print_summary(&state, &results);
+    let conflicted = results
+        .iter()
+        .filter(|(_, _, outcome)| matches!(outcome, Outcome::Conflicts(_)))
+        .count();
+    if conflicted > 0 {
+        return Err(XlaudeError::Vcs(format!(
+            "{conflicted} worktree{} stopped on conflicts and {} left unchanged; rebase {} by hand",
+            if conflicted == 1 { "" } else { "s" },
+            if conflicted == 1 { "was" } else { "were" },
+            if conflicted == 1 { "it" } else { "them" }
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// Worktrees of `repo`, parents before the worktrees stacked on them.
+fn stack_order(state: &XlaudeState, repo: &str) -> Vec<String> {
+    let depth = |info: &WorktreeInfo| {
+        let mut depth = 0;
+        let mut seen = BTreeSet::new();
+        let mut parent = info.parent.clone();
+        while let Some(name) = parent.filter(|name| seen.insert(name.clone())) {
+            depth += 1;
+            parent = state
+                .worktrees
+                .get(&XlaudeState::make_key(repo, &name))
+                .and_then(|info| info.parent.clone());
+        }
+        depth
+    };
+    let mut keys: Vec<(usize, String)> = state
+        .worktrees
+        .iter()
+        .filter(|(_, info)| info.repo_name == repo)
+        .map(|(key, info)| (depth(info), key.clone()))
+        .collect();
+    keys.sort();
+    keys.into_iter().map(|(_, key)| key).collect()
+}
+
+/// Fetch once per repository so remote bases are current.
+fn fetch_bases(state: &XlaudeState, keys: &[String]) {
+    if is_offline() {
+        return;
+    }
+    let repos: BTreeSet<_> = keys
+        .iter()
+        .map(|key| state.worktrees[key].main_repo_path())
+        .filter(|path| path.exists())
+        .collect();
+    for repo in repos {
+        let dir = repo.to_string_lossy();
+        if execute_git(&["-C", &dir, "remote", "get-url", "origin"]).is_err() {
+            continue;
+        }
+        let step = crate::output::step(format!("Fetching {}", repo.display()));
+        let fetched = execute_git(&["-C", &dir, "fetch", "--quiet", "origin"]);
+        drop(step);
+        if let Err(err) = fetched {
+            estatus!(
+                "{} Failed to fetch in {}: {err:#}",
+                "⚠️".yellow(),
+                repo.display()
+            );
+        }
+    }
+}
+
+/// Stacked worktrees follow their parent's branch, others their repository's base.
+fn base_of(state: &XlaudeState, info: &WorktreeInfo) -> Option<String> {
+    if let Some(parent) = info.parent.as_ref().and_then(|name| {
+        state
+            .worktrees
+            .get(&XlaudeState::make_key(&info.repo_name, name))
+    }) {
+        return Some(parent.branch.clone());
+    }
+    resolve_base_ref(&info.path).or_else(|| info.base_branch.clone())
+}
+
+fn rebase_one(info: &WorktreeInfo, base: &str, autostash: bool) -> Outcome {
+    if !info.path.exists() {
+        return Outcome::Skipped("directory missing".into());
+    }
+    if git::checked_out_branch(&info.path).is_none() {
+        return Outcome::Skipped("not on a branch".into());
+    }
+    if let Some(lock) = info.active_lock() {
+        return Outcome::Skipped(format!("locked ({})", lock.describe()));
+    }
+    let dir = info.path.to_string_lossy();
+    let dirty = execute_git(&["-C", &dir, "status", "--porcelain"])
+        .map(|status| !status.is_empty())
+        .unwrap_or(true);
+    if dirty && !autostash {
+        return Outcome::Skipped("uncommitted changes, use --autostash".into());
+    }
+
+    let behind = match ahead_behind(&info.path, base) {
+        Some((_, 0)) => return Outcome::UpToDate,
+        Some((_, behind)) => behind,
+        None => return Outcome::Skipped(format!("cannot compare with {base}")),
+    };
+
+    let mut args = vec!["-C", &dir, "rebase"];
+    if autostash {
+        args.push("--autostash");
+    }
+    args.push(base);
+    if execute_git(&args).is_ok() {
+        return Outcome::Rebased(behind);
+    }
+
+    let conflicted = execute_git(&["-C", &dir, "diff", "--name-only", "--diff-filter=U"])
+        .unwrap_or_default()
+        .lines()
+        .map(str::to_string)
+        .collect();
+    // Put the worktree back as it was, stashed changes included
+    let _ = execute_git(&["-C", &dir, "rebase", "--abort"]);
+    Outcome::Conflicts(conflicted)
+}
+
+fn print_summary(state: &XlaudeState, results: &[(String, Option<String>, Outcome)]) {
+    let name_width = results
+        .iter()
+        .map(|(key, _, _)| state.worktrees[key].name.len())
+        .max()
+        .unwrap_or_default()
+        .max("Worktree".len());
+    let base_width = results
+        .iter()
+        .map(|(_, base, _)| base.as_deref().map_or(1, str::len))
+        .max()
+        .unwrap_or_default()
+        .max("Base".len());
+
+    println!();
+    // Padded before coloring, escape codes would count towards the width
+    println!(
+        "  {}  {}  {}",
+        format!("{:name_width$}", "Worktree").bold(),
+        format!("{:base_width$}", "Base").bold(),
+        "Result".bold()
+    );
+    let (mut rebased, mut skipped, mut conflicts) = (0, 0, 0);
+    for (key, base, outcome) in results {
+        let result = match outcome {
+            Outcome::Rebased(count) => {
+                rebased += 1;
+                format!(
+                    "rebased over {count} commit{}",
+                    if *count == 1 { "" } else { "s" }
+                )
+                .green()
+                .to_string()
+            }
+            Outcome::UpToDate => "up to date".to_string(),
+            Outcome::Skipped(reason) => {
+                skipped += 1;
+                format!("skipped: {reason}").yellow().to_string()
+            }
+            Outcome::Conflicts(files) => {
+                conflicts += 1;
+                let files = if files.is_empty() {
+                    String::new()
+                } else {
+                    format!(" in {}", files.join(", "))
+                };
+                format!("conflicts{files}, left unchanged")
+                    .red()
+                    .to_string()
+            }
+        };
+        println!(
+            "  {}  {:base_width$}  {}",
+            format!("{:name_width$}", state.worktrees[key].name).cyan(),
+            base.as_deref().unwrap_or("-"),
+            result
+        );
+    }
+    println!();
+    status!(
+        "{} {} rebased, {} skipped, {} with conflicts",
+        "🔄".green(),
+        rebased,
+        skipped,
+        conflicts
+    );
+}
@@ -0,0 +1,102 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+
+use crate::audit;
+use crate::error::XlaudeError;
+use crate::git::{execute_git, get_repo_name, main_repo_dir};
+use crate::state::{WorktreeInfo, XlaudeState};
+use crate::utils::execute_in_dir;
+
+pub fn handle_recreate(name: String) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let key = state.resolve_key(Some(name))?;
+    let path = &state.worktrees[&key].path;
+    if path.exists() {
+        return Err(XlaudeError::Refused(format!(
+            "Worktree '{key}' still exists at {}",
+            path.display()
+        ))
+        .into());
+    }
+    recreate(&key)?;
+    Ok(())
+}
+
+/// Add the worktree of a state entry whose directory was removed again, on
+/// its recorded branch. It goes back to its recorded path, or next to the
+/// main checkout like `create` when that location is gone too.
+pub(crate) fn recreate(key: &str) -> Result<WorktreeInfo> {
+    let mut state = XlaudeState::load()?;
+    let mut info = state
+        .worktrees
+        .get(key)
+        .cloned()
+        .ok_or_else(|| XlaudeError::NotFound(format!("Worktree '{key}' not found")))?;
+
+    let main_repo = main_checkout(&state, &info);
+    if !main_repo.exists() {
+        return Err(XlaudeError::NotFound(format!(
+            "Repository '{}' not found at {}",
+            info.repo_name,
+            main_repo.display()
+        ))
+        .into());
+    }
+    if !info.path.parent().is_some_and(Path::exists) {
+        let parent = main_repo.parent().unwrap_or(&main_repo);
+        info.path = parent.join(format!("{}-{}", info.repo_name, info.name));
+    }
+
+    let path = info.path.to_string_lossy().to_string();
+    execute_in_dir(&main_repo, || {
+        // Drop git's record of the removed directory first
+        execute_git(&["worktree", "prune"])?;
+        let local = format!("refs/heads/{}", info.branch);
+        if execute_git(&["show-ref", "--verify", "--quiet", &local]).is_err() {
+            return Err(XlaudeError::NotFound(format!(
+                "Branch '{}' no longer exists",
+                info.branch
+            ))
+            .into());
+        }
+        execute_git(&["worktree", "add", &path, &info.branch])
+    })
+    .with_context(|| format!("Failed to recreate worktree '{key}'"))?;
+
+    state.worktrees.insert(key.to_string(), info.clone());
+    state.save()?;
+    audit::record("recreate", key, Some(path));
+    status!(
+        "{} Worktree recreated at {}",
+        "✅".green(),
+        info.path.display()
+    );
+    Ok(info)
+}
+
+/// The removed directory cannot tell where its repository is, so ask a
+/// sibling worktree or the current checkout before guessing.
+fn main_checkout(state: &XlaudeState, info: &WorktreeInfo) -> PathBuf {
+    if let Some(path) = state
+        .repos
+        .get(&info.repo_name)
+        .and_then(|repo| repo.path.clone())
+    {
+        return path;
+    }
+    let sibling = state
+        .worktrees
+        .values()
+        .filter(|other| other.repo_name == info.repo_name && other.path.exists())
+        .find_map(|other| main_repo_dir(&other.path));
+    let current = || {
+        get_repo_name()
+            .is_ok_and(|name| name == info.repo_name)
+            .then(|| main_repo_dir(Path::new(".")))
+            .flatten()
+    };
+    sibling
+        .or_else(current)
+        .unwrap_or_else(|| info.main_repo_path())
+}
@@ -1,10 +1,22 @@
 use anyhow::{Context, Result, bail};
 use colored::Colorize;
+use std::path::{Path, PathBuf};
 
-use crate::git;
+use crate::audit;
+use crate::error::XlaudeError;
+use crate::git::{self, execute_git};
+use crate::sessions;
 use crate::state::XlaudeState;
+use crate::tmux::TmuxManager;
+use crate::utils::sanitize_branch_name;
+
+pub fn handle_rename(old_name: String, new_name: String, force: bool) -> Result<()> {
+    // The name doubles as a directory and tmux session name, like on create
+    if !new_name.chars().any(|c| c.is_alphanumeric() || c == '_') {
+        bail!("'{new_name}' is not a usable worktree name");
+    }
+    let new_name = sanitize_branch_name(&new_name);
 
-pub fn handle_rename(old_name: String, new_name: String) -> Result<()> {
     let repo = git::get_repo_name()?;
     let mut state = XlaudeState::load()?;
 
@@ -12,7 +24,10 @@ pub fn handle_rename(old_name: String, new_name: String) -> Result<()> {
     let new_key = XlaudeState::make_key(&repo, &new_name);
 
     if !state.worktrees.contains_key(&old_key) {
-        bail!("Worktree '{}' not found in repository '{}'", old_name, repo);
+        return Err(XlaudeError::NotFound(format!(
+            "Worktree '{old_name}' not found in repository '{repo}'"
+        ))
+        .into());
     }
 
     if state.worktrees.contains_key(&new_key) {
@@ -27,12 +42,23 @@ pub fn handle_rename(old_name: String, new_name: String) -> Result<()> {
         .worktrees
         .remove(&old_key)
         .context("Failed to get worktree data")?;
+    worktree_data.ensure_unlocked(force)?;
+
+    // Renaming only changes the registry entry, so a running agent keeps working
+    if force && !sessions::running_in(&worktree_data.path).is_empty() {
+        status!(
+            "{} An agent session is running in this worktree; it is not affected by the rename",
+            "💡".cyan()
+        );
+    }
 
     // Update the name field in the worktree info
     worktree_data.name = new_name.clone();
 
-    state.worktrees.insert(new_key, worktree_data);
+    state.worktrees.insert(new_key.clone(), worktree_data);
+    state.reparent_children(&repo, &old_name, Some(&new_name));
     state.save()?;
+    audit::record("rename", &new_key, Some(format!("from {old_key}")));
 
     println!(
         "{} {} {} {} {} {}",
@@ -46,3 +72,191 @@ pub fn handle_rename(old_name: String, new_name: String) -> Result<()> {
 
     Ok(())
 }
+
+/// Follow a renamed repository directory or origin: rekey every entry of
+/// `old_repo`, move worktree directories named after it, and rename their
+/// tmux sessions. State is saved once, after everything on disk moved; if a
+/// move fails, what was already moved or renamed is put back first.
+pub fn handle_rename_repo(old_repo: String, new_repo: String, force: bool) -> Result<()> {
+    let mut state = XlaudeState::load()?;
+    let mut keys: Vec<String> = state
+        .worktrees
+        .iter()
+        .filter(|(_, info)| info.repo_name == old_repo)
+        .map(|(key, _)| key.clone())
+        .collect();
+    keys.sort();
+
+    if keys.is_empty() && !state.repos.contains_key(&old_repo) {
+        return Err(
+            XlaudeError::NotFound(format!("No worktrees of repository '{old_repo}'")).into(),
+        );
+    }
+    if state
+        .worktrees
+        .values()
+        .any(|info| info.repo_name == new_repo)
+        || state.repos.contains_key(&new_repo)
+    {
+        return Err(XlaudeError::Refused(format!(
+            "Repository '{new_repo}' is already known to xlaude"
+        ))
+        .into());
+    }
+
+    // Check everything before touching anything on disk
+    for key in &keys {
+        let info = &state.worktrees[key];
+        info.ensure_unlocked(force)?;
+        if !sessions::running_in(&info.path).is_empty() {
+            return Err(XlaudeError::Refused(format!(
+                "An agent session is still running in '{}'. Stop it before renaming the repository",
+                info.name
+            ))
+            .into());
+        }
+    }
+
+    let tmux = TmuxManager::new().ok();
+    let mut moved: Vec<(PathBuf, PathBuf)> = Vec::new();
+    let mut renamed_sessions: Vec<(String, String, PathBuf)> = Vec::new();
+    for key in &keys {
+        let mut info = state
+            .worktrees
+            .remove(key)
+            .context("Failed to get worktree data")?;
+        let old_session = TmuxManager::session_name(&info.repo_name, &info.name);
+        let old_path = info.path.clone();
+        info.repo_name = new_repo.clone();
+
+        // Worktrees of a renamed repository directory point at the old path
+        let main_repo = info.main_repo_path();
+        if info.path.exists() && main_repo.exists() {
+            let main_dir = main_repo.to_string_lossy();
+            let dir = info.path.to_string_lossy();
+            if let Err(err) = execute_git(&["-C", &main_dir, "worktree", "repair", &dir]) {
+                estatus!(
+                    "{} Failed to repair '{}': {err:#}",
+                    "⚠️".yellow(),
+                    info.name
+                );
+            }
+        }
+
+        if let Some(new_path) = renamed_path(&info.path, &old_repo, &new_repo, &info.name) {
+            if info.path.exists() && !new_path.exists() {
+                let old_dir = info.path.to_string_lossy();
+                let new_dir = new_path.to_string_lossy();
+                if let Err(err) =
+                    execute_git(&["-C", &old_dir, "worktree", "move", &old_dir, &new_dir])
+                {
+                    roll_back(&moved, &renamed_sessions, tmux.as_ref());
+                    return Err(err.context(format!(
+                        "Failed to move worktree '{}'; nothing was renamed",
+                        info.name
+                    )));
+                }
+                moved.push((info.path.clone(), new_path.clone()));
+                info.path = new_path;
+            } else if !info.path.exists() && new_path.exists() {
+                // Already moved by hand
+                info.path = new_path;
+            }
+        }
+
+        if let Some(tmux) = &tmux
+            && tmux.has_session(&old_session)
+        {
+            let session = TmuxManager::session_name(&new_repo, &info.name);
+            let renamed = tmux
+                .rename_session(&old_session, &session)
+                .and_then(|()| tmux.set_session_dir(&session, &info.path));
+            match renamed {
+                Ok(()) => renamed_sessions.push((old_session, session, old_path)),
+                Err(err) => status!(
+                    "{} Failed to update tmux session '{}': {err:#}",
+                    "⚠️".yellow(),
+                    old_session
+                ),
+            }
+        }
+
+        state
+            .worktrees
+            .insert(XlaudeState::make_key(&new_repo, &info.name), info);
+    }
+
+    if let Some(config) = state.repos.remove(&old_repo) {
+        state.repos.insert(new_repo.clone(), config);
+    }
+    for branch in state.trash.iter_mut().filter(|b| b.repo_name == old_repo) {
+        branch.repo_name = new_repo.clone();
+    }
+    for entry in state
+        .trashed_worktrees
+        .iter_mut()
+        .filter(|entry| entry.info.repo_name == old_repo)
+    {
+        entry.info.repo_name = new_repo.clone();
+    }
+    state.save()?;
+    audit::record("rename-repo", &new_repo, Some(format!("from {old_repo}")));
+
+    println!(
+        "{} {} {} {} {} {}",
+        "✓".green(),
+        "Renamed repository".green(),
+        old_repo.cyan(),
+        "to".green(),
+        new_repo.cyan(),
+        format!(
+            "({} worktree{})",
+            keys.len(),
+            if keys.len() == 1 { "" } else { "s" }
+        )
+        .dimmed()
+    );
+
+    Ok(())
+}
+
+/// Undo the directory moves and tmux renames of an aborted `rename --repo`,
+/// latest first. Failures are reported but do not stop the rest.
+fn roll_back(
+    moved: &[(PathBuf, PathBuf)],
+    sessions: &[(String, String, PathBuf)],
+    tmux: Option<&TmuxManager>,
+) {
+    if let Some(tmux) = tmux {
+        for (old_session, session, old_path) in sessions.iter().rev() {
+            let restored = tmux
+                .rename_session(session, old_session)
+                .and_then(|()| tmux.set_session_dir(old_session, old_path));
+            if let Err(err) = restored {
+                estatus!(
+                    "{} Failed to rename tmux session '{}' back: {err:#}",
+                    "⚠️".yellow(),
+                    session
+                );
+            }
+        }
+    }
+    for (old_path, new_path) in moved.iter().rev() {
+        let old_dir = old_path.to_string_lossy();
+        let new_dir = new_path.to_string_lossy();
+        if let Err(err) = execute_git(&["-C", &new_dir, "worktree", "move", &new_dir, &old_dir]) {
+            estatus!(
+                "{} Failed to move {} back to {}: {err:#}",
+                "⚠️".yellow(),
+                new_path.display(),
+                old_path.display()
+            );
+        }
+    }
+}
+
+/// `<dir>/<new_repo>-<name>` for a worktree at the default `<dir>/<old_repo>-<name>`.
+fn renamed_path(path: &Path, old_repo: &str, new_repo: &str, name: &str) -> Option<PathBuf> {
+    (path.file_name()? == format!("{old_repo}-{name}").as_str())
+        .then(|| path.with_file_name(format!("{new_repo}-{name}")))
+}
@@ -0,0 +1,73 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::audit;
+use crate::state::XlaudeState;
+
+pub fn handle_repo_set_base(repo: String, branch: String) -> Result<()> {
+    let branch = branch.trim().to_string();
+    if branch.is_empty() {
+        anyhow::bail!("Base branch cannot be empty");
+    }
+
+    let mut state = XlaudeState::load()?;
+    state.repos.entry(repo.clone()).or_default().base_branch = Some(branch.clone());
+    state.save()?;
+    audit::record("set-base", &repo, Some(branch.clone()));
+
+    status!(
+        "{} Base branch of '{}' set to '{}'",
+        "✅".green(),
+        repo.cyan(),
+        branch.cyan()
+    );
+    Ok(())
+}
+
+pub fn handle_repo_unset_base(repo: String) -> Result<()> {
+    let mut state = XlaudeState::load()?;
+    let Some(config) = state
+        .repos
+        .get_mut(&repo)
+        .filter(|c| c.base_branch.is_some())
+    else {
+        status!("{} No base branch configured for '{}'", "💡".cyan(), repo);
+        return Ok(());
+    };
+    config.base_branch = None;
+    // Drop entries that no longer configure anything
+    state.repos.retain(|_, config| !config.is_empty());
+    state.save()?;
+    audit::record("unset-base", &repo, None);
+
+    status!(
+        "{} '{}' uses the default base branches again",
+        "✅".green(),
+        repo.cyan()
+    );
+    Ok(())
+}
+
+pub fn handle_repo_list() -> Result<()> {
+    let state = XlaudeState::load()?;
+    if state.repos.is_empty() {
+        status!("{} No repository settings", "📭".yellow());
+        return Ok(());
+    }
+
+    for (repo, config) in &state.repos {
+        let details: Vec<String> = config
+            .base_branch
+            .iter()
+            .map(|base| format!("base: {base}"))
+            .chain(config.path.iter().map(|path| path.display().to_string()))
+            .collect();
+        status!(
+            "  {} {} {}",
+            "📦".blue(),
+            repo.bold(),
+            details.join(", ").bright_black()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,17 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::path::PathBuf;
+
+use crate::dashboard;
+
+pub fn handle_report(output: PathBuf) -> Result<()> {
+    let count = dashboard::export_report(&output)?;
+    status!(
+        "{} Wrote a snapshot of {} worktree{} to {}",
+        "✅".green(),
+        count,
+        if count == 1 { "" } else { "s" },
+        output.display().to_string().cyan()
+    );
+    Ok(())
+}
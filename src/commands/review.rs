@@ -0,0 +1,121 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::Deserialize;
+use std::path::Path;
+
+use crate::commands::checkout::checkout_pull_request;
+use crate::commands::open::{OpenOptions, handle_open};
+use crate::forge;
+use crate::git::{execute_git, resolve_base_ref};
+use crate::state::XlaudeState;
+use crate::tools::{self, Tool};
+use crate::utils::{RunPolicy, run_command};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PullRequest {
+    title: String,
+    #[serde(default)]
+    body: String,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    base_ref_name: String,
+}
+
+pub fn handle_review(pr: String, agent: Option<String>, new_window: bool) -> Result<()> {
+    let pr_number: u64 = pr
+        .trim()
+        .trim_start_matches('#')
+        .parse()
+        .with_context(|| format!("Invalid pull request number: {pr}"))?;
+
+    let pull_request = fetch_pull_request(pr_number)?;
+    let name = checkout_pull_request(pr_number)?;
+
+    let state = XlaudeState::load()?;
+    let path = state
+        .worktrees
+        .values()
+        .find(|w| w.name == name)
+        .map(|w| w.path.clone())
+        .context("Review worktree missing from state")?;
+    let prompt = review_prompt(pr_number, &pull_request, &path);
+
+    status!(
+        "{} Starting review of #{}: {}",
+        "🔍".cyan(),
+        pr_number,
+        pull_request.title
+    );
+    handle_open(
+        Some(name),
+        OpenOptions {
+            agent,
+            type_text: Some(prompt),
+            new_window,
+            ..OpenOptions::default()
+        },
+    )
+}
+
+fn fetch_pull_request(pr_number: u64) -> Result<PullRequest> {
+    forge::require()?;
+    let output = run_command(
+        tools::command(Tool::Gh).args([
+            "pr",
+            "view",
+            &pr_number.to_string(),
+            "--json",
+            "title,body,url,baseRefName",
+        ]),
+        RunPolicy::network(),
+    )
+    .context("Failed to run gh; is the GitHub CLI installed?")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Failed to read pull request #{pr_number}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    serde_json::from_slice(&output.stdout).context("gh returned invalid JSON")
+}
+
+fn review_prompt(pr_number: u64, pull_request: &PullRequest, path: &Path) -> String {
+    let mut prompt = format!(
+        "Please review pull request #{pr_number}: {}\n",
+        pull_request.title
+    );
+    if !pull_request.url.is_empty() {
+        prompt.push_str(&format!("{}\n", pull_request.url));
+    }
+    if !pull_request.body.trim().is_empty() {
+        prompt.push_str(&format!("\nDescription:\n{}\n", pull_request.body.trim()));
+    }
+    if let Some((base, stat)) = diff_stat(path, &pull_request.base_ref_name) {
+        prompt.push_str(&format!("\nChanges against {base}:\n{stat}\n"));
+    }
+    prompt.push_str(
+        "\nThe pull request is checked out in the current directory. Review it for \
+         correctness, bugs, missing tests and readability, and report your findings \
+         with file and line references. Do not commit or push anything.",
+    );
+    prompt
+}
+
+/// Diff stat of the checked out pull request against its base branch, falling
+/// back to the repository's default base when the PR base is not fetched.
+fn diff_stat(path: &Path, base_branch: &str) -> Option<(String, String)> {
+    let dir = path.to_string_lossy();
+    let remote_base = format!("origin/{base_branch}");
+    let base = if !base_branch.is_empty()
+        && execute_git(&["-C", &dir, "rev-parse", "--verify", "--quiet", &remote_base]).is_ok()
+    {
+        remote_base
+    } else {
+        resolve_base_ref(path)?
+    };
+    let merge_base = execute_git(&["-C", &dir, "merge-base", &base, "HEAD"]).ok()?;
+    let stat = execute_git(&["-C", &dir, "diff", "--stat", &merge_base, "HEAD"]).ok()?;
+    (!stat.is_empty()).then_some((base, stat))
+}
@@ -0,0 +1,273 @@
+//! `xlaude serve --stdio`: a JSON-RPC 2.0 server for editor extensions.
+//!
+//! Requests and responses are single lines of JSON on stdin and stdout.
+//! Status messages go to stderr and commands never prompt, so stdout only
+//! ever carries responses.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+use crate::commands::create::{CreateOptions, handle_create_in_dir_quiet};
+use crate::commands::delete::handle_delete;
+use crate::commands::list::json_worktree;
+use crate::commands::open::{OpenOptions, resolve_invocation};
+use crate::error::{self, XlaudeError};
+use crate::git;
+use crate::input;
+use crate::output;
+use crate::sessions::RunningAgents;
+use crate::state::{WorktreeInfo, XlaudeState};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+// Failures of the command itself; `data.exitCode` matches the CLI's exit code
+const COMMAND_FAILED: i64 = -32000;
+
+struct RpcError {
+    code: i64,
+    message: String,
+    data: Option<Value>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+}
+
+impl From<anyhow::Error> for RpcError {
+    fn from(err: anyhow::Error) -> Self {
+        Self {
+            code: COMMAND_FAILED,
+            message: format!("{err:#}"),
+            data: Some(json!({ "exitCode": error::exit_code(&err) })),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ListParams {
+    repo: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct NameParams {
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateParams {
+    // Repository to create the worktree in
+    repo_path: PathBuf,
+    name: Option<String>,
+    from_worktree: Option<String>,
+    ttl_days: Option<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OpenParams {
+    name: String,
+    agent: Option<String>,
+    prompt: Option<String>,
+    prompt_template: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DeleteParams {
+    name: String,
+    #[serde(default)]
+    force: bool,
+    #[serde(default)]
+    keep_branch: bool,
+}
+
+pub fn handle_serve() -> Result<()> {
+    // SAFETY: no other threads exist yet. Commands refuse instead of prompting,
+    // as stdin belongs to the client.
+    unsafe { std::env::set_var("XLAUDE_NON_INTERACTIVE", "1") };
+    input::reserve_stdin();
+    output::keep_stdout_clean();
+
+    let mut stdout = io::stdout().lock();
+    for line in io::stdin().lock().lines() {
+        let line = line.context("Failed to read request")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = respond(&line) {
+            writeln!(stdout, "{response}")?;
+            stdout.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// The response line for a request line, or `None` for notifications.
+fn respond(line: &str) -> Option<Value> {
+    let request = match serde_json::from_str::<Value>(line) {
+        Ok(Value::Object(request)) => request,
+        Ok(_) => {
+            let error = RpcError::new(INVALID_REQUEST, "Request must be a JSON object");
+            return Some(error_response(Value::Null, error));
+        }
+        Err(err) => {
+            let error = RpcError::new(PARSE_ERROR, format!("Invalid JSON: {err}"));
+            return Some(error_response(Value::Null, error));
+        }
+    };
+
+    let id = request.get("id").cloned();
+    let result = match request.get("method").and_then(Value::as_str) {
+        Some(method) => {
+            let params = request.get("params").cloned().unwrap_or(json!({}));
+            dispatch(method, params)
+        }
+        None => Err(RpcError::new(INVALID_REQUEST, "Missing method")),
+    };
+
+    let id = id?;
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => error_response(id, error),
+    })
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    let mut body = json!({ "code": error.code, "message": error.message });
+    if let Some(data) = error.data {
+        body["data"] = data;
+    }
+    json!({ "jsonrpc": "2.0", "id": id, "error": body })
+}
+
+fn dispatch(method: &str, params: Value) -> Result<Value, RpcError> {
+    match method {
+        "list" => list(parse_params(params)?),
+        "dir" => {
+            let NameParams { name } = parse_params(params)?;
+            Ok(json!({ "path": find(&name)?.path }))
+        }
+        "status" => {
+            let NameParams { name } = parse_params(params)?;
+            status(&find(&name)?)
+        }
+        "create" => create(parse_params(params)?),
+        "open" => open(parse_params(params)?),
+        "delete" => {
+            let DeleteParams {
+                name,
+                force,
+                keep_branch,
+            } = parse_params(params)?;
+            let info = find(&name)?;
+            handle_delete(vec![name], None, false, false, force, keep_branch)?;
+            Ok(json!({ "deleted": XlaudeState::make_key(&info.repo_name, &info.name) }))
+        }
+        other => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("Unknown method '{other}'"),
+        )),
+    }
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params)
+        .map_err(|err| RpcError::new(INVALID_PARAMS, format!("Invalid params: {err}")))
+}
+
+fn find(name: &str) -> Result<WorktreeInfo> {
+    let state = XlaudeState::load()?;
+    state
+        .find_by_name(name)
+        .map(|(_, info)| info.clone())
+        .ok_or_else(|| XlaudeError::NotFound(format!("Worktree '{name}' not found")).into())
+}
+
+fn list(params: ListParams) -> Result<Value, RpcError> {
+    let state = XlaudeState::load()?;
+    let running = RunningAgents::detect();
+    let mut infos: Vec<&WorktreeInfo> = state
+        .worktrees
+        .values()
+        .filter(|info| {
+            params
+                .repo
+                .as_ref()
+                .is_none_or(|repo| &info.repo_name == repo)
+        })
+        .collect();
+    infos.sort_by(|a, b| (&a.repo_name, &a.name).cmp(&(&b.repo_name, &b.name)));
+
+    let worktrees = infos
+        .into_iter()
+        .map(|info| json_worktree(&state, info, &running, None))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(json!({ "worktrees": worktrees }))
+}
+
+fn status(info: &WorktreeInfo) -> Result<Value, RpcError> {
+    let state = XlaudeState::load()?;
+    let summary = git::branch_summary(&info.path);
+    let entry = json_worktree(&state, info, &RunningAgents::detect(), Some(summary))?;
+    Ok(serde_json::to_value(entry).map_err(anyhow::Error::from)?)
+}
+
+fn create(params: CreateParams) -> Result<Value, RpcError> {
+    let options = CreateOptions {
+        from_worktree: params.from_worktree,
+        ttl_days: params.ttl_days,
+        ..Default::default()
+    };
+    let repo_name = git::get_repo_name_at(&params.repo_path)?;
+    let name = handle_create_in_dir_quiet(params.name, Some(params.repo_path), options, true)?;
+    let info = find(&XlaudeState::make_key(&repo_name, &name))?;
+    status(&info)
+}
+
+/// The command for the client to run in its own terminal.
+fn open(params: OpenParams) -> Result<Value, RpcError> {
+    let info = find(&params.name)?;
+    let options = OpenOptions {
+        agent: params.agent,
+        type_text: params.prompt,
+        prompt_template: params.prompt_template,
+        ..Default::default()
+    };
+    let invocation = resolve_invocation(&info.path, &info.repo_name, &info.name, &options)?;
+    Ok(serde_json::to_value(invocation).map_err(anyhow::Error::from)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protocol_errors() {
+        let response = respond("not json").unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+        assert_eq!(response["id"], Value::Null);
+
+        let response = respond(r#"{"jsonrpc":"2.0","id":7,"method":"nope"}"#).unwrap();
+        assert_eq!(response["id"], 7);
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        let response = respond(r#"{"jsonrpc":"2.0","id":"a","method":"dir"}"#).unwrap();
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+
+        // Notifications get no response, even when they fail
+        assert!(respond(r#"{"jsonrpc":"2.0","method":"nope"}"#).is_none());
+    }
+}
@@ -0,0 +1,285 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+const SYSTEMD_UNIT: &str = "xlaude-dashboard.service";
+const LAUNCHD_LABEL: &str = "com.xlaude.dashboard";
+
+// Variables that select which state the service should use
+const FORWARDED_ENV: &[&str] = &[
+    "XLAUDE_CONFIG_DIR",
+    "XLAUDE_DATA_DIR",
+    "XLAUDE_CACHE_DIR",
+    "XLAUDE_STATE_FILE",
+    "PATH",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ServiceManager {
+    Systemd,
+    Launchd,
+}
+
+impl ServiceManager {
+    fn detect() -> Result<Self> {
+        if cfg!(target_os = "macos") {
+            Ok(Self::Launchd)
+        } else if cfg!(target_os = "linux") {
+            Ok(Self::Systemd)
+        } else {
+            anyhow::bail!(
+                "Service installation is only supported on Linux (systemd) and macOS (launchd)"
+            )
+        }
+    }
+
+    fn unit_path(self) -> Result<PathBuf> {
+        let home = std::env::var_os("HOME")
+            .map(PathBuf::from)
+            .context("HOME is not set")?;
+        Ok(match self {
+            Self::Systemd => std::env::var_os("XDG_CONFIG_HOME")
+                .map(PathBuf::from)
+                .filter(|dir| dir.is_absolute())
+                .unwrap_or_else(|| home.join(".config"))
+                .join("systemd/user")
+                .join(SYSTEMD_UNIT),
+            Self::Launchd => home
+                .join("Library/LaunchAgents")
+                .join(format!("{LAUNCHD_LABEL}.plist")),
+        })
+    }
+}
+
+// Service managers are not touched in tests, only the unit files are written
+fn skip_activation() -> bool {
+    std::env::var("XLAUDE_TEST_MODE").is_ok()
+}
+
+pub fn handle_service_install(addr: Option<String>, peers: Vec<String>) -> Result<()> {
+    let manager = ServiceManager::detect()?;
+    let path = manager.unit_path()?;
+    let exe = std::env::current_exe().context("Failed to locate xlaude binary")?;
+
+    let mut args = vec![
+        exe.display().to_string(),
+        "dashboard".to_string(),
+        "--no-browser".to_string(),
+    ];
+    if let Some(addr) = addr {
+        args.extend(["--addr".to_string(), addr]);
+    }
+    for peer in peers {
+        args.extend(["--peer".to_string(), peer]);
+    }
+    let env: Vec<(String, String)> = FORWARDED_ENV
+        .iter()
+        .filter_map(|key| Some((key.to_string(), std::env::var(key).ok()?)))
+        .collect();
+
+    let content = match manager {
+        ServiceManager::Systemd => systemd_unit(&args, &env),
+        ServiceManager::Launchd => launchd_plist(&args, &env),
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+    status!(
+        "{} Wrote service definition to {}",
+        "✅".green(),
+        path.display().to_string().cyan()
+    );
+
+    if skip_activation() {
+        return Ok(());
+    }
+    match manager {
+        ServiceManager::Systemd => {
+            run_manager("systemctl", &["--user", "daemon-reload"])?;
+            run_manager("systemctl", &["--user", "enable", "--now", SYSTEMD_UNIT])?;
+        }
+        ServiceManager::Launchd => {
+            let plist = path.display().to_string();
+            // Reload so an updated plist takes effect
+            let _ = run_manager("launchctl", &["unload", &plist]);
+            run_manager("launchctl", &["load", "-w", &plist])?;
+        }
+    }
+    status!("{} Dashboard service started", "🚀".green());
+    Ok(())
+}
+
+pub fn handle_service_uninstall() -> Result<()> {
+    let manager = ServiceManager::detect()?;
+    let path = manager.unit_path()?;
+
+    if !path.exists() {
+        status!("{} Dashboard service is not installed", "💡".cyan());
+        return Ok(());
+    }
+
+    if !skip_activation() {
+        let result = match manager {
+            ServiceManager::Systemd => {
+                run_manager("systemctl", &["--user", "disable", "--now", SYSTEMD_UNIT])
+            }
+            ServiceManager::Launchd => {
+                run_manager("launchctl", &["unload", "-w", &path.display().to_string()])
+            }
+        };
+        if let Err(err) = result {
+            status!("{} Failed to stop service: {err:#}", "⚠️".yellow());
+        }
+    }
+
+    fs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))?;
+    if manager == ServiceManager::Systemd && !skip_activation() {
+        let _ = run_manager("systemctl", &["--user", "daemon-reload"]);
+    }
+    status!(
+        "{} Removed dashboard service ({})",
+        "✅".green(),
+        path.display()
+    );
+    Ok(())
+}
+
+pub fn handle_service_status() -> Result<()> {
+    let manager = ServiceManager::detect()?;
+    let path = manager.unit_path()?;
+
+    if !path.exists() {
+        status!("{} Dashboard service is not installed", "💡".cyan());
+        println!("  Run {} to set it up", "xlaude service install".cyan());
+        return Ok(());
+    }
+
+    status!(
+        "{} Installed at {}",
+        "📄".cyan(),
+        path.display().to_string().cyan()
+    );
+    if skip_activation() {
+        return Ok(());
+    }
+
+    let running = match manager {
+        ServiceManager::Systemd => Command::new("systemctl")
+            .args(["--user", "is-active", "--quiet", SYSTEMD_UNIT])
+            .status()
+            .is_ok_and(|status| status.success()),
+        ServiceManager::Launchd => Command::new("launchctl")
+            .args(["list", LAUNCHD_LABEL])
+            .output()
+            .is_ok_and(|output| output.status.success()),
+    };
+    if running {
+        status!("{} Running", "✅".green());
+    } else {
+        status!("{} Not running", "⚠️".yellow());
+    }
+    Ok(())
+}
+
+fn run_manager(program: &str, args: &[&str]) -> Result<()> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .with_context(|| format!("Failed to run {program}"))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "{program} {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+fn systemd_unit(args: &[String], env: &[(String, String)]) -> String {
+    // systemd splits ExecStart like a shell and expands `%` specifiers, so quote
+    // every argument and escape both
+    let quote = |value: &str| {
+        format!(
+            "\"{}\"",
+            value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('%', "%%")
+        )
+    };
+    let exec = args
+        .iter()
+        .map(|arg| quote(arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let environment: String = env
+        .iter()
+        .map(|(key, value)| format!("Environment={}\n", quote(&format!("{key}={value}"))))
+        .collect();
+
+    format!(
+        "[Unit]\n\
+         Description=xlaude dashboard\n\
+         After=network.target\n\
+         \n\
+         [Service]\n\
+         ExecStart={exec}\n\
+         {environment}\
+         Restart=on-failure\n\
+         RestartSec=5\n\
+         \n\
+         [Install]\n\
+         WantedBy=default.target\n"
+    )
+}
+
+fn launchd_plist(args: &[String], env: &[(String, String)]) -> String {
+    let escape = |value: &str| {
+        value
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    };
+    let arguments: String = args
+        .iter()
+        .map(|arg| format!("    <string>{}</string>\n", escape(arg)))
+        .collect();
+    let environment: String = env
+        .iter()
+        .map(|(key, value)| {
+            format!(
+                "    <key>{}</key>\n    <string>{}</string>\n",
+                escape(key),
+                escape(value)
+            )
+        })
+        .collect();
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+  <key>Label</key>
+  <string>{LAUNCHD_LABEL}</string>
+  <key>ProgramArguments</key>
+  <array>
+{arguments}  </array>
+  <key>EnvironmentVariables</key>
+  <dict>
+{environment}  </dict>
+  <key>RunAtLoad</key>
+  <true/>
+  <key>KeepAlive</key>
+  <true/>
+</dict>
+</plist>
+"#
+    )
+}
@@ -0,0 +1,217 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, Utc};
+use colored::Colorize;
+use std::path::Path;
+
+use crate::audit;
+use crate::error::XlaudeError;
+use crate::git::{apply_patch, commit_with_untracked, git_output_bytes, status_counts};
+use crate::input::get_command_arg;
+use crate::state::{WorktreeInfo, XlaudeState};
+use crate::utils::unique_name;
+
+// Under `refs/worktree/` so each worktree only sees its own snapshots
+const SNAPSHOT_REFS: &str = "refs/worktree/xlaude/snapshots";
+
+struct Snapshot {
+    id: String,
+    commit: String,
+    created_at: Option<DateTime<Utc>>,
+    message: String,
+}
+
+/// Commit the worktree's uncommitted changes, untracked files included, to a
+/// ref of its own. The branch, the index and the files stay as they are.
+pub fn handle_snapshot(name: Option<String>, message: Option<String>) -> Result<()> {
+    let (key, info) = resolve(name)?;
+    match take_snapshot(&info, message.as_deref())? {
+        Some(id) => {
+            audit::record("snapshot", &key, Some(id.clone()));
+            status!(
+                "{} Snapshot {} saved, restore it with 'xlaude restore-snapshot {} --snapshot {}'",
+                "📸".green(),
+                id.cyan(),
+                info.name,
+                id
+            );
+        }
+        None => status!("{} No uncommitted changes to snapshot", "💡".cyan()),
+    }
+    Ok(())
+}
+
+pub fn handle_snapshots(name: Option<String>) -> Result<()> {
+    let (_, info) = resolve(name)?;
+    let snapshots = list(&info.path)?;
+    if snapshots.is_empty() {
+        status!(
+            "{} No snapshots of '{}', take one with 'xlaude snapshot'",
+            "📭".yellow(),
+            info.name
+        );
+        return Ok(());
+    }
+
+    status!(
+        "{} Snapshots of '{}', newest first:",
+        "📸".cyan(),
+        info.name
+    );
+    for snapshot in &snapshots {
+        let created = snapshot
+            .created_at
+            .map(|at| {
+                at.with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_default();
+        println!(
+            "  {} {} {} {}",
+            "•".green(),
+            snapshot.id.cyan(),
+            created.dimmed(),
+            snapshot.message
+        );
+    }
+    Ok(())
+}
+
+/// Replace the worktree's uncommitted changes with those of a snapshot, the
+/// latest one by default. Changes present at that point are snapshotted
+/// first, so restoring never loses work.
+pub fn handle_restore_snapshot(name: Option<String>, snapshot: Option<String>) -> Result<()> {
+    let (key, info) = resolve(name)?;
+    let snapshots = list(&info.path)?;
+    let target = match &snapshot {
+        Some(id) => snapshots.iter().find(|s| &s.id == id).ok_or_else(|| {
+            XlaudeError::NotFound(format!("No snapshot '{id}' of '{}'", info.name))
+        })?,
+        None => snapshots
+            .first()
+            .ok_or_else(|| XlaudeError::NotFound(format!("No snapshots of '{}'", info.name)))?,
+    };
+    let patch = git_output_bytes(
+        &info.path,
+        &[
+            "diff",
+            "--binary",
+            &format!("{}^", target.commit),
+            &target.commit,
+        ],
+    )?;
+
+    let backup = take_snapshot(&info, Some(&format!("Before restoring {}", target.id)))?;
+    if let Some(id) = &backup {
+        status!(
+            "{} Current changes saved as snapshot {}",
+            "📸".green(),
+            id.cyan()
+        );
+    }
+
+    discard_changes(&info.path)?;
+    if let Err(err) = apply_patch(&info.path, &patch, &[]) {
+        // Put back what was there before giving up
+        if let Some(id) = &backup {
+            let previous = list(&info.path)?
+                .into_iter()
+                .find(|s| &s.id == id)
+                .context("Backup snapshot disappeared")?;
+            let diff =
+                git_output_bytes(&info.path, &["diff", "--binary", "HEAD", &previous.commit])?;
+            discard_changes(&info.path)?;
+            apply_patch(&info.path, &diff, &[])?;
+        }
+        return Err(err.context(format!(
+            "Snapshot {} does not apply on the current HEAD; the worktree is unchanged",
+            target.id
+        )));
+    }
+
+    audit::record("restore-snapshot", &key, Some(target.id.clone()));
+    status!(
+        "{} Restored snapshot {} in '{}'",
+        "✅".green(),
+        target.id.cyan(),
+        info.name
+    );
+    Ok(())
+}
+
+fn resolve(name: Option<String>) -> Result<(String, WorktreeInfo)> {
+    let state = XlaudeState::load()?;
+    let key = state.resolve_key(get_command_arg(name)?)?;
+    let info = state.worktrees[&key].clone();
+    if !info.path.exists() {
+        return Err(XlaudeError::NotFound(format!(
+            "Worktree directory {} no longer exists",
+            info.path.display()
+        ))
+        .into());
+    }
+    Ok((key, info))
+}
+
+/// Snapshot the worktree unless it is clean, returning the new id.
+fn take_snapshot(info: &WorktreeInfo, message: Option<&str>) -> Result<Option<String>> {
+    if status_counts(&info.path)?.is_clean() {
+        return Ok(None);
+    }
+
+    let now = Utc::now();
+    let message = message.map_or_else(
+        || format!("Snapshot of {} at {}", info.name, now.to_rfc3339()),
+        str::to_string,
+    );
+    let commit = commit_with_untracked(&info.path, &message)?;
+
+    // Ids sort by time; a second snapshot within the same second gets a suffix
+    let base = now.format("%Y%m%d-%H%M%S").to_string();
+    let taken: Vec<String> = list(&info.path)?.into_iter().map(|s| s.id).collect();
+    let id = unique_name(&base, |id| taken.iter().any(|t| t == id));
+    git_output_bytes(
+        &info.path,
+        &["update-ref", &format!("{SNAPSHOT_REFS}/{id}"), &commit],
+    )?;
+    Ok(Some(id))
+}
+
+/// Snapshots of the worktree at `path`, newest first.
+fn list(path: &Path) -> Result<Vec<Snapshot>> {
+    let output = git_output_bytes(
+        path,
+        &[
+            "for-each-ref",
+            "--sort=-refname",
+            "--format=%(refname)%00%(objectname)%00%(creatordate:iso-strict)%00%(contents:subject)",
+            SNAPSHOT_REFS,
+        ],
+    )?;
+    let prefix = format!("{SNAPSHOT_REFS}/");
+    Ok(String::from_utf8_lossy(&output)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\0');
+            let id = fields.next()?.strip_prefix(&prefix)?.to_string();
+            let commit = fields.next()?.to_string();
+            let created_at = DateTime::parse_from_rfc3339(fields.next()?)
+                .ok()
+                .map(|at| at.with_timezone(&Utc));
+            let message = fields.next().unwrap_or_default().to_string();
+            Some(Snapshot {
+                id,
+                commit,
+                created_at,
+                message,
+            })
+        })
+        .collect())
+}
+
+/// Reset tracked files to HEAD and remove untracked ones. Ignored files stay.
+fn discard_changes(path: &Path) -> Result<()> {
+    git_output_bytes(path, &["reset", "--hard", "--quiet", "HEAD"])?;
+    git_output_bytes(path, &["clean", "-fd", "--quiet"])?;
+    Ok(())
+}
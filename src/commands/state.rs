@@ -0,0 +1,242 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+use colored::Colorize;
+use serde_json::Value;
+
+use crate::audit;
+use crate::error::XlaudeError;
+use crate::input::smart_confirm;
+use crate::migrations;
+use crate::state::{WorktreeInfo, XlaudeState};
+
+/// Prefix used for paths under the home directory in exported files
+const HOME_PREFIX: &str = "~";
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum StateFormat {
+    Json,
+    Toml,
+}
+
+pub fn handle_state_export(format: StateFormat, output: Option<PathBuf>) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let home = home_dir();
+
+    let mut document = serde_json::to_value(&state).context("Failed to serialize state")?;
+    if let Some(object) = document.as_object_mut() {
+        // Revisions are local to each machine's state file, and the trash
+        // points at tags and patch files that only exist here
+        for key in ["revision", "trash", "trashed_worktrees"] {
+            object.remove(key);
+        }
+    }
+    for section in ["worktrees", "repos"] {
+        let Some(entries) = document.get_mut(section).and_then(Value::as_object_mut) else {
+            continue;
+        };
+        for entry in entries.values_mut() {
+            if let Some(path) = entry.get("path").and_then(Value::as_str) {
+                let portable = to_portable_path(Path::new(path), home.as_deref());
+                entry["path"] = Value::from(portable);
+            }
+        }
+    }
+
+    let content = match format {
+        StateFormat::Json => serde_json::to_string_pretty(&document)? + "\n",
+        StateFormat::Toml => toml::to_string_pretty(&document).context("Failed to encode TOML")?,
+    };
+
+    match output {
+        Some(path) => {
+            fs::write(&path, content)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            estatus!(
+                "{} Exported {} worktree{} to {}",
+                "✅".green(),
+                state.worktrees.len(),
+                if state.worktrees.len() == 1 { "" } else { "s" },
+                path.display()
+            );
+        }
+        None => print!("{content}"),
+    }
+
+    Ok(())
+}
+
+pub fn handle_state_import(file: PathBuf, merge: bool) -> Result<()> {
+    let content = if file.as_os_str() == "-" {
+        let mut buffer = String::new();
+        std::io::stdin()
+            .read_to_string(&mut buffer)
+            .context("Failed to read state from stdin")?;
+        buffer
+    } else {
+        fs::read_to_string(&file).with_context(|| format!("Failed to read {}", file.display()))?
+    };
+
+    let mut document = parse_document(&content)?;
+    migrations::migrate(&mut document)?;
+    let mut incoming: XlaudeState =
+        serde_json::from_value(document).context("Imported file is not a valid xlaude state")?;
+
+    let home = home_dir();
+    for config in incoming.repos.values_mut() {
+        if let Some(path) = &config.path {
+            config.path = Some(from_portable_path(path, home.as_deref()));
+        }
+    }
+    let imported: BTreeMap<String, WorktreeInfo> = incoming
+        .worktrees
+        .into_iter()
+        .map(|(key, mut info)| {
+            info.path = from_portable_path(&info.path, home.as_deref());
+            (key, info)
+        })
+        .collect();
+
+    let mut state = XlaudeState::load()?;
+
+    if !merge {
+        if !state.worktrees.is_empty()
+            && !smart_confirm(
+                &format!(
+                    "Replace {} local worktree entries with {} imported ones?",
+                    state.worktrees.len(),
+                    imported.len()
+                ),
+                false,
+            )?
+        {
+            status!("{} Cancelled", "❌".red());
+            return Err(XlaudeError::Cancelled.into());
+        }
+
+        let count = imported.len();
+        state.worktrees = imported.into_iter().collect();
+        state.agent = incoming.agent;
+        state.agents = incoming.agents;
+        state.prompt_templates = incoming.prompt_templates;
+        state.repos = incoming.repos;
+        state.editor = incoming.editor;
+        state.shell = incoming.shell;
+        state.save()?;
+        audit::record("import", "", Some(format!("replaced with {count} entries")));
+
+        status!("{} Imported {} worktree entries", "✅".green(), count);
+        return Ok(());
+    }
+
+    let (mut added, mut unchanged, mut conflicts) = (0usize, 0usize, 0usize);
+    for (key, info) in imported {
+        if let Some(existing) = state.worktrees.get(&key) {
+            if same_entry(existing, &info) {
+                unchanged += 1;
+            } else {
+                conflicts += 1;
+                status!(
+                    "  {} Skipping '{}': local entry differs ({} vs {})",
+                    "⚠️".yellow(),
+                    key.yellow(),
+                    existing.path.display(),
+                    info.path.display()
+                );
+            }
+            continue;
+        }
+
+        if let Some((other_key, _)) = state.find_by_path(&info.path)
+            && state.worktrees[other_key].path == info.path
+        {
+            conflicts += 1;
+            status!(
+                "  {} Skipping '{}': {} is already managed as '{}'",
+                "⚠️".yellow(),
+                key.yellow(),
+                info.path.display(),
+                other_key
+            );
+            continue;
+        }
+
+        status!("  {} Added '{}'", "➕".green(), key.cyan());
+        state.worktrees.insert(key, info);
+        added += 1;
+    }
+
+    // Only fill in settings this machine has not configured yet
+    state.agent = state.agent.take().or(incoming.agent);
+    for (name, command) in incoming.agents {
+        state.agents.entry(name).or_insert(command);
+    }
+    for (name, template) in incoming.prompt_templates {
+        state.prompt_templates.entry(name).or_insert(template);
+    }
+    for (name, config) in incoming.repos {
+        state.repos.entry(name).or_insert(config);
+    }
+    state.editor = state.editor.take().or(incoming.editor);
+    state.shell = state.shell.take().or(incoming.shell);
+    state.save()?;
+    audit::record(
+        "import",
+        "",
+        Some(format!(
+            "merged: {added} added, {unchanged} unchanged, {conflicts} conflicts"
+        )),
+    );
+
+    status!(
+        "{} Merge complete: {} added, {} unchanged, {} conflict{}",
+        "✅".green(),
+        added,
+        unchanged,
+        conflicts,
+        if conflicts == 1 { "" } else { "s" }
+    );
+
+    Ok(())
+}
+
+fn parse_document(content: &str) -> Result<Value> {
+    if let Ok(value) = serde_json::from_str::<Value>(content) {
+        return Ok(value);
+    }
+    match toml::from_str::<Value>(content) {
+        Ok(value) => Ok(value),
+        Err(err) => bail!("Failed to parse state as JSON or TOML: {err}"),
+    }
+}
+
+fn same_entry(a: &WorktreeInfo, b: &WorktreeInfo) -> bool {
+    a.name == b.name && a.branch == b.branch && a.repo_name == b.repo_name && a.path == b.path
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(PathBuf::from)
+}
+
+/// Rewrite paths under the home directory as `~/...` so they resolve on another machine.
+fn to_portable_path(path: &Path, home: Option<&Path>) -> String {
+    if let Some(home) = home
+        && let Ok(rest) = path.strip_prefix(home)
+    {
+        return Path::new(HOME_PREFIX).join(rest).display().to_string();
+    }
+    path.display().to_string()
+}
+
+fn from_portable_path(path: &Path, home: Option<&Path>) -> PathBuf {
+    if let Some(home) = home
+        && let Ok(rest) = path.strip_prefix(HOME_PREFIX)
+    {
+        return home.join(rest);
+    }
+    path.to_path_buf()
+}
@@ -0,0 +1,266 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::audit::{self, AuditEntry};
+use crate::claude::get_claude_sessions;
+use crate::codex;
+use crate::state::XlaudeState;
+
+const TOP_BRANCHES: usize = 5;
+
+#[derive(Debug, Default, Serialize)]
+struct WeekStats {
+    week: String,
+    created: usize,
+    deleted: usize,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct RepoStats {
+    repo_name: String,
+    worktrees: usize,
+    sessions: usize,
+    messages: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct BranchActivity {
+    repo_name: String,
+    branch: String,
+    sessions: usize,
+    messages: usize,
+    last_activity: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+struct Stats {
+    weeks: Vec<WeekStats>,
+    repos: Vec<RepoStats>,
+    // Average hours between creating and deleting a worktree, from the audit log
+    average_lifetime_hours: Option<f64>,
+    completed_lifetimes: usize,
+    top_branches: Vec<BranchActivity>,
+}
+
+pub fn handle_stats(weeks: usize, json: bool) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let entries = audit::read_entries()?;
+
+    let (average_lifetime_hours, completed_lifetimes) = average_lifetime(&entries);
+    let (repos, top_branches) = session_activity(&state);
+    let stats = Stats {
+        weeks: weekly_counts(&entries, weeks),
+        repos,
+        average_lifetime_hours,
+        completed_lifetimes,
+        top_branches,
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+
+    print_stats(&stats);
+    Ok(())
+}
+
+pub(crate) fn is_creation(action: &str) -> bool {
+    matches!(action, "create" | "checkout" | "add" | "copy")
+}
+
+pub(crate) fn is_removal(action: &str) -> bool {
+    matches!(action, "delete" | "clean")
+}
+
+fn weekly_counts(entries: &[AuditEntry], limit: usize) -> Vec<WeekStats> {
+    let mut weeks: BTreeMap<String, WeekStats> = BTreeMap::new();
+    for entry in entries {
+        let created = is_creation(&entry.action);
+        if !created && !is_removal(&entry.action) {
+            continue;
+        }
+
+        let week = entry.timestamp.format("%G-W%V").to_string();
+        let bucket = weeks.entry(week.clone()).or_insert_with(|| WeekStats {
+            week,
+            ..WeekStats::default()
+        });
+        if created {
+            bucket.created += 1;
+        } else {
+            bucket.deleted += 1;
+        }
+    }
+
+    let mut weeks: Vec<_> = weeks.into_values().collect();
+    if limit != 0 && weeks.len() > limit {
+        weeks.drain(..weeks.len() - limit);
+    }
+    weeks
+}
+
+fn average_lifetime(entries: &[AuditEntry]) -> (Option<f64>, usize) {
+    let mut created_at: HashMap<String, DateTime<Utc>> = HashMap::new();
+    let mut lifetimes = Vec::new();
+
+    for entry in entries {
+        if is_creation(&entry.action) {
+            created_at.insert(entry.target.clone(), entry.timestamp);
+        } else if entry.action == "rename" {
+            // Follow the worktree to its new key
+            if let Some(old_key) = entry
+                .detail
+                .as_deref()
+                .and_then(|d| d.strip_prefix("from "))
+                && let Some(ts) = created_at.remove(old_key)
+            {
+                created_at.insert(entry.target.clone(), ts);
+            }
+        } else if is_removal(&entry.action)
+            && let Some(ts) = created_at.remove(&entry.target)
+        {
+            lifetimes.push((entry.timestamp - ts).num_seconds() as f64 / 3600.0);
+        }
+    }
+
+    if lifetimes.is_empty() {
+        return (None, 0);
+    }
+    let average = lifetimes.iter().sum::<f64>() / lifetimes.len() as f64;
+    (Some(average), lifetimes.len())
+}
+
+fn session_activity(state: &XlaudeState) -> (Vec<RepoStats>, Vec<BranchActivity>) {
+    let mut repos: BTreeMap<String, RepoStats> = BTreeMap::new();
+    let mut branches = Vec::new();
+
+    for info in state.worktrees.values() {
+        let claude_sessions = get_claude_sessions(&info.path);
+        let codex_sessions = codex::recent_sessions(&info.path, usize::MAX)
+            .map(|(sessions, _)| sessions)
+            .unwrap_or_default();
+
+        let sessions = claude_sessions.len() + codex_sessions.len();
+        let messages = claude_sessions
+            .iter()
+            .map(|s| s.user_message_count)
+            .chain(codex_sessions.iter().map(|s| s.user_message_count))
+            .sum();
+        let last_activity = claude_sessions
+            .iter()
+            .filter_map(|s| s.last_timestamp)
+            .chain(codex_sessions.iter().filter_map(|s| s.last_timestamp))
+            .max();
+
+        let repo = repos
+            .entry(info.repo_name.clone())
+            .or_insert_with(|| RepoStats {
+                repo_name: info.repo_name.clone(),
+                ..RepoStats::default()
+            });
+        repo.worktrees += 1;
+        repo.sessions += sessions;
+        repo.messages += messages;
+
+        if sessions > 0 {
+            branches.push(BranchActivity {
+                repo_name: info.repo_name.clone(),
+                branch: info.branch.clone(),
+                sessions,
+                messages,
+                last_activity,
+            });
+        }
+    }
+
+    branches.sort_by(|a, b| {
+        b.messages
+            .cmp(&a.messages)
+            .then(b.sessions.cmp(&a.sessions))
+            .then(b.last_activity.cmp(&a.last_activity))
+    });
+    branches.truncate(TOP_BRANCHES);
+
+    (repos.into_values().collect(), branches)
+}
+
+fn print_stats(stats: &Stats) {
+    status!("{} Worktrees per week", "📅".cyan());
+    if stats.weeks.is_empty() {
+        println!("  {}", "No recorded activity".bright_black());
+    }
+    for week in &stats.weeks {
+        println!(
+            "  {}  {} created, {} deleted",
+            week.week.bright_black(),
+            week.created.to_string().green(),
+            week.deleted.to_string().red()
+        );
+    }
+
+    println!();
+    status!("{} Sessions per repository", "🤖".cyan());
+    if stats.repos.is_empty() {
+        println!("  {}", "No worktrees in state".bright_black());
+    }
+    for repo in &stats.repos {
+        println!(
+            "  {}  {} worktree{}, {} session{}, {} message{}",
+            repo.repo_name.cyan(),
+            repo.worktrees,
+            plural(repo.worktrees),
+            repo.sessions,
+            plural(repo.sessions),
+            repo.messages,
+            plural(repo.messages)
+        );
+    }
+
+    println!();
+    match stats.average_lifetime_hours {
+        Some(hours) => status!(
+            "{} Average worktree lifetime: {} ({} deleted worktree{})",
+            "⏳".cyan(),
+            format_hours(hours).yellow(),
+            stats.completed_lifetimes,
+            plural(stats.completed_lifetimes)
+        ),
+        None => status!(
+            "{} Average worktree lifetime: {}",
+            "⏳".cyan(),
+            "no deleted worktrees yet".bright_black()
+        ),
+    }
+
+    if !stats.top_branches.is_empty() {
+        println!();
+        status!("{} Most active branches", "🔥".cyan());
+        for branch in &stats.top_branches {
+            println!(
+                "  {}/{}  {} message{} in {} session{}",
+                branch.repo_name.bright_black(),
+                branch.branch.green(),
+                branch.messages,
+                plural(branch.messages),
+                branch.sessions,
+                plural(branch.sessions)
+            );
+        }
+    }
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 { "" } else { "s" }
+}
+
+fn format_hours(hours: f64) -> String {
+    if hours < 48.0 {
+        format!("{hours:.1}h")
+    } else {
+        format!("{:.1}d", hours / 24.0)
+    }
+}
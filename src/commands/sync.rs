@@ -0,0 +1,86 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+
+use crate::error::XlaudeError;
+use crate::git::execute_git;
+use crate::input::get_command_arg;
+use crate::state::XlaudeState;
+
+pub fn handle_sync(name: Option<String>) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let key = state.resolve_key(get_command_arg(name)?)?;
+    let root = &state.worktrees[&key];
+    let stacked = state.stacked_on(&root.repo_name, &root.name);
+
+    if stacked.is_empty() {
+        status!(
+            "{} No worktrees are stacked on '{}'",
+            "💡".cyan(),
+            root.name.cyan()
+        );
+        return Ok(());
+    }
+
+    let mut rebased = 0;
+    for child_key in &stacked {
+        let child = &state.worktrees[child_key];
+        let parent_name = child.parent.as_deref().unwrap_or_default();
+        let parent = state
+            .worktrees
+            .get(&XlaudeState::make_key(&child.repo_name, parent_name))
+            .with_context(|| format!("Parent worktree '{parent_name}' not found"))?;
+        let dir = child.path.to_string_lossy();
+
+        if let Some(lock) = child.active_lock() {
+            status!(
+                "{} Skipping '{}': locked ({})",
+                "⚠️".yellow(),
+                child.name,
+                lock.describe()
+            );
+            continue;
+        }
+        if !execute_git(&["-C", &dir, "status", "--porcelain"])?.is_empty() {
+            status!(
+                "{} Skipping '{}': uncommitted changes",
+                "⚠️".yellow(),
+                child.name
+            );
+            continue;
+        }
+
+        if execute_git(&["-C", &dir, "rebase", &parent.branch]).is_err() {
+            let conflicted = execute_git(&["-C", &dir, "diff", "--name-only", "--diff-filter=U"])
+                .unwrap_or_default();
+            if !conflicted.is_empty() {
+                status!("{} Conflicts in:", "⚠️".yellow());
+                for file in conflicted.lines() {
+                    println!("    {}", file.red());
+                }
+            }
+            return Err(XlaudeError::Vcs(format!(
+                "Rebasing '{}' onto '{}' stopped on conflicts. Resolve them in {} and run 'git rebase --continue', or 'git rebase --abort'",
+                child.name,
+                parent.branch,
+                child.path.display()
+            ))
+            .into());
+        }
+        status!(
+            "{} Rebased '{}' onto '{}'",
+            "✅".green(),
+            child.name.cyan(),
+            parent.branch.cyan()
+        );
+        rebased += 1;
+    }
+
+    status!(
+        "{} Synced {} of {} stacked worktree{}",
+        "🔄".green(),
+        rebased,
+        stacked.len(),
+        if stacked.len() == 1 { "" } else { "s" }
+    );
+    Ok(())
+}
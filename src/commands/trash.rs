@@ -0,0 +1,172 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::fs;
+
+use crate::audit;
+use crate::error::XlaudeError;
+use crate::git::{apply_patch, execute_git};
+use crate::input::smart_confirm;
+use crate::state::XlaudeState;
+use crate::utils::execute_in_dir;
+
+pub fn handle_trash_list() -> Result<()> {
+    let mut state = XlaudeState::load()?;
+    let before = state.trashed_worktrees.len();
+    state.purge_expired_trash();
+    if state.trashed_worktrees.len() != before {
+        state.save()?;
+    }
+
+    let Some(retention) = state.trash_retention() else {
+        status!(
+            "{} The trash is turned off, run 'xlaude config unset trash_retention_days' to keep deleted worktrees",
+            "💡".cyan()
+        );
+        return Ok(());
+    };
+    if state.trashed_worktrees.is_empty() {
+        status!("{} Trash is empty", "📭".yellow());
+        return Ok(());
+    }
+
+    status!(
+        "{} Deleted worktrees, kept for {} days:",
+        "🗑️".cyan(),
+        retention.num_days()
+    );
+    for entry in state.trashed_worktrees.iter().rev() {
+        let info = &entry.info;
+        let mut details = vec![format!(
+            "deleted {}",
+            entry
+                .deleted_at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+        )];
+        if let Some(head) = &entry.head {
+            details.push(format!("at {}", &head[..head.len().min(12)]));
+        }
+        if entry.patch.is_some() {
+            details.push("uncommitted changes saved".to_string());
+        }
+        println!(
+            "  {} {} → {} ({})",
+            "•".green(),
+            XlaudeState::make_key(&info.repo_name, &info.name).cyan(),
+            info.branch,
+            details.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Recreate a deleted worktree at its old path, on its branch or at the
+/// commit it had checked out, and reapply its uncommitted changes.
+pub fn handle_trash_restore(name: String) -> Result<()> {
+    let mut state = XlaudeState::load()?;
+    state.purge_expired_trash();
+
+    // The most recent deletion wins when a name was reused
+    let index = state
+        .trashed_worktrees
+        .iter()
+        .rposition(|entry| {
+            let info = &entry.info;
+            info.name == name || XlaudeState::make_key(&info.repo_name, &info.name) == name
+        })
+        .ok_or_else(|| {
+            XlaudeError::NotFound(format!("No deleted worktree '{name}' in the trash"))
+        })?;
+    let entry = state.trashed_worktrees[index].clone();
+    let info = entry.info.clone();
+    let key = XlaudeState::make_key(&info.repo_name, &info.name);
+
+    if state.worktrees.contains_key(&key) {
+        return Err(XlaudeError::Refused(format!(
+            "Worktree '{key}' exists again; rename or delete it first"
+        ))
+        .into());
+    }
+    if info.path.exists() {
+        return Err(XlaudeError::Refused(format!(
+            "{} already exists; move it away first",
+            info.path.display()
+        ))
+        .into());
+    }
+
+    let path = info.path.to_string_lossy().to_string();
+    execute_in_dir(info.main_repo_path(), || {
+        let local = format!("refs/heads/{}", info.branch);
+        if execute_git(&["show-ref", "--verify", "--quiet", &local]).is_ok() {
+            execute_git(&["worktree", "add", &path, &info.branch])
+        } else if let Some(head) = &entry.head {
+            execute_git(&["worktree", "add", "-b", &info.branch, &path, head])
+        } else {
+            Err(XlaudeError::NotFound(format!(
+                "Branch '{}' no longer exists and no commit was recorded",
+                info.branch
+            ))
+            .into())
+        }
+    })
+    .with_context(|| format!("Failed to recreate worktree '{key}'"))?;
+    status!(
+        "{} Worktree recreated at {}",
+        "✅".green(),
+        info.path.display()
+    );
+
+    let mut patch_applied = true;
+    if let Some(patch) = &entry.patch {
+        let applied = fs::read(patch)
+            .context("Failed to read saved changes")
+            .and_then(|diff| apply_patch(&info.path, &diff, &[]));
+        match applied {
+            Ok(()) => status!("{} Uncommitted changes restored", "✅".green()),
+            Err(err) => {
+                patch_applied = false;
+                estatus!(
+                    "{} Uncommitted changes did not apply ({err:#}); they are kept in {}",
+                    "⚠️".yellow(),
+                    patch.display()
+                );
+            }
+        }
+    }
+
+    state.trashed_worktrees.remove(index);
+    if patch_applied {
+        entry.discard();
+    }
+    state.worktrees.insert(key.clone(), info);
+    state.save()?;
+    audit::record("restore", &key, None);
+    Ok(())
+}
+
+pub fn handle_trash_empty() -> Result<()> {
+    let mut state = XlaudeState::load()?;
+    if state.trashed_worktrees.is_empty() {
+        status!("{} Trash is empty", "📭".yellow());
+        return Ok(());
+    }
+
+    let count = state.trashed_worktrees.len();
+    let prompt = format!(
+        "Permanently remove {count} deleted worktree{} and their saved changes?",
+        if count == 1 { "" } else { "s" }
+    );
+    if !smart_confirm(&prompt, false)? {
+        status!("{} Cancelled", "❌".red());
+        return Err(XlaudeError::Cancelled.into());
+    }
+
+    for entry in state.trashed_worktrees.drain(..) {
+        entry.discard();
+    }
+    state.save()?;
+    audit::record("empty-trash", "", Some(format!("{count} worktrees")));
+    status!("{} Trash emptied", "✅".green());
+    Ok(())
+}
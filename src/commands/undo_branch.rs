@@ -0,0 +1,72 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+
+use crate::audit;
+use crate::error::XlaudeError;
+use crate::git::{execute_git, get_repo_name};
+use crate::state::XlaudeState;
+
+pub fn handle_undo_branch(name: String) -> Result<()> {
+    let repo_name = get_repo_name().context("Not in a git repository")?;
+    let mut state = XlaudeState::load()?;
+
+    // The most recent deletion wins when a name was reused
+    let index = state
+        .trash
+        .iter()
+        .rposition(|t| t.repo_name == repo_name && (t.name == name || t.branch == name))
+        .ok_or_else(|| {
+            XlaudeError::NotFound(format!(
+                "No deleted branch '{name}' recorded for repository '{repo_name}'"
+            ))
+        })?;
+    let trashed = state.trash[index].clone();
+
+    let ref_name = format!("refs/heads/{}", trashed.branch);
+    if execute_git(&["show-ref", "--verify", "--quiet", &ref_name]).is_ok() {
+        return Err(XlaudeError::Refused(format!(
+            "Branch '{}' already exists; rename or delete it first",
+            trashed.branch
+        ))
+        .into());
+    }
+
+    // Fall back to the recorded commit if the tag was removed by hand
+    let tag_ref = format!("refs/tags/{}", trashed.tag);
+    let tag_exists = execute_git(&["show-ref", "--verify", "--quiet", &tag_ref]).is_ok();
+    let start = if tag_exists {
+        trashed.tag.as_str()
+    } else {
+        trashed.commit.as_str()
+    };
+    execute_git(&["branch", &trashed.branch, start]).with_context(|| {
+        format!(
+            "Failed to restore branch '{}' at {}",
+            trashed.branch, trashed.commit
+        )
+    })?;
+    if tag_exists {
+        execute_git(&["tag", "-d", &trashed.tag]).context("Failed to remove trash tag")?;
+    }
+
+    state.trash.remove(index);
+    state.save()?;
+    audit::record(
+        "undo-branch",
+        &XlaudeState::make_key(&trashed.repo_name, &trashed.name),
+        Some(format!("branch {} at {}", trashed.branch, trashed.commit)),
+    );
+
+    status!(
+        "{} Restored branch '{}' at {}",
+        "✅".green(),
+        trashed.branch.cyan(),
+        &trashed.commit[..trashed.commit.len().min(12)]
+    );
+    status!(
+        "  {} Recreate its worktree with: {}",
+        "💡".cyan(),
+        format!("xlaude checkout {}", trashed.branch).cyan()
+    );
+    Ok(())
+}
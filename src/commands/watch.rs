@@ -0,0 +1,158 @@
+use anyhow::Result;
+use chrono::Local;
+use colored::Colorize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+
+use crate::git;
+use crate::merged;
+use crate::sessions::RunningAgents;
+use crate::state::XlaudeState;
+
+/// What `watch` compares between polls for one worktree.
+#[derive(Debug)]
+struct Snapshot {
+    // None while the directory is missing
+    dirty: Option<bool>,
+    agents: BTreeSet<String>,
+    merged: bool,
+}
+
+/// Print a line whenever a worktree turns dirty or clean, an agent starts or
+/// stops in it, or its branch is found merged. Runs until interrupted.
+pub fn handle_watch(repo: Option<String>, interval: u64) -> Result<()> {
+    let interval = Duration::from_secs(interval.max(1));
+    let mut previous = snapshot(repo.as_deref())?;
+    status!(
+        "{} Watching {} worktree(s), press Ctrl-C to stop",
+        "👀".cyan(),
+        previous.len()
+    );
+
+    loop {
+        std::thread::sleep(interval);
+        let current = snapshot(repo.as_deref())?;
+        let time = Local::now().format("%H:%M:%S").to_string();
+        for (key, event) in changes(&previous, &current) {
+            println!("{} {key} {event}", time.dimmed());
+        }
+        previous = current;
+    }
+}
+
+fn snapshot(repo: Option<&str>) -> Result<BTreeMap<String, Snapshot>> {
+    // Cached for `merged::CHECK_INTERVAL`, so most polls only read state
+    if let Err(err) = merged::refresh_if_stale() {
+        estatus!("{} Could not check merged branches: {err}", "⚠️".yellow());
+    }
+    let state = XlaudeState::load()?;
+    let running = RunningAgents::detect();
+
+    Ok(state
+        .worktrees
+        .iter()
+        .filter(|(_, info)| repo.is_none_or(|repo| info.repo_name == repo))
+        .map(|(key, info)| {
+            let dirty = info
+                .path
+                .exists()
+                .then(|| git::status_counts(&info.path).map(|counts| !counts.is_clean()))
+                .and_then(Result::ok);
+            let agents = running
+                .in_worktree(info)
+                .into_iter()
+                .map(|session| format!("{} (pid {})", session.name(), session.pid))
+                .collect();
+            let snapshot = Snapshot {
+                dirty,
+                agents,
+                merged: info.merged_at.is_some(),
+            };
+            (key.clone(), snapshot)
+        })
+        .collect())
+}
+
+/// Events between two polls, in key order.
+fn changes(
+    previous: &BTreeMap<String, Snapshot>,
+    current: &BTreeMap<String, Snapshot>,
+) -> Vec<(String, String)> {
+    let mut events = Vec::new();
+    for key in previous.keys().filter(|key| !current.contains_key(*key)) {
+        events.push((key.clone(), "removed".red().to_string()));
+    }
+    for (key, now) in current {
+        let Some(before) = previous.get(key) else {
+            events.push((key.clone(), "added".green().to_string()));
+            continue;
+        };
+        let mut push = |event: String| events.push((key.clone(), event));
+        if before.dirty != now.dirty {
+            push(match now.dirty {
+                Some(true) => "dirty".yellow().to_string(),
+                Some(false) => "clean".green().to_string(),
+                None => "directory missing".red().to_string(),
+            });
+        }
+        for agent in now.agents.difference(&before.agents) {
+            push(format!("{} {agent}", "started".cyan()));
+        }
+        for agent in before.agents.difference(&now.agents) {
+            push(format!("{} {agent}", "stopped".cyan()));
+        }
+        if now.merged && !before.merged {
+            push("merged".magenta().to_string());
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(dirty: bool, agents: &[&str], merged: bool) -> Snapshot {
+        Snapshot {
+            dirty: Some(dirty),
+            agents: agents.iter().map(|a| a.to_string()).collect(),
+            merged,
+        }
+    }
+
+    #[test]
+    fn changes_reports_each_transition() {
+        colored::control::set_override(false);
+        let previous = BTreeMap::from([
+            (
+                "app/a".to_string(),
+                snapshot(false, &["claude (pid 1)"], false),
+            ),
+            ("app/gone".to_string(), snapshot(false, &[], false)),
+        ]);
+        let current = BTreeMap::from([
+            (
+                "app/a".to_string(),
+                snapshot(true, &["codex (pid 2)"], true),
+            ),
+            ("app/new".to_string(), snapshot(false, &[], false)),
+        ]);
+
+        let events: Vec<String> = changes(&previous, &current)
+            .into_iter()
+            .map(|(key, event)| format!("{key} {event}"))
+            .collect();
+        assert_eq!(
+            events,
+            [
+                "app/gone removed",
+                "app/a dirty",
+                "app/a started codex (pid 2)",
+                "app/a stopped claude (pid 1)",
+                "app/a merged",
+                "app/new added",
+            ]
+        );
+        assert!(changes(&current, &current).is_empty());
+    }
+}
@@ -1,12 +1,19 @@
-use anyhow::Result;
+use anyhow::{Context, Result, bail};
 use clap_complete::Shell;
+use colored::Colorize;
+use std::fs;
+use std::path::PathBuf;
 
-pub fn handle_completions(shell: Shell) -> Result<()> {
-    match shell {
-        Shell::Bash => print_bash_completions(),
-        Shell::Zsh => print_zsh_completions(),
-        Shell::Fish => print_fish_completions(),
-        _ => {
+pub fn handle_completions(shell: Option<Shell>, install: bool) -> Result<()> {
+    if install {
+        return install_completions(shell);
+    }
+    let Some(shell) = shell else {
+        bail!("Please specify a shell (bash, zsh or fish), or pass --install");
+    };
+    match script(shell) {
+        Some(script) => println!("{script}"),
+        None => {
             eprintln!("Unsupported shell: {:?}", shell);
             eprintln!("Supported shells: bash, zsh, fish");
         }
@@ -14,25 +21,90 @@ pub fn handle_completions(shell: Shell) -> Result<()> {
     Ok(())
 }
 
-fn print_bash_completions() {
-    println!(
-        r#"#!/bin/bash
+fn script(shell: Shell) -> Option<&'static str> {
+    match shell {
+        Shell::Bash => Some(BASH_COMPLETIONS),
+        Shell::Zsh => Some(ZSH_COMPLETIONS),
+        Shell::Fish => Some(FISH_COMPLETIONS),
+        _ => None,
+    }
+}
+
+/// Write the completion script to the per-user directory the shell loads
+/// completions from, for the given shell or the one in `$SHELL`.
+fn install_completions(shell: Option<Shell>) -> Result<()> {
+    let shell = match shell {
+        Some(shell) => shell,
+        None => Shell::from_env()
+            .context("Could not detect your shell from $SHELL, pass it explicitly")?,
+    };
+    let script = script(shell).with_context(|| {
+        format!("Unsupported shell: {shell}. Supported shells: bash, zsh, fish")
+    })?;
+    let path = install_path(shell)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(&path, format!("{script}\n"))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    status!(
+        "{} Installed {} completions to {}",
+        "✅".green(),
+        shell.to_string().cyan(),
+        path.display()
+    );
+    match shell {
+        Shell::Zsh => status!(
+            "  {} Make sure ~/.zshrc has {} before {}",
+            "💡".cyan(),
+            "fpath=(~/.zsh/completions $fpath)".cyan(),
+            "compinit".cyan()
+        ),
+        _ => status!("  {} Open a new shell to use them", "💡".cyan()),
+    }
+    Ok(())
+}
+
+fn install_path(shell: Shell) -> Result<PathBuf> {
+    let home = std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .context("HOME is not set")?;
+    let xdg_dir = |key: &str, default: &str| {
+        std::env::var_os(key)
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .unwrap_or_else(|| home.join(default))
+    };
+    Ok(match shell {
+        // Loaded on demand by bash-completion 2.x
+        Shell::Bash => {
+            xdg_dir("XDG_DATA_HOME", ".local/share").join("bash-completion/completions/xlaude")
+        }
+        Shell::Zsh => home.join(".zsh/completions/_xlaude"),
+        Shell::Fish => xdg_dir("XDG_CONFIG_HOME", ".config").join("fish/completions/xlaude.fish"),
+        _ => bail!("Unsupported shell: {shell}"),
+    })
+}
+
+const BASH_COMPLETIONS: &str = r#"#!/bin/bash
 
-_xlaude() {{
+_xlaude() {
     local cur prev words cword
     if type _init_completion &>/dev/null; then
         _init_completion || return
     else
         # Fallback for older bash-completion
         COMPREPLY=()
-        cur="${{COMP_WORDS[COMP_CWORD]}}"
-        prev="${{COMP_WORDS[COMP_CWORD-1]}}"
-        words=("${{COMP_WORDS[@]}}")
+        cur="${COMP_WORDS[COMP_CWORD]}"
+        prev="${COMP_WORDS[COMP_CWORD-1]}"
+        words=("${COMP_WORDS[@]}")
         cword=$COMP_CWORD
     fi
 
     # Main commands
-    local commands="create open delete add rename list clean dir completions"
+    local commands="create open attach delete add rename move lock unlock kill wait list clean dir completions"
 
     # Complete main commands
     if [[ $cword -eq 1 ]]; then
@@ -41,15 +113,15 @@ _xlaude() {{
     fi
 
     # Complete subcommand arguments
-    case "${{words[1]}}" in
-        open|dir|delete)
+    case "${words[1]}" in
+        open|attach|dir|delete|lock|unlock|kill|wait)
             if [[ $cword -eq 2 ]]; then
                 # Get worktree names for completion
                 local worktrees=$(xlaude complete-worktrees 2>/dev/null)
                 COMPREPLY=($(compgen -W "$worktrees" -- "$cur"))
             fi
             ;;
-        rename)
+        rename|move)
             if [[ $cword -eq 2 ]]; then
                 # Complete first argument (old name)
                 local worktrees=$(xlaude complete-worktrees 2>/dev/null)
@@ -62,25 +134,27 @@ _xlaude() {{
             fi
             ;;
     esac
-}}
+}
 
 complete -F _xlaude xlaude
-"#
-    );
-}
+"#;
 
-fn print_zsh_completions() {
-    println!(
-        r#"#compdef xlaude
+const ZSH_COMPLETIONS: &str = r#"#compdef xlaude
 
-_xlaude() {{
+_xlaude() {
     local -a commands
     commands=(
         'create:Create a new git worktree'
         'open:Open an existing worktree and launch Claude'
+        'attach:Attach to an agent started with open --new-window'
         'delete:Delete a worktree and clean up'
         'add:Add current worktree to xlaude management'
         'rename:Rename a worktree'
+        'move:Move a worktree to another directory'
+        'lock:Lock a worktree so delete, rename and move refuse to touch it'
+        'unlock:Remove a worktree lock'
+        'kill:Stop the agents running in a worktree'
+        'wait:Wait until the agents running in a worktree have exited'
         'list:List all active Claude instances'
         'clean:Clean up invalid worktrees from state'
         'dir:Get the directory path of a worktree'
@@ -94,8 +168,8 @@ _xlaude() {{
     fi
 
     # Subcommand argument completion
-    case "${{words[2]}}" in
-        open|dir|delete)
+    case "${words[2]}" in
+        open|attach|dir|delete|lock|unlock|kill|wait)
             if (( CURRENT == 3 )); then
                 _xlaude_worktrees
             fi
@@ -107,6 +181,13 @@ _xlaude() {{
                 _message "new name"
             fi
             ;;
+        move)
+            if (( CURRENT == 3 )); then
+                _xlaude_worktrees
+            elif (( CURRENT == 4 )); then
+                _files -/
+            fi
+            ;;
         create|add)
             if (( CURRENT == 3 )); then
                 _message "worktree name"
@@ -120,30 +201,37 @@ _xlaude() {{
             fi
             ;;
     esac
-}}
+}
 
-_xlaude_worktrees() {{
+_xlaude_worktrees() {
     local -a worktrees
     local IFS=$'\n'
     
-    # Get detailed worktree information (sorted by repo, then by name)
+    # Get detailed worktree information (sorted by repo, then most recently active first)
     local worktree_data
     worktree_data=($(xlaude complete-worktrees --format=detailed 2>/dev/null))
     
     if [[ -n "$worktree_data" ]]; then
         for line in $worktree_data; do
-            # Parse tab-separated values: name<TAB>repo<TAB>path<TAB>sessions
+            # Parse tab-separated values:
+            # name<TAB>repo<TAB>path<TAB>sessions<TAB>description<TAB>agent<TAB>dirty
             local name=$(echo "$line" | cut -f1)
             local repo=$(echo "$line" | cut -f2)
             local sessions=$(echo "$line" | cut -f4)
+            local description=$(echo "$line" | cut -f5)
+            local agent=$(echo "$line" | cut -f6)
+            local dirty=$(echo "$line" | cut -f7)
+            local status_text="$sessions"
+            [[ -n "$agent" && "$agent" != idle ]] && status_text="$agent, $status_text"
+            [[ -n "$dirty" ]] && status_text="$status_text $dirty"
             
-            # Add worktree with clear repo marker and session info
-            worktrees+=("$name:[$repo] $sessions")
+            # Add worktree with clear repo marker, agent status and session info
+            worktrees+=("$name:[$repo] $status_text${description:+ - $description}")
         done
         
         # Use _describe for better presentation
         # -V flag preserves the order (no sorting)
-        if (( ${{#worktrees[@]}} > 0 )); then
+        if (( ${#worktrees[@]} > 0 )); then
             _describe -V -t worktrees 'worktree' worktrees
         fi
     else
@@ -154,16 +242,12 @@ _xlaude_worktrees() {{
             compadd -a simple_worktrees
         fi
     fi
-}}
+}
 
 _xlaude "$@"
-"#
-    );
-}
+"#;
 
-fn print_fish_completions() {
-    println!(
-        r#"# Fish completion for xlaude
+const FISH_COMPLETIONS: &str = r#"# Fish completion for xlaude
 
 # Disable file completions by default
 complete -c xlaude -f
@@ -171,9 +255,15 @@ complete -c xlaude -f
 # Main commands
 complete -c xlaude -n "__fish_use_subcommand" -a create -d "Create a new git worktree"
 complete -c xlaude -n "__fish_use_subcommand" -a open -d "Open an existing worktree and launch Claude"
+complete -c xlaude -n "__fish_use_subcommand" -a attach -d "Attach to an agent started with open --new-window"
 complete -c xlaude -n "__fish_use_subcommand" -a delete -d "Delete a worktree and clean up"
 complete -c xlaude -n "__fish_use_subcommand" -a add -d "Add current worktree to xlaude management"
 complete -c xlaude -n "__fish_use_subcommand" -a rename -d "Rename a worktree"
+complete -c xlaude -n "__fish_use_subcommand" -a move -d "Move a worktree to another directory"
+complete -c xlaude -n "__fish_use_subcommand" -a lock -d "Lock a worktree so delete, rename and move refuse to touch it"
+complete -c xlaude -n "__fish_use_subcommand" -a unlock -d "Remove a worktree lock"
+complete -c xlaude -n "__fish_use_subcommand" -a kill -d "Stop the agents running in a worktree"
+complete -c xlaude -n "__fish_use_subcommand" -a wait -d "Wait until the agents running in a worktree have exited"
 complete -c xlaude -n "__fish_use_subcommand" -a list -d "List all active Claude instances"
 complete -c xlaude -n "__fish_use_subcommand" -a clean -d "Clean up invalid worktrees from state"
 complete -c xlaude -n "__fish_use_subcommand" -a dir -d "Get the directory path of a worktree"
@@ -182,13 +272,24 @@ complete -c xlaude -n "__fish_use_subcommand" -a completions -d "Generate shell
 # Function to get worktree completions with repo markers
 function __xlaude_worktrees
     xlaude complete-worktrees --format=detailed 2>/dev/null | while read -l line
-        # Split tab-separated values: name<TAB>repo<TAB>path<TAB>sessions
+        # Split tab-separated values:
+        # name<TAB>repo<TAB>path<TAB>sessions<TAB>description<TAB>agent<TAB>dirty
         set -l parts (string split \t $line)
         if test (count $parts) -ge 4
             set -l name $parts[1]
             set -l repo $parts[2]
-            set -l sessions $parts[4]
-            echo "$name\t[$repo] $sessions"
+            set -l status_text $parts[4]
+            if test (count $parts) -ge 6; and test -n "$parts[6]"; and test "$parts[6]" != idle
+                set status_text "$parts[6], $status_text"
+            end
+            if test (count $parts) -ge 7; and test -n "$parts[7]"
+                set status_text "$status_text $parts[7]"
+            end
+            if test (count $parts) -ge 5; and test -n "$parts[5]"
+                echo "$name\t[$repo] $status_text - $parts[5]"
+            else
+                echo "$name\t[$repo] $status_text"
+            end
         end
     end
 end
@@ -199,11 +300,9 @@ function __xlaude_worktrees_simple
 end
 
 # Worktree completions for commands
-complete -c xlaude -n "__fish_seen_subcommand_from open dir delete" -a "(__xlaude_worktrees)"
-complete -c xlaude -n "__fish_seen_subcommand_from rename" -n "not __fish_seen_argument_from (__xlaude_worktrees_simple)" -a "(__xlaude_worktrees)"
+complete -c xlaude -n "__fish_seen_subcommand_from open attach dir delete lock unlock kill wait" -a "(__xlaude_worktrees)"
+complete -c xlaude -n "__fish_seen_subcommand_from rename move" -n "not __fish_seen_argument_from (__xlaude_worktrees_simple)" -a "(__xlaude_worktrees)"
 
 # Shell completions for completions command
 complete -c xlaude -n "__fish_seen_subcommand_from completions" -a "bash zsh fish"
-"#
-    );
-}
+"#;
@@ -0,0 +1,72 @@
+//! Detection of worktrees in the same repository that change the same files,
+//! so branches that will collide at merge time are visible early.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use crate::git::{execute_git, resolve_base_ref};
+use crate::state::WorktreeInfo;
+
+#[derive(Debug, Serialize)]
+pub struct Overlap {
+    pub repo_name: String,
+    pub worktrees: [String; 2],
+    pub files: Vec<String>,
+}
+
+/// Files a worktree changes relative to where it forked from its base branch,
+/// including uncommitted and untracked ones.
+pub fn changed_files(path: &Path) -> Result<BTreeSet<String>> {
+    let dir = path.to_string_lossy();
+    let since = match resolve_base_ref(path) {
+        Some(base) => execute_git(&["-C", &dir, "merge-base", &base, "HEAD"])?,
+        None => "HEAD".to_string(),
+    };
+
+    let mut files: BTreeSet<String> = execute_git(&["-C", &dir, "diff", "--name-only", &since])?
+        .lines()
+        .map(str::to_string)
+        .collect();
+    files.extend(
+        execute_git(&["-C", &dir, "ls-files", "--others", "--exclude-standard"])?
+            .lines()
+            .map(str::to_string),
+    );
+    Ok(files)
+}
+
+/// Compare every pair of worktrees within each repository. Worktrees whose
+/// changes cannot be read (e.g. a missing directory) are skipped.
+pub fn find_overlaps<'a>(worktrees: impl IntoIterator<Item = &'a WorktreeInfo>) -> Vec<Overlap> {
+    let mut repos: BTreeMap<&str, Vec<(&str, BTreeSet<String>)>> = BTreeMap::new();
+    for info in worktrees {
+        if let Ok(files) = changed_files(&info.path)
+            && !files.is_empty()
+        {
+            repos
+                .entry(info.repo_name.as_str())
+                .or_default()
+                .push((info.name.as_str(), files));
+        }
+    }
+
+    let mut overlaps = Vec::new();
+    for (repo_name, mut trees) in repos {
+        trees.sort_by(|a, b| a.0.cmp(b.0));
+        for (i, (first, first_files)) in trees.iter().enumerate() {
+            for (second, second_files) in &trees[i + 1..] {
+                let files: Vec<String> = first_files.intersection(second_files).cloned().collect();
+                if !files.is_empty() {
+                    overlaps.push(Overlap {
+                        repo_name: repo_name.to_string(),
+                        worktrees: [first.to_string(), second.to_string()],
+                        files,
+                    });
+                }
+            }
+        }
+    }
+    overlaps
+}
@@ -1,21 +1,23 @@
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
 use std::io::{Read, Write};
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::process::{Command as StdCommand, Stdio};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result, anyhow};
+use axum::body::Bytes;
 use axum::extract::{
-    Path as AxumPath, State,
+    Path as AxumPath, Query, State,
     ws::{Message, WebSocket, WebSocketUpgrade},
 };
-use axum::http::StatusCode;
-use axum::response::{Html, IntoResponse};
-use axum::routing::{get, post};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::{get, patch, post};
 use axum::{Json, Router};
 use chrono::{DateTime, Utc};
 use futures_util::{SinkExt, StreamExt};
@@ -27,13 +29,21 @@ use tokio::signal;
 use tokio::sync::{Mutex, RwLock, broadcast};
 use uuid::Uuid;
 
-use shell_words::split as shell_split;
-
+use crate::ci;
 use crate::claude;
 use crate::codex;
 use crate::codex::CodexSession;
+use crate::commands::complete;
+use crate::conflicts;
+use crate::git::{self, HeadState};
+use crate::launcher;
+use crate::merged;
+use crate::sessions::{self, RunningAgents};
+use crate::staleness::{self, Staleness};
 use crate::state::{WorktreeInfo, XlaudeState};
-use crate::utils::prepare_agent_command;
+use crate::tools::{self, Tool};
+use crate::utils::{is_offline, prepare_agent_command};
+use crate::worktree_env;
 
 const STATIC_INDEX: &str = include_str!("../dashboard/static/index.html");
 const DEFAULT_ADDR: &str = "127.0.0.1:5710";
@@ -42,35 +52,230 @@ const SESSION_RETENTION_SECS: u64 = 300;
 const PTY_ROWS: u16 = 40;
 const PTY_COLS: u16 = 120;
 const CURSOR_POSITION_QUERY: &[u8] = b"\x1b[6n";
+// Payloads younger than this are served as-is; older ones are served while a refresh runs
+const PAYLOAD_TTL: Duration = Duration::from_secs(5);
+// Beyond this age a cached payload is too old to serve and is rebuilt inline
+const PAYLOAD_MAX_STALE: Duration = Duration::from_secs(60);
+// Files next to the state file describing the running instance
+const INSTANCE_FILE: &str = "dashboard.json";
+const DAEMON_LOG_FILE: &str = "dashboard.log";
+// Per-request budget for querying a federated peer dashboard
+const PEER_TIMEOUT: Duration = Duration::from_secs(3);
+// Budget for a worktree action; a stuck clipboard or file manager must not hang the request
+const ACTION_TIMEOUT: Duration = Duration::from_secs(10);
+// How often the completion cache behind `xlaude prompt` is rebuilt
+const COMPLETION_CACHE_INTERVAL: Duration = Duration::from_secs(30);
+// Never listed or served by the file browser
+const HIDDEN_FILES: &[&str] = &[".git", worktree_env::ENV_FILE];
+// Largest file the file browser returns; longer files are truncated
+const MAX_FILE_PREVIEW_BYTES: usize = 256 * 1024;
 
 #[derive(Clone)]
 pub struct DashboardConfig {
     session_limit: usize,
+    // Other xlaude dashboards whose worktrees are merged into ours, by display name
+    peers: Arc<BTreeMap<String, String>>,
 }
 
 impl Default for DashboardConfig {
     fn default() -> Self {
         Self {
             session_limit: DEFAULT_SESSION_LIMIT,
+            peers: Arc::default(),
         }
     }
 }
 
-pub fn run_dashboard(address: Option<String>, auto_open: bool) -> Result<()> {
+/// A running dashboard, recorded next to the state file so that only one
+/// instance serves a given state and `xlaude dashboard stop` can find it.
+#[derive(Serialize, Deserialize)]
+pub struct DashboardInstance {
+    pub pid: u32,
+    pub addr: SocketAddr,
+    pub started_at: DateTime<Utc>,
+}
+
+impl DashboardInstance {
+    fn path() -> Result<PathBuf> {
+        data_file(INSTANCE_FILE)
+    }
+
+    pub fn log_path() -> Result<PathBuf> {
+        data_file(DAEMON_LOG_FILE)
+    }
+
+    /// Return the dashboard serving this state, discarding records left behind
+    /// by an instance that is no longer listening.
+    pub fn running() -> Result<Option<Self>> {
+        let path = Self::path()?;
+        let Ok(content) = fs::read_to_string(&path) else {
+            return Ok(None);
+        };
+        match serde_json::from_str::<Self>(&content) {
+            Ok(instance) if instance.is_listening() => Ok(Some(instance)),
+            _ => {
+                let _ = fs::remove_file(&path);
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn is_listening(&self) -> bool {
+        std::net::TcpStream::connect_timeout(&self.local_addr(), Duration::from_millis(500)).is_ok()
+    }
+
+    /// Ask the instance for its `/api/health` report.
+    pub fn health(&self) -> Result<DashboardHealth> {
+        let agent: ureq::Agent = ureq::Agent::config_builder()
+            .timeout_global(Some(PEER_TIMEOUT))
+            .build()
+            .into();
+        agent
+            .get(format!("http://{}/api/health", self.local_addr()))
+            .call()
+            .context("Health check failed")?
+            .body_mut()
+            .read_json()
+            .context("Dashboard returned an invalid health report")
+    }
+
+    // Where to connect to an instance bound to all interfaces
+    fn local_addr(&self) -> SocketAddr {
+        let mut addr = self.addr;
+        if addr.ip().is_unspecified() {
+            addr.set_ip(std::net::Ipv4Addr::LOCALHOST.into());
+        }
+        addr
+    }
+
+    fn record(addr: SocketAddr) -> Result<()> {
+        let instance = Self {
+            pid: std::process::id(),
+            addr,
+            started_at: Utc::now(),
+        };
+        fs::write(Self::path()?, serde_json::to_string_pretty(&instance)?)
+            .context("Failed to write dashboard instance file")
+    }
+
+    fn clear() {
+        let Ok(path) = Self::path() else { return };
+        // Leave the file alone if another instance has taken over
+        let ours = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<Self>(&content).ok())
+            .is_some_and(|instance| instance.pid == std::process::id());
+        if ours {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Served at `/api/health` for service managers and `xlaude dashboard status`.
+#[derive(Serialize, Deserialize)]
+pub struct DashboardHealth {
+    pub version: String,
+    pub pid: u32,
+    pub uptime_secs: u64,
+    pub state_path: PathBuf,
+    pub worktrees: usize,
+    // Last time worktree data was gathered for the UI, none before the first request
+    pub last_payload_built_at: Option<DateTime<Utc>>,
+}
+
+fn data_file(name: &str) -> Result<PathBuf> {
+    let path = crate::state::get_state_path()?.with_file_name(name);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create data directory")?;
+    }
+    Ok(path)
+}
+
+pub fn run_dashboard(address: Option<String>, auto_open: bool, peers: Vec<String>) -> Result<()> {
     let addr: SocketAddr = address
         .unwrap_or_else(|| DEFAULT_ADDR.to_string())
         .parse()
         .context("Invalid bind address for dashboard")?;
 
-    let config = DashboardConfig::default();
+    if let Some(existing) = DashboardInstance::running()? {
+        anyhow::bail!(
+            "A dashboard is already running for this state at {} (pid {}). Stop it with 'xlaude dashboard stop'",
+            existing.url(),
+            existing.pid
+        );
+    }
+
+    crate::audit::set_source("dashboard");
+    let mut all_peers = XlaudeState::load()?.dashboard_peers;
+    for peer in peers {
+        let (name, url) = parse_peer(&peer)?;
+        all_peers.insert(name, url);
+    }
+    let config = DashboardConfig {
+        peers: Arc::new(all_peers),
+        ..DashboardConfig::default()
+    };
     let runtime = tokio::runtime::Runtime::new().context("Failed to start async runtime")?;
     runtime.block_on(async move { start_server(addr, config, auto_open).await })
 }
 
+/// Write the current dashboard as a standalone HTML file. The payload is
+/// embedded into the regular frontend, which then renders it read-only.
+pub fn export_report(output: &Path) -> Result<usize> {
+    let config = DashboardConfig {
+        peers: Arc::new(XlaudeState::load()?.dashboard_peers),
+        ..DashboardConfig::default()
+    };
+    let payload = build_dashboard_payload(&config, &WorktreeQuery::default())?;
+    fs::write(output, render_report(&payload)?)
+        .with_context(|| format!("Failed to write {}", output.display()))?;
+    Ok(payload.worktrees.len())
+}
+
+fn render_report(payload: &DashboardPayload) -> Result<String> {
+    // `<` only occurs inside JSON strings, escaping it keeps the payload from
+    // closing the script element
+    let snapshot = serde_json::to_string(payload)?.replace('<', "\\u003c");
+    let script = format!("<script>window.XLAUDE_SNAPSHOT = {snapshot};</script>\n  <script>");
+    Ok(STATIC_INDEX.replacen("<script>", &script, 1))
+}
+
+/// Parse a `--peer` value: `name=url`, or a bare URL named after its host.
+fn parse_peer(value: &str) -> Result<(String, String)> {
+    let (name, url) = match value.split_once('=') {
+        Some((name, url)) => (name.trim().to_string(), url.trim().to_string()),
+        None => {
+            let url = value.trim().to_string();
+            let host = url
+                .split("://")
+                .nth(1)
+                .unwrap_or(&url)
+                .split(['/', ':'])
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            (host, url)
+        }
+    };
+
+    if name.is_empty() || !(url.starts_with("http://") || url.starts_with("https://")) {
+        anyhow::bail!("Invalid peer '{value}', expected NAME=http(s)://HOST:PORT");
+    }
+    Ok((name, url.trim_end_matches('/').to_string()))
+}
+
 async fn start_server(addr: SocketAddr, config: DashboardConfig, auto_open: bool) -> Result<()> {
     let app = Router::new()
         .route("/", get(serve_index))
+        .route("/api/health", get(api_health))
         .route("/api/worktrees", get(api_worktrees))
+        .route("/api/worktrees/:repo/:name", patch(api_update_worktree))
+        .route("/api/worktrees/:repo/:name/files", get(api_worktree_files))
+        .route("/api/worktrees/:repo/:name/ci", get(api_worktree_ci))
         .route(
             "/api/worktrees/:repo/:name/actions",
             post(api_worktree_action),
@@ -95,42 +300,230 @@ async fn start_server(addr: SocketAddr, config: DashboardConfig, auto_open: bool
         .local_addr()
         .context("Failed to read listener address")?;
 
-    println!("🚀 xlaude dashboard available at http://{actual_addr} (press Ctrl+C to stop)");
+    DashboardInstance::record(actual_addr)?;
+    Lazy::force(&SERVER_STARTED);
+    tokio::spawn(check_merged_branches());
+    tokio::spawn(refresh_completion_cache());
+    status!("🚀 xlaude dashboard available at http://{actual_addr} (press Ctrl+C to stop)");
 
-    if auto_open {
+    if auto_open && !is_offline() {
         let url = format!("http://{actual_addr}");
         if let Err(err) = webbrowser::open(&url) {
-            eprintln!("⚠️  Unable to open browser automatically: {err}");
+            estatus!("⚠️  Unable to open browser automatically: {err}");
         }
     }
 
-    axum::serve(listener, app)
+    let result = axum::serve(listener, app)
         .with_graceful_shutdown(shutdown_signal())
         .await
-        .context("Dashboard server exited unexpectedly")?;
-
-    Ok(())
+        .context("Dashboard server exited unexpectedly");
+    DashboardInstance::clear();
+    result
 }
 
 async fn shutdown_signal() {
+    // `xlaude dashboard stop` sends SIGTERM to detached instances
+    #[cfg(unix)]
+    {
+        let mut terminate = signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler");
+        tokio::select! {
+            _ = signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
     let _ = signal::ctrl_c().await;
-    println!("👋 Stopping dashboard");
+
+    status!("👋 Stopping dashboard");
+}
+
+/// Periodically mark worktrees whose branch was merged; the payload picks the
+/// result up on its next rebuild.
+async fn check_merged_branches() {
+    let interval = merged::CHECK_INTERVAL
+        .to_std()
+        .unwrap_or(Duration::from_secs(600));
+    loop {
+        match tokio::task::spawn_blocking(merged::refresh).await {
+            Ok(Err(err)) => eprintln!("[dashboard] failed to check merged branches: {err:?}"),
+            Err(err) => eprintln!("[dashboard] merged check worker panicked: {err:?}"),
+            Ok(Ok(())) => {}
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Keep the completion cache current, which `xlaude prompt` reads instead of
+/// running git on every prompt render.
+async fn refresh_completion_cache() {
+    loop {
+        if let Err(err) = tokio::task::spawn_blocking(complete::refresh).await {
+            eprintln!("[dashboard] completion cache worker panicked: {err:?}");
+        }
+        tokio::time::sleep(COMPLETION_CACHE_INTERVAL).await;
+    }
 }
 
 async fn serve_index() -> Html<&'static str> {
     Html(STATIC_INDEX)
 }
 
-async fn api_worktrees(State(config): State<DashboardConfig>) -> impl IntoResponse {
-    let limit = config.session_limit;
-    match tokio::task::spawn_blocking(move || build_dashboard_payload(limit)).await {
-        Ok(Ok(payload)) => Json(payload).into_response(),
+async fn api_health() -> Response {
+    let last_payload_built_at = *LAST_PAYLOAD_BUILD.read().await;
+    let health = tokio::task::spawn_blocking(move || -> Result<DashboardHealth> {
+        Ok(DashboardHealth {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            pid: std::process::id(),
+            uptime_secs: SERVER_STARTED.elapsed().as_secs(),
+            state_path: crate::state::get_state_path()?,
+            worktrees: XlaudeState::load()?.worktrees.len(),
+            last_payload_built_at,
+        })
+    })
+    .await
+    .map_err(|err| anyhow!("health worker panicked: {err}"))
+    .and_then(|health| health);
+
+    match health {
+        Ok(health) => Json(health).into_response(),
+        Err(err) => (StatusCode::SERVICE_UNAVAILABLE, format!("{err:#}")).into_response(),
+    }
+}
+
+async fn api_worktrees(
+    State(config): State<DashboardConfig>,
+    Query(query): Query<WorktreeQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let cache_key = query.cache_key();
+
+    let cached = PAYLOAD_CACHE.read().await.get(&cache_key).cloned();
+    let payload = match cached {
+        Some(cached) if cached.built_at.elapsed() < PAYLOAD_TTL => cached,
+        Some(cached) if cached.built_at.elapsed() < PAYLOAD_MAX_STALE => {
+            tokio::spawn(async move {
+                if let Err(err) = refresh_payload(config, query, cache_key).await {
+                    eprintln!("[dashboard] background refresh failed: {err:?}");
+                }
+            });
+            cached
+        }
+        _ => match refresh_payload(config, query, cache_key).await {
+            Ok(payload) => payload,
+            Err(err) => {
+                eprintln!("[dashboard] failed to gather worktree info: {err:?}");
+                return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response();
+            }
+        },
+    };
+
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').any(|tag| tag.trim() == payload.etag));
+    let cache_headers = [
+        (header::ETAG, payload.etag.clone()),
+        (header::CACHE_CONTROL, "no-cache".to_string()),
+    ];
+
+    if not_modified {
+        return (StatusCode::NOT_MODIFIED, cache_headers).into_response();
+    }
+    (
+        cache_headers,
+        [(header::CONTENT_TYPE, "application/json")],
+        payload.body,
+    )
+        .into_response()
+}
+
+/// Rebuild the payload for a query and store it in the cache. Refreshes are
+/// serialized so concurrent requests share one round of git invocations.
+async fn refresh_payload(
+    config: DashboardConfig,
+    query: WorktreeQuery,
+    cache_key: String,
+) -> Result<CachedPayload> {
+    let _guard = PAYLOAD_REFRESH.lock().await;
+
+    // Another request may have refreshed this entry while we waited
+    if let Some(cached) = PAYLOAD_CACHE.read().await.get(&cache_key)
+        && cached.built_at.elapsed() < PAYLOAD_TTL
+    {
+        return Ok(cached.clone());
+    }
+
+    let payload = tokio::task::spawn_blocking(move || build_dashboard_payload(&config, &query))
+        .await
+        .map_err(|err| anyhow!("dashboard worker panicked: {err}"))??;
+
+    let cached = CachedPayload {
+        body: Bytes::from(serde_json::to_vec(&payload)?),
+        etag: payload_etag(&payload)?,
+        built_at: Instant::now(),
+    };
+
+    *LAST_PAYLOAD_BUILD.write().await = Some(Utc::now());
+    let mut cache = PAYLOAD_CACHE.write().await;
+    cache.retain(|_, entry| entry.built_at.elapsed() < PAYLOAD_MAX_STALE);
+    cache.insert(cache_key, cached.clone());
+    Ok(cached)
+}
+
+/// Derive the ETag from the payload contents, ignoring the generation timestamp
+/// so unchanged data keeps validating across refreshes.
+fn payload_etag(payload: &DashboardPayload) -> Result<String> {
+    use std::hash::{Hash, Hasher};
+
+    let content = serde_json::to_string(&(&payload.repos, &payload.worktrees))?;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    content.hash(&mut hasher);
+    Ok(format!("\"{:016x}\"", hasher.finish()))
+}
+
+async fn api_worktree_action(
+    AxumPath((repo, name)): AxumPath<(String, String)>,
+    Json(req): Json<ActionRequest>,
+) -> impl IntoResponse {
+    // Actions wait on helpers such as the clipboard tool, so keep them off the runtime
+    let action = tokio::task::spawn_blocking(move || handle_worktree_action(&repo, &name, &req));
+    match tokio::time::timeout(ACTION_TIMEOUT, action).await {
+        Ok(Ok(Ok(response))) => Json(response).into_response(),
+        Ok(Ok(Err((status, message)))) => (status, message).into_response(),
         Ok(Err(err)) => {
-            eprintln!("[dashboard] failed to gather worktree info: {err:?}");
-            (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response()
+            eprintln!("[dashboard] worktree action worker panicked: {err:?}");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "dashboard worker panicked".to_string(),
+            )
+                .into_response()
+        }
+        Err(_) => (
+            StatusCode::GATEWAY_TIMEOUT,
+            format!(
+                "The action did not finish within {} seconds",
+                ACTION_TIMEOUT.as_secs()
+            ),
+        )
+            .into_response(),
+    }
+}
+
+async fn api_update_worktree(
+    AxumPath((repo, name)): AxumPath<(String, String)>,
+    Json(req): Json<WorktreeUpdate>,
+) -> impl IntoResponse {
+    // Saving waits for the state lock and syncs to disk
+    match tokio::task::spawn_blocking(move || update_worktree_metadata(&repo, &name, req)).await {
+        Ok(Ok(metadata)) => {
+            // Metadata changes affect ordering, so drop every cached payload
+            PAYLOAD_CACHE.write().await.clear();
+            Json(metadata).into_response()
         }
+        Ok(Err((status, message))) => (status, message).into_response(),
         Err(err) => {
-            eprintln!("[dashboard] worker thread panicked: {err:?}");
+            eprintln!("[dashboard] worktree update worker panicked: {err:?}");
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "dashboard worker panicked".to_string(),
@@ -140,13 +533,36 @@ async fn api_worktrees(State(config): State<DashboardConfig>) -> impl IntoRespon
     }
 }
 
-async fn api_worktree_action(
+async fn api_worktree_files(
     AxumPath((repo, name)): AxumPath<(String, String)>,
-    Json(req): Json<ActionRequest>,
+    Query(query): Query<FilesQuery>,
 ) -> impl IntoResponse {
-    match handle_worktree_action(&repo, &name, req.action.as_str()) {
-        Ok(response) => Json(response).into_response(),
-        Err((status, message)) => (status, message).into_response(),
+    match tokio::task::spawn_blocking(move || browse_worktree(&repo, &name, &query)).await {
+        Ok(Ok(entry)) => Json(entry).into_response(),
+        Ok(Err((status, message))) => (status, message).into_response(),
+        Err(err) => {
+            eprintln!("[dashboard] file browser worker panicked: {err:?}");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "dashboard worker panicked".to_string(),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn api_worktree_ci(AxumPath((repo, name)): AxumPath<(String, String)>) -> impl IntoResponse {
+    match tokio::task::spawn_blocking(move || worktree_ci(&repo, &name)).await {
+        Ok(Ok(status)) => Json(status).into_response(),
+        Ok(Err((status, message))) => (status, message).into_response(),
+        Err(err) => {
+            eprintln!("[dashboard] CI worker panicked: {err:?}");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "dashboard worker panicked".to_string(),
+            )
+                .into_response()
+        }
     }
 }
 
@@ -352,8 +768,8 @@ fn spawn_session_blocking(
     })?;
 
     let (program, args) =
-        prepare_agent_command(&info.path).context("Failed to resolve agent command")?;
-    let mut builder = CommandBuilder::new(program);
+        prepare_agent_command(&info.path, None).context("Failed to resolve agent command")?;
+    let mut builder = CommandBuilder::new(&program);
     for arg in args {
         builder.arg(arg);
     }
@@ -362,12 +778,20 @@ fn spawn_session_blocking(
     for (key, value) in std::env::vars() {
         builder.env(&key, value);
     }
+    for (key, value) in worktree_env::merged(&info.path, info.env.clone())? {
+        builder.env(&key, value);
+    }
 
     let mut child = pair
         .slave
         .spawn_command(builder)
         .context("Failed to spawn agent")?;
     drop(pair.slave);
+    // Let `list` and other dashboards see the agent, like one started by `open`
+    let pid = child.process_id();
+    if let Some(pid) = pid {
+        sessions::register(pid, &info.path, &program, None);
+    }
 
     let reader = pair
         .master
@@ -423,30 +847,36 @@ fn spawn_session_blocking(
 
     let wait_runtime = runtime.clone();
     let wait_handle = handle.clone();
-    std::thread::spawn(move || match child.wait() {
-        Ok(status) => {
-            let mut detail = format!("exit code {}", status.exit_code());
-            if !status.success() {
-                detail.push_str(" (failed)");
-            }
-            let id = wait_runtime.id().to_string();
-            let key = wait_runtime.worktree_key().to_string();
-            wait_handle.spawn(async move {
-                wait_runtime.push_status("stopped", Some(detail)).await;
-                WORKTREE_SESSION_INDEX.write().await.remove(&key);
-                schedule_session_cleanup(id).await;
-            });
+    std::thread::spawn(move || {
+        let result = child.wait();
+        if let Some(pid) = pid {
+            sessions::unregister(pid);
         }
-        Err(err) => {
-            let id = wait_runtime.id().to_string();
-            let key = wait_runtime.worktree_key().to_string();
-            wait_handle.spawn(async move {
-                wait_runtime
-                    .push_status("stopped", Some(format!("wait error: {err}")))
-                    .await;
-                WORKTREE_SESSION_INDEX.write().await.remove(&key);
-                schedule_session_cleanup(id).await;
-            });
+        match result {
+            Ok(status) => {
+                let mut detail = format!("exit code {}", status.exit_code());
+                if !status.success() {
+                    detail.push_str(" (failed)");
+                }
+                let id = wait_runtime.id().to_string();
+                let key = wait_runtime.worktree_key().to_string();
+                wait_handle.spawn(async move {
+                    wait_runtime.push_status("stopped", Some(detail)).await;
+                    WORKTREE_SESSION_INDEX.write().await.remove(&key);
+                    schedule_session_cleanup(id).await;
+                });
+            }
+            Err(err) => {
+                let id = wait_runtime.id().to_string();
+                let key = wait_runtime.worktree_key().to_string();
+                wait_handle.spawn(async move {
+                    wait_runtime
+                        .push_status("stopped", Some(format!("wait error: {err}")))
+                        .await;
+                    WORKTREE_SESSION_INDEX.write().await.remove(&key);
+                    schedule_session_cleanup(id).await;
+                });
+            }
         }
     });
 
@@ -457,13 +887,19 @@ async fn get_session_runtime(id: &str) -> Option<Arc<SessionRuntime>> {
     SESSION_REGISTRY.read().await.get(id).cloned()
 }
 
-fn build_dashboard_payload(limit: usize) -> Result<DashboardPayload> {
+fn build_dashboard_payload(
+    config: &DashboardConfig,
+    query: &WorktreeQuery,
+) -> Result<DashboardPayload> {
+    let limit = config.session_limit;
     let state = XlaudeState::load()?;
-    let worktree_paths: Vec<PathBuf> = state
+    // Cheap filters run before gathering git and session data
+    let selected: Vec<&WorktreeInfo> = state
         .worktrees
         .values()
-        .map(|info| info.path.clone())
+        .filter(|info| query.matches_info(info))
         .collect();
+    let worktree_paths: Vec<PathBuf> = selected.iter().map(|info| info.path.clone()).collect();
 
     let (codex_sessions, codex_error) =
         match codex::collect_recent_sessions_for_paths(&worktree_paths, limit) {
@@ -479,24 +915,187 @@ fn build_dashboard_payload(limit: usize) -> Result<DashboardPayload> {
         error: codex_error,
     };
 
-    let mut worktrees: Vec<_> = state
-        .worktrees
-        .values()
-        .map(|info| summarize_worktree(info, limit, &codex_context))
+    let running = RunningAgents::detect();
+    let mut worktrees: Vec<_> = selected
+        .into_iter()
+        .map(|info| {
+            let mut summary = summarize_worktree(info, limit, &codex_context);
+            summary.staleness = staleness::classify(&state, info, summary.last_activity);
+            summary.running = running.is_running(info);
+            summary
+        })
+        .filter(|summary| {
+            query
+                .dirty
+                .is_none_or(|dirty| dirty != summary.git_status.clean)
+        })
+        .collect();
+    attach_overlaps(&state, &mut worktrees);
+
+    // Cache fresher activity so `list` and completions can sort without git
+    let fresher: Vec<(String, DateTime<Utc>)> = worktrees
+        .iter()
+        .filter(|summary| {
+            state.worktrees.get(&summary.key).is_some_and(|info| {
+                info.last_activity
+                    .is_none_or(|ts| ts < summary.last_activity)
+            })
+        })
+        .map(|summary| (summary.key.clone(), summary.last_activity))
         .collect();
+    if let Err(err) = XlaudeState::record_activity(fresher) {
+        eprintln!("[dashboard] failed to cache last activity: {err:?}");
+    }
+
+    let mut peers = Vec::new();
+    if !query.is_local() && !config.peers.is_empty() && !is_offline() {
+        for (status, remote) in fetch_peers(&config.peers, query) {
+            peers.push(status);
+            worktrees.extend(remote);
+        }
+    }
 
     worktrees.sort_by(|a, b| {
-        a.repo_name
-            .cmp(&b.repo_name)
+        b.pinned
+            .cmp(&a.pinned)
+            .then_with(|| a.repo_name.cmp(&b.repo_name))
             .then_with(|| a.name.cmp(&b.name))
+            .then_with(|| a.host.cmp(&b.host))
     });
 
     Ok(DashboardPayload {
         generated_at: Utc::now(),
+        repos: summarize_repos(&worktrees),
+        ready_to_clean: worktrees.iter().filter(|tree| tree.merged).count(),
         worktrees,
+        peers,
+    })
+}
+
+/// Flag worktrees that change the same files as another worktree of their
+/// repository. Worktrees hidden by the query still count as a collision.
+fn attach_overlaps(state: &XlaudeState, worktrees: &mut [WorktreeSummary]) {
+    let repos: HashSet<&str> = worktrees.iter().map(|t| t.repo_name.as_str()).collect();
+    let overlaps = conflicts::find_overlaps(
+        state
+            .worktrees
+            .values()
+            .filter(|info| repos.contains(info.repo_name.as_str())),
+    );
+
+    for tree in worktrees.iter_mut() {
+        for overlap in &overlaps {
+            if overlap.repo_name != tree.repo_name {
+                continue;
+            }
+            let other = match &overlap.worktrees {
+                [first, second] if *first == tree.name => second,
+                [first, second] if *second == tree.name => first,
+                _ => continue,
+            };
+            tree.overlaps.push(WorktreeOverlap {
+                worktree: other.clone(),
+                files: overlap.files.clone(),
+            });
+        }
+    }
+}
+
+/// Fetch worktrees from every peer dashboard concurrently, tagging each with
+/// the peer's name. Unreachable peers are reported instead of failing the payload.
+fn fetch_peers(
+    peers: &BTreeMap<String, String>,
+    query: &WorktreeQuery,
+) -> Vec<(PeerStatus, Vec<WorktreeSummary>)> {
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = peers
+            .iter()
+            .map(|(name, url)| {
+                scope.spawn(move || {
+                    let result = fetch_peer(url, query);
+                    let mut status = PeerStatus {
+                        name: name.clone(),
+                        url: url.clone(),
+                        worktrees: 0,
+                        error: None,
+                    };
+                    let worktrees = match result {
+                        Ok(mut worktrees) => {
+                            for tree in &mut worktrees {
+                                tree.key = format!("{name}:{}", tree.key);
+                                tree.host = Some(name.clone());
+                            }
+                            status.worktrees = worktrees.len();
+                            worktrees
+                        }
+                        Err(err) => {
+                            eprintln!("[dashboard] failed to reach peer {name}: {err:?}");
+                            status.error = Some(format!("{err:#}"));
+                            Vec::new()
+                        }
+                    };
+                    (status, worktrees)
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .filter_map(|handle| handle.join().ok())
+            .collect()
     })
 }
 
+fn fetch_peer(url: &str, query: &WorktreeQuery) -> Result<Vec<WorktreeSummary>> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(PEER_TIMEOUT))
+        .build()
+        .into();
+
+    let mut request = agent
+        .get(format!("{url}/api/worktrees"))
+        .query("local", "true");
+    if let Some(repo) = &query.repo {
+        request = request.query("repo", repo);
+    }
+    if let Some(dirty) = query.dirty {
+        request = request.query("dirty", dirty.to_string());
+    }
+    if let Some(q) = &query.q {
+        request = request.query("q", q);
+    }
+
+    let payload: PeerPayload = request
+        .call()
+        .with_context(|| format!("Failed to query {url}"))?
+        .body_mut()
+        .read_json()
+        .context("Peer returned an invalid payload")?;
+    Ok(payload.worktrees)
+}
+
+fn summarize_repos(worktrees: &[WorktreeSummary]) -> Vec<RepoSummary> {
+    let mut repos: BTreeMap<&str, RepoSummary> = BTreeMap::new();
+    for tree in worktrees {
+        let repo = repos
+            .entry(tree.repo_name.as_str())
+            .or_insert_with(|| RepoSummary {
+                repo_name: tree.repo_name.clone(),
+                worktrees: 0,
+                dirty: 0,
+                sessions: 0,
+                last_activity: tree.last_activity,
+            });
+        repo.worktrees += 1;
+        if !tree.git_status.clean {
+            repo.dirty += 1;
+        }
+        repo.sessions += tree.sessions.len();
+        repo.last_activity = repo.last_activity.max(tree.last_activity);
+    }
+    repos.into_values().collect()
+}
+
 fn summarize_worktree(
     info: &WorktreeInfo,
     limit: usize,
@@ -533,7 +1132,7 @@ fn summarize_worktree(
     sessions.sort_by(|a, b| compare_option_desc(a.timestamp, b.timestamp));
     sessions.truncate(limit);
 
-    let mut last_activity = info.created_at;
+    let mut last_activity = info.recent_activity();
     if let Some(ts) = git_status.last_commit_time
         && ts > last_activity
     {
@@ -549,35 +1148,264 @@ fn summarize_worktree(
 
     WorktreeSummary {
         key: format!("{}/{}", info.repo_name, info.name),
+        host: None,
         repo_name: info.repo_name.clone(),
         name: info.name.clone(),
         branch: info.branch.clone(),
         path: info.path.display().to_string(),
         created_at: info.created_at,
+        description: info.description.clone(),
+        tags: info.tags.clone(),
+        pinned: info.pinned,
         last_activity,
         git_status,
         sessions,
         session_error,
+        overlaps: Vec::new(),
+        merged: info.merged_at.is_some(),
+        staleness: None,
+        ttl_days: info.ttl_days,
+        running: false,
+        head_state: git::head_state(&info.path, &info.branch),
+        base_branch: info.base_branch.clone(),
+        base_commit: info.base_commit.clone(),
+        base_moved: info.base_moved(),
+    }
+}
+
+fn update_worktree_metadata(
+    repo: &str,
+    name: &str,
+    req: WorktreeUpdate,
+) -> Result<WorktreeMetadata, (StatusCode, String)> {
+    let internal = |err: anyhow::Error| {
+        eprintln!("[dashboard] failed to update worktree metadata: {err:?}");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to update worktree".to_string(),
+        )
+    };
+
+    let mut state = XlaudeState::load().map_err(internal)?;
+    let key = XlaudeState::make_key(repo, name);
+    let info = state.worktrees.get_mut(&key).ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            format!("Worktree '{repo}/{name}' not found"),
+        )
+    })?;
+
+    let mut changes = Vec::new();
+    if let Some(description) = req.description {
+        info.description = normalize_setting(Some(description));
+        changes.push("description");
+    }
+    if let Some(tags) = req.tags {
+        let mut seen = HashSet::new();
+        info.tags = tags
+            .into_iter()
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty() && seen.insert(tag.clone()))
+            .collect();
+        changes.push("tags");
+    }
+    if let Some(pinned) = req.pinned {
+        info.pinned = pinned;
+        changes.push(if pinned { "pinned" } else { "unpinned" });
     }
+    if let Some(ttl_days) = req.ttl_days {
+        info.ttl_days = (ttl_days > 0).then_some(ttl_days);
+        changes.push("ttl");
+    }
+
+    let metadata = WorktreeMetadata {
+        description: info.description.clone(),
+        tags: info.tags.clone(),
+        pinned: info.pinned,
+        ttl_days: info.ttl_days,
+    };
+
+    if !changes.is_empty() {
+        state.save().map_err(internal)?;
+        crate::audit::record("annotate", &key, Some(changes.join(", ")));
+    }
+    Ok(metadata)
+}
+
+fn worktree_ci(repo: &str, name: &str) -> Result<ci::CiStatus, (StatusCode, String)> {
+    let state = XlaudeState::load().map_err(|err| {
+        eprintln!("[dashboard] failed to load state: {err:?}");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to load state".to_string(),
+        )
+    })?;
+    let info = state
+        .worktrees
+        .get(&XlaudeState::make_key(repo, name))
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Worktree '{repo}/{name}' not found"),
+            )
+        })?;
+    // gh failures (not installed, not authenticated) are shown in the UI
+    ci::fetch(&info.path, &info.branch).map_err(|err| (StatusCode::BAD_GATEWAY, format!("{err:#}")))
+}
+
+fn is_hidden_name(name: &str) -> bool {
+    HIDDEN_FILES.contains(&name)
+}
+
+fn browse_worktree(
+    repo: &str,
+    name: &str,
+    query: &FilesQuery,
+) -> Result<FileBrowserEntry, (StatusCode, String)> {
+    let state = XlaudeState::load().map_err(|err| {
+        eprintln!("[dashboard] failed to load state: {err:?}");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to load state".to_string(),
+        )
+    })?;
+    let key = XlaudeState::make_key(repo, name);
+    let info = state.worktrees.get(&key).ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            format!("Worktree '{repo}/{name}' not found"),
+        )
+    })?;
+
+    let root = info
+        .path
+        .canonicalize()
+        .map_err(|_| (StatusCode::NOT_FOUND, "Worktree path missing".to_string()))?;
+    let relative = query
+        .path
+        .as_deref()
+        .unwrap_or_default()
+        .trim_start_matches('/');
+    // Canonicalizing resolves `..` and symlinks, so anything outside the root is rejected
+    let target = root
+        .join(relative)
+        .canonicalize()
+        .map_err(|_| (StatusCode::NOT_FOUND, format!("'{relative}' not found")))?;
+    if !target.starts_with(&root) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Path escapes the worktree".to_string(),
+        ));
+    }
+    // Hidden from listings, and not served either: they may hold credentials
+    let is_hidden = |path: &Path| {
+        path.components()
+            .any(|part| is_hidden_name(part.as_os_str().to_str().unwrap_or_default()))
+    };
+    if is_hidden(Path::new(relative)) || is_hidden(target.strip_prefix(&root).unwrap_or(&target)) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            format!("'{relative}' is not browsable"),
+        ));
+    }
+
+    let display_path = |path: &Path| {
+        path.strip_prefix(&root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string()
+    };
+    let io_error = |err: std::io::Error| {
+        eprintln!("[dashboard] failed to read {}: {err:?}", target.display());
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to read '{relative}'"),
+        )
+    };
+
+    if target.is_dir() {
+        let mut entries: Vec<FileListing> = fs::read_dir(&target)
+            .map_err(io_error)?
+            .flatten()
+            .filter(|entry| !is_hidden_name(&entry.file_name().to_string_lossy()))
+            .map(|entry| {
+                let metadata = entry.metadata().ok();
+                let is_dir = entry.path().is_dir();
+                FileListing {
+                    name: entry.file_name().to_string_lossy().to_string(),
+                    path: display_path(&entry.path()),
+                    is_dir,
+                    size: metadata.filter(|_| !is_dir).map(|metadata| metadata.len()),
+                }
+            })
+            .collect();
+        entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+        return Ok(FileBrowserEntry::Directory {
+            path: display_path(&target),
+            entries,
+        });
+    }
+
+    // Only the preview is read, however large the file is
+    let file = fs::File::open(&target).map_err(io_error)?;
+    let size = file.metadata().map_err(io_error)?.len();
+    let mut bytes = Vec::new();
+    file.take(MAX_FILE_PREVIEW_BYTES as u64 + 1)
+        .read_to_end(&mut bytes)
+        .map_err(io_error)?;
+    let truncated = bytes.len() > MAX_FILE_PREVIEW_BYTES;
+    let preview = &bytes[..bytes.len().min(MAX_FILE_PREVIEW_BYTES)];
+    let content = match std::str::from_utf8(preview) {
+        Ok(text) => Some(text),
+        // Truncation may split a multi-byte character at the very end
+        Err(err) if truncated && err.error_len().is_none() => {
+            std::str::from_utf8(&preview[..err.valid_up_to()]).ok()
+        }
+        Err(_) => None,
+    }
+    .filter(|text| !text.contains('\0'))
+    .map(str::to_string);
+
+    Ok(FileBrowserEntry::File {
+        path: display_path(&target),
+        size,
+        binary: content.is_none(),
+        truncated: truncated && content.is_some(),
+        content,
+    })
 }
 
 fn load_settings_payload() -> Result<SettingsPayload> {
     let state = XlaudeState::load()?;
-    Ok(SettingsPayload {
+    Ok(settings_payload(&state))
+}
+
+fn settings_payload(state: &XlaudeState) -> SettingsPayload {
+    SettingsPayload {
         editor: state.editor.clone(),
         terminal: state.shell.clone(),
-    })
+        refresh_secs: Some(state.dashboard_refresh_secs()),
+        agents: state.agent_names(),
+    }
 }
 
 fn update_settings_state(req: SettingsPayload) -> Result<SettingsPayload> {
     let mut state = XlaudeState::load()?;
     state.editor = normalize_setting(req.editor);
     state.shell = normalize_setting(req.terminal);
+    state.dashboard_refresh_secs = req.refresh_secs;
     state.save()?;
-    Ok(SettingsPayload {
-        editor: state.editor.clone(),
-        terminal: state.shell.clone(),
-    })
+    crate::audit::record(
+        "settings",
+        "",
+        Some(format!(
+            "editor={} terminal={} refresh={}s",
+            state.editor.as_deref().unwrap_or("-"),
+            state.shell.as_deref().unwrap_or("-"),
+            state.dashboard_refresh_secs()
+        )),
+    );
+    Ok(settings_payload(&state))
 }
 
 fn normalize_setting(value: Option<String>) -> Option<String> {
@@ -624,12 +1452,148 @@ struct CodexContext {
 #[serde(rename_all = "camelCase")]
 struct DashboardPayload {
     generated_at: DateTime<Utc>,
+    repos: Vec<RepoSummary>,
     worktrees: Vec<WorktreeSummary>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    peers: Vec<PeerStatus>,
+    // Worktrees whose branch was merged
+    ready_to_clean: usize,
+}
+
+// The subset of a peer's `/api/worktrees` response that federation consumes
+#[derive(Deserialize)]
+struct PeerPayload {
+    #[serde(default)]
+    worktrees: Vec<WorktreeSummary>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PeerStatus {
+    name: String,
+    url: String,
+    worktrees: usize,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RepoSummary {
+    repo_name: String,
+    worktrees: usize,
+    dirty: usize,
+    sessions: usize,
+    last_activity: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Default, Clone)]
+struct WorktreeQuery {
+    repo: Option<String>,
+    dirty: Option<bool>,
+    q: Option<String>,
+    // Only report this instance's worktrees; set on federated requests to avoid loops
+    local: Option<bool>,
+}
+
+impl WorktreeQuery {
+    fn cache_key(&self) -> String {
+        format!(
+            "{}\0{:?}\0{}\0{}",
+            self.repo.as_deref().unwrap_or_default(),
+            self.dirty,
+            self.q.as_deref().map(str::trim).unwrap_or_default(),
+            self.is_local()
+        )
+    }
+
+    fn is_local(&self) -> bool {
+        self.local.unwrap_or(false)
+    }
+
+    fn matches_info(&self, info: &WorktreeInfo) -> bool {
+        if let Some(repo) = self.repo.as_deref().filter(|r| !r.is_empty())
+            && info.repo_name != repo
+        {
+            return false;
+        }
+
+        let Some(needle) = self.q.as_deref().map(str::trim).filter(|q| !q.is_empty()) else {
+            return true;
+        };
+        let needle = needle.to_lowercase();
+        [
+            info.name.as_str(),
+            info.branch.as_str(),
+            info.repo_name.as_str(),
+            &info.path.to_string_lossy(),
+        ]
+        .iter()
+        .any(|field| field.to_lowercase().contains(&needle))
+    }
 }
 
 #[derive(Deserialize)]
 struct ActionRequest {
     action: String,
+    // Agent name from the registry for `open_agent`; the global agent when unset
+    #[serde(default)]
+    agent: Option<String>,
+    // Initial prompt for `open_agent`
+    #[serde(default)]
+    prompt: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct FilesQuery {
+    // Path relative to the worktree root; the root itself when empty
+    path: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+enum FileBrowserEntry {
+    Directory {
+        path: String,
+        entries: Vec<FileListing>,
+    },
+    #[serde(rename_all = "camelCase")]
+    File {
+        path: String,
+        size: u64,
+        // Binary files are reported without content
+        binary: bool,
+        truncated: bool,
+        content: Option<String>,
+    },
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FileListing {
+    name: String,
+    path: String,
+    is_dir: bool,
+    size: Option<u64>,
+}
+
+// Fields left out of a PATCH body are not touched
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WorktreeUpdate {
+    description: Option<String>,
+    tags: Option<Vec<String>>,
+    pinned: Option<bool>,
+    // 0 removes the worktree's own TTL
+    ttl_days: Option<u32>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WorktreeMetadata {
+    description: Option<String>,
+    tags: Vec<String>,
+    pinned: bool,
+    ttl_days: Option<u32>,
 }
 
 #[derive(Serialize)]
@@ -643,6 +1607,12 @@ struct ActionResponse {
 struct SettingsPayload {
     editor: Option<String>,
     terminal: Option<String>,
+    // Seconds between list refreshes, 0 for none; the default when unset on update
+    #[serde(default)]
+    refresh_secs: Option<u32>,
+    // Agent names available for `open_agent`; ignored on update
+    #[serde(default)]
+    agents: Vec<String>,
 }
 
 #[derive(Serialize)]
@@ -657,35 +1627,70 @@ struct SendMessageRequest {
     message: String,
 }
 
-#[derive(Serialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
 struct WorktreeSummary {
     key: String,
+    // Peer dashboard the worktree lives on; `None` for local worktrees
+    #[serde(skip_serializing_if = "Option::is_none")]
+    host: Option<String>,
     repo_name: String,
     name: String,
     branch: String,
     path: String,
     created_at: DateTime<Utc>,
+    description: Option<String>,
+    tags: Vec<String>,
+    pinned: bool,
     last_activity: DateTime<Utc>,
     git_status: GitStatusSummary,
     sessions: Vec<SessionPreview>,
     session_error: Option<String>,
+    // Other worktrees of the same repository that change the same files
+    overlaps: Vec<WorktreeOverlap>,
+    // Branch found merged, so the worktree can be cleaned up
+    merged: bool,
+    // Past its TTL or idle for too long
+    staleness: Option<Staleness>,
+    ttl_days: Option<u32>,
+    // An agent runs in the worktree, started by `open` or from the dashboard
+    running: bool,
+    // Set when the worktree is detached or its branch was deleted
+    head_state: Option<HeadState>,
+    // Branch and commit the worktree was started from
+    base_branch: Option<String>,
+    base_commit: Option<String>,
+    // Commits added to the base branch since then; above zero it needs a rebase
+    base_moved: Option<usize>,
 }
 
-#[derive(Serialize, Default, Clone)]
-#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase", default)]
+struct WorktreeOverlap {
+    worktree: String,
+    files: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase", default)]
 struct GitStatusSummary {
     clean: bool,
     staged_files: usize,
     unstaged_files: usize,
     untracked_files: usize,
     conflict_files: usize,
+    // Ref the branch is compared against, e.g. `origin/main`
+    base_ref: Option<String>,
+    // Commits on HEAD that are not on the base
+    ahead: Option<usize>,
+    // Commits on the base that HEAD does not contain yet
+    behind: Option<usize>,
     last_commit_message: Option<String>,
     last_commit_time: Option<DateTime<Utc>>,
     error: Option<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct SessionPreview {
     provider: String,
@@ -819,6 +1824,18 @@ static SESSION_REGISTRY: Lazy<RwLock<HashMap<String, Arc<SessionRuntime>>>> =
     Lazy::new(|| RwLock::new(HashMap::new()));
 static WORKTREE_SESSION_INDEX: Lazy<RwLock<HashMap<String, String>>> =
     Lazy::new(|| RwLock::new(HashMap::new()));
+static PAYLOAD_CACHE: Lazy<RwLock<HashMap<String, CachedPayload>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+static PAYLOAD_REFRESH: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+static LAST_PAYLOAD_BUILD: Lazy<RwLock<Option<DateTime<Utc>>>> = Lazy::new(|| RwLock::new(None));
+static SERVER_STARTED: Lazy<Instant> = Lazy::new(Instant::now);
+
+#[derive(Clone)]
+struct CachedPayload {
+    body: Bytes,
+    etag: String,
+    built_at: Instant,
+}
 
 fn summarize_git(path: &Path) -> GitStatusSummary {
     if !path.exists() {
@@ -830,27 +1847,16 @@ fn summarize_git(path: &Path) -> GitStatusSummary {
 
     let mut summary = GitStatusSummary::default();
 
-    match StdCommand::new("git")
-        .current_dir(path)
-        .args(["status", "--short"])
-        .output()
-    {
-        Ok(output) if output.status.success() => {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            for line in stdout.lines() {
-                apply_status_line(line, &mut summary);
-            }
-            summary.clean = summary.staged_files == 0
-                && summary.unstaged_files == 0
-                && summary.untracked_files == 0
-                && summary.conflict_files == 0;
-        }
-        Ok(output) => {
-            summary.error = Some(String::from_utf8_lossy(&output.stderr).trim().to_string());
-            return summary;
+    match git::status_counts(path) {
+        Ok(counts) => {
+            summary.clean = counts.is_clean();
+            summary.staged_files = counts.staged;
+            summary.unstaged_files = counts.unstaged;
+            summary.untracked_files = counts.untracked;
+            summary.conflict_files = counts.conflicted;
         }
         Err(err) => {
-            summary.error = Some(err.to_string());
+            summary.error = Some(err.to_string().trim().to_string());
             return summary;
         }
     }
@@ -860,33 +1866,15 @@ fn summarize_git(path: &Path) -> GitStatusSummary {
         summary.last_commit_time = Some(commit.timestamp);
     }
 
-    summary
-}
-
-fn apply_status_line(line: &str, summary: &mut GitStatusSummary) {
-    if line.starts_with("??") {
-        summary.untracked_files += 1;
-        return;
-    }
-    if line.starts_with("!!") {
-        return;
+    if let Some(base) = git::resolve_base_ref(path)
+        && let Some((ahead, behind)) = git::ahead_behind(path, &base)
+    {
+        summary.base_ref = Some(base);
+        summary.ahead = Some(ahead);
+        summary.behind = Some(behind);
     }
 
-    let mut chars = line.chars();
-    if let Some(first) = chars.next() {
-        match first {
-            ' ' => {}
-            'U' => summary.conflict_files += 1,
-            _ => summary.staged_files += 1,
-        }
-    }
-    if let Some(second) = chars.next() {
-        match second {
-            ' ' => {}
-            'U' => summary.conflict_files += 1,
-            _ => summary.unstaged_files += 1,
-        }
-    }
+    summary
 }
 
 struct CommitSummary {
@@ -895,7 +1883,7 @@ struct CommitSummary {
 }
 
 fn read_last_commit(path: &Path) -> Option<CommitSummary> {
-    let output = StdCommand::new("git")
+    let output = tools::command(Tool::Git)
         .current_dir(path)
         .args(["log", "-1", "--pretty=format:%s%x1f%cI"])
         .output()
@@ -923,8 +1911,9 @@ fn read_last_commit(path: &Path) -> Option<CommitSummary> {
 fn handle_worktree_action(
     repo: &str,
     name: &str,
-    action: &str,
+    req: &ActionRequest,
 ) -> Result<ActionResponse, (StatusCode, String)> {
+    let action = req.action.as_str();
     let state = XlaudeState::load().map_err(|err| {
         eprintln!("[dashboard] failed to load state: {err:?}");
         (
@@ -943,41 +1932,60 @@ fn handle_worktree_action(
 
     let editor_override = state.editor.clone();
     let shell_override = state.shell.clone();
+    let agent = req
+        .agent
+        .as_deref()
+        .map(str::trim)
+        .filter(|agent| !agent.is_empty());
+    if let Some(agent) = agent
+        && state.agent_command(agent).is_none()
+    {
+        return Err((StatusCode::BAD_REQUEST, format!("Unknown agent '{agent}'")));
+    }
 
-    match action {
-        "open_agent" => launch_agent(&info).map(|_| ActionResponse {
-            message: format!("Launching agent for {}/{}", info.repo_name, info.name),
+    let result = match action {
+        "open_agent" => launch_agent(&info, agent, req.prompt.as_deref()).map(|_| ActionResponse {
+            message: format!(
+                "Launching {} for {}/{}",
+                agent.unwrap_or("agent"),
+                info.repo_name,
+                info.name
+            ),
         }),
         "open_shell" => launch_shell(&info, shell_override).map(|_| ActionResponse {
             message: format!("Opening shell in {}", info.path.display()),
         }),
-        "open_editor" => launch_editor(&info.path, editor_override).map(|_| ActionResponse {
+        "open_editor" => launch_editor(&info, editor_override).map(|_| ActionResponse {
             message: format!("Opening editor for {}", info.path.display()),
         }),
+        "reveal" => reveal(&info).map(|_| ActionResponse {
+            message: format!("Showing {} in the file manager", info.path.display()),
+        }),
+        "copy_path" => launcher::copy_to_clipboard(&info.path.to_string_lossy())
+            .map(|_| ActionResponse {
+                message: format!("Copied {}", info.path.display()),
+            })
+            .map_err(|err| {
+                eprintln!("[dashboard] failed to copy path: {err:?}");
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("{err:#}"))
+            }),
         other => Err((
             StatusCode::BAD_REQUEST,
             format!("Unsupported action '{other}'"),
         )),
-    }
-}
-
-fn editor_command(override_cmd: Option<String>) -> String {
-    override_cmd
-        .filter(|s| !s.trim().is_empty())
-        .or_else(|| std::env::var("XLAUDE_DASHBOARD_EDITOR").ok())
-        .or_else(|| std::env::var("EDITOR").ok())
-        .unwrap_or_else(|| "code".to_string())
-}
+    };
 
-fn shell_command(override_cmd: Option<String>) -> String {
-    override_cmd
-        .filter(|s| !s.trim().is_empty())
-        .or_else(|| std::env::var("XLAUDE_DASHBOARD_SHELL").ok())
-        .or_else(|| std::env::var("SHELL").ok())
-        .unwrap_or_else(|| "/bin/zsh".to_string())
+    if result.is_ok() {
+        crate::audit::record(action, &key, agent.map(|agent| format!("agent {agent}")));
+    }
+    result
 }
 
-fn launch_agent(info: &WorktreeInfo) -> Result<(), (StatusCode, String)> {
+fn launch_agent(
+    info: &WorktreeInfo,
+    agent: Option<&str>,
+    prompt: Option<&str>,
+) -> Result<(), (StatusCode, String)> {
     let exe = std::env::current_exe().map_err(|err| {
         eprintln!("[dashboard] failed to locate binary: {err:?}");
         (
@@ -986,10 +1994,18 @@ fn launch_agent(info: &WorktreeInfo) -> Result<(), (StatusCode, String)> {
         )
     })?;
 
-    StdCommand::new(exe)
-        .arg("open")
-        .arg(&info.name)
-        .stdin(Stdio::null())
+    let mut cmd = StdCommand::new(exe);
+    // The qualified key, as worktrees of different repositories can share a name
+    cmd.arg("open")
+        .arg(XlaudeState::make_key(&info.repo_name, &info.name));
+    if let Some(agent) = agent {
+        cmd.arg("--agent").arg(agent);
+    }
+    if let Some(prompt) = prompt.map(str::trim).filter(|p| !p.is_empty()) {
+        cmd.arg("--type-text").arg(prompt);
+    }
+
+    cmd.stdin(Stdio::null())
         .stdout(Stdio::null())
         .stderr(Stdio::null())
         .spawn()
@@ -1007,25 +2023,15 @@ fn launch_shell(
     info: &WorktreeInfo,
     shell_override: Option<String>,
 ) -> Result<(), (StatusCode, String)> {
-    let command = shell_command(shell_override);
-    let mut parts = shell_split(&command).map_err(|err| {
-        eprintln!("[dashboard] failed to parse shell command: {err:?}");
+    let command = launcher::shell_command(shell_override);
+    let mut cmd = launcher::shell(&command, &info.path).map_err(|err| {
+        eprintln!("[dashboard] invalid shell command: {err:?}");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            "Failed to parse shell command".to_string(),
+            format!("Invalid shell command: {err}"),
         )
     })?;
-    if parts.is_empty() {
-        return Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Shell command is empty".to_string(),
-        ));
-    }
-
-    let program = parts.remove(0);
-    let mut cmd = StdCommand::new(program);
-    cmd.args(parts);
-    cmd.current_dir(&info.path);
+    cmd.envs(worktree_env_or_error(info)?);
     cmd.stdin(Stdio::null());
     cmd.stdout(Stdio::null());
     cmd.stderr(Stdio::null());
@@ -1038,26 +2044,19 @@ fn launch_shell(
     })
 }
 
-fn launch_editor(path: &Path, editor_override: Option<String>) -> Result<(), (StatusCode, String)> {
-    let command = editor_command(editor_override);
-    let mut parts = shell_split(&command).map_err(|err| {
-        eprintln!("[dashboard] failed to parse editor command: {err:?}");
+fn launch_editor(
+    info: &WorktreeInfo,
+    editor_override: Option<String>,
+) -> Result<(), (StatusCode, String)> {
+    let command = launcher::editor_command(editor_override);
+    let mut cmd = launcher::editor(&command, &info.path).map_err(|err| {
+        eprintln!("[dashboard] invalid editor command: {err:?}");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            "Failed to parse editor command".to_string(),
+            format!("Invalid editor command: {err}"),
         )
     })?;
-    if parts.is_empty() {
-        return Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Editor command is empty".to_string(),
-        ));
-    }
-
-    let program = parts.remove(0);
-    let mut cmd = StdCommand::new(program);
-    cmd.args(parts);
-    cmd.arg(path);
+    cmd.envs(worktree_env_or_error(info)?);
     cmd.stdin(Stdio::null());
     cmd.stdout(Stdio::null());
     cmd.stderr(Stdio::null());
@@ -1070,6 +2069,32 @@ fn launch_editor(path: &Path, editor_override: Option<String>) -> Result<(), (St
     })?;
     Ok(())
 }
+
+fn reveal(info: &WorktreeInfo) -> Result<(), (StatusCode, String)> {
+    launcher::reveal(&info.path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| ())
+        .map_err(|err| {
+            eprintln!("[dashboard] failed to open file manager: {err:?}");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to open file manager".to_string(),
+            )
+        })
+}
+
+fn worktree_env_or_error(
+    info: &WorktreeInfo,
+) -> Result<BTreeMap<String, String>, (StatusCode, String)> {
+    worktree_env::merged(&info.path, info.env.clone()).map_err(|err| {
+        eprintln!("[dashboard] invalid worktree environment: {err:?}");
+        (StatusCode::BAD_REQUEST, format!("{err:#}"))
+    })
+}
+
 async fn schedule_session_cleanup(id: String) {
     let retention = Duration::from_secs(SESSION_RETENTION_SECS);
     tokio::spawn(async move {
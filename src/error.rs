@@ -0,0 +1,54 @@
+//! Errors that map to distinct process exit codes, so scripts can tell why a
+//! command failed. Anything else exits with 1 (and clap usage errors with 2).
+
+use std::fmt;
+
+#[derive(Debug)]
+pub enum XlaudeError {
+    /// A worktree, repository or other named target does not exist
+    NotFound(String),
+    /// The worktree has pending work, is locked or has a running agent
+    Refused(String),
+    /// The user declined a confirmation; the command already reported it
+    Cancelled,
+    /// A git command failed
+    Vcs(String),
+    /// The agent could not be started or exited with an error
+    Agent(String),
+}
+
+impl XlaudeError {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::NotFound(_) => 3,
+            Self::Refused(_) => 4,
+            Self::Cancelled => 5,
+            Self::Vcs(_) => 6,
+            Self::Agent(_) => 7,
+        }
+    }
+}
+
+impl fmt::Display for XlaudeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(message)
+            | Self::Refused(message)
+            | Self::Vcs(message)
+            | Self::Agent(message) => f.write_str(message),
+            Self::Cancelled => f.write_str("Cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for XlaudeError {}
+
+/// The typed error anywhere in the chain, if any.
+pub fn find(err: &anyhow::Error) -> Option<&XlaudeError> {
+    err.chain()
+        .find_map(|cause| cause.downcast_ref::<XlaudeError>())
+}
+
+pub fn exit_code(err: &anyhow::Error) -> u8 {
+    find(err).map_or(1, XlaudeError::exit_code)
+}
@@ -0,0 +1,112 @@
+//! Availability of the GitHub CLI for lookups that go beyond local git
+//! (pull requests, CI). When `gh` is missing or logged out, optional lookups
+//! are skipped with a warning printed once per run. `forge_checks = false`
+//! and offline mode turn them off entirely.
+
+use anyhow::Result;
+use colored::Colorize;
+use std::fmt;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::state::XlaudeState;
+use crate::tools::{self, Tool};
+use crate::utils::{RunPolicy, is_offline, run_command};
+
+static GH_STATUS: OnceLock<Result<(), Unavailable>> = OnceLock::new();
+static WARNED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unavailable {
+    Disabled,
+    Offline,
+    Missing,
+    LoggedOut,
+}
+
+impl fmt::Display for Unavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Disabled => write!(
+                f,
+                "GitHub lookups are disabled; run 'xlaude config unset forge_checks' to enable them"
+            ),
+            Self::Offline => write!(f, "GitHub lookups are skipped in offline mode"),
+            Self::Missing => write!(
+                f,
+                "GitHub CLI (gh) is not installed; install it from https://cli.github.com"
+            ),
+            Self::LoggedOut => write!(f, "GitHub CLI (gh) is not logged in; run 'gh auth login'"),
+        }
+    }
+}
+
+/// Whether `gh` can be used. The probe runs once per process, the
+/// `forge_checks` setting is read on every call.
+pub fn available() -> Result<(), Unavailable> {
+    if is_offline() {
+        return Err(Unavailable::Offline);
+    }
+    let disabled = XlaudeState::load().is_ok_and(|state| state.forge_checks == Some(false));
+    if disabled {
+        return Err(Unavailable::Disabled);
+    }
+    *GH_STATUS.get_or_init(probe)
+}
+
+/// For optional lookups: whether `gh` can be used, warning once per run when
+/// it is missing or logged out.
+pub fn usable() -> bool {
+    match available() {
+        Ok(()) => true,
+        Err(Unavailable::Disabled | Unavailable::Offline) => false,
+        Err(reason) => {
+            if !WARNED.swap(true, Ordering::Relaxed) {
+                estatus!(
+                    "{} {reason}. Merges done on GitHub (squash, rebase) are not detected; \
+                     run 'xlaude config set forge_checks false' to skip these checks.",
+                    "⚠️".yellow()
+                );
+            }
+            false
+        }
+    }
+}
+
+/// For commands that cannot work without `gh`.
+pub fn require() -> Result<()> {
+    available().map_err(|reason| anyhow::anyhow!("{reason}"))
+}
+
+/// State of the pull request for `branch` (`open`, `closed` or `merged`), if
+/// there is one and `gh` can be used.
+pub fn pr_state(path: &Path, branch: &str) -> Option<String> {
+    if !usable() {
+        return None;
+    }
+    let output = run_command(
+        tools::command(Tool::Gh)
+            .current_dir(path)
+            .args(["pr", "view", branch, "--json", "state", "-q", ".state"]),
+        RunPolicy::network(),
+    )
+    .ok()
+    .filter(|output| output.status.success())?;
+    let state = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .to_lowercase();
+    (!state.is_empty()).then_some(state)
+}
+
+// `gh auth token` only reads the local credentials, it never hits the network
+fn probe() -> Result<(), Unavailable> {
+    match run_command(
+        tools::command(Tool::Gh).args(["auth", "token"]),
+        RunPolicy::local(),
+    ) {
+        Ok(output) if output.status.success() => Ok(()),
+        Ok(_) => Err(Unavailable::LoggedOut),
+        Err(_) => Err(Unavailable::Missing),
+    }
+}
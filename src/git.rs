@@ -1,25 +1,62 @@
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Stdio};
+
+use crate::error::XlaudeError;
+use crate::state::configured_base_branch;
+use crate::tools::{self, Tool};
+use crate::utils::{RunPolicy, ensure_online, run_command};
 
 pub fn execute_git(args: &[&str]) -> Result<String> {
-    let output = Command::new("git")
-        .args(args)
-        .output()
+    let policy = match network_subcommand(args) {
+        Some(subcommand) => {
+            ensure_online(&format!("git {subcommand}"))?;
+            RunPolicy::network()
+        }
+        None => RunPolicy::local(),
+    };
+    let output = run_command(tools::command(Tool::Git).args(args), policy)
         .context("Failed to execute git command")?;
 
     if output.status.success() {
         Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("Git command failed: {}", stderr);
+        Err(XlaudeError::Vcs(format!("Git command failed: {stderr}")).into())
     }
 }
 
+/// The git subcommand in `args` when it reaches a remote. Those get the
+/// network timeout and retries, and are refused in offline mode.
+fn network_subcommand<'a>(args: &[&'a str]) -> Option<&'a str> {
+    let mut args = args.iter();
+    let subcommand = loop {
+        match args.next() {
+            // Global options taking a value
+            Some(&"-C" | &"-c") => {
+                args.next();
+            }
+            Some(arg) if arg.starts_with('-') => {}
+            other => break other,
+        }
+    };
+    subcommand
+        .copied()
+        .filter(|subcommand| matches!(*subcommand, "fetch" | "pull" | "ls-remote"))
+}
+
 pub fn get_repo_name() -> Result<String> {
+    get_repo_name_at(Path::new("."))
+}
+
+/// Repository name of the checkout containing `dir`, see `get_repo_name`.
+pub fn get_repo_name_at(dir: &Path) -> Result<String> {
+    let dir_str = dir.to_str().context("Path contains invalid UTF-8")?;
     // First, try to get the repository name from the remote URL
     // This gives us the true repository name regardless of local directory name
-    if let Ok(remote_url) = execute_git(&["remote", "get-url", "origin"]) {
+    if let Ok(remote_url) = execute_git(&["-C", dir_str, "remote", "get-url", "origin"]) {
         // Extract repo name from URL
         // Supports:
         // - https://github.com/user/repo.git
@@ -30,13 +67,13 @@ pub fn get_repo_name() -> Result<String> {
             name
         } else {
             // Fallback to directory name if URL parsing fails
-            get_repo_name_from_directory()?
+            get_repo_name_from_directory(dir_str)?
         };
         return Ok(repo_name);
     }
 
     // If no remote, use the directory name of the main repository
-    get_repo_name_from_directory()
+    get_repo_name_from_directory(dir_str)
 }
 
 pub fn extract_repo_name_from_url(url: &str) -> Option<String> {
@@ -61,39 +98,166 @@ pub fn extract_repo_name_from_url(url: &str) -> Option<String> {
         .map(|s| s.to_string())
 }
 
-fn get_repo_name_from_directory() -> Result<String> {
+/// Turn a remote URL into the web address of the repository, e.g.
+/// `git@github.com:user/repo.git` into `https://github.com/user/repo`.
+/// Returns `None` for local paths.
+pub fn remote_web_url(url: &str) -> Option<String> {
+    let url = url.trim();
+    let url = url.strip_suffix(".git").unwrap_or(url);
+
+    let (host, path) = if let Some(rest) = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .or_else(|| url.strip_prefix("ssh://"))
+    {
+        rest.split_once('/')?
+    } else if url.contains("://") {
+        return None;
+    } else {
+        // scp-like syntax: user@host:path
+        url.split_once(':')
+            .filter(|(host, _)| !host.contains('/'))?
+    };
+
+    // Drop credentials and ssh ports
+    let host = host.rsplit('@').next()?;
+    let host = host.split(':').next()?;
+    if host.is_empty() || path.is_empty() {
+        return None;
+    }
+    Some(format!("https://{host}/{}", path.trim_start_matches('/')))
+}
+
+/// Page for opening a pull (or merge) request of `branch` against `base`.
+pub fn compare_url(web_url: &str, base: &str, branch: &str) -> String {
+    if web_url.contains("gitlab") {
+        format!("{web_url}/-/merge_requests/new?merge_request[source_branch]={branch}")
+    } else {
+        format!("{web_url}/compare/{base}...{branch}?expand=1")
+    }
+}
+
+fn get_repo_name_from_directory(dir: &str) -> Result<String> {
     // For worktrees, we need to get the main repository path
     // Try to get the common git directory first (which points to main repo for worktrees)
-    let git_common_dir = execute_git(&["rev-parse", "--git-common-dir"])?;
-    let git_dir = execute_git(&["rev-parse", "--git-dir"])?;
-
-    let repo_path = if git_common_dir != git_dir {
-        // We're in a worktree - git-common-dir points to main repo's .git
-        let path = Path::new(&git_common_dir);
-        if path.file_name().is_some_and(|n| n == ".git") {
-            // Get the parent directory which is the main repo
-            path.parent()
-                .and_then(|p| p.to_str())
-                .map(|s| s.to_string())
-                .context("Failed to get main repository path")?
-        } else {
-            // git-common-dir doesn't end with .git, use it directly
-            git_common_dir
-        }
+    let rev_parse = |flag| execute_git(&["-C", dir, "rev-parse", "--path-format=absolute", flag]);
+    let git_common_dir = rev_parse("--git-common-dir")?;
+    let git_dir = rev_parse("--git-dir")?;
+
+    let repo_path = if git_common_dir != git_dir || is_bare_repo(Path::new(dir)) {
+        // In a worktree git-common-dir is the main repository's git directory,
+        // and in a bare repository it is the repository itself
+        repo_dir_from_git_dir(Path::new(&git_common_dir))
     } else {
         // Not in a worktree, use toplevel
-        execute_git(&["rev-parse", "--show-toplevel"])?
+        PathBuf::from(execute_git(&["-C", dir, "rev-parse", "--show-toplevel"])?)
     };
 
-    let path = Path::new(&repo_path);
-    path.file_name()
-        .and_then(|n| n.to_str())
-        .map(std::string::ToString::to_string)
-        .context("Failed to get repository name")
+    repo_name_from_path(&repo_path).context("Failed to get repository name")
+}
+
+/// The `.git` directory of a checkout belongs to its parent, anything else
+/// is a bare repository.
+fn repo_dir_from_git_dir(git_dir: &Path) -> PathBuf {
+    match git_dir.file_name() {
+        Some(name) if name == ".git" => git_dir.parent().unwrap_or(git_dir).to_path_buf(),
+        _ => git_dir.to_path_buf(),
+    }
+}
+
+/// Name a repository after its directory, dropping the `.git` suffix of bare
+/// clones. A bare repository kept as `project/.bare` is named `project`.
+fn repo_name_from_path(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_str()?;
+    if name == ".bare" {
+        return repo_name_from_path(path.parent()?);
+    }
+    Some(name.strip_suffix(".git").unwrap_or(name).to_string())
+}
+
+pub fn is_bare_repo(dir: &Path) -> bool {
+    dir.to_str().is_some_and(|dir| {
+        execute_git(&["-C", dir, "rev-parse", "--is-bare-repository"])
+            .is_ok_and(|output| output == "true")
+    })
+}
+
+/// Repository a worktree was added from, either a regular checkout or a bare
+/// repository. `None` when `worktree` is not a readable git directory.
+pub fn main_repo_dir(worktree: &Path) -> Option<PathBuf> {
+    let common_dir = execute_git(&[
+        "-C",
+        worktree.to_str()?,
+        "rev-parse",
+        "--path-format=absolute",
+        "--git-common-dir",
+    ])
+    .ok()?;
+    Some(repo_dir_from_git_dir(Path::new(&common_dir)))
+}
+
+/// Default branch as known locally, without contacting the remote: the
+/// target of `origin/HEAD`, or what HEAD points at in a bare repository.
+pub fn local_default_branch(dir: &Path) -> Option<String> {
+    let dir = dir.to_str()?;
+    if let Ok(output) = execute_git(&["-C", dir, "symbolic-ref", "refs/remotes/origin/HEAD"])
+        && let Some(branch) = output.strip_prefix("refs/remotes/origin/")
+    {
+        return Some(branch.to_string());
+    }
+    if is_bare_repo(Path::new(dir)) {
+        return execute_git(&["-C", dir, "symbolic-ref", "--short", "HEAD"]).ok();
+    }
+    None
 }
 
 pub fn get_current_branch() -> Result<String> {
-    execute_git(&["symbolic-ref", "--short", "HEAD"])
+    execute_git(&["symbolic-ref", "--short", "HEAD"]).map_err(|err| {
+        if execute_git(&["rev-parse", "--verify", "-q", "HEAD"]).is_ok() {
+            XlaudeError::Vcs("HEAD is detached, check out a branch first".to_string()).into()
+        } else {
+            err
+        }
+    })
+}
+
+/// Ways a worktree's checkout can disagree with the branch recorded for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HeadState {
+    /// HEAD points at a commit rather than a branch
+    Detached,
+    /// The recorded branch no longer exists locally
+    MissingBranch,
+}
+
+impl HeadState {
+    pub fn describe(self, branch: &str) -> String {
+        match self {
+            Self::Detached => "(detached)".to_string(),
+            Self::MissingBranch => format!("{branch} (deleted)"),
+        }
+    }
+}
+
+/// Branch checked out in the worktree at `dir`, `None` when HEAD is
+/// detached or the worktree cannot be read.
+pub fn checked_out_branch(dir: &Path) -> Option<String> {
+    execute_git(&["-C", dir.to_str()?, "symbolic-ref", "--short", "-q", "HEAD"]).ok()
+}
+
+/// Check the worktree at `dir` against its recorded `branch`. `None` when
+/// the branch is checked out as expected or the worktree cannot be read.
+pub fn head_state(dir: &Path, branch: &str) -> Option<HeadState> {
+    let dir = dir.to_str()?;
+    execute_git(&["-C", dir, "rev-parse", "--verify", "-q", "HEAD"]).ok()?;
+    if execute_git(&["-C", dir, "symbolic-ref", "-q", "HEAD"]).is_err() {
+        return Some(HeadState::Detached);
+    }
+    let local = format!("refs/heads/{branch}");
+    execute_git(&["-C", dir, "show-ref", "--verify", "--quiet", &local])
+        .is_err()
+        .then_some(HeadState::MissingBranch)
 }
 
 pub fn get_default_branch() -> Result<String> {
@@ -106,11 +270,9 @@ pub fn get_default_branch() -> Result<String> {
         }
     }
 
-    // Fallback: try to get HEAD from symbolic-ref
-    if let Ok(output) = execute_git(&["symbolic-ref", "refs/remotes/origin/HEAD"])
-        && let Some(branch) = output.strip_prefix("refs/remotes/origin/")
-    {
-        return Ok(branch.to_string());
+    // Fallback: origin/HEAD, or HEAD of a bare repository
+    if let Some(branch) = local_default_branch(Path::new(".")) {
+        return Ok(branch);
     }
 
     // Final fallback: return "main" as the most common default
@@ -120,6 +282,13 @@ pub fn get_default_branch() -> Result<String> {
 pub fn is_base_branch() -> Result<bool> {
     let current = get_current_branch()?;
 
+    // A base branch configured for the repository replaces the defaults
+    if let Ok(repo_name) = get_repo_name()
+        && let Some(pattern) = configured_base_branch(&repo_name)
+    {
+        return Ok(matches_base_branch(&pattern, &current));
+    }
+
     // Get the actual default branch from remote
     let default_branch = get_default_branch().unwrap_or_else(|_| "main".to_string());
 
@@ -163,12 +332,80 @@ pub fn branch_exists(branch_name: &str) -> Result<bool> {
 }
 
 pub fn is_working_tree_clean() -> Result<bool> {
-    let status = execute_git(&["status", "--porcelain"])?;
-    Ok(status.is_empty())
+    Ok(status_counts(Path::new("."))?.is_clean())
 }
 
 pub fn has_unpushed_commits() -> bool {
-    execute_git(&["log", "@{u}.."]).is_ok_and(|output| !output.is_empty())
+    execute_git(&["rev-list", "--count", "@{u}..HEAD"])
+        .is_ok_and(|count| count.parse::<usize>().is_ok_and(|count| count > 0))
+}
+
+/// Changed files of a worktree by kind. Ignored files are not counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatusCounts {
+    pub staged: usize,
+    pub unstaged: usize,
+    pub untracked: usize,
+    pub conflicted: usize,
+}
+
+impl StatusCounts {
+    pub fn is_clean(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// `+` for staged, `*` for modified and `?` for untracked files, empty
+    /// when clean.
+    pub fn markers(&self) -> String {
+        let mut markers = String::new();
+        if self.staged > 0 {
+            markers.push('+');
+        }
+        // Conflicted files are changed in the worktree too
+        if self.unstaged + self.conflicted > 0 {
+            markers.push('*');
+        }
+        if self.untracked > 0 {
+            markers.push('?');
+        }
+        markers
+    }
+}
+
+/// Read `git status` in its machine format, which does not change with the
+/// locale, color settings or git version.
+pub fn status_counts(dir: &Path) -> Result<StatusCounts> {
+    let dir = dir.to_str().context("Path contains invalid UTF-8")?;
+    let output = execute_git(&["-C", dir, "status", "--porcelain=v2", "-z"])?;
+    Ok(parse_status_v2(&output))
+}
+
+fn parse_status_v2(output: &str) -> StatusCounts {
+    let mut counts = StatusCounts::default();
+    let mut records = output.split('\0');
+    while let Some(record) = records.next() {
+        let mut fields = record.split(' ');
+        match fields.next() {
+            Some(kind @ ("1" | "2")) => {
+                // `.` marks an unchanged side
+                let xy = fields.next().unwrap_or_default().as_bytes();
+                if xy.first().is_some_and(|x| *x != b'.') {
+                    counts.staged += 1;
+                }
+                if xy.get(1).is_some_and(|y| *y != b'.') {
+                    counts.unstaged += 1;
+                }
+                // Renames and copies are followed by the original path
+                if kind == "2" {
+                    records.next();
+                }
+            }
+            Some("u") => counts.conflicted += 1,
+            Some("?") => counts.untracked += 1,
+            _ => {}
+        }
+    }
+    counts
 }
 
 pub fn is_in_worktree() -> Result<bool> {
@@ -231,10 +468,280 @@ pub fn update_submodules(worktree_path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Whether `branch` is the configured base branch `pattern`, where a trailing
+/// `*` matches any suffix.
+pub fn matches_base_branch(pattern: &str, branch: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => branch.starts_with(prefix),
+        None => branch == pattern,
+    }
+}
+
+/// Pick the ref a worktree branch should be compared with: the base branch
+/// configured for the repository, the remote default branch when known,
+/// otherwise the first local base branch that exists.
+pub fn resolve_base_ref(path: &Path) -> Option<String> {
+    let git = |args: &[&str]| {
+        run_command(
+            tools::command(Tool::Git).current_dir(path).args(args),
+            RunPolicy::local(),
+        )
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|stdout| !stdout.is_empty())
+    };
+
+    // Prefer the remote copy of a configured branch; for a pattern, take the
+    // most recently updated match
+    if let Ok(repo_name) = get_repo_name_at(path)
+        && let Some(pattern) = configured_base_branch(&repo_name)
+        && let Some(base) = ["refs/remotes/origin", "refs/heads"]
+            .iter()
+            .find_map(|prefix| {
+                git(&[
+                    "for-each-ref",
+                    "--sort=-committerdate",
+                    "--count=1",
+                    "--format=%(refname:short)",
+                    &format!("{prefix}/{pattern}"),
+                ])
+            })
+    {
+        return Some(base);
+    }
+
+    if let Some(remote_head) = git(&["symbolic-ref", "--quiet", "refs/remotes/origin/HEAD"])
+        && let Some(branch) = remote_head.strip_prefix("refs/remotes/")
+    {
+        return Some(branch.to_string());
+    }
+
+    ["main", "master", "develop"]
+        .into_iter()
+        .find(|branch| {
+            git(&[
+                "rev-parse",
+                "--verify",
+                "--quiet",
+                &format!("refs/heads/{branch}"),
+            ])
+            .is_some()
+        })
+        .map(str::to_string)
+}
+
+/// Commits only on HEAD and only on `base`, as `(ahead, behind)`.
+/// Commit `branch` forked from `base`, recorded with new worktrees so later
+/// changes to the base can be told apart from the branch's own.
+pub fn fork_point(dir: &Path, branch: &str, base: &str) -> Option<String> {
+    execute_git(&["-C", dir.to_str()?, "merge-base", base, branch]).ok()
+}
+
+pub fn ahead_behind(path: &Path, base: &str) -> Option<(usize, usize)> {
+    let range = format!("{base}...HEAD");
+    let output = execute_git(&[
+        "-C",
+        path.to_str()?,
+        "rev-list",
+        "--left-right",
+        "--count",
+        &range,
+    ])
+    .ok()?;
+
+    // Left side counts commits only on the base, right side only on HEAD
+    let mut counts = output.split_whitespace().map(str::parse::<usize>);
+    let behind = counts.next()?.ok()?;
+    let ahead = counts.next()?.ok()?;
+    Some((ahead, behind))
+}
+
+/// Size of a worktree branch compared with its base, for `list --verbose`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BranchSummary {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_ref: Option<String>,
+    pub ahead: usize,
+    pub behind: usize,
+    // Committed changes since the merge base
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_commit_subject: Option<String>,
+}
+
+pub fn branch_summary(path: &Path) -> BranchSummary {
+    let mut summary = BranchSummary::default();
+    let Some(dir) = path.to_str().filter(|_| path.exists()) else {
+        return summary;
+    };
+    summary.last_commit_subject = execute_git(&["-C", dir, "log", "-1", "--format=%s"]).ok();
+
+    let Some(base) = resolve_base_ref(path) else {
+        return summary;
+    };
+    if let Some((ahead, behind)) = ahead_behind(path, &base) {
+        summary.ahead = ahead;
+        summary.behind = behind;
+    }
+    let range = format!("{base}...HEAD");
+    // `--shortstat` is translated, `--numstat` is meant for scripts
+    if let Ok(stat) = execute_git(&["-C", dir, "diff", "--numstat", "-z", &range]) {
+        (summary.files_changed, summary.insertions, summary.deletions) = parse_numstat(&stat);
+    }
+    summary.base_ref = Some(base);
+    summary
+}
+
+/// Files, insertions and deletions from `git diff --numstat -z`.
+fn parse_numstat(output: &str) -> (usize, usize, usize) {
+    let (mut files, mut insertions, mut deletions) = (0, 0, 0);
+    let mut records = output.split('\0').filter(|record| !record.is_empty());
+    while let Some(record) = records.next() {
+        let mut fields = record.splitn(3, '\t');
+        let (Some(added), Some(deleted), Some(path)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        files += 1;
+        // Binary files show `-` for both counts
+        insertions += added.parse::<usize>().unwrap_or(0);
+        deletions += deleted.parse::<usize>().unwrap_or(0);
+        // Renames leave the path empty and follow with both paths
+        if path.is_empty() {
+            records.next();
+            records.next();
+        }
+    }
+    (files, insertions, deletions)
+}
+
+/// Run git in `dir` and return raw stdout, for output that may be binary (diffs).
+pub fn git_output_bytes(dir: &Path, args: &[&str]) -> Result<Vec<u8>> {
+    command_output_bytes(tools::command(Tool::Git).current_dir(dir).args(args))
+}
+
+/// Binary diff from HEAD to everything in `dir`, untracked files included.
+/// They are staged in a scratch copy of the index, the real one is untouched.
+pub fn diff_with_untracked(dir: &Path) -> Result<Vec<u8>> {
+    with_scratch_index(dir, |git| {
+        git(&["add", "--all"])?;
+        git(&["diff", "--cached", "--binary", "HEAD"])
+    })
+}
+
+/// Commit everything in `dir`, untracked files included, on top of HEAD
+/// without touching the branch, the index or the files. Returns the commit.
+pub fn commit_with_untracked(dir: &Path, message: &str) -> Result<String> {
+    with_scratch_index(dir, |git| {
+        git(&["add", "--all"])?;
+        let tree = String::from_utf8_lossy(&git(&["write-tree"])?)
+            .trim()
+            .to_string();
+        let commit = git(&["commit-tree", &tree, "-p", "HEAD", "-m", message])?;
+        Ok(String::from_utf8_lossy(&commit).trim().to_string())
+    })
+}
+
+/// Run `f` with a git runner whose index is a scratch copy of the real one,
+/// removed afterwards.
+fn with_scratch_index<T>(
+    dir: &Path,
+    f: impl FnOnce(&dyn Fn(&[&str]) -> Result<Vec<u8>>) -> Result<T>,
+) -> Result<T> {
+    let index = git_output_bytes(
+        dir,
+        &["rev-parse", "--path-format=absolute", "--git-path", "index"],
+    )?;
+    let index = PathBuf::from(String::from_utf8_lossy(&index).trim());
+    let mut scratch = index.clone().into_os_string();
+    scratch.push(".xlaude-snapshot");
+    let scratch = PathBuf::from(scratch);
+    if index.exists() {
+        std::fs::copy(&index, &scratch).context("Failed to copy the git index")?;
+    }
+
+    let git = |args: &[&str]| {
+        command_output_bytes(
+            tools::command(Tool::Git)
+                .current_dir(dir)
+                .env("GIT_INDEX_FILE", &scratch)
+                .args(args),
+        )
+    };
+    let result = f(&git);
+    let _ = std::fs::remove_file(&scratch);
+    result
+}
+
+fn command_output_bytes(cmd: &mut Command) -> Result<Vec<u8>> {
+    let output = run_command(cmd, RunPolicy::local()).context("Failed to execute git command")?;
+    if !output.status.success() {
+        return Err(XlaudeError::Vcs(format!(
+            "Git command failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ))
+        .into());
+    }
+    Ok(output.stdout)
+}
+
+/// Apply a patch produced by `git diff --binary` in `dir`.
+pub fn apply_patch(dir: &Path, patch: &[u8], extra: &[&str]) -> Result<()> {
+    let mut child = tools::command(Tool::Git)
+        .current_dir(dir)
+        .args(["apply", "--binary"])
+        .args(extra)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to execute git command")?;
+    child
+        .stdin
+        .take()
+        .context("Failed to open git stdin")?
+        .write_all(patch)?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(XlaudeError::Vcs(format!(
+            "Git command failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ))
+        .into());
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_machine_readable_status_and_numstat() {
+        let status = "1 M. N... 100644 100644 100644 a b staged.rs\0\
+                      1 .M N... 100644 100644 100644 a b edited.rs\0\
+                      2 R. N... 100644 100644 100644 a b R100 new name.rs\0old name.rs\0\
+                      u UU N... 100644 100644 100644 100644 a b c both.rs\0\
+                      ? new file.txt\0! target\0";
+        assert_eq!(
+            parse_status_v2(status),
+            StatusCounts {
+                staged: 2,
+                unstaged: 1,
+                untracked: 1,
+                conflicted: 1,
+            }
+        );
+        assert!(parse_status_v2("").is_clean());
+
+        let numstat = "3\t1\tsrc/a b.rs\0-\t-\tlogo.png\0\x32\t0\t\0old.rs\0new.rs\0";
+        assert_eq!(parse_numstat(numstat), (3, 5, 1));
+    }
+
     #[test]
     fn test_extract_repo_name_from_url() {
         // GitHub HTTPS
@@ -274,6 +781,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_remote_web_url() {
+        assert_eq!(
+            remote_web_url("git@github.com:user/my-repo.git"),
+            Some("https://github.com/user/my-repo".to_string())
+        );
+        assert_eq!(
+            remote_web_url("https://token@github.com/user/my-repo.git"),
+            Some("https://github.com/user/my-repo".to_string())
+        );
+        assert_eq!(
+            remote_web_url("ssh://git@gitlab.com:2222/group/sub/my-repo.git"),
+            Some("https://gitlab.com/group/sub/my-repo".to_string())
+        );
+        assert_eq!(remote_web_url("/path/to/repos/my-repo.git"), None);
+        assert_eq!(remote_web_url("file:///path/to/my-repo.git"), None);
+    }
+
+    #[test]
+    fn test_matches_base_branch() {
+        assert!(matches_base_branch("trunk", "trunk"));
+        assert!(!matches_base_branch("trunk", "trunk-old"));
+        assert!(matches_base_branch("release/*", "release/1.2"));
+        assert!(!matches_base_branch("release/*", "main"));
+    }
+
     #[test]
     fn test_get_default_branch() {
         // This test will work based on the actual git repository it's run in
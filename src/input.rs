@@ -1,8 +1,11 @@
 use anyhow::Result;
 use atty::Stream;
-use dialoguer::{Confirm, Select};
+use dialoguer::{Confirm, FuzzySelect, Select};
 use std::io::{self, BufRead, BufReader};
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static STDIN_RESERVED: AtomicBool = AtomicBool::new(false);
 
 /// Check if stdin is piped (not a terminal)
 pub fn is_piped_input() -> bool {
@@ -48,8 +51,16 @@ static PIPED_INPUT: std::sync::LazyLock<Mutex<Option<PipedInputReader>>> =
         }
     });
 
+/// Stop prompts from reading stdin, for commands that read a protocol from it.
+pub fn reserve_stdin() {
+    STDIN_RESERVED.store(true, Ordering::Relaxed);
+}
+
 /// Read a single line from piped input
 pub fn read_piped_line() -> Result<Option<String>> {
+    if STDIN_RESERVED.load(Ordering::Relaxed) {
+        return Ok(None);
+    }
     let mut reader = PIPED_INPUT.lock().unwrap();
     match reader.as_mut() {
         Some(r) => r.read_line(),
@@ -126,6 +137,28 @@ where
     Ok(Some(selection))
 }
 
+/// Like `smart_select`, but the interactive list is filtered as you type.
+pub fn smart_fuzzy_select<T>(
+    prompt: &str,
+    items: &[T],
+    display_fn: impl Fn(&T) -> String,
+) -> Result<Option<usize>>
+where
+    T: Clone,
+{
+    if is_piped_input() || std::env::var("XLAUDE_NON_INTERACTIVE").is_ok() {
+        return smart_select(prompt, items, display_fn);
+    }
+
+    let display_items: Vec<String> = items.iter().map(display_fn).collect();
+    let selection = FuzzySelect::new()
+        .with_prompt(prompt)
+        .items(&display_items)
+        .interact_opt()?;
+
+    Ok(selection)
+}
+
 /// Get command argument with pipe input support
 /// Priority: CLI argument > piped input > None
 pub fn get_command_arg(arg: Option<String>) -> Result<Option<String>> {
@@ -0,0 +1,203 @@
+//! Editor, shell and file manager launchers for the dashboard and `xlaude
+//! config`, with defaults that exist on each platform.
+//!
+//! A command may contain a `{path}` argument, replaced by the directory being
+//! opened. Without one, editors get the path appended, and so do shells
+//! started through macOS `open` (e.g. `open -a iTerm`), which would
+//! otherwise ignore the working directory.
+
+use anyhow::{Context, Result, anyhow, bail};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+const PATH_PLACEHOLDER: &str = "{path}";
+
+/// Editor for opening a worktree from the dashboard: the configured command,
+/// `XLAUDE_DASHBOARD_EDITOR`, `EDITOR`, then VS Code when installed or the
+/// platform's file opener.
+pub fn editor_command(configured: Option<String>) -> String {
+    configured
+        .filter(|s| !s.trim().is_empty())
+        .or_else(|| env_command("XLAUDE_DASHBOARD_EDITOR"))
+        .or_else(|| env_command("EDITOR"))
+        .unwrap_or_else(|| {
+            if on_path("code") {
+                "code".to_string()
+            } else {
+                default_opener().to_string()
+            }
+        })
+}
+
+/// Shell for opening a worktree from the dashboard: the configured command,
+/// `XLAUDE_DASHBOARD_SHELL`, the user's shell, then the platform default.
+pub fn shell_command(configured: Option<String>) -> String {
+    configured
+        .filter(|s| !s.trim().is_empty())
+        .or_else(|| env_command("XLAUDE_DASHBOARD_SHELL"))
+        .or_else(|| env_command(if cfg!(windows) { "COMSPEC" } else { "SHELL" }))
+        .unwrap_or_else(|| if cfg!(windows) { "cmd.exe" } else { "/bin/sh" }.to_string())
+}
+
+/// Editor for files edited in the terminal, such as `xlaude config`.
+pub fn terminal_editor_command() -> String {
+    env_command("VISUAL")
+        .or_else(|| env_command("EDITOR"))
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string())
+}
+
+/// Build the process for an editor command opening `path`.
+pub fn editor(command: &str, path: &Path) -> Result<Command> {
+    build(command, path, true)
+}
+
+/// Build the process for a shell command started in `path`.
+pub fn shell(command: &str, path: &Path) -> Result<Command> {
+    let mut cmd = build(command, path, false)?;
+    cmd.current_dir(path);
+    Ok(cmd)
+}
+
+/// Build the process showing `path` in the platform's file manager.
+pub fn reveal(path: &Path) -> Command {
+    let mut cmd = Command::new(default_opener());
+    cmd.arg(path);
+    cmd
+}
+
+/// Put `text` on the system clipboard through the platform's clipboard tool.
+pub fn copy_to_clipboard(text: &str) -> Result<()> {
+    let candidates: &[&[&str]] = if cfg!(target_os = "macos") {
+        &[&["pbcopy"]]
+    } else if cfg!(windows) {
+        &[&["clip"]]
+    } else {
+        &[
+            &["wl-copy"],
+            &["xclip", "-selection", "clipboard"],
+            &["xsel", "--clipboard", "--input"],
+        ]
+    };
+    let (program, args) = candidates
+        .iter()
+        .filter_map(|parts| parts.split_first())
+        .find(|(program, _)| cfg!(any(target_os = "macos", windows)) || on_path(program))
+        .context("No clipboard tool found (install wl-copy, xclip or xsel)")?;
+
+    let mut child = Command::new(program)
+        .args(args.iter())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("Failed to run {program}"))?;
+    child
+        .stdin
+        .take()
+        .context("Clipboard tool has no stdin")?
+        .write_all(text.as_bytes())?;
+    let status = child.wait()?;
+    if !status.success() {
+        bail!("{program} exited with {status}");
+    }
+    Ok(())
+}
+
+fn build(command: &str, path: &Path, append_path: bool) -> Result<Command> {
+    let parts = shell_words::split(command)
+        .map_err(|e| anyhow!("Failed to parse command: {command} ({e})"))?;
+    let Some((program, args)) = parts.split_first() else {
+        bail!("Command is empty");
+    };
+
+    let path = path.to_str().context("Path is not valid UTF-8")?;
+    let mut cmd = Command::new(program);
+    let mut placed = false;
+    for arg in args {
+        if arg.contains(PATH_PLACEHOLDER) {
+            placed = true;
+            cmd.arg(arg.replace(PATH_PLACEHOLDER, path));
+        } else {
+            cmd.arg(arg);
+        }
+    }
+    if !placed && (append_path || is_macos_open(program)) {
+        cmd.arg(path);
+    }
+    Ok(cmd)
+}
+
+fn is_macos_open(program: &str) -> bool {
+    cfg!(target_os = "macos") && matches!(program, "open" | "/usr/bin/open")
+}
+
+fn default_opener() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(windows) {
+        "explorer"
+    } else {
+        "xdg-open"
+    }
+}
+
+fn env_command(key: &str) -> Option<String> {
+    std::env::var(key).ok().filter(|s| !s.trim().is_empty())
+}
+
+fn on_path(program: &str) -> bool {
+    let Some(paths) = std::env::var_os("PATH") else {
+        return false;
+    };
+    std::env::split_paths(&paths).any(|dir| {
+        let candidate = dir.join(program);
+        candidate.is_file() || (cfg!(windows) && candidate.with_extension("cmd").is_file())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(cmd: &Command) -> Vec<String> {
+        cmd.get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_editor_appends_path() {
+        let cmd = editor("code --new-window", Path::new("/tmp/wt")).unwrap();
+        assert_eq!(cmd.get_program(), "code");
+        assert_eq!(args(&cmd), ["--new-window", "/tmp/wt"]);
+    }
+
+    #[test]
+    fn test_placeholder_replaces_appended_path() {
+        let cmd = editor(
+            r#"open -a "Visual Studio Code" {path}"#,
+            Path::new("/tmp/wt"),
+        )
+        .unwrap();
+        assert_eq!(args(&cmd), ["-a", "Visual Studio Code", "/tmp/wt"]);
+
+        let cmd = shell("tmux new -c {path}", Path::new("/tmp/wt")).unwrap();
+        assert_eq!(args(&cmd), ["new", "-c", "/tmp/wt"]);
+    }
+
+    #[test]
+    fn test_shell_runs_in_path() {
+        let cmd = shell("zsh -l", Path::new("/tmp/wt")).unwrap();
+        assert_eq!(args(&cmd), ["-l"]);
+        assert_eq!(cmd.get_current_dir(), Some(Path::new("/tmp/wt")));
+        assert!(shell("", Path::new("/tmp/wt")).is_err());
+    }
+
+    #[test]
+    fn test_reveal_uses_platform_opener() {
+        let cmd = reveal(Path::new("/tmp/wt"));
+        assert_eq!(cmd.get_program(), default_opener());
+        assert_eq!(args(&cmd), ["/tmp/wt"]);
+    }
+}
@@ -0,0 +1,56 @@
+//! Worktree management behind the `xlaude` command line, for tools that
+//! want to drive it without spawning the binary (editor plugins, bots).
+//!
+//! The public modules are the supported surface:
+//!
+//! - [`state`]: the state file, [`XlaudeState`] and [`WorktreeInfo`]
+//! - [`git`]: repository and worktree queries
+//! - [`sessions`]: agents started by xlaude that are still running
+//! - [`commands`]: the command handlers the CLI calls, plus building blocks
+//!   such as [`commands::open::resolve_invocation`] that return data instead
+//!   of printing it
+//! - [`error`]: typed failures and their exit codes
+//!
+//! Handlers print status lines like the CLI does; [`output::init`] and
+//! [`output::keep_stdout_clean`] control where they go.
+//!
+//! ```no_run
+//! use xlaude::state::XlaudeState;
+//!
+//! let state = XlaudeState::load()?;
+//! for (key, info) in &state.worktrees {
+//!     println!("{key} {}", info.path.display());
+//! }
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+// Declared first so its `status!` macros are visible in every other module
+#[macro_use]
+pub mod output;
+
+pub mod commands;
+pub mod completions;
+pub mod error;
+pub mod git;
+pub mod sessions;
+pub mod state;
+
+mod audit;
+mod ci;
+mod claude;
+mod codex;
+mod conflicts;
+mod dashboard;
+mod forge;
+mod input;
+mod launcher;
+mod merged;
+mod migrations;
+mod staleness;
+mod templates;
+mod tmux;
+mod tools;
+mod utils;
+mod worktree_env;
+
+pub use state::{WorktreeInfo, XlaudeState};
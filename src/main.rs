@@ -1,26 +1,51 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 use clap_complete::Shell;
+use std::process::ExitCode;
+use xlaude::{commands, completions, error, output};
 
-mod claude;
-mod codex;
-mod commands;
-mod completions;
-mod dashboard;
-mod git;
-mod input;
-mod state;
-mod utils;
-
-use commands::{
-    handle_add, handle_checkout, handle_clean, handle_config, handle_create, handle_dashboard,
-    handle_delete, handle_dir, handle_list, handle_open, handle_rename,
+use xlaude::commands::{
+    CreateOptions, GcAction, ListFormat, ListSort, OpenOptions, StateFormat, handle_add,
+    handle_apply, handle_attach, handle_batch, handle_checkout, handle_ci, handle_clean,
+    handle_clone, handle_config, handle_config_get, handle_config_list, handle_config_set,
+    handle_config_unset, handle_conflicts, handle_copy, handle_create, handle_current,
+    handle_dashboard, handle_dashboard_status, handle_dashboard_stop, handle_delete, handle_digest,
+    handle_dir, handle_gc, handle_history, handle_kill, handle_list, handle_lock, handle_move,
+    handle_open, handle_prompt, handle_prune, handle_push, handle_rebase, handle_recreate,
+    handle_rename, handle_rename_repo, handle_repo_list, handle_repo_set_base,
+    handle_repo_unset_base, handle_report, handle_restore_snapshot, handle_review, handle_serve,
+    handle_service_install, handle_service_status, handle_service_uninstall, handle_snapshot,
+    handle_snapshots, handle_state_export, handle_state_import, handle_stats, handle_sync,
+    handle_trash_empty, handle_trash_list, handle_trash_restore, handle_undo_branch, handle_unlock,
+    handle_wait, handle_watch,
 };
 
 #[derive(Parser)]
 #[command(name = "xlaude")]
 #[command(about = "Manage Claude instances with git worktrees", long_about = None)]
 struct Cli {
+    /// Use this state file instead of the default location
+    #[arg(long, global = true, env = "XLAUDE_STATE_FILE", value_name = "FILE")]
+    state: Option<std::path::PathBuf>,
+    /// Print only results, warnings and errors
+    #[arg(long, short, global = true)]
+    quiet: bool,
+    /// Leave out emoji and colors, e.g. for CI logs
+    #[arg(
+        long,
+        global = true,
+        env = "XLAUDE_PLAIN",
+        value_parser = clap::builder::BoolishValueParser::new()
+    )]
+    no_emoji: bool,
+    /// Skip everything that needs the network (fetch, gh, opening a browser)
+    #[arg(
+        long,
+        global = true,
+        env = "XLAUDE_OFFLINE",
+        value_parser = clap::builder::BoolishValueParser::new()
+    )]
+    offline: bool,
     #[command(subcommand)]
     command: Commands,
 }
@@ -31,89 +56,698 @@ enum Commands {
     Create {
         /// Name for the worktree (random BIP39 word if not provided)
         name: Option<String>,
+        /// Stack the new worktree on the branch of this worktree
+        #[arg(long, value_name = "NAME")]
+        from_worktree: Option<String>,
+        /// Initial prompt for the agent; also names the worktree when no name is given
+        #[arg(long)]
+        type_text: Option<String>,
+        /// Flag the worktree as expired after this many days
+        #[arg(long, value_name = "DAYS")]
+        ttl: Option<u32>,
     },
     /// Checkout a branch or pull request into a worktree
     Checkout {
-        /// Branch name or pull request number
+        /// Branch name or pull request number; pick from open pull requests if omitted
         target: Option<String>,
     },
+    /// Check out a pull request and start an agent reviewing it
+    Review {
+        /// Pull request number
+        pr: String,
+        /// Agent to launch from the agent registry
+        #[arg(long)]
+        agent: Option<String>,
+        /// Start the agent in a detached tmux session and return immediately
+        #[arg(long)]
+        new_window: bool,
+    },
     /// Open an existing worktree and launch Claude
     Open {
         /// Name of the worktree to open (interactive selection if not provided)
         name: Option<String>,
+        /// Agent to launch from the agent registry (e.g. codex, gemini, aider)
+        #[arg(long)]
+        agent: Option<String>,
+        /// Initial prompt passed to the agent
+        #[arg(long, value_name = "TEXT")]
+        type_text: Option<String>,
+        /// Read the initial prompt from a file (appended after --type-text)
+        #[arg(long, value_name = "PATH")]
+        type_file: Option<std::path::PathBuf>,
+        /// Initial prompt rendered from a named template in `prompt_templates`
+        #[arg(long, value_name = "NAME", conflicts_with = "type_text")]
+        prompt_template: Option<String>,
+        /// Start the agent in a detached tmux session and return immediately
+        #[arg(long)]
+        new_window: bool,
+        /// Print the working directory and agent command line instead of running it
+        #[arg(long, conflicts_with = "new_window")]
+        print_cmd: bool,
+        /// Open the managed worktree containing the current directory, never prompting
+        #[arg(long, conflicts_with = "name")]
+        here: bool,
+    },
+    /// Create a new worktree from another one, including its uncommitted changes
+    Copy {
+        /// Name of the worktree to copy
+        source: String,
+        /// Branch name for the new worktree
+        new_branch: String,
+    },
+    /// Transplant commits and uncommitted changes from one worktree onto another
+    Apply {
+        /// Worktree to take the changes from
+        source: String,
+        /// Worktree to apply them to
+        destination: String,
+    },
+    /// Attach to an agent started with `open --new-window`
+    Attach {
+        /// Name of the worktree (select among running sessions if not provided)
+        name: Option<String>,
+    },
+    /// Create and open worktrees from JSON tasks on stdin, one object per line
+    Batch {
+        /// Start each agent in a detached tmux session
+        #[arg(long)]
+        new_window: bool,
     },
     /// Delete a worktree and clean up
     Delete {
-        /// Name of the worktree to delete (current if not provided)
-        name: Option<String>,
+        /// Names of the worktrees to delete (current if not provided)
+        names: Vec<String>,
+        /// Only delete worktrees of this repository
+        #[arg(long)]
+        repo: Option<String>,
+        /// Only delete worktrees whose branch is merged
+        #[arg(long)]
+        merged: bool,
+        /// Stop agent sessions still running in the worktree without asking
+        #[arg(long)]
+        kill_session: bool,
+        /// Skip all prompts, deleting locked worktrees and unmerged branches
+        #[arg(long)]
+        force: bool,
+        /// Remove the worktree but keep its branch
+        #[arg(long)]
+        keep_branch: bool,
+    },
+    /// Restore a branch that `delete` force deleted
+    UndoBranch {
+        /// Name of the deleted worktree or its branch
+        name: String,
     },
     /// Add current worktree to xlaude management
     Add {
         /// Name for the worktree (defaults to current branch name)
         name: Option<String>,
     },
-    /// Rename a worktree
+    /// Clone a repository and register it with xlaude
+    Clone {
+        /// Repository URL
+        url: String,
+        /// Directory to clone into (defaults to the repository name)
+        dir: Option<std::path::PathBuf>,
+        /// Also create a first worktree with this name
+        #[arg(long)]
+        worktree: Option<String>,
+    },
+    /// Rename a worktree, or a repository with --repo
     Rename {
-        /// Current name of the worktree
+        /// Current name of the worktree (or repository)
         old_name: String,
-        /// New name for the worktree
+        /// New name for the worktree (or repository)
         new_name: String,
+        /// Rename a repository: rekey all its worktrees and move their directories
+        #[arg(long)]
+        repo: bool,
+        /// Rename even if the worktree is locked
+        #[arg(long)]
+        force: bool,
+    },
+    /// Move a worktree to another directory
+    Move {
+        /// Name of the worktree
+        name: String,
+        /// New location of the worktree directory
+        new_path: std::path::PathBuf,
+    },
+    /// Lock a worktree so delete, rename and move refuse to touch it
+    Lock {
+        /// Name of the worktree to lock (current if not provided)
+        name: Option<String>,
+        /// Why the worktree is locked, shown when a command refuses
+        #[arg(long)]
+        reason: Option<String>,
+    },
+    /// Remove a worktree lock
+    Unlock {
+        /// Name of the worktree to unlock (current if not provided)
+        name: Option<String>,
+    },
+    /// Stop the agents running in a worktree
+    Kill {
+        /// Name of the worktree (current if not provided)
+        name: Option<String>,
+    },
+    /// Wait until the agents running in a worktree have exited
+    Wait {
+        /// Name of the worktree (current if not provided)
+        name: Option<String>,
+        /// Give up after this many seconds
+        #[arg(long, value_name = "SECS")]
+        timeout: Option<u64>,
+    },
+    /// Print a line whenever a worktree changes, until interrupted
+    Watch {
+        /// Only watch worktrees of this repository
+        #[arg(long)]
+        repo: Option<String>,
+        /// Seconds between checks
+        #[arg(long, value_name = "SECS", default_value_t = 5)]
+        interval: u64,
     },
     /// List all active Claude instances
     List {
         /// Output as JSON
         #[arg(long)]
         json: bool,
+        /// Show stacked worktrees nested under their parents
+        #[arg(long, conflicts_with = "json")]
+        tree: bool,
+        /// Order of worktrees within each repository
+        #[arg(long, value_enum, default_value_t, conflicts_with = "tree")]
+        sort: ListSort,
+        /// Show commits ahead/behind the base, diff stats and the last commit
+        #[arg(short, long, conflicts_with = "tree")]
+        verbose: bool,
+        /// Print one line per worktree: `vim` for a stable format for other
+        /// tools, or a template such as '{repo}/{name} {branch} {status}'
+        #[arg(long, conflicts_with_all = ["json", "tree", "verbose"])]
+        format: Option<ListFormat>,
+        /// Only list worktrees of this repository
+        #[arg(long)]
+        repo: Option<String>,
+    },
+    /// Show CI status for a worktree's pull request or branch
+    Ci {
+        /// Name of the worktree (current worktree if not provided)
+        name: Option<String>,
+        /// Poll until no check is pending
+        #[arg(long)]
+        watch: bool,
+    },
+    /// Push a worktree branch and set its upstream
+    Push {
+        /// Name of the worktree (current worktree if not provided)
+        name: Option<String>,
+        /// Remote to push to
+        #[arg(long, default_value = "origin")]
+        remote: String,
+    },
+    /// Rebase a worktree, or every worktree of a repository, onto its base
+    Rebase {
+        /// Name of the worktree (current worktree if not provided)
+        #[arg(conflicts_with = "all")]
+        name: Option<String>,
+        /// Rebase every worktree of the repository
+        #[arg(long)]
+        all: bool,
+        /// Repository for --all (current repository if not provided)
+        #[arg(long, requires = "all")]
+        repo: Option<String>,
+        /// Stash uncommitted changes around the rebase instead of skipping
+        #[arg(long)]
+        autostash: bool,
+    },
+    /// Rebase the worktrees stacked on a worktree onto their parents
+    Sync {
+        /// Worktree whose stack to update (current worktree if not provided)
+        name: Option<String>,
+    },
+    /// Delete worktrees past their TTL
+    Prune {
+        /// Also offer worktrees without recent activity
+        #[arg(long)]
+        stale: bool,
+        /// Only prune worktrees of this repository
+        #[arg(long)]
+        repo: Option<String>,
     },
     /// Clean up invalid worktrees from state
-    Clean,
+    Clean {
+        /// Only check worktrees of this repository
+        #[arg(long)]
+        repo: Option<String>,
+    },
+    /// Archive or delete agent session files of removed worktrees
+    Gc {
+        /// Move them under `archive/` next to the state file
+        #[arg(long, conflicts_with = "delete")]
+        archive: bool,
+        /// Delete them permanently
+        #[arg(long)]
+        delete: bool,
+    },
     /// Get the directory path of a worktree
     Dir {
         /// Name of the worktree (interactive selection if not provided)
         name: Option<String>,
     },
+    /// Add a worktree again whose directory was removed, from its recorded branch
+    Recreate {
+        /// Name of the worktree
+        name: String,
+    },
+    /// Print the repo/name, branch and path of the worktree containing the current directory
+    Current {
+        /// Only print the worktree's root directory
+        #[arg(long)]
+        path: bool,
+    },
     /// Generate shell completions
     Completions {
-        /// Shell to generate completions for
-        #[arg(value_enum)]
-        shell: Shell,
+        /// Shell to generate completions for (detected from $SHELL with --install)
+        #[arg(value_enum, required_unless_present = "install")]
+        shell: Option<Shell>,
+        /// Write the script to the shell's per-user completions directory
+        #[arg(long)]
+        install: bool,
     },
     /// Output worktree info for shell completions (hidden)
     #[command(hide = true)]
     CompleteWorktrees {
-        /// Output format: simple or detailed
+        /// Output format: simple, detailed or json
         #[arg(long, default_value = "simple")]
         format: String,
     },
-    /// Open the xlaude state file in $EDITOR
-    Config,
+    /// Show the log of operations that changed xlaude state
+    History {
+        /// Only show entries whose target contains this text
+        filter: Option<String>,
+        /// Number of most recent entries to show (0 for all)
+        #[arg(long, short = 'n', default_value_t = 50)]
+        limit: usize,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Find worktrees of the same repository that change the same files
+    Conflicts {
+        /// Only check worktrees of this repository
+        #[arg(long)]
+        repo: Option<String>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Summarize worktree and agent activity
+    Stats {
+        /// Number of most recent weeks to show (0 for all)
+        #[arg(long, default_value_t = 8)]
+        weeks: usize,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Summarize recent agent work per repository as Markdown
+    Digest {
+        /// How far back to look (e.g. 7d, 2w, 12h)
+        #[arg(long, default_value = "7d")]
+        since: String,
+    },
+    /// Print a compact status segment for shell prompts
+    Prompt {
+        /// Directory to inspect (defaults to the current directory)
+        #[arg(long)]
+        path: Option<std::path::PathBuf>,
+    },
+    /// Open the xlaude state file in $EDITOR, or read and change single settings
+    Config {
+        #[command(subcommand)]
+        action: Option<ConfigCommands>,
+    },
+    /// Export or import the worktree registry
+    State {
+        #[command(subcommand)]
+        command: StateCommands,
+    },
+    /// Serve worktree actions over JSON-RPC for editor extensions
+    Serve {
+        /// Read requests from stdin and write responses to stdout
+        #[arg(long, required = true)]
+        stdio: bool,
+    },
     /// Launch the embedded dashboard
+    #[command(args_conflicts_with_subcommands = true)]
     Dashboard {
+        #[command(subcommand)]
+        action: Option<DashboardCommands>,
         /// Bind address (default 127.0.0.1:5710)
         #[arg(long)]
         addr: Option<String>,
         /// Do not open the browser automatically
         #[arg(long)]
         no_browser: bool,
+        /// Merge worktrees from another dashboard (NAME=URL, repeatable)
+        #[arg(long = "peer", value_name = "NAME=URL")]
+        peers: Vec<String>,
+        /// Run in the background; stop with 'xlaude dashboard stop'
+        #[arg(long)]
+        daemon: bool,
+    },
+    /// Save the dashboard as a standalone HTML file to share
+    Report {
+        /// File to write
+        #[arg(long, short, default_value = "xlaude-report.html")]
+        output: std::path::PathBuf,
+    },
+    /// Manage a login service that keeps the dashboard running
+    Service {
+        #[command(subcommand)]
+        action: ServiceCommands,
+    },
+    /// Manage per-repository settings
+    Repo {
+        #[command(subcommand)]
+        action: RepoCommands,
+    },
+    /// Save a worktree's uncommitted changes, untracked files included
+    Snapshot {
+        /// Name of the worktree (current if not provided)
+        name: Option<String>,
+        /// Describe the snapshot
+        #[arg(short, long)]
+        message: Option<String>,
+    },
+    /// List the snapshots of a worktree
+    Snapshots {
+        /// Name of the worktree (current if not provided)
+        name: Option<String>,
+    },
+    /// Replace a worktree's uncommitted changes with a snapshot
+    RestoreSnapshot {
+        /// Name of the worktree (current if not provided)
+        name: Option<String>,
+        /// Snapshot id from 'xlaude snapshots' (the latest if not provided)
+        #[arg(long, value_name = "ID")]
+        snapshot: Option<String>,
+    },
+    /// Look at or restore recently deleted worktrees
+    Trash {
+        #[command(subcommand)]
+        action: TrashCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum TrashCommands {
+    /// Show deleted worktrees that can still be restored
+    List,
+    /// Recreate a deleted worktree with its uncommitted changes
+    Restore {
+        /// Worktree name or repo/name
+        name: String,
     },
+    /// Permanently remove everything in the trash
+    Empty,
 }
 
-fn main() -> Result<()> {
+#[derive(Subcommand)]
+enum RepoCommands {
+    /// Use a different base branch (e.g. trunk or 'release/*') for a repository
+    SetBase {
+        /// Repository name, as shown by `xlaude list`
+        repo: String,
+        /// Branch name; a trailing `*` matches any suffix
+        branch: String,
+    },
+    /// Go back to the default base branches for a repository
+    UnsetBase {
+        /// Repository name
+        repo: String,
+    },
+    /// Show configured repository settings
+    List,
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Print the value of a setting
+    Get {
+        /// Setting key, e.g. editor or repos.<repo>.base_branch
+        key: String,
+    },
+    /// Change a setting
+    Set {
+        /// Setting key, e.g. editor or repos.<repo>.base_branch
+        key: String,
+        value: String,
+    },
+    /// Remove a setting
+    Unset {
+        /// Setting key, e.g. editor or repos.<repo>.base_branch
+        key: String,
+    },
+    /// Print every configured setting as key=value
+    List,
+}
+
+#[derive(Subcommand)]
+enum DashboardCommands {
+    /// Stop the dashboard running for this state
+    Stop,
+    /// Show whether a dashboard is running for this state
+    Status,
+}
+
+#[derive(Subcommand)]
+enum ServiceCommands {
+    /// Run the dashboard at login via systemd (Linux) or launchd (macOS)
+    Install {
+        /// Bind address passed to the dashboard
+        #[arg(long)]
+        addr: Option<String>,
+        /// Peer dashboards to federate (NAME=URL, repeatable)
+        #[arg(long = "peer", value_name = "NAME=URL")]
+        peers: Vec<String>,
+    },
+    /// Stop the service and remove its definition
+    Uninstall,
+    /// Show whether the service is installed and running
+    Status,
+}
+
+#[derive(Subcommand)]
+enum StateCommands {
+    /// Print the state with home-relative paths for use on another machine
+    Export {
+        /// Output format
+        #[arg(long, value_enum, default_value = "json")]
+        format: StateFormat,
+        /// Write to a file instead of stdout
+        #[arg(long, short)]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Load a previously exported state (JSON or TOML)
+    Import {
+        /// File to import, or '-' for stdin
+        file: std::path::PathBuf,
+        /// Keep local entries and only add missing ones
+        #[arg(long)]
+        merge: bool,
+    },
+}
+
+fn main() -> ExitCode {
     let cli = Cli::parse();
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            // Cancellations were already reported to the user
+            if !matches!(error::find(&err), Some(error::XlaudeError::Cancelled)) {
+                eprintln!("Error: {err:?}");
+            }
+            ExitCode::from(error::exit_code(&err))
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    output::init(cli.no_emoji, cli.quiet);
+    if let Some(state_file) = &cli.state {
+        // SAFETY: no other threads exist yet. Exporting the variable also makes
+        // child xlaude processes (e.g. spawned by the dashboard) use the same file.
+        unsafe { std::env::set_var("XLAUDE_STATE_FILE", state_file) };
+    }
+    if cli.offline {
+        // SAFETY: as above, and child xlaude processes stay offline too
+        unsafe { std::env::set_var("XLAUDE_OFFLINE", "1") };
+    }
 
     match cli.command {
-        Commands::Create { name } => handle_create(name),
+        Commands::Create {
+            name,
+            from_worktree,
+            type_text,
+            ttl,
+        } => handle_create(
+            name,
+            CreateOptions {
+                from_worktree,
+                type_text,
+                ttl_days: ttl,
+            },
+        ),
         Commands::Checkout { target } => handle_checkout(target),
-        Commands::Open { name } => handle_open(name),
-        Commands::Delete { name } => handle_delete(name),
+        Commands::Open {
+            name,
+            agent,
+            type_text,
+            type_file,
+            prompt_template,
+            new_window,
+            print_cmd,
+            here,
+        } => handle_open(
+            name,
+            OpenOptions {
+                agent,
+                type_text,
+                type_file,
+                prompt_template,
+                new_window,
+                print_cmd,
+                here,
+            },
+        ),
+        Commands::Review {
+            pr,
+            agent,
+            new_window,
+        } => handle_review(pr, agent, new_window),
+        Commands::Copy { source, new_branch } => handle_copy(source, new_branch),
+        Commands::Apply {
+            source,
+            destination,
+        } => handle_apply(source, destination),
+        Commands::Attach { name } => handle_attach(name),
+        Commands::Batch { new_window } => handle_batch(new_window),
+        Commands::Delete {
+            names,
+            repo,
+            merged,
+            kill_session,
+            force,
+            keep_branch,
+        } => handle_delete(names, repo, merged, kill_session, force, keep_branch),
+        Commands::UndoBranch { name } => handle_undo_branch(name),
         Commands::Add { name } => handle_add(name),
-        Commands::Rename { old_name, new_name } => handle_rename(old_name, new_name),
-        Commands::List { json } => handle_list(json),
-        Commands::Clean => handle_clean(),
+        Commands::Clone { url, dir, worktree } => handle_clone(url, dir, worktree),
+        Commands::Rename {
+            old_name,
+            new_name,
+            repo: true,
+            force,
+        } => handle_rename_repo(old_name, new_name, force),
+        Commands::Rename {
+            old_name,
+            new_name,
+            repo: false,
+            force,
+        } => handle_rename(old_name, new_name, force),
+        Commands::Move { name, new_path } => handle_move(name, new_path),
+        Commands::Lock { name, reason } => handle_lock(name, reason),
+        Commands::Unlock { name } => handle_unlock(name),
+        Commands::Kill { name } => handle_kill(name),
+        Commands::Wait { name, timeout } => handle_wait(name, timeout),
+        Commands::Watch { repo, interval } => handle_watch(repo, interval),
+        Commands::List {
+            json,
+            tree,
+            sort,
+            verbose,
+            format,
+            repo,
+        } => handle_list(json, tree, sort, verbose, format, repo),
+        Commands::Ci { name, watch } => handle_ci(name, watch),
+        Commands::Push { name, remote } => handle_push(name, remote),
+        Commands::Rebase {
+            name,
+            all,
+            repo,
+            autostash,
+        } => handle_rebase(name, all, repo, autostash),
+        Commands::Sync { name } => handle_sync(name),
+        Commands::Prune { stale, repo } => handle_prune(stale, repo),
+        Commands::Clean { repo } => handle_clean(repo),
+        Commands::Gc { archive, delete } => handle_gc(if archive {
+            Some(GcAction::Archive)
+        } else if delete {
+            Some(GcAction::Delete)
+        } else {
+            None
+        }),
         Commands::Dir { name } => handle_dir(name),
-        Commands::Completions { shell } => completions::handle_completions(shell),
+        Commands::Current { path } => handle_current(path),
+        Commands::Recreate { name } => handle_recreate(name),
+        Commands::Completions { shell, install } => completions::handle_completions(shell, install),
         Commands::CompleteWorktrees { format } => commands::handle_complete_worktrees(&format),
-        Commands::Config => handle_config(),
-        Commands::Dashboard { addr, no_browser } => handle_dashboard(addr, no_browser),
+        Commands::History {
+            filter,
+            limit,
+            json,
+        } => handle_history(filter, limit, json),
+        Commands::Conflicts { repo, json } => handle_conflicts(repo, json),
+        Commands::Stats { weeks, json } => handle_stats(weeks, json),
+        Commands::Digest { since } => handle_digest(since),
+        Commands::Prompt { path } => handle_prompt(path),
+        Commands::Config { action } => match action {
+            None => handle_config(),
+            Some(ConfigCommands::Get { key }) => handle_config_get(key),
+            Some(ConfigCommands::Set { key, value }) => handle_config_set(key, value),
+            Some(ConfigCommands::Unset { key }) => handle_config_unset(key),
+            Some(ConfigCommands::List) => handle_config_list(),
+        },
+        Commands::State { command } => match command {
+            StateCommands::Export { format, output } => handle_state_export(format, output),
+            StateCommands::Import { file, merge } => handle_state_import(file, merge),
+        },
+        Commands::Serve { stdio: _ } => handle_serve(),
+        Commands::Dashboard {
+            action: Some(DashboardCommands::Stop),
+            ..
+        } => handle_dashboard_stop(),
+        Commands::Dashboard {
+            action: Some(DashboardCommands::Status),
+            ..
+        } => handle_dashboard_status(),
+        Commands::Dashboard {
+            action: None,
+            addr,
+            no_browser,
+            peers,
+            daemon,
+        } => handle_dashboard(addr, no_browser, peers, daemon),
+        Commands::Report { output } => handle_report(output),
+        Commands::Service { action } => match action {
+            ServiceCommands::Install { addr, peers } => handle_service_install(addr, peers),
+            ServiceCommands::Uninstall => handle_service_uninstall(),
+            ServiceCommands::Status => handle_service_status(),
+        },
+        Commands::Repo { action } => match action {
+            RepoCommands::SetBase { repo, branch } => handle_repo_set_base(repo, branch),
+            RepoCommands::UnsetBase { repo } => handle_repo_unset_base(repo),
+            RepoCommands::List => handle_repo_list(),
+        },
+        Commands::Snapshot { name, message } => handle_snapshot(name, message),
+        Commands::Snapshots { name } => handle_snapshots(name),
+        Commands::RestoreSnapshot { name, snapshot } => handle_restore_snapshot(name, snapshot),
+        Commands::Trash { action } => match action {
+            TrashCommands::List => handle_trash_list(),
+            TrashCommands::Restore { name } => handle_trash_restore(name),
+            TrashCommands::Empty => handle_trash_empty(),
+        },
     }
 }
@@ -0,0 +1,203 @@
+//! Detection of worktrees whose branch has been merged, recorded in state so
+//! `list` and the dashboard can suggest cleaning them up.
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::audit;
+use crate::forge;
+use crate::git::{execute_git, main_repo_dir, resolve_base_ref};
+use crate::state::{WorktreeInfo, XlaudeState, get_cache_dir};
+use crate::tools::{self, Tool};
+use crate::utils::{RunPolicy, run_command};
+
+/// How long a merged-status check stays fresh.
+pub const CHECK_INTERVAL: Duration = Duration::minutes(10);
+const PR_CACHE_FILE: &str = "merged-prs.json";
+
+/// Merged pull requests per repository, shared by every command and the
+/// dashboard so `gh` is asked at most once per `CHECK_INTERVAL`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PrCache {
+    // Keyed by the repository's main checkout
+    repos: BTreeMap<PathBuf, CachedPrs>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedPrs {
+    fetched_at: DateTime<Utc>,
+    branches: BTreeSet<String>,
+}
+
+/// Re-check merged branches when the last check is older than `CHECK_INTERVAL`.
+pub fn refresh_if_stale() -> Result<()> {
+    let state = XlaudeState::load()?;
+    if state
+        .merge_checked_at
+        .is_some_and(|at| Utc::now() - at < CHECK_INTERVAL)
+    {
+        return Ok(());
+    }
+    refresh()
+}
+
+/// Check every repository once for merged branches and mark or unmark its
+/// worktrees accordingly.
+pub fn refresh() -> Result<()> {
+    let mut state = XlaudeState::load()?;
+
+    let mut repos: BTreeMap<&str, Vec<&WorktreeInfo>> = BTreeMap::new();
+    for info in state.worktrees.values() {
+        repos.entry(&info.repo_name).or_default().push(info);
+    }
+
+    let mut merged_keys = HashSet::new();
+    for worktrees in repos.values() {
+        // Any existing worktree can answer for the whole repository
+        let Some(probe) = worktrees.iter().find(|w| w.path.exists()) else {
+            continue;
+        };
+        let merged = merged_branches(&probe.path);
+        for info in worktrees {
+            if merged.contains(&info.branch) && has_own_commits(&probe.path, &info.branch) {
+                merged_keys.insert(XlaudeState::make_key(&info.repo_name, &info.name));
+            }
+        }
+    }
+
+    let now = Utc::now();
+    for (key, info) in state.worktrees.iter_mut() {
+        if !merged_keys.contains(key) {
+            info.merged_at = None;
+        } else if info.merged_at.is_none() {
+            info.merged_at = Some(now);
+            // Kept in the audit log so `digest` can count merges after deletion
+            audit::record("merged", key, Some(format!("branch {}", info.branch)));
+        }
+    }
+    state.merge_checked_at = Some(now);
+    state.save()
+}
+
+/// Worktrees whose branch was found merged, sorted by key.
+pub fn ready_to_clean(state: &XlaudeState) -> Vec<&WorktreeInfo> {
+    let mut worktrees: Vec<_> = state
+        .worktrees
+        .values()
+        .filter(|w| w.merged_at.is_some())
+        .collect();
+    worktrees.sort_by(|a, b| (&a.repo_name, &a.name).cmp(&(&b.repo_name, &b.name)));
+    worktrees
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MergedPullRequest {
+    head_ref_name: String,
+}
+
+/// A freshly created branch is contained in its base too, so only count
+/// branches whose reflog shows they moved after being created.
+fn has_own_commits(path: &Path, branch: &str) -> bool {
+    let dir = path.to_string_lossy();
+    execute_git(&[
+        "-C",
+        &dir,
+        "reflog",
+        "show",
+        "--format=%H",
+        &format!("refs/heads/{branch}"),
+    ])
+    .map(|log| log.lines().collect::<HashSet<_>>().len() > 1)
+    // Without a reflog there is nothing to tell them apart
+    .unwrap_or(true)
+}
+
+fn merged_branches(path: &Path) -> HashSet<String> {
+    let mut merged = HashSet::new();
+    let dir = path.to_string_lossy();
+
+    if let Some(base) = resolve_base_ref(path)
+        && let Ok(output) = execute_git(&[
+            "-C",
+            &dir,
+            "for-each-ref",
+            &format!("--merged={base}"),
+            "--format=%(refname:short)",
+            "refs/heads",
+        ])
+    {
+        merged.extend(output.lines().map(str::to_string));
+        let base_branch = base.rsplit('/').next().unwrap_or(&base);
+        merged.remove(base_branch);
+    }
+
+    // Squash and rebase merges only show up on the forge
+    merged.extend(merged_pr_branches(path));
+    merged
+}
+
+/// Head branches of the merged pull requests of the repository at `path`.
+/// One `gh pr list` answers for all its branches, and the answer is cached
+/// for `CHECK_INTERVAL`. Empty when `gh` cannot be used.
+pub fn merged_pr_branches(path: &Path) -> BTreeSet<String> {
+    if !forge::usable() {
+        return BTreeSet::new();
+    }
+    let repo = main_repo_dir(path).unwrap_or_else(|| path.to_path_buf());
+    let cache_path = get_cache_dir().ok().map(|dir| dir.join(PR_CACHE_FILE));
+    let mut cache: PrCache = cache_path
+        .as_ref()
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default();
+    if let Some(cached) = cache.repos.get(&repo)
+        && Utc::now() - cached.fetched_at < CHECK_INTERVAL
+    {
+        return cached.branches.clone();
+    }
+
+    // Failures are not cached, the next caller tries again
+    let Some(branches) = fetch_merged_prs(&repo) else {
+        return BTreeSet::new();
+    };
+    cache.repos.insert(
+        repo,
+        CachedPrs {
+            fetched_at: Utc::now(),
+            branches: branches.clone(),
+        },
+    );
+    if let Some(cache_path) = cache_path
+        && let Ok(bytes) = serde_json::to_vec(&cache)
+        && let Some(dir) = cache_path.parent()
+        && fs::create_dir_all(dir).is_ok()
+    {
+        let _ = fs::write(&cache_path, bytes);
+    }
+    branches
+}
+
+fn fetch_merged_prs(repo: &Path) -> Option<BTreeSet<String>> {
+    let output = run_command(
+        tools::command(Tool::Gh).current_dir(repo).args([
+            "pr",
+            "list",
+            "--state",
+            "merged",
+            "--limit",
+            "200",
+            "--json",
+            "headRefName",
+        ]),
+        RunPolicy::network(),
+    )
+    .ok()
+    .filter(|output| output.status.success())?;
+    let prs: Vec<MergedPullRequest> = serde_json::from_slice(&output.stdout).ok()?;
+    Some(prs.into_iter().map(|pr| pr.head_ref_name).collect())
+}
@@ -0,0 +1,114 @@
+//! Ordered schema migrations for the state file.
+//!
+//! Each migration upgrades the raw JSON document by exactly one schema version.
+//! To change the schema, bump `CURRENT_VERSION` and append a migration here.
+
+use anyhow::{Result, bail};
+use serde_json::{Map, Value};
+
+pub const CURRENT_VERSION: u32 = 1;
+
+struct Migration {
+    /// Schema version produced by this migration
+    to: u32,
+    description: &'static str,
+    /// Rewrites the document in place, returning whether anything changed
+    apply: fn(&mut Value) -> Result<bool>,
+}
+
+const MIGRATIONS: &[Migration] = &[Migration {
+    to: 1,
+    description: "qualify worktree keys with the repository name (v0.2 -> v0.3)",
+    apply: qualify_worktree_keys,
+}];
+
+pub struct MigrationOutcome {
+    pub from: u32,
+    /// Descriptions of the migrations that modified the document
+    pub applied: Vec<&'static str>,
+}
+
+/// Upgrade `document` to `CURRENT_VERSION`. Files without a `version` field are
+/// treated as schema 0, the format used before versioning was introduced.
+pub fn migrate(document: &mut Value) -> Result<MigrationOutcome> {
+    let from = document.get("version").and_then(Value::as_u64).unwrap_or(0) as u32;
+
+    if from > CURRENT_VERSION {
+        bail!(
+            "State file uses schema v{from}, but this xlaude only understands up to v{CURRENT_VERSION}. Please upgrade xlaude."
+        );
+    }
+
+    let mut applied = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|m| m.to > from) {
+        if (migration.apply)(document)? {
+            applied.push(migration.description);
+        }
+    }
+
+    if let Some(object) = document.as_object_mut() {
+        object.insert("version".to_string(), Value::from(CURRENT_VERSION));
+    }
+
+    Ok(MigrationOutcome { from, applied })
+}
+
+/// In v0.2 keys were just the worktree name ("feature-x"); v0.3 keys include the
+/// repository ("repo-name/feature-x") so equally named worktrees can coexist.
+fn qualify_worktree_keys(document: &mut Value) -> Result<bool> {
+    let Some(worktrees) = document.get_mut("worktrees").and_then(Value::as_object_mut) else {
+        return Ok(false);
+    };
+
+    if worktrees.keys().all(|key| key.contains('/')) {
+        return Ok(false);
+    }
+
+    let mut migrated = Map::new();
+    for (key, info) in std::mem::take(worktrees) {
+        let new_key = if key.contains('/') {
+            key
+        } else {
+            let repo = info.get("repo_name").and_then(Value::as_str);
+            let name = info.get("name").and_then(Value::as_str);
+            match (repo, name) {
+                (Some(repo), Some(name)) => format!("{repo}/{name}"),
+                _ => bail!("Cannot migrate worktree entry '{key}': missing repo_name or name"),
+            }
+        };
+        migrated.insert(new_key, info);
+    }
+    *worktrees = migrated;
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn migrate_unversioned_legacy_keys() {
+        let mut document = json!({
+            "worktrees": {
+                "feature": { "name": "feature", "repo_name": "repo" },
+                "other/fix": { "name": "fix", "repo_name": "other" }
+            }
+        });
+
+        let outcome = migrate(&mut document).unwrap();
+
+        assert_eq!(outcome.from, 0);
+        assert_eq!(outcome.applied.len(), 1);
+        assert_eq!(document["version"], json!(CURRENT_VERSION));
+        let keys: Vec<_> = document["worktrees"].as_object().unwrap().keys().collect();
+        assert_eq!(keys, vec!["other/fix", "repo/feature"]);
+    }
+
+    #[test]
+    fn migrate_rejects_newer_schema() {
+        let mut document = json!({ "version": CURRENT_VERSION + 1, "worktrees": {} });
+        assert!(migrate(&mut document).is_err());
+    }
+}
@@ -0,0 +1,181 @@
+//! Human-facing status lines and spinners, adjusted by the global output
+//! flags: `--no-emoji` (or `XLAUDE_PLAIN=1`) strips emoji and colors so logs
+//! stay readable, and `--quiet` drops hints and progress chatter.
+//!
+//! Machine-readable output (JSON, paths, config values) keeps using
+//! `println!` directly and is never altered.
+
+use indicatif::{ProgressBar, ProgressStyle};
+use std::borrow::Cow;
+use std::fmt;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+static PLAIN: AtomicBool = AtomicBool::new(false);
+static QUIET: AtomicBool = AtomicBool::new(false);
+static STDERR_ONLY: AtomicBool = AtomicBool::new(false);
+
+// Icons that introduce hints, notes and progress messages
+const CHATTER_ICONS: &[char] = &['💡', 'ℹ', '🔍', '⏳', '📋'];
+
+/// Print a status line to stdout, like `println!`.
+macro_rules! status {
+    ($($arg:tt)*) => {
+        $crate::output::emit(format!($($arg)*), false)
+    };
+}
+
+/// Print a status line to stderr, like `eprintln!`.
+macro_rules! estatus {
+    ($($arg:tt)*) => {
+        $crate::output::emit(format!($($arg)*), true)
+    };
+}
+
+pub fn init(plain: bool, quiet: bool) {
+    PLAIN.store(plain, Ordering::Relaxed);
+    QUIET.store(quiet, Ordering::Relaxed);
+    if plain {
+        colored::control::set_override(false);
+    }
+}
+
+/// Send every status line to stderr, for commands whose stdout carries a
+/// protocol.
+pub fn keep_stdout_clean() {
+    STDERR_ONLY.store(true, Ordering::Relaxed);
+}
+
+pub fn emit(line: String, to_stderr: bool) {
+    let trimmed = line.trim_start();
+    // Warnings go to stderr and are never chatter
+    if !to_stderr && QUIET.load(Ordering::Relaxed) && trimmed.starts_with(CHATTER_ICONS) {
+        return;
+    }
+    let line = if PLAIN.load(Ordering::Relaxed) {
+        strip_emoji(&line)
+    } else {
+        line
+    };
+    if to_stderr || STDERR_ONLY.load(Ordering::Relaxed) {
+        eprintln!("{line}");
+    } else {
+        println!("{line}");
+    }
+}
+
+/// A spinner on stderr while a slow step runs. It is only drawn on a
+/// terminal and not in quiet mode, and is cleared when dropped.
+pub struct Step {
+    bar: ProgressBar,
+    started: Instant,
+}
+
+pub fn step(message: impl Into<Cow<'static, str>>) -> Step {
+    let bar = if QUIET.load(Ordering::Relaxed) || !std::io::stderr().is_terminal() {
+        ProgressBar::hidden()
+    } else {
+        ProgressBar::new_spinner()
+    };
+    let ticks = if PLAIN.load(Ordering::Relaxed) {
+        "-\\|/ "
+    } else {
+        "⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏ "
+    };
+    if let Ok(style) = ProgressStyle::with_template("{spinner} {msg} ({elapsed})") {
+        bar.set_style(style.tick_chars(ticks));
+    }
+    bar.set_message(message);
+    bar.enable_steady_tick(Duration::from_millis(100));
+    Step {
+        bar,
+        started: Instant::now(),
+    }
+}
+
+impl Step {
+    /// Clear the spinner and return how long the step took.
+    pub fn finish(self) -> Elapsed {
+        Elapsed(self.started.elapsed())
+    }
+}
+
+impl Drop for Step {
+    fn drop(&mut self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+/// Duration of a step, shown as ` (2.4s)` after a result line. Steps under a
+/// second print nothing.
+pub struct Elapsed(Duration);
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 < Duration::from_secs(1) {
+            return Ok(());
+        }
+        write!(f, " ({:.1}s)", self.0.as_secs_f64())
+    }
+}
+
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x2139
+            | 0x23E9..=0x23FA
+            | 0x2600..=0x26FF
+            | 0x2705
+            | 0x2728
+            | 0x274C
+            | 0x2795..=0x2797
+            | 0x1F000..=0x1FAFF
+            // Variation selector and zero-width joiner inside emoji sequences
+            | 0xFE0F
+            | 0x200D
+    )
+}
+
+/// Remove emoji along with the spaces that separated them from the text.
+fn strip_emoji(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if !is_emoji(c) {
+            out.push(c);
+            continue;
+        }
+        while chars.peek().is_some_and(|&next| is_emoji(next)) {
+            chars.next();
+        }
+        while chars.peek() == Some(&' ') {
+            chars.next();
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_emoji() {
+        assert_eq!(
+            strip_emoji("✅ Worktree 'a' deleted"),
+            "Worktree 'a' deleted"
+        );
+        assert_eq!(strip_emoji("⚠️  Failed to write"), "Failed to write");
+        assert_eq!(strip_emoji("  📦 repo"), "  repo");
+        assert_eq!(strip_emoji("🗑️ Removing"), "Removing");
+        assert_eq!(strip_emoji("    • name → main"), "    • name → main");
+        assert_eq!(strip_emoji("  ✓ pass"), "  ✓ pass");
+    }
+
+    #[test]
+    fn test_elapsed_only_shown_for_slow_steps() {
+        assert_eq!(Elapsed(Duration::from_millis(300)).to_string(), "");
+        assert_eq!(Elapsed(Duration::from_millis(2450)).to_string(), " (2.5s)");
+    }
+}
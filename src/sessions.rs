@@ -0,0 +1,233 @@
+//! Registry of agent processes launched by `xlaude open`, stored as JSON next to the state file.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::claude::last_session_write;
+use crate::state::{StateLock, WorktreeInfo, write_atomic};
+use crate::tmux::TmuxManager;
+use crate::utils::{is_process_alive, process_start_time, terminate_process};
+
+const SESSIONS_FILE: &str = "sessions.json";
+/// A session log written within this window counts as an active agent.
+const ACTIVE_WINDOW: Duration = Duration::from_secs(120);
+
+/// What the agent of a worktree is doing, as far as xlaude can tell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AgentStatus {
+    /// Running and writing to its session log
+    Running,
+    /// Running, but its log has been quiet for a while, usually because it
+    /// waits for input
+    Waiting,
+    /// No agent running
+    Idle,
+}
+
+impl AgentStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Waiting => "waiting",
+            Self::Idle => "idle",
+        }
+    }
+}
+
+/// Whether a Claude session log in the worktree was written within `ACTIVE_WINDOW`.
+pub fn recently_active(path: &Path) -> bool {
+    last_session_write(path)
+        .and_then(|ts| SystemTime::now().duration_since(ts).ok())
+        .is_some_and(|age| age <= ACTIVE_WINDOW)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentSession {
+    pub pid: u32,
+    // Directory the agent was started in
+    pub path: PathBuf,
+    pub program: String,
+    // Name passed to `--agent`, unset for the default agent command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    pub started_at: DateTime<Utc>,
+    // Start time of the process as `ps` reports it, so a reused pid is not
+    // mistaken for the agent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub process_started: Option<String>,
+}
+
+impl AgentSession {
+    /// Whether the recorded process is still the one running under its pid.
+    pub fn is_alive(&self) -> bool {
+        is_process_alive(self.pid) && process_start_time(self.pid) == self.process_started
+    }
+
+    /// The `--agent` name, or the program's file name for the default agent.
+    pub fn name(&self) -> &str {
+        self.agent.as_deref().unwrap_or_else(|| {
+            Path::new(&self.program)
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or(&self.program)
+        })
+    }
+
+    /// Short description such as `codex (pid 123, up 5m)`.
+    pub fn describe(&self) -> String {
+        let name = self.name();
+        let minutes = (Utc::now() - self.started_at).num_minutes();
+        let uptime = if minutes < 60 {
+            format!("{minutes}m")
+        } else {
+            format!("{}h{}m", minutes / 60, minutes % 60)
+        };
+        format!("{name} (pid {}, up {uptime})", self.pid)
+    }
+}
+
+pub fn sessions_path() -> Result<PathBuf> {
+    let state_path = crate::state::get_state_path()?;
+    Ok(state_path.with_file_name(SESSIONS_FILE))
+}
+
+/// Remember a launched agent. Failures only emit a warning so that tracking
+/// can never prevent the agent from running.
+pub fn register(pid: u32, path: &Path, program: &str, agent: Option<&str>) {
+    let session = AgentSession {
+        pid,
+        path: path.to_path_buf(),
+        program: program.to_string(),
+        agent: agent.map(str::to_string),
+        started_at: Utc::now(),
+        process_started: process_start_time(pid),
+    };
+    let result = update(|sessions| {
+        sessions.retain(|s| s.pid != pid);
+        sessions.push(session);
+    });
+    if let Err(err) = result {
+        estatus!("⚠️  Failed to record agent session: {err:#}");
+    }
+}
+
+pub fn unregister(pid: u32) {
+    let _ = update(|sessions| sessions.retain(|s| s.pid != pid));
+}
+
+/// Agents that are still alive inside `path`. Entries whose process has
+/// exited without unregistering (crash, killed terminal) are skipped here and
+/// dropped by the next registration.
+pub fn running_in(path: &Path) -> Vec<AgentSession> {
+    alive()
+        .into_iter()
+        .filter(|s| s.path.starts_with(path) && s.pid != std::process::id())
+        .collect()
+}
+
+/// Snapshot of running agents, taken once so that checking many worktrees
+/// does not rescan processes and tmux for each one.
+pub struct RunningAgents {
+    sessions: Vec<AgentSession>,
+    tmux_sessions: Vec<String>,
+}
+
+impl RunningAgents {
+    pub fn detect() -> Self {
+        let sessions = alive();
+        let tmux_sessions = TmuxManager::new()
+            .map(|tmux| tmux.list_sessions())
+            .unwrap_or_default();
+        Self {
+            sessions,
+            tmux_sessions,
+        }
+    }
+
+    /// Tracked agents running inside the worktree.
+    pub fn in_worktree(&self, info: &WorktreeInfo) -> Vec<&AgentSession> {
+        self.sessions
+            .iter()
+            .filter(|s| s.path.starts_with(&info.path))
+            .collect()
+    }
+
+    /// Running agents combined with how recently their session log moved.
+    pub fn status(&self, info: &WorktreeInfo) -> AgentStatus {
+        if !self.is_running(info) {
+            AgentStatus::Idle
+        } else if recently_active(&info.path) {
+            AgentStatus::Running
+        } else {
+            AgentStatus::Waiting
+        }
+    }
+
+    /// Whether an agent tracked by pid runs inside the worktree, or its
+    /// `open --new-window` tmux session still exists.
+    pub fn is_running(&self, info: &WorktreeInfo) -> bool {
+        !self.in_worktree(info).is_empty()
+            || self
+                .tmux_sessions
+                .contains(&TmuxManager::session_name(&info.repo_name, &info.name))
+    }
+}
+
+/// Stop a tracked agent. Returns `false` without signalling anything when the
+/// pid now belongs to another process, in which case the record is dropped.
+pub fn terminate(session: &AgentSession) -> Result<bool> {
+    let alive = session.is_alive();
+    if alive {
+        terminate_process(session.pid)?;
+    }
+    unregister(session.pid);
+    Ok(alive)
+}
+
+fn load(path: &Path) -> Vec<AgentSession> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Registered agents whose process is still running. Read only, dead
+/// entries are left for the next write to clean up.
+fn alive() -> Vec<AgentSession> {
+    let Ok(path) = sessions_path() else {
+        return Vec::new();
+    };
+    load(&path)
+        .into_iter()
+        .filter(AgentSession::is_alive)
+        .collect()
+}
+
+/// Rewrite the registry under the state lock, dropping dead entries.
+fn update(f: impl FnOnce(&mut Vec<AgentSession>)) -> Result<()> {
+    let state_path = crate::state::get_state_path()?;
+    let path = state_path.with_file_name(SESSIONS_FILE);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create data directory")?;
+    }
+    let _lock = StateLock::acquire(&state_path)?;
+
+    let mut sessions = load(&path);
+    sessions.retain(AgentSession::is_alive);
+    f(&mut sessions);
+
+    if sessions.is_empty() {
+        match fs::remove_file(&path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                return Err(err).with_context(|| format!("Failed to remove {}", path.display()));
+            }
+            _ => return Ok(()),
+        }
+    }
+    write_atomic(&path, &serde_json::to_string_pretty(&sessions)?)
+}
@@ -0,0 +1,87 @@
+//! Worktrees that outlived their time-to-live or saw no activity for a while,
+//! pointed out by `list` and the dashboard and cleaned up by `prune`.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::claude::get_claude_sessions;
+use crate::codex;
+use crate::git::execute_git;
+use crate::state::{WorktreeInfo, XlaudeState};
+
+/// Days without activity before a worktree counts as stale, unless
+/// `stale_after_days` is set.
+pub const DEFAULT_STALE_DAYS: u32 = 14;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Staleness {
+    #[serde(rename_all = "camelCase")]
+    Expired { expired_at: DateTime<Utc> },
+    #[serde(rename_all = "camelCase")]
+    Stale { idle_days: i64 },
+}
+
+impl Staleness {
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Expired { expired_at } => {
+                let days = (Utc::now() - *expired_at).num_days();
+                if days == 0 {
+                    "TTL expired today".to_string()
+                } else {
+                    format!("TTL expired {days}d ago")
+                }
+            }
+            Self::Stale { idle_days } => format!("no activity for {idle_days}d"),
+        }
+    }
+}
+
+/// Classify a worktree given its last activity. An expired TTL wins over
+/// staleness; `stale_after_days = 0` turns staleness off.
+pub fn classify(
+    state: &XlaudeState,
+    info: &WorktreeInfo,
+    last_activity: DateTime<Utc>,
+) -> Option<Staleness> {
+    let now = Utc::now();
+    if let Some(ttl) = info.ttl_days.or(state.default_ttl_days) {
+        let expired_at = info.created_at + Duration::days(i64::from(ttl));
+        if expired_at <= now {
+            return Some(Staleness::Expired { expired_at });
+        }
+    }
+
+    let stale_days = state.stale_after_days.unwrap_or(DEFAULT_STALE_DAYS);
+    let idle_days = (now - last_activity).num_days();
+    (stale_days > 0 && idle_days >= i64::from(stale_days)).then_some(Staleness::Stale { idle_days })
+}
+
+/// Latest of the creation time, the cached activity, the last commit and the
+/// last agent message.
+pub fn last_activity(info: &WorktreeInfo) -> DateTime<Utc> {
+    let dir = info.path.to_string_lossy();
+    let last_commit = execute_git(&["-C", &dir, "log", "-1", "--format=%cI"])
+        .ok()
+        .and_then(|ts| DateTime::parse_from_rfc3339(&ts).ok())
+        .map(|ts| ts.with_timezone(&Utc));
+    let claude = get_claude_sessions(&info.path)
+        .into_iter()
+        .filter_map(|s| s.last_timestamp)
+        .max();
+    let codex = codex::recent_sessions(&info.path, 1)
+        .ok()
+        .and_then(|(sessions, _)| sessions.into_iter().next())
+        .and_then(|s| s.last_timestamp);
+
+    [last_commit, claude, codex]
+        .into_iter()
+        .flatten()
+        .fold(info.recent_activity(), DateTime::max)
+}
+
+/// Check a worktree from scratch, for callers without a precomputed activity.
+pub fn check(state: &XlaudeState, info: &WorktreeInfo) -> Option<Staleness> {
+    classify(state, info, last_activity(info))
+}
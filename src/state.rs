@@ -2,32 +2,316 @@ use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::fs;
-use std::path::PathBuf;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::error::XlaudeError;
+use crate::migrations;
+
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+const LOCK_RETRY_DELAY: Duration = Duration::from_millis(25);
+// A lock older than this was left behind by a crashed process.
+const LOCK_STALE_AFTER: Duration = Duration::from_secs(30);
+const DEFAULT_TRASH_RETENTION_DAYS: u32 = 30;
+const DEFAULT_DASHBOARD_REFRESH_SECS: u32 = 15;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct WorktreeInfo {
     pub name: String,
     pub branch: String,
     pub path: PathBuf,
     pub repo_name: String,
     pub created_at: DateTime<Utc>,
+    // Free-form note shown in the dashboard
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    // Pinned worktrees are listed first in the dashboard
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lock: Option<WorktreeLock>,
+    // Name of the worktree in the same repository this one is stacked on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    // When the branch was first found merged, see `merged::refresh`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merged_at: Option<DateTime<Utc>>,
+    // Days after creation the worktree is flagged as expired, overriding `default_ttl_days`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_days: Option<u32>,
+    // Cached latest commit or agent activity, refreshed by `open` and the dashboard
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_activity: Option<DateTime<Utc>>,
+    // Variables for the agent, shell and editor, see `worktree_env`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    // Branch the worktree was started from, and the commit it pointed at then
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_branch: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_commit: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorktreeLock {
+    pub locked_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    // Set on the automatic lock held while `xlaude open` runs an agent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+}
+
+impl WorktreeLock {
+    /// Automatic locks stop counting once their agent process has exited,
+    /// so a crash never leaves a worktree locked forever.
+    pub fn is_active(&self) -> bool {
+        self.pid.is_none_or(crate::utils::is_process_alive)
+    }
+
+    pub fn describe(&self) -> String {
+        match (&self.reason, self.pid) {
+            (Some(reason), _) => reason.clone(),
+            (None, Some(pid)) => format!("held by agent pid {pid}"),
+            (None, None) => "locked manually".to_string(),
+        }
+    }
+}
+
+impl WorktreeInfo {
+    /// Cached last activity, never earlier than creation. Cheap enough to
+    /// sort by without touching git or session logs.
+    pub fn recent_activity(&self) -> DateTime<Utc> {
+        self.last_activity
+            .map_or(self.created_at, |ts| ts.max(self.created_at))
+    }
+
+    /// Repository the worktree was added from. Git knows while the worktree
+    /// exists, which also covers bare repositories; otherwise assume the
+    /// `<repo>` directory next to it.
+    pub fn main_repo_path(&self) -> PathBuf {
+        crate::git::main_repo_dir(&self.path).unwrap_or_else(|| {
+            self.path
+                .parent()
+                .unwrap_or(&self.path)
+                .join(&self.repo_name)
+        })
+    }
+
+    /// Commits added to the base branch since the worktree was started from
+    /// it; more than zero means the branch needs a rebase. `None` without a
+    /// recorded base or when it no longer resolves.
+    pub fn base_moved(&self) -> Option<usize> {
+        let (branch, commit) = (self.base_branch.as_ref()?, self.base_commit.as_ref()?);
+        let count = crate::git::execute_git(&[
+            "-C",
+            self.path.to_str()?,
+            "rev-list",
+            "--count",
+            &format!("{commit}..{branch}"),
+        ])
+        .ok()?;
+        count.parse().ok()
+    }
+
+    pub fn active_lock(&self) -> Option<&WorktreeLock> {
+        self.lock.as_ref().filter(|lock| lock.is_active())
+    }
+
+    /// Refuse to modify a locked worktree unless `force` is set.
+    pub fn ensure_unlocked(&self, force: bool) -> Result<()> {
+        if force {
+            return Ok(());
+        }
+        if let Some(lock) = self.active_lock() {
+            return Err(XlaudeError::Refused(format!(
+                "Worktree '{}' is locked ({}). Unlock it with 'xlaude unlock {}' or pass --force",
+                self.name,
+                lock.describe(),
+                self.name
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepoConfig {
+    // Integration branch used instead of main/master/develop; a trailing `*`
+    // matches any suffix, e.g. `release/*`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_branch: Option<String>,
+    // Main checkout, for repositories registered with `xlaude clone`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+
+impl RepoConfig {
+    pub fn is_empty(&self) -> bool {
+        self.base_branch.is_none() && self.path.is_none()
+    }
+}
+
+// Generated worktree names, see `utils::generate_random_name`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NameConfig {
+    // Placeholders name word lists, e.g. `{adjective}-{animal}`; `{word}` is a
+    // random BIP39 word and the default pattern
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub words: BTreeMap<String, Vec<String>>,
+    // Agent command asked to name worktrees created for a prompt, e.g. `claude -p`;
+    // the question is passed as its last argument
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+}
+
+impl NameConfig {
+    pub fn is_empty(&self) -> bool {
+        self.pattern.is_none() && self.words.is_empty() && self.agent.is_none()
+    }
+}
+
+// Executables xlaude runs, see `tools`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolsConfig {
+    // Program names looked up in PATH or absolute paths, e.g. `/opt/homebrew/bin/git`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gh: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tmux: Option<String>,
+    // Extra variables for every git, gh and tmux process, e.g. `GIT_SSH_COMMAND`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+}
+
+impl ToolsConfig {
+    pub fn is_empty(&self) -> bool {
+        self.git.is_none() && self.gh.is_none() && self.tmux.is_none() && self.env.is_empty()
+    }
+}
+
+// Unmerged branch force-deleted by `xlaude delete`, restorable with `xlaude undo-branch`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedBranch {
+    pub repo_name: String,
+    // Name of the worktree the branch belonged to
+    pub name: String,
+    pub branch: String,
+    pub commit: String,
+    // Lightweight tag keeping the commit reachable
+    pub tag: String,
+    pub deleted_at: DateTime<Utc>,
+}
+
+// Worktree removed by `xlaude delete`, restorable with `xlaude trash restore`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedWorktree {
+    pub info: WorktreeInfo,
+    pub deleted_at: DateTime<Utc>,
+    // Commit the worktree had checked out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub head: Option<String>,
+    // Uncommitted changes, including untracked files, as a binary diff against `head`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub patch: Option<PathBuf>,
+}
+
+impl TrashedWorktree {
+    /// Remove the saved patch along with the entry.
+    pub fn discard(&self) {
+        if let Some(patch) = &self.patch {
+            let _ = fs::remove_file(patch);
+        }
+    }
+}
+
+/// Base branch configured for a repository with `xlaude repo set-base`.
+pub fn configured_base_branch(repo_name: &str) -> Option<String> {
+    XlaudeState::load()
+        .ok()?
+        .repos
+        .remove(repo_name)?
+        .base_branch
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct XlaudeState {
+    // Schema version, see `migrations::CURRENT_VERSION`
+    #[serde(default)]
+    pub version: u32,
+    // Monotonic counter bumped on every save, used to detect concurrent writers
+    #[serde(default)]
+    pub revision: u64,
     // Key format: "{repo_name}/{worktree_name}"
     pub worktrees: HashMap<String, WorktreeInfo>,
     // Global agent command to launch sessions (full command line string)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub agent: Option<String>,
+    // Named agent commands selectable with `--agent <name>`, overriding the built-ins
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub agents: BTreeMap<String, String>,
+    // Named initial prompts for `open --prompt-template`, see `templates::PLACEHOLDERS`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub prompt_templates: BTreeMap<String, String>,
     // Preferred editor command (full command line string)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub editor: Option<String>,
     // Preferred interactive shell command
     #[serde(skip_serializing_if = "Option::is_none")]
     pub shell: Option<String>,
+    // Other dashboards federated into `xlaude dashboard`, name -> base URL
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub dashboard_peers: BTreeMap<String, String>,
+    // Seconds between dashboard list refreshes, see `XlaudeState::dashboard_refresh_secs`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dashboard_refresh_secs: Option<u32>,
+    // Per-repository settings keyed by repository name, see `xlaude repo`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub repos: BTreeMap<String, RepoConfig>,
+    // Time-to-live of worktrees without their own `ttl_days`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_ttl_days: Option<u32>,
+    // Days without activity before a worktree is flagged as stale, see `staleness`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stale_after_days: Option<u32>,
+    // Set to false to skip GitHub lookups through `gh`, see `forge`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forge_checks: Option<bool>,
+    // Skip everything that needs the network, like `--offline`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offline: Option<bool>,
+    // Pattern and word lists for generated worktree names
+    #[serde(default, skip_serializing_if = "NameConfig::is_empty")]
+    pub names: NameConfig,
+    // Paths of git, gh and tmux and extra environment for them
+    #[serde(default, skip_serializing_if = "ToolsConfig::is_empty")]
+    pub tools: ToolsConfig,
+    // Force-deleted branches, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trash: Vec<TrashedBranch>,
+    // Deleted worktrees, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trashed_worktrees: Vec<TrashedWorktree>,
+    // Days deleted worktrees are kept in the trash, see `XlaudeState::trash_retention`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trash_retention_days: Option<u32>,
+    // Last time merged branches were checked for every repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merge_checked_at: Option<DateTime<Utc>>,
+    // State as it was read from disk, used to replay our changes after a conflict
+    #[serde(skip)]
+    baseline: Option<Value>,
 }
 
 impl XlaudeState {
@@ -35,47 +319,205 @@ impl XlaudeState {
         format!("{repo_name}/{worktree_name}")
     }
 
+    /// Keys of the worktrees stacked on `repo_name/name`, recursively, with
+    /// every parent listed before its children.
+    pub fn stacked_on(&self, repo_name: &str, name: &str) -> Vec<String> {
+        let mut keys = Vec::new();
+        let mut parents = vec![name.to_string()];
+        while let Some(parent) = parents.pop() {
+            let mut children: Vec<(&String, &WorktreeInfo)> = self
+                .worktrees
+                .iter()
+                .filter(|(_, w)| w.repo_name == repo_name && w.parent.as_ref() == Some(&parent))
+                .collect();
+            children.sort_by_key(|(_, w)| &w.name);
+            for (key, info) in children {
+                // Guard against a hand-edited cycle
+                if !keys.contains(key) {
+                    keys.push(key.clone());
+                    parents.push(info.name.clone());
+                }
+            }
+        }
+        keys
+    }
+
+    /// How often the dashboard reloads its worktree list (15 seconds by
+    /// default). `0` turns automatic refreshes off.
+    pub fn dashboard_refresh_secs(&self) -> u32 {
+        self.dashboard_refresh_secs
+            .unwrap_or(DEFAULT_DASHBOARD_REFRESH_SECS)
+    }
+
+    /// How long deleted worktrees stay in the trash (30 days by default).
+    /// `None` when `trash_retention_days = 0` turns the trash off.
+    pub fn trash_retention(&self) -> Option<chrono::Duration> {
+        let days = self
+            .trash_retention_days
+            .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS);
+        (days > 0).then(|| chrono::Duration::days(days.into()))
+    }
+
+    /// Drop trashed worktrees past the retention period, with their patches.
+    pub fn purge_expired_trash(&mut self) {
+        let retention = self.trash_retention();
+        let now = Utc::now();
+        self.trashed_worktrees.retain(|entry| {
+            let keep = retention.is_some_and(|retention| entry.deleted_at + retention > now);
+            if !keep {
+                entry.discard();
+            }
+            keep
+        });
+    }
+
+    /// Point the direct children of `repo_name/old` at `new` after it was
+    /// renamed or removed.
+    pub fn reparent_children(&mut self, repo_name: &str, old: &str, new: Option<&str>) {
+        for info in self.worktrees.values_mut() {
+            if info.repo_name == repo_name && info.parent.as_deref() == Some(old) {
+                info.parent = new.map(str::to_string);
+            }
+        }
+    }
+
+    /// Store newer activity timestamps keyed like `worktrees`, saving only
+    /// when something moved forward.
+    pub fn record_activity(
+        updates: impl IntoIterator<Item = (String, DateTime<Utc>)>,
+    ) -> Result<()> {
+        let updates: Vec<_> = updates.into_iter().collect();
+        if updates.is_empty() {
+            return Ok(());
+        }
+        let mut state = Self::load()?;
+        let mut changed = false;
+        for (key, ts) in updates {
+            if let Some(info) = state.worktrees.get_mut(&key)
+                && info.last_activity.is_none_or(|cached| cached < ts)
+            {
+                info.last_activity = Some(ts);
+                changed = true;
+            }
+        }
+        if changed {
+            state.save()?;
+        }
+        Ok(())
+    }
+
+    /// Look up a named agent command, falling back to the built-in registry.
+    pub fn agent_command(&self, name: &str) -> Option<String> {
+        self.agents
+            .get(name)
+            .cloned()
+            .or_else(|| builtin_agent(name).map(str::to_string))
+    }
+
+    /// All agent names that can be passed to `--agent`, sorted.
+    pub fn agent_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = BUILTIN_AGENTS
+            .iter()
+            .map(|(name, _)| name.to_string())
+            .chain(self.agents.keys().cloned())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Find the managed worktree whose directory contains `path`.
+    /// The deepest match wins so nested layouts resolve to the innermost worktree.
+    pub fn find_by_path(&self, path: &Path) -> Option<(&String, &WorktreeInfo)> {
+        let target = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        self.worktrees
+            .iter()
+            .filter(|(_, info)| {
+                let root = fs::canonicalize(&info.path).unwrap_or_else(|_| info.path.clone());
+                target.starts_with(&root)
+            })
+            .max_by_key(|(_, info)| info.path.components().count())
+    }
+
+    /// Check the repository given to `--repo`, which is most likely a typo
+    /// when it has no worktrees.
+    pub fn ensure_repo_known(&self, repo: &str) -> Result<()> {
+        if self.worktrees.values().any(|info| info.repo_name == repo) {
+            return Ok(());
+        }
+        Err(XlaudeError::NotFound(format!("No worktrees of repository '{repo}'")).into())
+    }
+
+    /// Number of worktrees per repository.
+    pub fn repo_counts(&self) -> BTreeMap<&str, usize> {
+        let mut counts = BTreeMap::new();
+        for info in self.worktrees.values() {
+            *counts.entry(info.repo_name.as_str()).or_default() += 1;
+        }
+        counts
+    }
+
+    /// Find a worktree by name, or by its `repo/name` key to tell apart
+    /// worktrees named alike in different repositories. The original branch
+    /// name works too, as long as only one repository has that branch.
+    pub fn find_by_name(&self, name: &str) -> Option<(&String, &WorktreeInfo)> {
+        self.worktrees
+            .get_key_value(name)
+            .or_else(|| self.worktrees.iter().find(|(_, w)| w.name == name))
+            .or_else(|| {
+                let mut by_branch = self.worktrees.iter().filter(|(_, w)| w.branch == name);
+                by_branch.next().filter(|_| by_branch.next().is_none())
+            })
+    }
+
+    /// Resolve a worktree by name across all projects, or by the current directory.
+    pub fn resolve_key(&self, name: Option<String>) -> Result<String> {
+        if let Some(n) = name {
+            return self
+                .find_by_name(&n)
+                .map(|(k, _)| k.clone())
+                .ok_or_else(|| XlaudeError::NotFound(format!("Worktree '{n}' not found")).into());
+        }
+
+        let current_dir = std::env::current_dir()?;
+        self.find_by_path(&current_dir)
+            .map(|(k, _)| k.clone())
+            .ok_or_else(|| {
+                XlaudeError::NotFound("Current directory is not a managed worktree".to_string())
+                    .into()
+            })
+    }
+
     pub fn load() -> Result<Self> {
-        let config_path = get_config_path()?;
+        let config_path = get_state_path()?;
         if config_path.exists() {
             let content = fs::read_to_string(&config_path).context("Failed to read config file")?;
-            let mut state: Self =
+            let mut raw: Value =
                 serde_json::from_str(&content).context("Failed to parse config file")?;
 
-            // ============================================================================
-            // MIGRATION LOGIC: Upgrade from v0.2 to v0.3 format
-            // TODO: Remove this migration code after v0.3 is stable and most users have upgraded
-            //
-            // In v0.2, keys were just the worktree name: "feature-x"
-            // In v0.3, keys include the repo name: "repo-name/feature-x"
-            // ============================================================================
-            let needs_migration = state.worktrees.keys().any(|k| !k.contains('/'));
-
-            if needs_migration {
-                eprintln!("🔄 Migrating xlaude state from v0.2 to v0.3 format...");
-
-                let mut migrated_worktrees = HashMap::new();
-                for (old_key, info) in state.worktrees {
-                    // Check if this entry needs migration (doesn't contain '/')
-                    let new_key = if old_key.contains('/') {
-                        // Already in new format, keep as-is
-                        old_key
-                    } else {
-                        // Old format, create new key
-                        Self::make_key(&info.repo_name, &info.name)
-                    };
-                    migrated_worktrees.insert(new_key, info);
-                }
+            let outcome = migrations::migrate(&mut raw)?;
+            let mut state: Self =
+                serde_json::from_value(raw.clone()).context("Failed to parse config file")?;
+            state.baseline = Some(raw);
 
-                state.worktrees = migrated_worktrees;
+            if !outcome.applied.is_empty() {
+                let backup = backup_state_file(&config_path, outcome.from)?;
+                estatus!(
+                    "🔄 Migrating xlaude state from schema v{} to v{}...",
+                    outcome.from,
+                    migrations::CURRENT_VERSION
+                );
+                for description in &outcome.applied {
+                    eprintln!("   • {description}");
+                }
 
                 // Save the migrated state immediately
                 state.save().context("Failed to save migrated state")?;
-                eprintln!("✅ Migration completed successfully");
+                estatus!(
+                    "✅ Migration completed successfully (backup: {})",
+                    backup.display()
+                );
             }
-            // ============================================================================
-            // END OF MIGRATION LOGIC
-            // ============================================================================
 
             Ok(state)
         } else {
@@ -83,34 +525,396 @@ impl XlaudeState {
         }
     }
 
-    pub fn save(&self) -> Result<()> {
-        let config_path = get_config_path()?;
+    /// Persist the state atomically.
+    ///
+    /// Writers are serialized through a lock file. If another process saved since this
+    /// state was loaded, our changes are replayed on top of the newer file instead of
+    /// clobbering it.
+    pub fn save(&mut self) -> Result<()> {
+        let config_path = get_state_path()?;
         if let Some(parent) = config_path.parent() {
-            fs::create_dir_all(parent).context("Failed to create config directory")?;
+            fs::create_dir_all(parent).context("Failed to create data directory")?;
+        }
+
+        let _lock = StateLock::acquire(&config_path)?;
+
+        let mut value = serde_json::to_value(&*self).context("Failed to serialize state")?;
+        let on_disk = read_state_value(&config_path)?;
+        let disk_revision = on_disk.as_ref().map_or(0, revision_of);
+
+        if let Some(theirs) = on_disk
+            && disk_revision != self.revision
+        {
+            let base = self
+                .baseline
+                .clone()
+                .unwrap_or_else(|| Value::Object(Map::new()));
+            value = merge_changes(&base, &value, theirs);
         }
-        let content = serde_json::to_string_pretty(self).context("Failed to serialize state")?;
-        fs::write(&config_path, content).context("Failed to write config file")?;
+
+        value["version"] = Value::from(migrations::CURRENT_VERSION);
+        value["revision"] = Value::from(disk_revision + 1);
+
+        let content = serde_json::to_string_pretty(&value).context("Failed to serialize state")?;
+        write_atomic(&config_path, &content)?;
+
+        *self = serde_json::from_value(value.clone()).context("Failed to reload saved state")?;
+        self.baseline = Some(value);
         Ok(())
     }
 }
 
+/// Copy the state file aside before a migration rewrites it.
+fn backup_state_file(path: &Path, from_version: u32) -> Result<PathBuf> {
+    let mut backup_name = path.as_os_str().to_owned();
+    backup_name.push(format!(
+        ".v{from_version}-{}.bak",
+        Utc::now().format("%Y%m%dT%H%M%SZ")
+    ));
+    let backup = PathBuf::from(backup_name);
+    fs::copy(path, &backup)
+        .with_context(|| format!("Failed to back up state to {}", backup.display()))?;
+    Ok(backup)
+}
+
+fn read_state_value(path: &Path) -> Result<Option<Value>> {
+    match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map(Some)
+            .context("Failed to parse config file"),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err).context("Failed to read config file"),
+    }
+}
+
+fn revision_of(value: &Value) -> u64 {
+    value.get("revision").and_then(Value::as_u64).unwrap_or(0)
+}
+
+/// Replay the changes between `base` and `mine` onto `theirs`.
+///
+/// Object-valued fields (such as `worktrees`) are merged per entry, so two processes
+/// touching different worktrees both keep their edits. Other fields are last-writer-wins.
+fn merge_changes(base: &Value, mine: &Value, mut theirs: Value) -> Value {
+    let empty = Map::new();
+    let base = base.as_object().unwrap_or(&empty);
+    let mine = mine.as_object().unwrap_or(&empty);
+    let Some(target) = theirs.as_object_mut() else {
+        return Value::Object(mine.clone());
+    };
+
+    for key in base.keys().chain(mine.keys()) {
+        if key == "revision" || key == "version" {
+            continue;
+        }
+        let (before, after) = (base.get(key), mine.get(key));
+        if before == after {
+            continue;
+        }
+
+        let before_entries = before.and_then(Value::as_object).unwrap_or(&empty);
+        if let Some(after_entries) = after.and_then(Value::as_object)
+            && let Some(Value::Object(target_entries)) = target.get_mut(key)
+        {
+            for entry in before_entries.keys().chain(after_entries.keys()) {
+                let changed = before_entries.get(entry) != after_entries.get(entry);
+                match after_entries.get(entry) {
+                    Some(value) if changed => {
+                        target_entries.insert(entry.clone(), value.clone());
+                    }
+                    None => {
+                        target_entries.remove(entry);
+                    }
+                    _ => {}
+                }
+            }
+            continue;
+        }
+
+        // Entries of the trash lists are matched by worktree and deletion
+        // time. An empty list is left out of the file, so absent means empty.
+        if key == "trash" || key == "trashed_worktrees" {
+            let items = |value: Option<&Value>| {
+                value.and_then(Value::as_array).cloned().unwrap_or_default()
+            };
+            let (before_items, after_items) = (items(before), items(after));
+            let mut target_items = items(target.get(key));
+            let find = |items: &[Value], item: &Value| {
+                items
+                    .iter()
+                    .position(|other| trash_entry_id(other) == trash_entry_id(item))
+            };
+            for item in &before_items {
+                if find(&after_items, item).is_none()
+                    && let Some(index) = find(&target_items, item)
+                {
+                    target_items.remove(index);
+                }
+            }
+            for item in &after_items {
+                match (find(&before_items, item), find(&target_items, item)) {
+                    (Some(index), Some(target_index)) if before_items[index] != *item => {
+                        target_items[target_index] = item.clone();
+                    }
+                    (None, Some(target_index)) => target_items[target_index] = item.clone(),
+                    (None, None) => target_items.push(item.clone()),
+                    _ => {}
+                }
+            }
+            if target_items.is_empty() {
+                target.remove(key);
+            } else {
+                target.insert(key.clone(), Value::Array(target_items));
+            }
+            continue;
+        }
+
+        match after {
+            Some(value) => {
+                target.insert(key.clone(), value.clone());
+            }
+            None => {
+                target.remove(key);
+            }
+        }
+    }
+
+    theirs
+}
+
+/// Worktree key and deletion time of a `trash` or `trashed_worktrees` entry.
+fn trash_entry_id(entry: &Value) -> [Option<&Value>; 3] {
+    let info = entry.get("info").unwrap_or(entry);
+    [
+        info.get("repo_name"),
+        info.get("name"),
+        entry.get("deleted_at"),
+    ]
+}
+
+/// Write `content` to a sibling temp file, fsync it and rename it over `path`,
+/// so readers never observe a partially written file.
+pub(crate) fn write_atomic(path: &Path, content: &str) -> Result<()> {
+    let mut tmp_name = path.as_os_str().to_owned();
+    tmp_name.push(format!(".tmp.{}", std::process::id()));
+    let tmp_path = PathBuf::from(tmp_name);
+
+    let write_tmp = || -> std::io::Result<()> {
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()
+    };
+    if let Err(err) = write_tmp().and_then(|_| fs::rename(&tmp_path, path)) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(err).with_context(|| format!("Failed to write {}", path.display()));
+    }
+
+    // Persist the rename itself; not all platforms allow opening directories.
+    if let Some(parent) = path.parent()
+        && let Ok(dir) = File::open(parent)
+    {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// Exclusive advisory lock held while reading, merging and writing the state
+/// file. Files next to it that several processes update, such as the agent
+/// registry, are written under the same lock.
+pub(crate) struct StateLock {
+    path: PathBuf,
+}
+
+impl StateLock {
+    pub(crate) fn acquire(state_path: &Path) -> Result<Self> {
+        let mut lock_name = state_path.as_os_str().to_owned();
+        lock_name.push(".lock");
+        let path = PathBuf::from(lock_name);
+        let deadline = Instant::now() + LOCK_TIMEOUT;
+
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    let _ = writeln!(file, "{}", std::process::id());
+                    return Ok(Self { path });
+                }
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+                    if lock_is_stale(&path) {
+                        let _ = fs::remove_file(&path);
+                        continue;
+                    }
+                    if Instant::now() >= deadline {
+                        anyhow::bail!(
+                            "Timed out waiting for state lock at {} (remove it if no xlaude process is running)",
+                            path.display()
+                        );
+                    }
+                    std::thread::sleep(LOCK_RETRY_DELAY);
+                }
+                Err(err) => {
+                    return Err(err).with_context(|| {
+                        format!("Failed to create state lock at {}", path.display())
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl Drop for StateLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn lock_is_stale(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age > LOCK_STALE_AFTER)
+}
+
+const STATE_FILE: &str = "state.json";
+
+// Files that lived in the config directory before machine-local data moved
+// to the data directory. The state file goes last, so an interrupted move
+// leaves it where it is still found.
+const LEGACY_DATA_FILES: &[&str] = &[
+    "sessions.json",
+    "history.jsonl",
+    "dashboard.json",
+    "dashboard.log",
+    "archive",
+    STATE_FILE,
+];
+
+fn project_dirs() -> Result<ProjectDirs> {
+    ProjectDirs::from("com", "xuanwo", "xlaude").context("Failed to determine xlaude directories")
+}
+
+fn env_dir(key: &str) -> Option<PathBuf> {
+    std::env::var_os(key)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Directory for settings meant to be shared, e.g. through dotfiles.
+/// `XLAUDE_CONFIG_DIR` overrides it.
 pub fn get_config_dir() -> Result<PathBuf> {
     // Allow overriding config directory for testing
-    if let Ok(config_dir) = std::env::var("XLAUDE_CONFIG_DIR") {
-        return Ok(PathBuf::from(config_dir));
+    if let Some(config_dir) = env_dir("XLAUDE_CONFIG_DIR") {
+        return Ok(config_dir);
     }
+    Ok(project_dirs()?.config_dir().to_path_buf())
+}
+
+/// Directory for machine-local data: the state file, running agents,
+/// history, dashboard logs and archived sessions. `XLAUDE_DATA_DIR`
+/// overrides it, and a lone `XLAUDE_CONFIG_DIR` keeps everything together.
+pub fn get_data_dir() -> Result<PathBuf> {
+    if let Some(dir) = env_dir("XLAUDE_DATA_DIR").or_else(|| env_dir("XLAUDE_CONFIG_DIR")) {
+        return Ok(dir);
+    }
+    Ok(project_dirs()?.data_dir().to_path_buf())
+}
 
-    let proj_dirs = ProjectDirs::from("com", "xuanwo", "xlaude")
-        .context("Failed to determine config directory")?;
-    Ok(proj_dirs.config_dir().to_path_buf())
+/// Directory for caches that can be deleted at any time. `XLAUDE_CACHE_DIR`
+/// overrides it, and a lone `XLAUDE_CONFIG_DIR` keeps everything together.
+pub fn get_cache_dir() -> Result<PathBuf> {
+    if let Some(dir) = env_dir("XLAUDE_CACHE_DIR").or_else(|| env_dir("XLAUDE_CONFIG_DIR")) {
+        return Ok(dir);
+    }
+    Ok(project_dirs()?.cache_dir().to_path_buf())
 }
 
 pub fn get_state_path() -> Result<PathBuf> {
-    get_config_path()
+    // Earlier versions kept everything in the config directory
+    let Some(legacy_dir) = legacy_state_dir()? else {
+        return peek_state_path();
+    };
+    let data_dir = get_data_dir()?;
+    match move_legacy_data(&legacy_dir, &data_dir) {
+        Ok(()) => {
+            estatus!(
+                "📦 Moved xlaude state from {} to {}",
+                legacy_dir.display(),
+                data_dir.display()
+            );
+            Ok(data_dir.join(STATE_FILE))
+        }
+        Err(err) => {
+            estatus!(
+                "⚠️  Failed to move xlaude state to {}, still using {}: {err:#}",
+                data_dir.display(),
+                legacy_dir.display()
+            );
+            Ok(legacy_dir.join(STATE_FILE))
+        }
+    }
+}
+
+/// The state file in use, without moving one left by an earlier version:
+/// that is only done by `get_state_path`. For read-only callers such as
+/// shell prompt hooks.
+pub fn peek_state_path() -> Result<PathBuf> {
+    // An explicit state file wins over the data directory
+    if let Some(file) = env_dir("XLAUDE_STATE_FILE") {
+        return Ok(file);
+    }
+    Ok(match legacy_state_dir()? {
+        Some(legacy_dir) => legacy_dir.join(STATE_FILE),
+        None => get_data_dir()?.join(STATE_FILE),
+    })
+}
+
+/// The config directory when it still holds the state file of an earlier
+/// version, which kept everything there, and nothing overrides the location.
+fn legacy_state_dir() -> Result<Option<PathBuf>> {
+    if env_dir("XLAUDE_STATE_FILE").is_some()
+        || env_dir("XLAUDE_DATA_DIR").is_some()
+        || env_dir("XLAUDE_CONFIG_DIR").is_some()
+    {
+        return Ok(None);
+    }
+    let data_dir = get_data_dir()?;
+    let legacy_dir = get_config_dir()?;
+    if data_dir.join(STATE_FILE).exists()
+        || legacy_dir == data_dir
+        || !legacy_dir.join(STATE_FILE).exists()
+    {
+        return Ok(None);
+    }
+    Ok(Some(legacy_dir))
+}
+
+fn move_legacy_data(legacy_dir: &Path, data_dir: &Path) -> Result<()> {
+    fs::create_dir_all(data_dir)
+        .with_context(|| format!("Failed to create {}", data_dir.display()))?;
+    for name in LEGACY_DATA_FILES {
+        match fs::rename(legacy_dir.join(name), data_dir.join(name)) {
+            Ok(()) => {}
+            // Missing files, or another process moving them at the same time
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => return Err(err).with_context(|| format!("Failed to move {name}")),
+        }
+    }
+    Ok(())
 }
 
-fn get_config_path() -> Result<PathBuf> {
-    Ok(get_config_dir()?.join("state.json"))
+// Agents that can be selected by name without any configuration
+const BUILTIN_AGENTS: &[(&str, &str)] = &[
+    ("aider", "aider"),
+    ("claude", "claude --dangerously-skip-permissions"),
+    ("codex", "codex"),
+    ("gemini", "gemini"),
+];
+
+pub fn builtin_agent(name: &str) -> Option<&'static str> {
+    BUILTIN_AGENTS
+        .iter()
+        .find(|(builtin, _)| *builtin == name)
+        .map(|(_, command)| *command)
 }
 
 /// Resolve the agent command from state with a sensible default.
@@ -118,3 +922,96 @@ fn get_config_path() -> Result<PathBuf> {
 pub fn get_default_agent() -> String {
     "claude --dangerously-skip-permissions".to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn merge_changes_keeps_concurrent_worktree_edits() {
+        let base = json!({
+            "revision": 1,
+            "worktrees": { "repo/a": { "name": "a" }, "repo/b": { "name": "b" } },
+            "agent": "claude"
+        });
+        // We removed `a`, added `c`, and changed the agent.
+        let mine = json!({
+            "revision": 1,
+            "worktrees": { "repo/b": { "name": "b" }, "repo/c": { "name": "c" } },
+            "agent": "codex"
+        });
+        // Meanwhile another process added `d` and set an editor.
+        let theirs = json!({
+            "revision": 2,
+            "worktrees": {
+                "repo/a": { "name": "a" },
+                "repo/b": { "name": "b" },
+                "repo/d": { "name": "d" }
+            },
+            "agent": "claude",
+            "editor": "vim"
+        });
+
+        let merged = merge_changes(&base, &mine, theirs);
+
+        assert_eq!(
+            merged,
+            json!({
+                "revision": 2,
+                "worktrees": {
+                    "repo/b": { "name": "b" },
+                    "repo/c": { "name": "c" },
+                    "repo/d": { "name": "d" }
+                },
+                "agent": "codex",
+                "editor": "vim"
+            })
+        );
+    }
+
+    #[test]
+    fn merge_changes_keeps_concurrent_trash_edits() {
+        let entry = |name: &str, at: &str| {
+            json!({
+                "info": { "repo_name": "repo", "name": name },
+                "deleted_at": at
+            })
+        };
+        let branch = |name: &str, at: &str| json!({ "repo_name": "repo", "name": name, "branch": name, "deleted_at": at });
+        let base = json!({
+            "trashed_worktrees": [entry("a", "t1"), entry("b", "t1")],
+            "trash": [branch("a", "t1")]
+        });
+        // We restored `a` and trashed `c`.
+        let mine = json!({
+            "trashed_worktrees": [entry("b", "t1"), entry("c", "t2")],
+            "trash": [branch("a", "t1"), branch("c", "t2")]
+        });
+        // Meanwhile another process emptied the branch trash, trashed `d`
+        // and `a` again.
+        let theirs = json!({
+            "trashed_worktrees": [
+                entry("a", "t1"),
+                entry("b", "t1"),
+                entry("d", "t3"),
+                entry("a", "t3")
+            ]
+        });
+
+        let merged = merge_changes(&base, &mine, theirs);
+
+        assert_eq!(
+            merged,
+            json!({
+                "trashed_worktrees": [
+                    entry("b", "t1"),
+                    entry("d", "t3"),
+                    entry("a", "t3"),
+                    entry("c", "t2")
+                ],
+                "trash": [branch("c", "t2")]
+            })
+        );
+    }
+}
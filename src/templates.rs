@@ -0,0 +1,135 @@
+//! Named initial prompts (`prompt_templates` in the state file) with
+//! placeholders filled in from the worktree at open time. The renderer is
+//! shared with `list --format` templates.
+
+use std::path::Path;
+
+use crate::git::resolve_base_ref;
+use crate::tools::{self, Tool};
+
+pub const PLACEHOLDERS: &[&str] = &[
+    "name",
+    "repo",
+    "branch",
+    "path",
+    "base",
+    "pr_url",
+    "diff_stat",
+];
+
+pub struct TemplateContext<'a> {
+    pub path: &'a Path,
+    pub repo_name: &'a str,
+    pub worktree_name: &'a str,
+}
+
+impl TemplateContext<'_> {
+    /// Fill in every known `{placeholder}`. Values that need git or gh are only
+    /// computed when the template uses them, and fall back to an empty string.
+    pub fn render(&self, template: &str) -> String {
+        render(template, PLACEHOLDERS, |key| self.value(key))
+    }
+
+    fn value(&self, key: &str) -> Option<String> {
+        match key {
+            "name" => Some(self.worktree_name.to_string()),
+            "repo" => Some(self.repo_name.to_string()),
+            "path" => Some(self.path.display().to_string()),
+            "branch" => self.git(&["symbolic-ref", "--short", "HEAD"]),
+            "base" => resolve_base_ref(self.path),
+            "pr_url" => self.run(Tool::Gh, &["pr", "view", "--json", "url", "-q", ".url"]),
+            "diff_stat" => {
+                let base = resolve_base_ref(self.path)?;
+                self.git(&["diff", "--stat", &format!("{base}...HEAD")])
+            }
+            _ => None,
+        }
+    }
+
+    fn git(&self, args: &[&str]) -> Option<String> {
+        self.run(Tool::Git, args)
+    }
+
+    fn run(&self, tool: Tool, args: &[&str]) -> Option<String> {
+        tools::command(tool)
+            .current_dir(self.path)
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+}
+
+/// Single left-to-right pass so substituted values are never expanded again.
+/// Braces that do not form a known placeholder are kept verbatim.
+pub fn render(
+    template: &str,
+    placeholders: &[&str],
+    mut lookup: impl FnMut(&str) -> Option<String>,
+) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let candidate = &rest[start + 1..];
+        match candidate.find('}') {
+            Some(end) if placeholders.contains(&&candidate[..end]) => {
+                output.push_str(&lookup(&candidate[..end]).unwrap_or_default());
+                rest = &candidate[end + 1..];
+            }
+            _ => {
+                output.push('{');
+                rest = candidate;
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// `{word}` names in a template that are not placeholders, to catch typos
+/// where braces are not expected to appear otherwise.
+pub fn unknown_placeholders<'a>(template: &'a str, placeholders: &[&str]) -> Vec<&'a str> {
+    template
+        .split('{')
+        .skip(1)
+        .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+        .filter(|name| {
+            !name.is_empty()
+                && name.chars().all(|c| c.is_ascii_lowercase() || c == '_')
+                && !placeholders.contains(name)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_replaces_known_placeholders_once() {
+        let rendered = render(
+            "Review {branch} against {base}: {\"json\": true} {unknown} {diff_stat}",
+            PLACEHOLDERS,
+            |key| match key {
+                "branch" => Some("feature/{base}".to_string()),
+                "base" => Some("origin/main".to_string()),
+                _ => None,
+            },
+        );
+        assert_eq!(
+            rendered,
+            "Review feature/{base} against origin/main: {\"json\": true} {unknown} "
+        );
+    }
+
+    #[test]
+    fn unknown_placeholders_ignores_other_braces() {
+        assert_eq!(
+            unknown_placeholders("{name} {nmae} {\"json\": 1} {} {Path}", PLACEHOLDERS),
+            vec!["nmae"]
+        );
+    }
+}
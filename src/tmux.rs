@@ -0,0 +1,185 @@
+//! Thin wrapper around the tmux CLI for running agents in detached sessions.
+
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::tools::{self, Tool};
+use crate::utils::{RunPolicy, run_command};
+
+pub struct TmuxManager;
+
+impl TmuxManager {
+    /// Fails when tmux is not installed.
+    pub fn new() -> Result<Self> {
+        let available = run_command(tools::command(Tool::Tmux).arg("-V"), RunPolicy::local())
+            .is_ok_and(|output| output.status.success());
+        if !available {
+            anyhow::bail!("tmux is not installed or not on PATH");
+        }
+        Ok(Self)
+    }
+
+    /// Session name for a worktree. tmux reserves `.` and `:` in targets,
+    /// so anything outside `[A-Za-z0-9_-]` becomes `-`. Names in other
+    /// scripts would all collapse to dashes, so those get a hash of the
+    /// original name appended to keep their sessions apart.
+    pub fn session_name(repo_name: &str, worktree_name: &str) -> String {
+        let full = format!("xlaude-{repo_name}-{worktree_name}");
+        let mut session: String = full
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        if !full.is_ascii() {
+            session.push_str(&format!("-{:08x}", fnv1a(&full)));
+        }
+        session
+    }
+
+    pub fn has_session(&self, session: &str) -> bool {
+        run_command(
+            tools::command(Tool::Tmux).args(["has-session", "-t", &format!("={session}")]),
+            RunPolicy::local(),
+        )
+        .is_ok_and(|output| output.status.success())
+    }
+
+    /// Names of all sessions on the default server, empty when none is running.
+    pub fn list_sessions(&self) -> Vec<String> {
+        self.run(&["list-sessions", "-F", "#{session_name}"])
+            .map(|output| output.lines().map(str::to_string).collect())
+            .unwrap_or_default()
+    }
+
+    /// Run `program` in `dir` inside a detached session, or in a new window of
+    /// the session if it already exists, with `env` added to its environment.
+    /// Returns the pid of the new pane.
+    pub fn create_session(
+        &self,
+        session: &str,
+        dir: &Path,
+        program: &str,
+        args: &[String],
+        env: &BTreeMap<String, String>,
+    ) -> Result<u32> {
+        let dir = dir.to_string_lossy();
+        let target = format!("={session}:");
+        let env: Vec<String> = env
+            .iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect();
+        let mut tmux_args = if self.has_session(session) {
+            vec!["new-window", "-d", "-t", &target]
+        } else {
+            vec!["new-session", "-d", "-s", session]
+        };
+        for var in &env {
+            tmux_args.extend(["-e", var]);
+        }
+        tmux_args.extend(["-P", "-F", "#{pane_pid}", "-c", &dir, program]);
+        tmux_args.extend(args.iter().map(String::as_str));
+
+        let output = self.run(&tmux_args)?;
+        output
+            .trim()
+            .parse()
+            .with_context(|| format!("Unexpected tmux output: {output}"))
+    }
+
+    /// Attach the terminal to `session`, or switch to it when already inside tmux.
+    /// Blocks until the client detaches.
+    pub fn attach_session(&self, session: &str) -> Result<()> {
+        let target = format!("={session}");
+        let subcommand = if std::env::var_os("TMUX").is_some() {
+            "switch-client"
+        } else {
+            "attach-session"
+        };
+        let status = tools::command(Tool::Tmux)
+            .args([subcommand, "-t", &target])
+            .status()
+            .context("Failed to run tmux")?;
+        if !status.success() {
+            anyhow::bail!("tmux {subcommand} failed");
+        }
+        Ok(())
+    }
+
+    /// Point the session's working directory at `dir`, used for windows opened
+    /// without an explicit directory. tmux only changes it on attach, so
+    /// attach a control-mode client that exits immediately.
+    pub fn set_session_dir(&self, session: &str, dir: &Path) -> Result<()> {
+        let dir = dir.to_string_lossy();
+        let target = format!("={session}");
+        self.run(&["-C", "attach-session", "-t", &target, "-c", &dir])?;
+        Ok(())
+    }
+
+    pub fn rename_session(&self, session: &str, new_name: &str) -> Result<()> {
+        let target = format!("={session}");
+        self.run(&["rename-session", "-t", &target, new_name])?;
+        Ok(())
+    }
+
+    fn run(&self, args: &[&str]) -> Result<String> {
+        let output = run_command(tools::command(Tool::Tmux).args(args), RunPolicy::local())
+            .context("Failed to run tmux")?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "tmux {} failed: {}",
+                args.first().unwrap_or(&""),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+}
+
+/// 32-bit FNV-1a, stable across Rust releases unlike `DefaultHasher`.
+fn fnv1a(text: &str) -> u32 {
+    text.bytes().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_name_tells_apart_other_scripts() {
+        assert_eq!(
+            TmuxManager::session_name("app.rs", "fix-v1.2"),
+            "xlaude-app-rs-fix-v1-2"
+        );
+        let cyrillic = TmuxManager::session_name("app", "ите");
+        let japanese = TmuxManager::session_name("app", "日本語");
+        assert!(cyrillic.starts_with("xlaude-app----"));
+        assert_ne!(cyrillic, japanese);
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn session_names_are_valid_tmux_targets(repo in "\\PC*", name in "\\PC*") {
+            let session = TmuxManager::session_name(&repo, &name);
+            proptest::prop_assert!(
+                session.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            );
+        }
+
+        #[test]
+        fn non_ascii_names_get_distinct_sessions(a in "\\PC{1,12}", b in "\\PC{1,12}") {
+            proptest::prop_assume!(a != b && !(a.is_ascii() && b.is_ascii()));
+            proptest::prop_assert_ne!(
+                TmuxManager::session_name("app", &a),
+                TmuxManager::session_name("app", &b)
+            );
+        }
+    }
+}
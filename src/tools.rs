@@ -0,0 +1,51 @@
+//! The external programs xlaude runs: git, gh and tmux. `tools` in the state
+//! file can point each at another binary, e.g. Homebrew's git instead of the
+//! system one, and add environment variables such as `GIT_SSH_COMMAND`.
+
+use std::process::Command;
+use std::sync::OnceLock;
+
+use crate::state::{ToolsConfig, XlaudeState};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Tool {
+    Git,
+    Gh,
+    Tmux,
+}
+
+impl Tool {
+    fn default_program(self) -> &'static str {
+        match self {
+            Self::Git => "git",
+            Self::Gh => "gh",
+            Self::Tmux => "tmux",
+        }
+    }
+}
+
+// Read once per process; loading the state never runs any of the tools
+fn configured() -> &'static ToolsConfig {
+    static CONFIG: OnceLock<ToolsConfig> = OnceLock::new();
+    CONFIG.get_or_init(|| {
+        XlaudeState::load()
+            .map(|state| state.tools)
+            .unwrap_or_default()
+    })
+}
+
+/// A command for `tool` with the configured program and environment.
+pub fn command(tool: Tool) -> Command {
+    let config = configured();
+    let program = match tool {
+        Tool::Git => config.git.as_deref(),
+        Tool::Gh => config.gh.as_deref(),
+        Tool::Tmux => config.tmux.as_deref(),
+    }
+    .filter(|program| !program.trim().is_empty())
+    .unwrap_or(tool.default_program());
+
+    let mut cmd = Command::new(program);
+    cmd.envs(&config.env);
+    cmd
+}
@@ -1,9 +1,56 @@
 use anyhow::{Context, Result};
+use colored::Colorize;
 use rand::seq::IndexedRandom;
 use rand::{RngCore, SeedableRng};
+use std::io::Read;
 use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
 
-pub fn generate_random_name() -> Result<String> {
+use crate::error::XlaudeError;
+use crate::state::{NameConfig, XlaudeState};
+
+// Generated names are retried this often before falling back to a numbered suffix
+const NAME_ATTEMPTS: usize = 20;
+
+// Words that carry no meaning in a name derived from a prompt
+const SLUG_STOPWORDS: &[&str] = &[
+    "a", "an", "and", "for", "in", "of", "on", "please", "the", "to", "with",
+];
+const SLUG_MAX_WORDS: usize = 4;
+const SLUG_MAX_LEN: usize = 40;
+
+const DEFAULT_NETWORK_TIMEOUT_SECS: u64 = 120;
+const DEFAULT_NETWORK_RETRIES: u32 = 2;
+// A naming agent is a convenience; past this the prompt's words are used
+const DEFAULT_NAME_AGENT_TIMEOUT_SECS: u64 = 30;
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+// stderr fragments of failures that may go away when the command is retried
+const TRANSIENT_ERRORS: &[&str] = &[
+    "could not resolve host",
+    "connection reset",
+    "connection refused",
+    "connection timed out",
+    "operation timed out",
+    "timeout",
+    "early eof",
+    "the remote end hung up unexpectedly",
+    "tls",
+    "http 502",
+    "http 503",
+    "http 504",
+    "secondary rate limit",
+];
+
+/// Generate a worktree name from the configured pattern (a random BIP39 word by
+/// default) that `is_taken` does not reject.
+pub fn generate_random_name(
+    config: &NameConfig,
+    is_taken: impl Fn(&str) -> bool,
+) -> Result<String> {
     // Allow setting seed for testing
     let mut rng = if let Ok(seed_str) = std::env::var("XLAUDE_TEST_SEED") {
         let seed: u64 = seed_str.parse().unwrap_or(42);
@@ -12,13 +59,6 @@ pub fn generate_random_name() -> Result<String> {
         Box::new(rand::rng()) as Box<dyn RngCore>
     };
 
-    // Generate 128 bits of entropy for a 12-word mnemonic
-    let mut entropy = [0u8; 16];
-    rng.fill_bytes(&mut entropy);
-
-    let mnemonic = bip39::Mnemonic::from_entropy(&entropy)?;
-    let words: Vec<&str> = mnemonic.words().collect();
-
     // Use the same RNG for choosing the word
     let mut chooser_rng = if let Ok(seed_str) = std::env::var("XLAUDE_TEST_SEED") {
         let seed: u64 = seed_str.parse().unwrap_or(42);
@@ -28,16 +68,402 @@ pub fn generate_random_name() -> Result<String> {
         rand::rngs::StdRng::from_rng(&mut entropy_rng)
     };
 
+    let pattern = config.pattern.as_deref().unwrap_or("{word}");
+    let mut name = String::new();
+    for _ in 0..NAME_ATTEMPTS {
+        name = fill_name_pattern(pattern, config, &mut *rng, &mut chooser_rng)?;
+        if !is_taken(&name) {
+            return Ok(name);
+        }
+    }
+    // Small word lists run out quickly
+    Ok(unique_name(&name, is_taken))
+}
+
+/// `base`, or `base-2`, `base-3`, ... when it is taken.
+pub fn unique_name(base: &str, is_taken: impl Fn(&str) -> bool) -> String {
+    if !is_taken(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{base}-{n}"))
+        .find(|name| !is_taken(name))
+        .expect("unbounded suffixes")
+}
+
+fn fill_name_pattern(
+    pattern: &str,
+    config: &NameConfig,
+    rng: &mut dyn RngCore,
+    chooser_rng: &mut rand::rngs::StdRng,
+) -> Result<String> {
+    let mut name = String::new();
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..]
+            .find('}')
+            .map(|end| start + end)
+            .with_context(|| format!("Unclosed placeholder in name pattern '{pattern}'"))?;
+        name.push_str(&rest[..start]);
+        let list = &rest[start + 1..end];
+        let word = if list == "word" {
+            bip39_word(rng, chooser_rng)?
+        } else {
+            config
+                .words
+                .get(list)
+                .with_context(|| format!("Unknown word list '{list}' in name pattern"))?
+                .choose(chooser_rng)
+                .cloned()
+                .with_context(|| format!("Word list '{list}' is empty"))?
+        };
+        name.push_str(&word);
+        rest = &rest[end + 1..];
+    }
+    name.push_str(rest);
+    Ok(name)
+}
+
+fn bip39_word(rng: &mut dyn RngCore, chooser_rng: &mut rand::rngs::StdRng) -> Result<String> {
+    // Generate 128 bits of entropy for a 12-word mnemonic
+    let mut entropy = [0u8; 16];
+    rng.fill_bytes(&mut entropy);
+
+    let mnemonic = bip39::Mnemonic::from_entropy(&entropy)?;
+    let words: Vec<&str> = mnemonic.words().collect();
+
     words
-        .choose(&mut chooser_rng)
+        .choose(chooser_rng)
         .map(|&word| word.to_string())
         .context("Failed to generate random name")
 }
 
-/// Sanitize a branch name for use in directory names
-/// Replaces forward slashes with hyphens to avoid creating subdirectories
+/// Name a worktree after its task prompt, asking the agent command configured in
+/// `names.agent` first and falling back to `slugify_prompt`.
+pub fn name_from_prompt(config: &NameConfig, prompt: &str) -> Option<String> {
+    config
+        .agent
+        .as_deref()
+        .and_then(|command| ask_agent_for_name(command, prompt))
+        .or_else(|| slugify_prompt(prompt))
+}
+
+fn ask_agent_for_name(command: &str, prompt: &str) -> Option<String> {
+    let mut parts = shell_words::split(command).ok()?;
+    if parts.is_empty() {
+        return None;
+    }
+    let program = parts.remove(0);
+    let question = format!(
+        "Suggest a 2-3 word git branch name for the task below. \
+         Reply with the name only, in kebab-case.\n\nTask: {prompt}"
+    );
+
+    let mut cmd = Command::new(program);
+    cmd.args(parts).arg(question);
+    let policy = RunPolicy {
+        timeout: non_zero(
+            env_secs("XLAUDE_NAME_AGENT_TIMEOUT").unwrap_or(DEFAULT_NAME_AGENT_TIMEOUT_SECS),
+        ),
+        retries: 0,
+    };
+    let output = match run_command(&mut cmd, policy) {
+        Ok(output) if output.status.success() => output,
+        Ok(_) => return None,
+        Err(err) => {
+            estatus!(
+                "{} Could not ask for a worktree name: {err:#}",
+                "⚠️".yellow()
+            );
+            return None;
+        }
+    };
+    // Agents may chat before answering, the name is on the last line
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let answer = stdout.lines().rev().find(|line| !line.trim().is_empty())?;
+    if answer.split_whitespace().count() > SLUG_MAX_WORDS {
+        return None;
+    }
+    slugify_prompt(answer)
+}
+
+/// Derive a short name from the first meaningful words of an agent prompt,
+/// e.g. "Fix the flaky login test" becomes `fix-flaky-login-test`.
+pub fn slugify_prompt(prompt: &str) -> Option<String> {
+    let mut slug = String::new();
+    let words = prompt
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_ascii_lowercase)
+        .filter(|word| !SLUG_STOPWORDS.contains(&word.as_str()))
+        .take(SLUG_MAX_WORDS);
+    for word in words {
+        if !slug.is_empty() && slug.len() + word.len() + 1 > SLUG_MAX_LEN {
+            break;
+        }
+        if !slug.is_empty() {
+            slug.push('-');
+        }
+        slug.push_str(&word);
+    }
+    slug.truncate(SLUG_MAX_LEN);
+    (!slug.is_empty()).then_some(slug)
+}
+
+/// Turn a branch name into the worktree name used in state keys and
+/// directory names. Letters and digits in any script, `_` and `.` are kept;
+/// everything else, `/` and whitespace included, becomes `-`. Runs of `-` are
+/// collapsed and `-` or `.` at either end is dropped, so the result is a
+/// single visible path component. A name with nothing left becomes
+/// `worktree`. Sanitizing twice changes nothing.
+///
+/// Different branches can map to the same name (`feat/x` and `feat-x`);
+/// the branch itself is stored with each worktree and `create` refuses a
+/// name that is already tracked.
 pub fn sanitize_branch_name(branch: &str) -> String {
-    branch.replace('/', "-")
+    let mut name = String::with_capacity(branch.len());
+    for c in branch.chars() {
+        if c.is_alphanumeric() || c == '_' || c == '.' {
+            name.push(c);
+        } else if !name.ends_with('-') {
+            name.push('-');
+        }
+    }
+    let name = name.trim_matches(['-', '.']);
+    if name.is_empty() {
+        "worktree".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Whether a process with this pid still exists.
+pub fn is_process_alive(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        std::process::Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .output()
+            .is_ok_and(|output| output.status.success())
+    }
+    #[cfg(not(unix))]
+    {
+        // tasklist succeeds even when no process matches the filter
+        std::process::Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {pid}"), "/NH"])
+            .output()
+            .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()))
+    }
+}
+
+/// When the process started, as reported by `ps`. Together with the pid this
+/// tells a process apart from a later one that was given the same pid.
+/// Always `None` on Windows.
+pub fn process_start_time(pid: u32) -> Option<String> {
+    #[cfg(unix)]
+    {
+        std::process::Command::new("ps")
+            .args(["-o", "lstart=", "-p", &pid.to_string()])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .filter(|started| !started.is_empty())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+/// Ask a process to exit (SIGTERM on Unix, `taskkill /F` on Windows).
+pub fn terminate_process(pid: u32) -> Result<()> {
+    #[cfg(unix)]
+    let status = std::process::Command::new("kill")
+        .arg(pid.to_string())
+        .status();
+    #[cfg(not(unix))]
+    let status = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/F"])
+        .status();
+
+    let status = status.with_context(|| format!("Failed to signal process {pid}"))?;
+    if !status.success() {
+        anyhow::bail!("Failed to stop process {pid}");
+    }
+    Ok(())
+}
+
+/// Whether network access is turned off, by `--offline` (`XLAUDE_OFFLINE`) or
+/// the `offline` setting.
+pub fn is_offline() -> bool {
+    static CONFIGURED: OnceLock<bool> = OnceLock::new();
+    let flag = std::env::var("XLAUDE_OFFLINE").is_ok_and(|value| {
+        !matches!(
+            value.trim().to_ascii_lowercase().as_str(),
+            "" | "0" | "false" | "no" | "off"
+        )
+    });
+    flag || *CONFIGURED
+        .get_or_init(|| XlaudeState::load().is_ok_and(|state| state.offline == Some(true)))
+}
+
+/// Refuse `action` in offline mode.
+pub fn ensure_online(action: &str) -> Result<()> {
+    if is_offline() {
+        return Err(XlaudeError::Refused(format!(
+            "{action} needs network access, which is turned off in offline mode"
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// Time limit and retries for an external command.
+#[derive(Debug, Clone, Copy)]
+pub struct RunPolicy {
+    pub timeout: Option<Duration>,
+    pub retries: u32,
+}
+
+impl RunPolicy {
+    /// Commands that only touch local files (git on the worktree, tmux). They
+    /// are never retried and only limited by `XLAUDE_COMMAND_TIMEOUT`.
+    pub fn local() -> Self {
+        Self {
+            timeout: env_secs("XLAUDE_COMMAND_TIMEOUT").and_then(non_zero),
+            retries: 0,
+        }
+    }
+
+    /// Commands that talk to a remote (`git fetch`, `gh`). They are limited
+    /// by `XLAUDE_NETWORK_TIMEOUT` and retried `XLAUDE_NETWORK_RETRIES` times
+    /// when they time out or fail with a transient network error.
+    pub fn network() -> Self {
+        let timeout = env_secs("XLAUDE_NETWORK_TIMEOUT").unwrap_or(DEFAULT_NETWORK_TIMEOUT_SECS);
+        Self {
+            timeout: non_zero(timeout),
+            retries: env_secs("XLAUDE_NETWORK_RETRIES")
+                .map_or(DEFAULT_NETWORK_RETRIES, |n| n.min(u32::MAX as u64) as u32),
+        }
+    }
+}
+
+fn env_secs(key: &str) -> Option<u64> {
+    std::env::var(key).ok()?.trim().parse().ok()
+}
+
+fn non_zero(secs: u64) -> Option<Duration> {
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// Run `cmd` to completion like `Command::output`, applying the policy's
+/// time limit and retries. A non-zero exit status is returned as is for the
+/// caller to report; only failing to start and timing out are errors.
+pub fn run_command(cmd: &mut Command, policy: RunPolicy) -> Result<Output> {
+    let secs = policy.timeout.unwrap_or_default().as_secs();
+    let mut attempt = 0;
+    loop {
+        let reason = match run_once(cmd, policy.timeout)? {
+            Some(output) => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                if output.status.success() || !is_transient(&stderr) || attempt >= policy.retries {
+                    return Ok(output);
+                }
+                format!("failed: {}", last_line(&stderr))
+            }
+            None if attempt >= policy.retries => {
+                anyhow::bail!("`{}` timed out after {secs}s", describe_command(cmd));
+            }
+            None => format!("timed out after {secs}s"),
+        };
+
+        attempt += 1;
+        estatus!(
+            "{} `{}` {reason}, retrying ({attempt}/{})",
+            "⚠️".yellow(),
+            describe_command(cmd),
+            policy.retries
+        );
+        std::thread::sleep(RETRY_DELAY * attempt);
+    }
+}
+
+/// Run once, returning `None` when the command was killed for running past
+/// `timeout`.
+fn run_once(cmd: &mut Command, timeout: Option<Duration>) -> Result<Option<Output>> {
+    let program = cmd.get_program().to_string_lossy().into_owned();
+    cmd.stdin(Stdio::null());
+    let Some(timeout) = timeout else {
+        return cmd
+            .output()
+            .map(Some)
+            .with_context(|| format!("Failed to run {program}"));
+    };
+
+    let mut child = cmd
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run {program}"))?;
+    // Drain both pipes while waiting so a chatty command cannot block on them
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            // Descendants may keep the pipes open, so the readers are not joined
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    };
+
+    Ok(Some(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    }))
+}
+
+fn read_pipe(pipe: Option<impl Read + Send + 'static>) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+fn is_transient(stderr: &str) -> bool {
+    let stderr = stderr.to_lowercase();
+    TRANSIENT_ERRORS
+        .iter()
+        .any(|pattern| stderr.contains(pattern))
+}
+
+fn last_line(stderr: &str) -> &str {
+    stderr
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or_default()
+        .trim()
+}
+
+fn describe_command(cmd: &Command) -> String {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|arg| arg.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 pub fn execute_in_dir<P, F, R>(path: P, f: F) -> Result<R>
@@ -57,12 +483,22 @@ where
 }
 
 /// Resolve agent command from state or default, and split into program + args.
-pub fn resolve_agent_command() -> Result<(String, Vec<String>)> {
+/// `agent` selects a named entry from the agent registry; without it the global
+/// `agent` setting (or the default) is used.
+pub fn resolve_agent_command(agent: Option<&str>) -> Result<(String, Vec<String>)> {
     let state = crate::state::XlaudeState::load()?;
-    let cmdline = state
-        .agent
-        .clone()
-        .unwrap_or_else(crate::state::get_default_agent);
+    let cmdline = match agent {
+        Some(name) => state.agent_command(name).with_context(|| {
+            format!(
+                "Unknown agent '{name}'. Available agents: {}",
+                state.agent_names().join(", ")
+            )
+        })?,
+        None => state
+            .agent
+            .clone()
+            .unwrap_or_else(crate::state::get_default_agent),
+    };
 
     // Use shell-style splitting to handle quotes and spaces.
     let parts = shell_words::split(&cmdline)
@@ -131,8 +567,11 @@ fn codex_has_positional_arguments(args: &[String]) -> bool {
     false
 }
 
-pub fn prepare_agent_command(worktree_path: &Path) -> Result<(String, Vec<String>)> {
-    let (program, args) = resolve_agent_command()?;
+pub fn prepare_agent_command(
+    worktree_path: &Path,
+    agent: Option<&str>,
+) -> Result<(String, Vec<String>)> {
+    let (program, args) = resolve_agent_command(agent)?;
 
     if !program.eq_ignore_ascii_case("codex") {
         return Ok((program, args));
@@ -163,6 +602,83 @@ mod tests {
 
     static ENV_MUTEX: OnceLock<Mutex<()>> = OnceLock::new();
 
+    #[test]
+    fn sanitize_branch_name_keeps_other_scripts() {
+        assert_eq!(
+            sanitize_branch_name("feat/ите/日本語 #1"),
+            "feat-ите-日本語-1"
+        );
+        assert_eq!(sanitize_branch_name("fix/v1.2"), "fix-v1.2");
+        assert_eq!(sanitize_branch_name("../.hidden/"), "hidden");
+        assert_eq!(sanitize_branch_name("///"), "worktree");
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn sanitized_names_are_single_path_components(branch in "\\PC*") {
+            let name = sanitize_branch_name(&branch);
+            proptest::prop_assert!(!name.is_empty());
+            proptest::prop_assert!(!name.contains("--"));
+            proptest::prop_assert!(!name.starts_with(['-', '.']) && !name.ends_with(['-', '.']));
+            proptest::prop_assert!(
+                name.chars().all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            );
+            proptest::prop_assert_eq!(sanitize_branch_name(&name), name);
+        }
+    }
+
+    #[test]
+    fn slugify_prompt_keeps_meaningful_words() {
+        assert_eq!(
+            slugify_prompt("Fix the flaky login test, please!").as_deref(),
+            Some("fix-flaky-login-test")
+        );
+        assert_eq!(
+            slugify_prompt("Add support for OAuth2 device flow").as_deref(),
+            Some("add-support-oauth2-device")
+        );
+        assert_eq!(slugify_prompt("  ... "), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_command_kills_commands_past_the_timeout() {
+        let policy = RunPolicy {
+            timeout: Some(Duration::from_millis(200)),
+            retries: 0,
+        };
+        let started = Instant::now();
+        let err = run_command(Command::new("sleep").arg("5"), policy).unwrap_err();
+        assert!(err.to_string().contains("`sleep 5` timed out"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let output = run_command(Command::new("echo").arg("hi"), policy).unwrap();
+        assert_eq!(output.stdout, b"hi\n");
+    }
+
+    #[test]
+    fn transient_errors_are_recognized() {
+        assert!(is_transient(
+            "fatal: unable to access 'https://x/': Could not resolve host: x"
+        ));
+        assert!(is_transient("fatal: the remote end hung up unexpectedly"));
+        assert!(!is_transient("fatal: couldn't find remote ref nope"));
+    }
+
+    #[test]
+    fn generate_random_name_avoids_taken_names() {
+        let config = NameConfig {
+            pattern: Some("{color}-fox".to_string()),
+            words: [("color".to_string(), vec!["red".to_string()])].into(),
+            ..NameConfig::default()
+        };
+        assert_eq!(generate_random_name(&config, |_| false).unwrap(), "red-fox");
+        assert_eq!(
+            generate_random_name(&config, |name| name == "red-fox").unwrap(),
+            "red-fox-2"
+        );
+    }
+
     #[test]
     fn prepare_agent_command_resumes_latest_codex_session() {
         let _guard = ENV_MUTEX.get_or_init(|| Mutex::new(())).lock().unwrap();
@@ -232,7 +748,7 @@ mod tests {
                 ("XLAUDE_CODEX_SESSIONS_DIR", Some(sessions_dir_str.as_str())),
             ],
             || {
-                let (program, args) = prepare_agent_command(&worktree_path).unwrap();
+                let (program, args) = prepare_agent_command(&worktree_path, None).unwrap();
                 assert_eq!(program, "codex");
                 assert_eq!(args, vec!["resume".to_string(), "session-123".to_string()]);
             },
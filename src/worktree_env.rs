@@ -0,0 +1,125 @@
+//! Per-worktree environment for the agent, shell and editor started in a
+//! worktree: the `env` map of its state entry, overridden by a `.xlaude.env`
+//! file in the worktree itself.
+//!
+//! The file uses the usual dotenv syntax: `KEY=value` lines, optionally
+//! prefixed with `export`, `#` comments, and single or double quoted values.
+
+use anyhow::{Result, bail};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use crate::state::XlaudeState;
+
+pub const ENV_FILE: &str = ".xlaude.env";
+
+/// Variables for processes started in the worktree at `path`, tracked or not.
+pub fn for_path(path: &Path) -> Result<BTreeMap<String, String>> {
+    let state = XlaudeState::load()?;
+    let configured = state
+        .find_by_path(path)
+        .map(|(_, info)| info.env.clone())
+        .unwrap_or_default();
+    merged(path, configured)
+}
+
+/// `configured` variables overridden by the worktree's `.xlaude.env`.
+pub fn merged(
+    path: &Path,
+    mut configured: BTreeMap<String, String>,
+) -> Result<BTreeMap<String, String>> {
+    let file = path.join(ENV_FILE);
+    match fs::read_to_string(&file) {
+        Ok(content) => configured.extend(
+            parse(&content).map_err(|err| anyhow::anyhow!("Invalid {}: {err}", file.display()))?,
+        ),
+        Err(err) if err.kind() == ErrorKind::NotFound => {}
+        Err(err) => bail!("Failed to read {}: {err}", file.display()),
+    }
+    Ok(configured)
+}
+
+fn parse(content: &str) -> Result<Vec<(String, String)>> {
+    let mut vars = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            bail!("line {}: expected KEY=value", index + 1);
+        };
+        let key = key.trim();
+        if key.is_empty()
+            || key.starts_with(|c: char| c.is_ascii_digit())
+            || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            bail!("line {}: invalid variable name '{key}'", index + 1);
+        }
+        vars.push((key.to_string(), unquote(value.trim())));
+    }
+    Ok(vars)
+}
+
+fn unquote(value: &str) -> String {
+    if let Some(inner) = value
+        .strip_prefix('\'')
+        .and_then(|rest| rest.strip_suffix('\''))
+    {
+        return inner.to_string();
+    }
+    if let Some(inner) = value
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+    {
+        let mut out = String::with_capacity(inner.len());
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                out.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('n') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => out.push('\\'),
+            }
+        }
+        return out;
+    }
+    // Unquoted values end at a comment
+    value
+        .split_once(" #")
+        .map_or(value, |(value, _)| value)
+        .trim_end()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_env_file() {
+        let vars = parse(
+            "# secrets\nAPI_KEY=abc123\nexport FLAG = on # enabled\n\
+             GREETING=\"hello \\\"you\\\"\\nbye\"\nRAW='a # b'\n\n",
+        )
+        .unwrap();
+        assert_eq!(
+            vars,
+            [
+                ("API_KEY".to_string(), "abc123".to_string()),
+                ("FLAG".to_string(), "on".to_string()),
+                ("GREETING".to_string(), "hello \"you\"\nbye".to_string()),
+                ("RAW".to_string(), "a # b".to_string()),
+            ]
+        );
+
+        assert!(parse("NOT A LINE").is_err());
+        assert!(parse("1KEY=x").is_err());
+    }
+}
@@ -179,6 +179,16 @@ impl TestContext {
     }
 }
 
+/// Start time of a process as xlaude records it for agent sessions.
+#[cfg(unix)]
+fn process_started(pid: u32) -> String {
+    let output = std::process::Command::new("ps")
+        .args(["-o", "lstart=", "-p", &pid.to_string()])
+        .output()
+        .unwrap();
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
 // Create command tests
 #[test]
 fn test_create_with_name() {
@@ -202,6 +212,9 @@ fn test_create_with_name() {
     if let Some(worktrees) = state["worktrees"].as_object_mut() {
         for (_, worktree) in worktrees {
             worktree["created_at"] = json!("[TIMESTAMP]");
+            if worktree.get("base_commit").is_some() {
+                worktree["base_commit"] = json!("[COMMIT]");
+            }
             if let Some(path) = worktree["path"].as_str() {
                 worktree["path"] = json!(ctx.redact_paths(path));
             }
@@ -274,6 +287,9 @@ fn test_checkout_branch_creates_worktree() {
     if let Some(worktrees) = state["worktrees"].as_object_mut() {
         for (_, worktree) in worktrees.iter_mut() {
             worktree["created_at"] = json!("[TIMESTAMP]");
+            if worktree.get("base_commit").is_some() {
+                worktree["base_commit"] = json!("[COMMIT]");
+            }
             if let Some(path) = worktree["path"].as_str() {
                 worktree["path"] = json!(ctx.redact_paths(path));
             }
@@ -363,6 +379,9 @@ fn test_checkout_pull_request_creates_worktree() {
     if let Some(worktrees) = state["worktrees"].as_object_mut() {
         for (_, worktree) in worktrees.iter_mut() {
             worktree["created_at"] = json!("[TIMESTAMP]");
+            if worktree.get("base_commit").is_some() {
+                worktree["base_commit"] = json!("[COMMIT]");
+            }
             if let Some(path) = worktree["path"].as_str() {
                 worktree["path"] = json!(ctx.redact_paths(path));
             }
@@ -436,18 +455,116 @@ fn test_delete_with_changes() {
     let worktree_path = ctx.temp_dir.path().join("test-repo-with-changes");
     fs::write(worktree_path.join("new-file.txt"), "content").unwrap();
 
-    // Try to delete, in non-interactive mode it will be cancelled automatically
-    let output = ctx.xlaude(&["delete", "with-changes"]).assert().success();
+    // Try to delete, in non-interactive mode it is refused with exit code 4
+    let output = ctx.xlaude(&["delete", "with-changes"]).assert().code(4);
 
-    // Check that output mentions uncommitted changes and cancellation
+    // Check that output mentions uncommitted changes
     let stdout = String::from_utf8_lossy(&output.get_output().stdout);
     assert!(stdout.contains("uncommitted changes"));
-    assert!(stdout.contains("Cancelled"));
 
     // Verify worktree was not deleted
     assert!(worktree_path.exists());
 }
 
+#[cfg(unix)]
+#[test]
+fn test_delete_with_running_agent_session() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "busy"]).assert().success();
+    let worktree_path = ctx.temp_dir.path().join("test-repo-busy");
+
+    let mut agent = std::process::Command::new("sleep")
+        .arg("30")
+        .current_dir(&worktree_path)
+        .spawn()
+        .unwrap();
+    let sessions = json!([{
+        "pid": agent.id(),
+        "process_started": process_started(agent.id()),
+        "path": worktree_path,
+        "program": "claude",
+        "started_at": "2025-01-01T00:00:00Z"
+    }]);
+    fs::write(
+        ctx.config_dir.join("sessions.json"),
+        serde_json::to_string(&sessions).unwrap(),
+    )
+    .unwrap();
+
+    // Refused without --kill-session in non-interactive mode
+    let output = ctx.xlaude(&["delete", "busy"]).assert().failure();
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+    assert!(stderr.contains("--kill-session"));
+    assert!(worktree_path.exists());
+
+    let output = ctx
+        .xlaude(&["delete", "busy", "--kill-session"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains(&format!("Stopped agent (pid {})", agent.id())));
+    assert!(!worktree_path.exists());
+    assert!(!agent.wait().unwrap().success());
+}
+
+#[test]
+fn test_lock_blocks_delete_and_rename() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "guarded"]).assert().success();
+    ctx.xlaude(&["lock", "guarded", "--reason", "release in progress"])
+        .assert()
+        .success();
+    assert_eq!(
+        ctx.read_state()["worktrees"]["test-repo/guarded"]["lock"]["reason"],
+        "release in progress"
+    );
+
+    let output = ctx.xlaude(&["delete", "guarded"]).assert().failure();
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+    assert!(stderr.contains("is locked (release in progress)"));
+
+    let output = ctx
+        .xlaude(&["rename", "guarded", "renamed"])
+        .assert()
+        .failure();
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+    assert!(stderr.contains("--force"));
+
+    ctx.xlaude(&["unlock", "guarded"]).assert().success();
+    assert!(ctx.read_state()["worktrees"]["test-repo/guarded"]["lock"].is_null());
+
+    // The automatic lock taken by `open` is released when the agent exits
+    ctx.xlaude(&["open", "guarded"]).assert().success();
+    assert!(ctx.read_state()["worktrees"]["test-repo/guarded"]["lock"].is_null());
+
+    ctx.xlaude(&["delete", "guarded"]).assert().success();
+}
+
+#[test]
+fn test_exit_codes() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "coded"]).assert().success();
+
+    ctx.xlaude(&["open", "missing"]).assert().code(3);
+    ctx.xlaude(&["delete", "missing"]).assert().code(3);
+
+    let mut state = ctx.read_state();
+    state["agent"] = json!("false");
+    ctx.write_state(&state);
+    ctx.xlaude(&["open", "coded"]).assert().code(7);
+
+    // Declining the confirmation interactively is a cancellation
+    let export = ctx.temp_dir.path().join("export.json");
+    fs::write(&export, r#"{"worktrees": {}}"#).unwrap();
+    let output = ctx
+        .xlaude(&["state", "import", export.to_str().unwrap()])
+        .env_remove("XLAUDE_NON_INTERACTIVE")
+        .write_stdin("n\n")
+        .assert()
+        .code(5);
+    assert!(output.get_output().stderr.is_empty());
+}
+
 #[test]
 fn test_delete_current_worktree() {
     let ctx = TestContext::new("test-repo");
@@ -507,6 +624,9 @@ fn test_add_existing_worktree() {
     if let Some(worktrees) = state["worktrees"].as_object_mut() {
         for (_, worktree) in worktrees {
             worktree["created_at"] = json!("[TIMESTAMP]");
+            if worktree.get("base_commit").is_some() {
+                worktree["base_commit"] = json!("[COMMIT]");
+            }
             if let Some(path) = worktree["path"].as_str() {
                 worktree["path"] = json!(ctx.redact_paths(path));
             }
@@ -721,6 +841,26 @@ fn test_v02_to_v03_migration() {
     // Verify data integrity
     assert_eq!(worktrees["test-repo/feature-old"]["name"], "feature-old");
     assert_eq!(worktrees["another-repo/bugfix"]["name"], "bugfix");
+
+    // Verify schema version is recorded and the original file was backed up
+    assert_eq!(migrated_state["version"], 1);
+    let backups: Vec<_> = fs::read_dir(&ctx.config_dir)
+        .unwrap()
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.starts_with("state.json.v0-") && name.ends_with(".bak")
+        })
+        .collect();
+    assert_eq!(backups.len(), 1);
+    let backup: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(backups[0].path()).unwrap()).unwrap();
+    assert!(
+        backup["worktrees"]
+            .as_object()
+            .unwrap()
+            .contains_key("bugfix")
+    );
 }
 
 #[test]
@@ -906,6 +1046,16 @@ fn test_rename_command() {
         .assert()
         .failure()
         .stderr(predicates::str::contains("already exists"));
+
+    // New names are sanitized like created ones, and must keep something
+    ctx.xlaude(&["rename", "new-name", "feat/renamed"])
+        .assert()
+        .success();
+    assert!(ctx.read_state()["worktrees"]["test-repo/feat-renamed"].is_object());
+    ctx.xlaude(&["rename", "feat-renamed", "//"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("not a usable worktree name"));
 }
 
 #[test]
@@ -1078,6 +1228,24 @@ fn test_create_with_slash_in_branch_name() {
     assert!(String::from_utf8_lossy(&branch_output.stdout).contains("fix/bug"));
 }
 
+#[test]
+fn test_create_with_non_ascii_branch_name() {
+    let ctx = TestContext::new("test-repo");
+    let branch = "feat/ите/日本語-#1";
+
+    ctx.xlaude(&["create", branch]).assert().success();
+
+    assert!(ctx.worktree_exists("feat-ите-日本語-1"));
+    let state = ctx.read_state();
+    let info = &state["worktrees"]["test-repo/feat-ите-日本語-1"];
+    assert_eq!(info["branch"], branch);
+
+    // The original branch name resolves to the same worktree
+    let output = ctx.xlaude(&["dir", branch]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.trim().ends_with("test-repo-feat-ите-日本語-1"));
+}
+
 #[test]
 fn test_delete_with_slash_in_branch_name() {
     let ctx = TestContext::new("test-repo");
@@ -1112,3 +1280,2703 @@ fn test_delete_with_slash_in_branch_name() {
     let key = "test-repo/feature-awesome".to_string();
     assert!(!state["worktrees"].as_object().unwrap().contains_key(&key));
 }
+
+// Prompt command tests
+#[test]
+fn test_prompt_inside_worktree_subdirectory() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.xlaude(&["create", "prompt-me"]).assert().success();
+
+    let worktree_dir = ctx.repo_dir.parent().unwrap().join("test-repo-prompt-me");
+    let nested = worktree_dir.join("nested");
+    fs::create_dir_all(&nested).unwrap();
+    fs::write(worktree_dir.join("README.md"), "changed").unwrap();
+    fs::write(nested.join("notes.txt"), "untracked").unwrap();
+
+    // Only the completion cache is read, so nothing is known before it is built
+    let output = ctx.xlaude_in_dir(&nested, &["prompt"]).assert().success();
+    assert!(output.get_output().stdout.is_empty());
+    ctx.xlaude(&["complete-worktrees"]).assert().success();
+
+    let output = ctx.xlaude_in_dir(&nested, &["prompt"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert_eq!(stdout.trim(), "prompt-me *?");
+
+    // Unmanaged directories print nothing
+    let output = ctx.xlaude(&["prompt"]).assert().success();
+    assert!(output.get_output().stdout.is_empty());
+
+    // A prompt hook never touches state, even one an upgrade would migrate
+    let state_path = ctx.config_dir.join("state.json");
+    let mut state = ctx.read_state();
+    state["version"] = json!(0);
+    ctx.write_state(&state);
+    let before = fs::read(&state_path).unwrap();
+    ctx.xlaude_in_dir(&nested, &["prompt"]).assert().success();
+    assert_eq!(fs::read(&state_path).unwrap(), before);
+    let backups = fs::read_dir(&ctx.config_dir)
+        .unwrap()
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().ends_with(".bak"))
+        .count();
+    assert_eq!(backups, 0);
+}
+
+// State export/import tests
+#[test]
+fn test_state_export_and_merge_import() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.xlaude(&["create", "exported"]).assert().success();
+    ctx.xlaude(&["create", "trashed"]).assert().success();
+    ctx.xlaude(&["delete", "trashed", "--force"])
+        .assert()
+        .success();
+    let mut state = ctx.read_state();
+    state["repos"] = json!({ "test-repo": { "path": ctx.repo_dir } });
+    ctx.write_state(&state);
+    assert!(ctx.read_state()["trashed_worktrees"][0].is_object());
+
+    let export_path = ctx.temp_dir.path().join("export.toml");
+    ctx.xlaude(&[
+        "state",
+        "export",
+        "--format",
+        "toml",
+        "--output",
+        export_path.to_str().unwrap(),
+    ])
+    .assert()
+    .success();
+
+    let exported = fs::read_to_string(&export_path).unwrap();
+    assert!(exported.contains("path = \"~/test-repo-exported\""));
+    assert!(exported.contains("path = \"~/test-repo\""));
+    // Machine-local: the trash refers to tags and patch files on this machine
+    assert!(!exported.contains("trashed"));
+    let home = ctx.temp_dir.path().to_str().unwrap();
+    assert!(!exported.contains(home), "{exported}");
+
+    // Replace local state with a different entry, then merge the export back in
+    let mut state = ctx.read_state();
+    let mut entry = state["worktrees"]["test-repo/exported"].clone();
+    entry["name"] = json!("local");
+    entry["path"] = json!(ctx.temp_dir.path().join("test-repo-local"));
+    state["worktrees"] = json!({ "test-repo/local": entry });
+    state.as_object_mut().unwrap().remove("repos");
+    ctx.write_state(&state);
+
+    let output = ctx
+        .xlaude(&["state", "import", "--merge", export_path.to_str().unwrap()])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("1 added, 0 unchanged, 0 conflicts"));
+
+    let state = ctx.read_state();
+    let worktrees = state["worktrees"].as_object().unwrap();
+    assert!(worktrees.contains_key("test-repo/local"));
+    let imported_path = worktrees["test-repo/exported"]["path"].as_str().unwrap();
+    assert_eq!(
+        Path::new(imported_path),
+        ctx.temp_dir.path().join("test-repo-exported")
+    );
+    let repo_path = state["repos"]["test-repo"]["path"].as_str().unwrap();
+    assert_eq!(Path::new(repo_path), ctx.repo_dir);
+}
+
+#[test]
+fn test_explicit_state_file() {
+    let ctx = TestContext::new("test-repo");
+    let state_file = ctx.temp_dir.path().join("custom").join("xlaude.json");
+
+    ctx.xlaude(&[
+        "--state",
+        state_file.to_str().unwrap(),
+        "create",
+        "isolated",
+    ])
+    .assert()
+    .success();
+
+    let custom: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&state_file).unwrap()).unwrap();
+    assert!(custom["worktrees"]["test-repo/isolated"].is_object());
+    assert!(
+        ctx.read_state()["worktrees"]
+            .as_object()
+            .unwrap()
+            .is_empty()
+    );
+
+    // The environment variable selects the same file
+    let output = ctx
+        .xlaude(&["list", "--json"])
+        .env("XLAUDE_STATE_FILE", &state_file)
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("\"name\": \"isolated\""));
+}
+
+#[test]
+fn test_history_records_mutations() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.xlaude(&["create", "audited"]).assert().success();
+    ctx.xlaude(&["rename", "audited", "renamed"])
+        .assert()
+        .success();
+
+    let output = ctx.xlaude(&["history", "--json"]).assert().success();
+    let entries: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let entries = entries.as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["action"], "create");
+    assert_eq!(entries[0]["target"], "test-repo/audited");
+    assert_eq!(entries[1]["action"], "rename");
+    assert_eq!(entries[1]["detail"], "from test-repo/audited");
+    assert_eq!(entries[1]["source"], "cli");
+
+    let output = ctx
+        .xlaude(&["history", "renamed", "--limit", "1"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("rename"));
+    assert!(stdout.contains("test-repo/renamed"));
+}
+
+#[test]
+fn test_stats_aggregates_sessions_and_lifetimes() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.xlaude(&["create", "short-lived"]).assert().success();
+    ctx.xlaude(&["delete", "short-lived"]).assert().success();
+    ctx.xlaude(&["create", "busy"]).assert().success();
+
+    // Fake a Claude session log with two user messages for the busy worktree
+    let worktree = ctx
+        .temp_dir
+        .path()
+        .join("test-repo-busy")
+        .canonicalize()
+        .unwrap();
+    let encoded = worktree.to_string_lossy().replace('/', "-");
+    let session_dir = ctx.temp_dir.path().join(".claude/projects").join(encoded);
+    fs::create_dir_all(&session_dir).unwrap();
+    fs::write(
+        session_dir.join("session.jsonl"),
+        concat!(
+            r#"{"type":"user","timestamp":"2025-01-01T10:00:00Z","message":{"content":"first"}}"#,
+            "\n",
+            r#"{"type":"user","timestamp":"2025-01-01T10:05:00Z","message":{"content":"second"}}"#,
+            "\n"
+        ),
+    )
+    .unwrap();
+
+    let output = ctx.xlaude(&["stats", "--json"]).assert().success();
+    let stats: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+
+    assert_eq!(stats["weeks"][0]["created"], 2);
+    assert_eq!(stats["weeks"][0]["deleted"], 1);
+    assert_eq!(stats["completed_lifetimes"], 1);
+    assert_eq!(stats["repos"][0]["repo_name"], "test-repo");
+    assert_eq!(stats["repos"][0]["sessions"], 1);
+    assert_eq!(stats["repos"][0]["messages"], 2);
+    assert_eq!(stats["top_branches"][0]["branch"], "busy");
+}
+
+#[test]
+fn test_plain_and_quiet_output() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "plain"]).assert().success();
+
+    let output = ctx.xlaude(&["--no-emoji", "list"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.starts_with("Active worktrees:"), "{stdout}");
+    assert!(stdout.contains("  test-repo\n"));
+    assert!(!stdout.contains('📦'));
+
+    ctx.xlaude(&["lock", "plain"])
+        .env("XLAUDE_PLAIN", "1")
+        .assert()
+        .success()
+        .stdout("Locked worktree 'plain'\n");
+
+    let output = ctx.xlaude(&["list", "--quiet"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(!stdout.contains("Active worktrees"));
+    assert!(stdout.contains("plain"));
+}
+
+#[test]
+fn test_config_get_set_unset_list() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.xlaude(&["config", "set", "editor", "code --wait"])
+        .assert()
+        .success();
+    ctx.xlaude(&["config", "set", "default_ttl_days", "7"])
+        .assert()
+        .success();
+    ctx.xlaude(&["config", "set", "agents.fast", "claude --model haiku"])
+        .assert()
+        .success();
+    ctx.xlaude(&["config", "set", "repos.test-repo.base_branch", "develop"])
+        .assert()
+        .success();
+
+    let state = ctx.read_state();
+    assert_eq!(state["editor"], "code --wait");
+    assert_eq!(state["default_ttl_days"], 7);
+    assert_eq!(state["agents"]["fast"], "claude --model haiku");
+    assert_eq!(state["repos"]["test-repo"]["base_branch"], "develop");
+
+    ctx.xlaude(&["config", "get", "editor"])
+        .assert()
+        .success()
+        .stdout("code --wait\n");
+    ctx.xlaude(&["config", "list"]).assert().success().stdout(
+        "editor=code --wait\nagent=true\ndefault_ttl_days=7\nagents.fast=claude --model haiku\nrepos.test-repo.base_branch=develop\n",
+    );
+
+    // Invalid keys and values leave the state alone
+    ctx.xlaude(&["config", "set", "colour", "blue"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Unknown config key"));
+    ctx.xlaude(&["config", "set", "stale_after_days", "soon"])
+        .assert()
+        .failure();
+    ctx.xlaude(&["config", "set", "dashboard_peers.build", "build:5710"])
+        .assert()
+        .failure();
+    assert!(ctx.read_state().get("stale_after_days").is_none());
+
+    ctx.xlaude(&["config", "unset", "repos.test-repo.base_branch"])
+        .assert()
+        .success();
+    assert!(ctx.read_state().get("repos").is_none());
+    ctx.xlaude(&["config", "get", "shell"]).assert().code(3);
+}
+
+#[test]
+fn test_digest_summarizes_recent_work() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "gone"]).assert().success();
+    ctx.xlaude(&["delete", "gone"]).assert().success();
+    ctx.xlaude(&["create", "busy"]).assert().success();
+
+    let worktree = ctx
+        .temp_dir
+        .path()
+        .join("test-repo-busy")
+        .canonicalize()
+        .unwrap();
+    fs::write(worktree.join("work.txt"), "work").unwrap();
+    for args in [&["add", "work.txt"][..], &["commit", "-m", "Do work"]] {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(&worktree)
+            .output()
+            .unwrap();
+    }
+
+    let encoded = worktree.to_string_lossy().replace('/', "-");
+    let session_dir = ctx.temp_dir.path().join(".claude/projects").join(encoded);
+    fs::create_dir_all(&session_dir).unwrap();
+    let now = chrono::Utc::now().to_rfc3339();
+    fs::write(
+        session_dir.join("session.jsonl"),
+        format!(
+            "{}\n{}\n",
+            json!({"type": "user", "timestamp": now, "message": {"content": "go"}}),
+            json!({"type": "assistant", "timestamp": now, "costUSD": 1.25})
+        ),
+    )
+    .unwrap();
+
+    let output = ctx.xlaude(&["digest", "--since", "2d"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("## test-repo"), "{stdout}");
+    assert!(stdout.contains("- Worktrees: 2 created, 0 merged, 1 deleted"));
+    assert!(stdout.contains("- Commits: 1"));
+    assert!(stdout.contains("- Sessions: 1 ($1.25)"));
+    assert!(stdout.contains("`busy` (busy): 1 commit, 1 session, $1.25"));
+
+    ctx.xlaude(&["digest", "--since", "soon"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_open_with_named_agent_and_prompt() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "agent-pick"]).assert().success();
+
+    let mut state = ctx.read_state();
+    state["agents"] = json!({ "recorder": "sh -c 'printf %s \"$0\" > prompt.txt'" });
+    ctx.write_state(&state);
+
+    ctx.xlaude(&[
+        "open",
+        "agent-pick",
+        "--agent",
+        "recorder",
+        "--type-text",
+        "fix the tests",
+    ])
+    .assert()
+    .success();
+
+    let worktree = ctx.temp_dir.path().join("test-repo-agent-pick");
+    assert_eq!(
+        fs::read_to_string(worktree.join("prompt.txt")).unwrap(),
+        "fix the tests"
+    );
+
+    let output = ctx
+        .xlaude(&["open", "agent-pick", "--agent", "nope"])
+        .assert()
+        .failure();
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+    assert!(stderr.contains("Unknown agent 'nope'"));
+    assert!(stderr.contains("codex"));
+    assert!(stderr.contains("recorder"));
+}
+
+#[test]
+fn test_open_with_type_file() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "briefed"]).assert().success();
+
+    let mut state = ctx.read_state();
+    state["agent"] = json!("sh -c 'printf %s \"$0\" > prompt.txt'");
+    ctx.write_state(&state);
+    // Relative to the directory xlaude is invoked from, not the worktree
+    fs::write(
+        ctx.repo_dir.join("brief.md"),
+        "# Task\n\nMigrate the parser.\n",
+    )
+    .unwrap();
+
+    ctx.xlaude(&[
+        "open",
+        "briefed",
+        "--type-text",
+        "Read the brief:",
+        "--type-file",
+        "brief.md",
+    ])
+    .assert()
+    .success();
+    let worktree = ctx.temp_dir.path().join("test-repo-briefed");
+    assert_eq!(
+        fs::read_to_string(worktree.join("prompt.txt")).unwrap(),
+        "Read the brief:\n\n# Task\n\nMigrate the parser."
+    );
+}
+
+#[test]
+fn test_open_with_prompt_template() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "templated"]).assert().success();
+
+    let mut state = ctx.read_state();
+    state["agent"] = json!("sh -c 'printf %s \"$0\" > prompt.txt'");
+    state["prompt_templates"] = json!({ "review": "Review {branch} in {repo}/{name} {unknown}" });
+    ctx.write_state(&state);
+
+    ctx.xlaude(&["open", "templated", "--prompt-template", "review"])
+        .assert()
+        .success();
+    let worktree = ctx.temp_dir.path().join("test-repo-templated");
+    assert_eq!(
+        fs::read_to_string(worktree.join("prompt.txt")).unwrap(),
+        "Review templated in test-repo/templated {unknown}"
+    );
+
+    let output = ctx
+        .xlaude(&["open", "templated", "--prompt-template", "nope"])
+        .assert()
+        .failure();
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+    assert!(stderr.contains("Unknown prompt template 'nope'. Available templates: review"));
+}
+
+#[test]
+fn test_open_print_cmd_shows_invocation_without_running() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "printed"]).assert().success();
+
+    let mut state = ctx.read_state();
+    state["agents"] = json!({ "reviewer": "sh -c 'touch ran'" });
+    state["prompt_templates"] = json!({ "review": "Review {branch}" });
+    ctx.write_state(&state);
+
+    let output = ctx
+        .xlaude(&[
+            "open",
+            "printed",
+            "--agent",
+            "reviewer",
+            "--prompt-template",
+            "review",
+            "--print-cmd",
+        ])
+        .assert()
+        .success();
+    let worktree = ctx.temp_dir.path().join("test-repo-printed");
+    assert_eq!(
+        String::from_utf8_lossy(&output.get_output().stdout),
+        format!(
+            "cd {}\nsh -c 'touch ran' 'Review printed'\n",
+            worktree.display()
+        )
+    );
+    assert!(!worktree.join("ran").exists());
+    assert!(ctx.read_state()["worktrees"]["test-repo/printed"]["lock"].is_null());
+}
+
+#[test]
+fn test_list_vim_format() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "alpha"]).assert().success();
+    ctx.xlaude(&["create", "beta"]).assert().success();
+    let mut state = ctx.read_state();
+    state["worktrees"]["test-repo/beta"]["created_at"] = json!("2020-01-01T00:00:00Z");
+    state["worktrees"]["test-repo/beta"]["ttl_days"] = json!(1);
+    ctx.write_state(&state);
+
+    let root = ctx.temp_dir.path();
+    ctx.xlaude(&["list", "--format", "vim"])
+        .assert()
+        .success()
+        .stdout(format!(
+            "{}\ttest-repo/alpha\talpha\tok\n{}\ttest-repo/beta\tbeta\texpired\n",
+            root.join("test-repo-alpha").display(),
+            root.join("test-repo-beta").display()
+        ));
+
+    fs::remove_dir_all(root.join("test-repo-alpha")).unwrap();
+    ctx.xlaude(&["list", "--format", "vim"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("\talpha\tmissing\n"));
+}
+
+#[test]
+fn test_list_template_format() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "alpha"]).assert().success();
+    let worktree = ctx.temp_dir.path().join("test-repo-alpha");
+    fs::write(worktree.join("new.txt"), "new").unwrap();
+    for args in [&["add", "."][..], &["commit", "-m", "Add new file"]] {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(&worktree)
+            .output()
+            .unwrap();
+    }
+
+    ctx.xlaude(&[
+        "list",
+        "--format",
+        "{key} on {branch}: {status} +{ahead}/-{behind} pr={pr_state}",
+    ])
+    .assert()
+    .success()
+    .stdout("test-repo/alpha on alpha: ok +1/-0 pr=\n");
+
+    ctx.xlaude(&["list", "--format", "{name} {stauts}"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("unknown placeholder '{stauts}'"));
+    ctx.xlaude(&["list", "--format", "csv"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("expected 'vim' or a template"));
+}
+
+#[test]
+fn test_serve_stdio_handles_worktree_requests() {
+    let ctx = TestContext::new("test-repo");
+    let requests = [
+        json!({"jsonrpc": "2.0", "id": 1, "method": "create",
+               "params": {"repoPath": ctx.repo_dir, "name": "rpc"}}),
+        json!({"jsonrpc": "2.0", "id": 2, "method": "list"}),
+        json!({"jsonrpc": "2.0", "id": 3, "method": "dir", "params": {"name": "test-repo/rpc"}}),
+        json!({"jsonrpc": "2.0", "id": 4, "method": "open",
+               "params": {"name": "rpc", "prompt": "hello"}}),
+        json!({"jsonrpc": "2.0", "method": "list"}),
+        json!({"jsonrpc": "2.0", "id": 5, "method": "delete", "params": {"name": "rpc"}}),
+        json!({"jsonrpc": "2.0", "id": 6, "method": "status", "params": {"name": "rpc"}}),
+    ];
+    let input: String = requests.iter().map(|r| format!("{r}\n")).collect();
+
+    let output = ctx
+        .xlaude(&["serve", "--stdio"])
+        .write_stdin(input)
+        .assert()
+        .success();
+    let responses: Vec<serde_json::Value> = String::from_utf8_lossy(&output.get_output().stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    // The notification gets no response
+    assert_eq!(responses.len(), 6);
+
+    let worktree = ctx.temp_dir.path().join("test-repo-rpc");
+    assert_eq!(responses[0]["id"], 1);
+    assert_eq!(responses[0]["result"]["name"], "rpc");
+    assert_eq!(responses[0]["result"]["summary"]["ahead"], 0);
+    assert_eq!(responses[1]["result"]["worktrees"][0]["name"], "rpc");
+    assert_eq!(
+        responses[2]["result"]["path"],
+        worktree.to_string_lossy().as_ref()
+    );
+    assert_eq!(responses[3]["result"]["program"], "true");
+    assert_eq!(responses[3]["result"]["args"], json!(["hello"]));
+    assert_eq!(responses[4]["result"]["deleted"], "test-repo/rpc");
+    assert_eq!(responses[5]["id"], 6);
+    assert_eq!(responses[5]["error"]["code"], -32000);
+    assert_eq!(responses[5]["error"]["data"]["exitCode"], 3);
+    assert!(!worktree.exists());
+}
+
+#[test]
+fn test_gc_archives_sessions_of_removed_worktrees() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "alive"]).assert().success();
+    let alive = ctx.temp_dir.path().join("test-repo-alive");
+    let gone = ctx.temp_dir.path().join("test-repo-gone");
+
+    let claude_projects = ctx.temp_dir.path().join(".claude/projects");
+    for (dir, cwd) in [("-alive", &alive), ("-gone", &gone)] {
+        fs::create_dir_all(claude_projects.join(dir)).unwrap();
+        fs::write(
+            claude_projects.join(dir).join("session.jsonl"),
+            format!(
+                "{}\n",
+                json!({"type": "user", "cwd": cwd, "message": {"content": "hi"}})
+            ),
+        )
+        .unwrap();
+    }
+    let codex_day = ctx.temp_dir.path().join(".codex/sessions/2025/01/02");
+    fs::create_dir_all(&codex_day).unwrap();
+    fs::write(
+        codex_day.join("rollout.jsonl"),
+        format!(
+            "{}\n",
+            json!({"type": "session_meta", "payload": {"id": "abc", "cwd": gone}})
+        ),
+    )
+    .unwrap();
+
+    // Without an action, non-interactive runs only report
+    ctx.xlaude(&["gc"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("2 session locations"))
+        .stdout(predicates::str::contains("Nothing changed"));
+    assert!(claude_projects.join("-gone").exists());
+
+    ctx.xlaude(&["gc", "--archive"])
+        .env("XLAUDE_YES", "1")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Archived"));
+    assert!(!claude_projects.join("-gone").exists());
+    assert!(!codex_day.join("rollout.jsonl").exists());
+    assert!(claude_projects.join("-alive/session.jsonl").exists());
+    let archive = ctx.config_dir.join("archive");
+    assert!(archive.join("claude/-gone/session.jsonl").exists());
+    assert!(archive.join("codex/2025/01/02/rollout.jsonl").exists());
+
+    ctx.xlaude(&["gc"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("No session files"));
+}
+
+#[test]
+fn test_open_passes_worktree_env_to_agent() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "flagged"]).assert().success();
+    let worktree = ctx.temp_dir.path().join("test-repo-flagged");
+
+    let mut state = ctx.read_state();
+    state["agent"] = json!("sh -c 'printf %s \"$FEATURE/$TOKEN\" > env.txt'");
+    state["worktrees"]["test-repo/flagged"]["env"] =
+        json!({ "FEATURE": "from-state", "TOKEN": "state-token" });
+    ctx.write_state(&state);
+    // The file in the worktree wins over the state file
+    fs::write(
+        worktree.join(".xlaude.env"),
+        "# local secrets\nexport TOKEN=\"file token\"\n",
+    )
+    .unwrap();
+
+    ctx.xlaude(&["open", "flagged"]).assert().success();
+    assert_eq!(
+        fs::read_to_string(worktree.join("env.txt")).unwrap(),
+        "from-state/file token"
+    );
+
+    ctx.xlaude(&["open", "flagged", "--print-cmd"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "export FEATURE=from-state\nexport TOKEN='file token'\n",
+        ));
+
+    fs::write(worktree.join(".xlaude.env"), "not valid\n").unwrap();
+    ctx.xlaude(&["open", "flagged"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("line 1: expected KEY=value"));
+}
+
+#[test]
+fn test_open_here_resolves_by_path() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "alpha"]).assert().success();
+    let worktree = ctx.temp_dir.path().join("test-repo-alpha");
+    let nested = worktree.join("src/nested");
+    fs::create_dir_all(&nested).unwrap();
+
+    ctx.xlaude_in_dir(&nested, &["open", "--here", "--print-cmd"])
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with(format!(
+            "cd {}\n",
+            worktree.display()
+        )));
+
+    ctx.xlaude_in_dir(&ctx.repo_dir, &["open", "--here", "--print-cmd"])
+        .assert()
+        .code(3)
+        .stderr(predicates::str::contains(
+            "is not inside a worktree managed by xlaude",
+        ));
+}
+
+#[test]
+fn test_open_missing_worktree_recreates_or_forgets() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "alpha"]).assert().success();
+    let worktree = ctx.temp_dir.path().join("test-repo-alpha");
+    fs::remove_dir_all(&worktree).unwrap();
+
+    ctx.xlaude(&["open", "alpha"])
+        .assert()
+        .code(3)
+        .stderr(predicates::str::contains("no longer exists"));
+
+    ctx.xlaude(&["open", "alpha"])
+        .write_stdin("0\n")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Worktree recreated"));
+    assert!(worktree.join(".git").exists());
+
+    fs::remove_dir_all(&worktree).unwrap();
+    ctx.xlaude(&["open", "alpha"])
+        .write_stdin("1\n")
+        .assert()
+        .success();
+    assert!(
+        ctx.read_state()["worktrees"]
+            .get("test-repo/alpha")
+            .is_none()
+    );
+}
+
+#[test]
+fn test_recreate_readds_removed_worktree() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "alpha"]).assert().success();
+    ctx.xlaude(&["create", "beta"]).assert().success();
+    let root = ctx.temp_dir.path();
+
+    fs::remove_dir_all(root.join("test-repo-beta")).unwrap();
+    ctx.xlaude(&["recreate", "beta"]).assert().success();
+    assert!(root.join("test-repo-beta/.git").exists());
+    ctx.xlaude(&["recreate", "beta"]).assert().code(4);
+
+    // The recorded location is gone as well: back next to the main checkout
+    fs::remove_dir_all(root.join("test-repo-alpha")).unwrap();
+    let mut state = ctx.read_state();
+    state["worktrees"]["test-repo/alpha"]["path"] = json!(root.join("gone/test-repo-alpha"));
+    ctx.write_state(&state);
+    ctx.xlaude_in_dir(root, &["recreate", "alpha"])
+        .assert()
+        .success();
+    assert!(root.join("test-repo-alpha/.git").exists());
+    assert_eq!(
+        ctx.read_state()["worktrees"]["test-repo/alpha"]["path"],
+        json!(root.join("test-repo-alpha"))
+    );
+}
+
+#[test]
+fn test_current_resolves_subdirectories() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "alpha"]).assert().success();
+    let worktree = ctx.temp_dir.path().join("test-repo-alpha");
+    let nested = worktree.join("docs");
+    fs::create_dir_all(&nested).unwrap();
+
+    ctx.xlaude_in_dir(&nested, &["current"])
+        .assert()
+        .success()
+        .stdout(format!("test-repo/alpha\talpha\t{}\n", worktree.display()));
+    ctx.xlaude_in_dir(&nested, &["current", "--path"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", worktree.display()));
+    // Without a name, open also goes by path from a subdirectory
+    ctx.xlaude_in_dir(&nested, &["open", "--print-cmd"])
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with(format!(
+            "cd {}\n",
+            worktree.display()
+        )));
+
+    ctx.xlaude_in_dir(&ctx.repo_dir, &["current"])
+        .assert()
+        .code(3)
+        .stdout("")
+        .stderr(predicates::str::contains(
+            "Current directory is not a managed worktree",
+        ));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_state_moves_from_config_to_xdg_data_dir() {
+    let ctx = TestContext::new("test-repo");
+    let home = ctx.temp_dir.path();
+    let legacy = home.join("config/xlaude");
+    fs::create_dir_all(&legacy).unwrap();
+    fs::write(
+        legacy.join("state.json"),
+        r#"{"worktrees": {}, "agent": "vim"}"#,
+    )
+    .unwrap();
+    fs::write(legacy.join("history.jsonl"), "").unwrap();
+
+    let run = |args: &[&str]| {
+        let mut cmd = ctx.xlaude(args);
+        cmd.env_remove("XLAUDE_CONFIG_DIR")
+            .env("XDG_CONFIG_HOME", home.join("config"))
+            .env("XDG_DATA_HOME", home.join("data"))
+            .env("XDG_CACHE_HOME", home.join("cache"));
+        cmd
+    };
+
+    run(&["config", "get", "agent"])
+        .assert()
+        .success()
+        .stdout("vim\n")
+        .stderr(predicates::str::contains("Moved xlaude state"));
+    let data = home.join("data/xlaude");
+    assert!(data.join("state.json").exists());
+    assert!(data.join("history.jsonl").exists());
+    assert!(!legacy.join("state.json").exists());
+
+    run(&["complete-worktrees"]).assert().success().stderr("");
+    assert!(home.join("cache/xlaude/completions.json").exists());
+
+    // Each location can be overridden on its own
+    let custom = home.join("custom-data");
+    run(&["config", "set", "agent", "true"])
+        .env("XLAUDE_DATA_DIR", &custom)
+        .assert()
+        .success();
+    assert!(custom.join("state.json").exists());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_service_install_writes_systemd_unit() {
+    let ctx = TestContext::new("test-repo");
+    let unit = ctx
+        .temp_dir
+        .path()
+        .join(".config/systemd/user/xlaude-dashboard.service");
+
+    ctx.xlaude(&["service", "install", "--addr", "127.0.0.1:6000"])
+        .env_remove("XDG_CONFIG_HOME")
+        .assert()
+        .success();
+
+    let content = fs::read_to_string(&unit).unwrap();
+    assert!(content.contains("\"dashboard\" \"--no-browser\" \"--addr\" \"127.0.0.1:6000\""));
+    assert!(content.contains("Environment=\"XLAUDE_CONFIG_DIR="));
+    assert!(content.contains("WantedBy=default.target"));
+
+    ctx.xlaude(&["service", "uninstall"])
+        .env_remove("XDG_CONFIG_HOME")
+        .assert()
+        .success();
+    assert!(!unit.exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_dashboard_daemon_lifecycle() {
+    let ctx = TestContext::new("test-repo");
+    let instance_file = ctx.config_dir.join("dashboard.json");
+    let stdout = |args: &[&str]| {
+        let output = ctx.xlaude(args).assert().success();
+        String::from_utf8_lossy(&output.get_output().stdout).to_string()
+    };
+
+    let started = stdout(&[
+        "dashboard",
+        "--daemon",
+        "--no-browser",
+        "--addr",
+        "127.0.0.1:0",
+    ]);
+    assert!(started.contains("running in the background"));
+    assert!(instance_file.exists());
+    let status = stdout(&["dashboard", "status"]);
+    assert!(status.contains("Dashboard running at http://127.0.0.1:"));
+    assert!(status.contains(&format!("Version: {}", env!("CARGO_PKG_VERSION"))));
+    assert!(status.contains("Worktrees: 0"));
+    assert!(status.contains("Last refresh: not yet requested"));
+
+    // A second instance on the same state is refused
+    let output = ctx
+        .xlaude(&["dashboard", "--no-browser", "--addr", "127.0.0.1:0"])
+        .assert()
+        .failure();
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+    assert!(stderr.contains("already running"));
+
+    assert!(stdout(&["dashboard", "stop"]).contains("Stopped dashboard"));
+    assert!(!instance_file.exists());
+    assert!(stdout(&["dashboard", "status"]).contains("No dashboard is running"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_dashboard_file_browser_hides_git_and_env_file() {
+    use std::io::{Read, Write};
+
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "browse"]).assert().success();
+    let worktree = ctx.temp_dir.path().join("test-repo-browse");
+    fs::write(worktree.join(".xlaude.env"), "TOKEN=secret\n").unwrap();
+
+    ctx.xlaude(&[
+        "dashboard",
+        "--daemon",
+        "--no-browser",
+        "--addr",
+        "127.0.0.1:0",
+    ])
+    .assert()
+    .success();
+    let output = ctx.xlaude(&["dashboard", "status"]).assert().success();
+    let status = String::from_utf8_lossy(&output.get_output().stdout).to_string();
+    let addr = status
+        .split("http://")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .unwrap()
+        .trim_end_matches('/')
+        .to_string();
+
+    let get = |path: &str| {
+        let mut stream = std::net::TcpStream::connect(&addr).unwrap();
+        write!(
+            stream,
+            "GET /api/worktrees/test-repo/browse/files?path={path} HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\n\r\n"
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    };
+
+    let listing = get("");
+    assert!(listing.starts_with("HTTP/1.1 200"), "{listing}");
+    assert!(listing.contains("README.md"));
+    assert!(!listing.contains(".git"));
+    assert!(!listing.contains(".xlaude.env"));
+    for hidden in [".git", ".xlaude.env"] {
+        let response = get(hidden);
+        assert!(response.starts_with("HTTP/1.1 403"), "{response}");
+        assert!(!response.contains("secret"));
+    }
+
+    ctx.xlaude(&["dashboard", "stop"]).assert().success();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_open_new_window_and_attach() {
+    if std::process::Command::new("tmux")
+        .arg("-V")
+        .output()
+        .is_err()
+    {
+        return;
+    }
+    let ctx = TestContext::new("test-repo");
+    let tmux_dir = ctx.temp_dir.path().join("tmux");
+    fs::create_dir_all(&tmux_dir).unwrap();
+    let tmux = |args: &[&str]| {
+        std::process::Command::new("tmux")
+            .args(args)
+            .env("TMUX_TMPDIR", &tmux_dir)
+            .env_remove("TMUX")
+            .output()
+            .unwrap()
+    };
+
+    ctx.xlaude(&["create", "detached"]).assert().success();
+    let mut state = ctx.read_state();
+    state["agent"] = json!("sleep 30");
+    ctx.write_state(&state);
+
+    let output = ctx
+        .xlaude(&["open", "detached", "--new-window"])
+        .env("TMUX_TMPDIR", &tmux_dir)
+        .env_remove("TMUX")
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("tmux attach -t xlaude-test-repo-detached"));
+
+    let sessions = tmux(&["list-sessions", "-F", "#{session_name}"]);
+    assert_eq!(
+        String::from_utf8_lossy(&sessions.stdout).trim(),
+        "xlaude-test-repo-detached"
+    );
+    assert!(
+        ctx.read_state()["worktrees"]["test-repo/detached"]["lock"]["pid"]
+            .as_u64()
+            .is_some()
+    );
+
+    ctx.xlaude(&["create", "idle"]).assert().success();
+    let output = ctx
+        .xlaude(&["attach", "idle"])
+        .env("TMUX_TMPDIR", &tmux_dir)
+        .env_remove("TMUX")
+        .assert()
+        .failure();
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+    assert!(stderr.contains("No tmux session for 'idle'"));
+
+    tmux(&["kill-server"]);
+}
+
+#[test]
+fn test_batch_creates_and_opens_worktrees() {
+    let ctx = TestContext::new("test-repo");
+    let mut state = ctx.read_state();
+    state["agent"] = json!("sh -c 'printf %s \"$0\" > prompt.txt'");
+    ctx.write_state(&state);
+
+    let tasks = [
+        r#"{"name": "task-one", "prompt": "fix the build"}"#,
+        r#"{"repo": "test-repo", "name": "task-one", "open": false}"#,
+        r#"{"repo": "missing", "name": "task-two"}"#,
+    ]
+    .join("\n");
+    let output = ctx.xlaude(&["batch"]).write_stdin(tasks).assert().failure();
+
+    let results: Vec<serde_json::Value> = String::from_utf8_lossy(&output.get_output().stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0]["status"], "created");
+    assert_eq!(results[0]["opened"], true);
+    assert_eq!(results[1]["status"], "exists");
+    assert_eq!(results[1]["opened"], false);
+    assert_eq!(results[2]["status"], "failed");
+    assert_eq!(results[2]["error"], "Unknown repository 'missing'");
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+    assert!(stderr.contains("1 of 3 batch tasks failed"));
+
+    // The agent is launched in the background
+    let prompt = ctx.temp_dir.path().join("test-repo-task-one/prompt.txt");
+    let mut content = String::new();
+    for _ in 0..50 {
+        content = fs::read_to_string(&prompt).unwrap_or_default();
+        if !content.is_empty() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    assert_eq!(content, "fix the build");
+}
+
+#[test]
+fn test_copy_worktree_with_uncommitted_changes() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "original"]).assert().success();
+    let source = ctx.temp_dir.path().join("test-repo-original");
+
+    fs::write(source.join("staged.txt"), "staged").unwrap();
+    std::process::Command::new("git")
+        .args(["add", "staged.txt"])
+        .current_dir(&source)
+        .output()
+        .unwrap();
+    fs::write(source.join("README.md"), "# Changed").unwrap();
+    fs::create_dir_all(source.join("notes")).unwrap();
+    fs::write(source.join("notes/todo.md"), "- try another approach").unwrap();
+
+    ctx.xlaude(&["copy", "original", "attempt-two"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "1 staged, 1 modified, 1 untracked",
+        ));
+
+    let copy = ctx.temp_dir.path().join("test-repo-attempt-two");
+    assert_eq!(
+        fs::read_to_string(copy.join("README.md")).unwrap(),
+        "# Changed"
+    );
+    assert_eq!(
+        fs::read_to_string(copy.join("staged.txt")).unwrap(),
+        "staged"
+    );
+    assert_eq!(
+        fs::read_to_string(copy.join("notes/todo.md")).unwrap(),
+        "- try another approach"
+    );
+    let staged = std::process::Command::new("git")
+        .args(["diff", "--cached", "--name-only"])
+        .current_dir(&copy)
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&staged.stdout).trim(), "staged.txt");
+
+    let state = ctx.read_state();
+    let copied = &state["worktrees"]["test-repo/attempt-two"];
+    assert_eq!(copied["branch"], "attempt-two");
+    assert_eq!(copied["base_branch"], "original");
+    let head = std::process::Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(&source)
+        .output()
+        .unwrap();
+    assert_eq!(
+        copied["base_commit"],
+        String::from_utf8_lossy(&head.stdout).trim()
+    );
+    // The source is left untouched
+    assert_eq!(
+        fs::read_to_string(source.join("README.md")).unwrap(),
+        "# Changed"
+    );
+}
+
+#[test]
+fn test_apply_transplants_changes_between_worktrees() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "source"]).assert().success();
+    ctx.xlaude(&["create", "target"]).assert().success();
+    let source = ctx.temp_dir.path().join("test-repo-source");
+    let target = ctx.temp_dir.path().join("test-repo-target");
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+
+    fs::write(source.join("feature.txt"), "feature").unwrap();
+    git(&source, &["add", "feature.txt"]);
+    git(&source, &["commit", "-m", "Add feature"]);
+    fs::write(source.join("README.md"), "# Work in progress").unwrap();
+    fs::write(source.join("scratch.txt"), "notes").unwrap();
+
+    // A dirty destination is refused
+    fs::write(target.join("dirty.txt"), "dirty").unwrap();
+    ctx.xlaude(&["apply", "source", "target"])
+        .assert()
+        .failure()
+        .code(4);
+    fs::remove_file(target.join("dirty.txt")).unwrap();
+
+    ctx.xlaude(&["apply", "source", "target"])
+        .assert()
+        .success();
+
+    assert_eq!(git(&target, &["log", "-1", "--format=%s"]), "Add feature");
+    assert_eq!(
+        fs::read_to_string(target.join("feature.txt")).unwrap(),
+        "feature"
+    );
+    assert_eq!(
+        fs::read_to_string(target.join("README.md")).unwrap(),
+        "# Work in progress"
+    );
+    assert_eq!(
+        fs::read_to_string(target.join("scratch.txt")).unwrap(),
+        "notes"
+    );
+    // Uncommitted changes stay unstaged
+    assert_eq!(git(&target, &["diff", "--cached", "--name-only"]), "");
+    assert_eq!(git(&target, &["diff", "--name-only"]), "README.md");
+}
+
+#[test]
+fn test_conflicts_reports_overlapping_files() {
+    let ctx = TestContext::new("test-repo");
+    for name in ["alpha", "beta", "gamma"] {
+        ctx.xlaude(&["create", name]).assert().success();
+    }
+    let root = ctx.temp_dir.path();
+    fs::write(root.join("test-repo-alpha/README.md"), "# Alpha").unwrap();
+    fs::write(root.join("test-repo-alpha/shared.rs"), "// alpha").unwrap();
+    fs::write(root.join("test-repo-beta/shared.rs"), "// beta").unwrap();
+    fs::write(root.join("test-repo-gamma/other.rs"), "// gamma").unwrap();
+
+    let output = ctx.xlaude(&["conflicts", "--json"]).assert().success();
+    let overlaps: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(
+        overlaps,
+        serde_json::json!([{
+            "repo_name": "test-repo",
+            "worktrees": ["alpha", "beta"],
+            "files": ["shared.rs"],
+        }])
+    );
+
+    let output = ctx.xlaude(&["conflicts"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("alpha ↔ beta (1 file)"));
+    assert!(!stdout.contains("gamma"));
+}
+
+#[test]
+fn test_stacked_worktrees_and_sync() {
+    let ctx = TestContext::new("test-repo");
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+
+    ctx.xlaude(&["create", "base"]).assert().success();
+    let base = ctx.temp_dir.path().join("test-repo-base");
+    fs::write(base.join("api.rs"), "// v1").unwrap();
+    git(&base, &["add", "api.rs"]);
+    git(&base, &["commit", "-m", "Add api"]);
+
+    ctx.xlaude(&["create", "child", "--from-worktree", "base"])
+        .assert()
+        .success();
+    let child = ctx.temp_dir.path().join("test-repo-child");
+    assert_eq!(fs::read_to_string(child.join("api.rs")).unwrap(), "// v1");
+    assert_eq!(
+        ctx.read_state()["worktrees"]["test-repo/child"]["parent"],
+        "base"
+    );
+
+    fs::write(child.join("client.rs"), "// client").unwrap();
+    git(&child, &["add", "client.rs"]);
+    git(&child, &["commit", "-m", "Add client"]);
+
+    let output = ctx.xlaude(&["list", "--tree"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("  base base\n  └─ child child"), "{stdout}");
+
+    // The parent moves on; sync replays the child on top of it
+    fs::write(base.join("api.rs"), "// v2").unwrap();
+    git(&base, &["commit", "-am", "Update api"]);
+    ctx.xlaude(&["sync", "base"]).assert().success();
+    assert_eq!(fs::read_to_string(child.join("api.rs")).unwrap(), "// v2");
+    assert_eq!(
+        git(&child, &["log", "--format=%s", "-3"]),
+        "Add client\nUpdate api\nAdd api"
+    );
+
+    // Deleting the parent moves the child to the top of the stack
+    ctx.xlaude(&["delete", "base"])
+        .write_stdin("y\n")
+        .assert()
+        .success();
+    assert!(ctx.read_state()["worktrees"]["test-repo/child"]["parent"].is_null());
+}
+
+#[test]
+fn test_push_sets_upstream() {
+    let ctx = TestContext::new("test-repo");
+    let remote = ctx.temp_dir.path().join("remote/test-repo.git");
+    std::process::Command::new("git")
+        .args(["init", "--bare"])
+        .arg(&remote)
+        .output()
+        .unwrap();
+    std::process::Command::new("git")
+        .args(["remote", "add", "origin"])
+        .arg(&remote)
+        .current_dir(&ctx.repo_dir)
+        .output()
+        .unwrap();
+
+    ctx.xlaude(&["create", "feature"]).assert().success();
+    ctx.xlaude(&["push", "feature"]).assert().success();
+
+    let worktree = ctx.temp_dir.path().join("test-repo-feature");
+    let upstream = std::process::Command::new("git")
+        .args(["rev-parse", "--abbrev-ref", "feature@{upstream}"])
+        .current_dir(&worktree)
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&upstream.stdout).trim(),
+        "origin/feature"
+    );
+
+    ctx.xlaude(&["push", "missing"]).assert().failure().code(3);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_configured_git_binary_and_environment() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let ctx = TestContext::new("test-repo");
+    let log = ctx.temp_dir.path().join("git.log");
+    let wrapper = ctx.temp_dir.path().join("my-git");
+    fs::write(
+        &wrapper,
+        format!(
+            "#!/bin/sh\necho \"$XLAUDE_TOOL_MARK $1\" >> '{}'\nexec git \"$@\"\n",
+            log.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&wrapper, fs::Permissions::from_mode(0o755)).unwrap();
+
+    ctx.xlaude(&["config", "set", "tools.git", wrapper.to_str().unwrap()])
+        .assert()
+        .success();
+    ctx.xlaude(&["config", "set", "tools.env.XLAUDE_TOOL_MARK", "marked"])
+        .assert()
+        .success();
+    ctx.xlaude(&["config", "get", "tools.env.XLAUDE_TOOL_MARK"])
+        .assert()
+        .success()
+        .stdout("marked\n");
+
+    ctx.xlaude(&["create", "alpha"]).assert().success();
+    let calls = fs::read_to_string(&log).unwrap();
+    assert!(
+        calls.lines().any(|line| line == "marked worktree"),
+        "{calls}"
+    );
+    assert!(ctx.temp_dir.path().join("test-repo-alpha").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_merged_pull_requests_are_fetched_once_per_repository() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let ctx = TestContext::new("test-repo");
+    for name in ["alpha", "beta"] {
+        ctx.xlaude(&["create", name]).assert().success();
+        let worktree = ctx.temp_dir.path().join(format!("test-repo-{name}"));
+        fs::write(worktree.join(format!("{name}.txt")), name).unwrap();
+        for args in [&["add", "."][..], &["commit", "-m", "Work"]] {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(&worktree)
+                .output()
+                .unwrap();
+        }
+    }
+
+    // Both branches were squash merged on GitHub
+    let bin = ctx.temp_dir.path().join("bin");
+    fs::create_dir_all(&bin).unwrap();
+    let log = ctx.temp_dir.path().join("gh.log");
+    fs::write(
+        bin.join("gh"),
+        format!(
+            r#"#!/bin/sh
+[ "$1" = "auth" ] && exit 0
+echo "$*" >> '{}'
+echo '[{{"headRefName":"alpha"}},{{"headRefName":"beta"}}]'
+"#,
+            log.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(bin.join("gh"), fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!("{}:{}", bin.display(), std::env::var("PATH").unwrap());
+
+    ctx.xlaude(&["list"]).env("PATH", &path).assert().success();
+    let state = ctx.read_state();
+    assert!(state["worktrees"]["test-repo/alpha"]["merged_at"].is_string());
+    // Deleting without a prompt only works for merged branches
+    ctx.xlaude(&["delete", "alpha", "beta"])
+        .env("PATH", &path)
+        .assert()
+        .success();
+
+    let calls = fs::read_to_string(&log).unwrap();
+    assert_eq!(calls.lines().count(), 1, "{calls}");
+    assert!(calls.starts_with("pr list --state merged"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_ci_reports_checks_from_gh() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "with-pr"]).assert().success();
+    ctx.xlaude(&["create", "no-pr"]).assert().success();
+
+    // Stand-in for the GitHub CLI: only `with-pr` has a pull request
+    let bin = ctx.temp_dir.path().join("bin");
+    fs::create_dir_all(&bin).unwrap();
+    let gh = bin.join("gh");
+    fs::write(
+        &gh,
+        r#"#!/bin/sh
+if [ "$1 $2 $3" = "pr checks with-pr" ]; then
+  echo '[{"name":"build","bucket":"pass","link":"https://ci.example/1"},{"name":"lint","bucket":"fail","link":""}]'
+  exit 1
+elif [ "$1" = "pr" ]; then
+  echo 'no pull requests found for branch' >&2
+  exit 1
+fi
+echo '[{"workflowName":"test","status":"completed","conclusion":"success","url":"https://ci.example/3","headSha":"b"},{"workflowName":"test","status":"completed","conclusion":"failure","url":"https://ci.example/2","headSha":"a"}]'
+"#,
+    )
+    .unwrap();
+    fs::set_permissions(&gh, fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!("{}:{}", bin.display(), std::env::var("PATH").unwrap());
+
+    let output = ctx
+        .xlaude(&["ci", "with-pr"])
+        .env("PATH", &path)
+        .assert()
+        .failure()
+        .code(1);
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("pull request checks"), "{stdout}");
+    assert!(stdout.contains("✓ pass") && stdout.contains("build"));
+    assert!(stdout.contains("✗ fail") && stdout.contains("lint"));
+
+    // Without a pull request, the latest run of each workflow is used
+    let output = ctx
+        .xlaude(&["ci", "no-pr"])
+        .env("PATH", &path)
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("latest workflow runs"), "{stdout}");
+    assert!(stdout.contains("https://ci.example/3"));
+    assert!(!stdout.contains("https://ci.example/2"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_review_checks_out_pull_request_with_prompt() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let ctx = TestContext::new("test-repo");
+    let git = |args: &[&str]| {
+        let output = std::process::Command::new("git")
+            .args(args)
+            .current_dir(&ctx.repo_dir)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    let remote = ctx.temp_dir.path().join("remote/test-repo.git");
+    std::process::Command::new("git")
+        .args(["init", "--bare"])
+        .arg(&remote)
+        .output()
+        .unwrap();
+    git(&["remote", "add", "origin", remote.to_str().unwrap()]);
+    let base = git(&["branch", "--show-current"]);
+    git(&["push", "origin", &base]);
+
+    // Publish a pull request head the way GitHub does
+    git(&["checkout", "-b", "contributor"]);
+    fs::write(ctx.repo_dir.join("parser.rs"), "fn parse() {}\n").unwrap();
+    git(&["add", "parser.rs"]);
+    git(&["commit", "-m", "Add parser"]);
+    git(&["push", "origin", "contributor:refs/pull/7/head"]);
+    git(&["checkout", &base]);
+    git(&["branch", "-D", "contributor"]);
+    git(&["fetch", "origin"]);
+
+    let bin = ctx.temp_dir.path().join("bin");
+    fs::create_dir_all(&bin).unwrap();
+    let gh = bin.join("gh");
+    fs::write(
+        &gh,
+        format!(
+            "#!/bin/sh\necho '{{\"title\":\"Add a parser\",\"body\":\"Parses things.\",\"url\":\"https://example.com/pull/7\",\"baseRefName\":\"{base}\"}}'\n"
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&gh, fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!("{}:{}", bin.display(), std::env::var("PATH").unwrap());
+
+    let mut state = ctx.read_state();
+    state["agent"] = json!("sh -c 'printf %s \"$0\" > prompt.txt'");
+    ctx.write_state(&state);
+
+    ctx.xlaude(&["review", "#7"])
+        .env("PATH", &path)
+        .assert()
+        .success();
+
+    let worktree = ctx.temp_dir.path().join("test-repo-pr-7");
+    assert_eq!(
+        ctx.read_state()["worktrees"]["test-repo/pr-7"]["branch"],
+        "pr/7"
+    );
+    let prompt = fs::read_to_string(worktree.join("prompt.txt")).unwrap();
+    assert!(
+        prompt.starts_with(
+            "Please review pull request #7: Add a parser\nhttps://example.com/pull/7\n"
+        )
+    );
+    assert!(prompt.contains("Description:\nParses things."));
+    assert!(prompt.contains(&format!("Changes against origin/{base}:")));
+    assert!(prompt.contains("parser.rs | 1 +"), "{prompt}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_logged_out_gh_warns_once_unless_disabled() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "one"]).assert().success();
+    ctx.xlaude(&["create", "two"]).assert().success();
+
+    let bin = ctx.temp_dir.path().join("bin");
+    fs::create_dir_all(&bin).unwrap();
+    let gh = bin.join("gh");
+    fs::write(
+        &gh,
+        "#!/bin/sh
+echo 'not logged in' >&2
+exit 1
+",
+    )
+    .unwrap();
+    fs::set_permissions(&gh, fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!("{}:{}", bin.display(), std::env::var("PATH").unwrap());
+
+    let output = ctx.xlaude(&["list"]).env("PATH", &path).assert().success();
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+    assert_eq!(
+        stderr.matches("not logged in; run 'gh auth login'").count(),
+        1
+    );
+    assert!(stderr.contains("forge_checks false"), "{stderr}");
+
+    ctx.xlaude(&["config", "set", "forge_checks", "false"])
+        .assert()
+        .success();
+    let mut state = ctx.read_state();
+    state.as_object_mut().unwrap().remove("merge_checked_at");
+    ctx.write_state(&state);
+    let output = ctx.xlaude(&["list"]).env("PATH", &path).assert().success();
+    assert!(output.get_output().stderr.is_empty());
+
+    ctx.xlaude(&["ci", "one"])
+        .env("PATH", &path)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("GitHub lookups are disabled"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_offline_mode_skips_network() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "local"]).assert().success();
+
+    // A gh that leaves a mark whenever it is called
+    let bin = ctx.temp_dir.path().join("bin");
+    fs::create_dir_all(&bin).unwrap();
+    let gh = bin.join("gh");
+    let marker = ctx.temp_dir.path().join("gh-called");
+    fs::write(
+        &gh,
+        format!(
+            "#!/bin/sh
+touch '{}'
+echo '[]'
+",
+            marker.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&gh, fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!("{}:{}", bin.display(), std::env::var("PATH").unwrap());
+
+    ctx.xlaude(&["--offline", "list"])
+        .env("PATH", &path)
+        .assert()
+        .success();
+    assert!(!marker.exists());
+
+    ctx.xlaude(&["--offline", "checkout", "not-here"])
+        .assert()
+        .code(4)
+        .stderr(predicates::str::contains("offline mode"));
+
+    // The setting works like the flag
+    ctx.xlaude(&["config", "set", "offline", "true"])
+        .assert()
+        .success();
+    ctx.xlaude(&["push", "local"])
+        .assert()
+        .code(4)
+        .stderr(predicates::str::contains("offline mode"));
+    assert!(!marker.exists());
+}
+
+#[test]
+fn test_completions_install_writes_per_user_files() {
+    let ctx = TestContext::new("test-repo");
+    let home = ctx.temp_dir.path();
+
+    ctx.xlaude(&["completions", "--install"])
+        .env("SHELL", "/bin/zsh")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Installed zsh completions"));
+    let zsh = fs::read_to_string(home.join(".zsh/completions/_xlaude")).unwrap();
+    assert!(zsh.starts_with("#compdef xlaude"));
+
+    ctx.xlaude(&["completions", "fish", "--install"])
+        .env_remove("XDG_CONFIG_HOME")
+        .assert()
+        .success();
+    assert!(home.join(".config/fish/completions/xlaude.fish").exists());
+
+    let data_dir = home.join("data");
+    ctx.xlaude(&["completions", "bash", "--install"])
+        .env("XDG_DATA_HOME", &data_dir)
+        .assert()
+        .success();
+    let bash = fs::read_to_string(data_dir.join("bash-completion/completions/xlaude")).unwrap();
+    assert!(bash.contains("complete -F _xlaude xlaude"));
+}
+
+#[test]
+fn test_list_suggests_cleaning_merged_worktrees() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "done"]).assert().success();
+    ctx.xlaude(&["create", "fresh"]).assert().success();
+
+    let done = ctx.temp_dir.path().join("test-repo-done");
+    fs::write(done.join("feature.txt"), "feature").unwrap();
+    for (dir, args) in [
+        (&done, vec!["add", "feature.txt"]),
+        (&done, vec!["commit", "-m", "Add feature"]),
+        (
+            &ctx.repo_dir,
+            vec!["merge", "--no-ff", "-m", "Merge done", "done"],
+        ),
+    ] {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+    }
+
+    let output = ctx.xlaude(&["list"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(
+        stdout.contains("1 worktree is ready to clean: done"),
+        "{stdout}"
+    );
+
+    let state = ctx.read_state();
+    assert!(state["merge_checked_at"].is_string());
+    assert!(state["worktrees"]["test-repo/done"]["merged_at"].is_string());
+    // A branch without commits of its own is not considered merged
+    assert!(state["worktrees"]["test-repo/fresh"]["merged_at"].is_null());
+}
+
+#[test]
+fn test_repo_set_base_overrides_create_guard() {
+    let ctx = TestContext::new("test-repo");
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(&ctx.repo_dir)
+            .output()
+            .unwrap();
+    };
+    let base = String::from_utf8_lossy(
+        &std::process::Command::new("git")
+            .args(["branch", "--show-current"])
+            .current_dir(&ctx.repo_dir)
+            .output()
+            .unwrap()
+            .stdout,
+    )
+    .trim()
+    .to_string();
+    git(&["branch", "trunk"]);
+
+    ctx.xlaude(&["repo", "set-base", "test-repo", "trunk"])
+        .assert()
+        .success();
+    assert_eq!(
+        ctx.read_state()["repos"]["test-repo"]["base_branch"],
+        "trunk"
+    );
+    let output = ctx.xlaude(&["repo", "list"]).assert().success();
+    assert!(String::from_utf8_lossy(&output.get_output().stdout).contains("test-repo base: trunk"));
+
+    // The default base branch no longer counts
+    let output = ctx.xlaude(&["create", "from-main"]).assert().failure();
+    assert!(
+        String::from_utf8_lossy(&output.get_output().stderr)
+            .contains("Must be on the base branch configured for 'test-repo' (trunk)")
+    );
+
+    git(&["checkout", "trunk"]);
+    ctx.xlaude(&["create", "from-trunk"]).assert().success();
+
+    git(&["checkout", &base]);
+    ctx.xlaude(&["repo", "unset-base", "test-repo"])
+        .assert()
+        .success();
+    assert!(ctx.read_state().get("repos").is_none());
+    ctx.xlaude(&["create", "from-main"]).assert().success();
+}
+
+#[test]
+fn test_undo_branch_restores_force_deleted_branch() {
+    let ctx = TestContext::new("test-repo");
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+
+    ctx.xlaude(&["create", "scratch"]).assert().success();
+    let worktree = ctx.temp_dir.path().join("test-repo-scratch");
+    fs::write(worktree.join("idea.txt"), "idea").unwrap();
+    git(&worktree, &["add", "idea.txt"]);
+    git(&worktree, &["commit", "-m", "Unmerged idea"]);
+    let commit = git(&worktree, &["rev-parse", "HEAD"]);
+
+    // Confirm both the unpushed commits warning and the force delete
+    ctx.xlaude(&["delete", "scratch"])
+        .env_remove("XLAUDE_NON_INTERACTIVE")
+        .write_stdin("y\ny\n")
+        .assert()
+        .success();
+    assert!(git(&ctx.repo_dir, &["branch", "--list", "scratch"]).is_empty());
+    let tag = git(&ctx.repo_dir, &["tag", "--list", "xlaude/trash/scratch/*"]);
+    assert_eq!(git(&ctx.repo_dir, &["rev-parse", &tag]), commit);
+    let state = ctx.read_state();
+    assert_eq!(state["trash"][0]["branch"], "scratch");
+    assert_eq!(state["trash"][0]["commit"], commit.as_str());
+
+    ctx.xlaude(&["undo-branch", "scratch"]).assert().success();
+    assert_eq!(git(&ctx.repo_dir, &["rev-parse", "scratch"]), commit);
+    assert!(git(&ctx.repo_dir, &["tag", "--list", "xlaude/trash/*"]).is_empty());
+    assert!(ctx.read_state().get("trash").is_none());
+
+    // Nothing left to restore
+    ctx.xlaude(&["undo-branch", "scratch"]).assert().code(3);
+}
+
+#[test]
+fn test_delete_multiple_worktrees() {
+    let ctx = TestContext::new("test-repo");
+    for name in ["one", "two", "done", "wip"] {
+        ctx.xlaude(&["create", name]).assert().success();
+    }
+
+    // By name, with one summary for both
+    let output = ctx.xlaude(&["delete", "one", "two"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("Worktrees to delete:"), "{stdout}");
+    assert!(!ctx.temp_dir.path().join("test-repo-one").exists());
+    assert!(!ctx.temp_dir.path().join("test-repo-two").exists());
+
+    // By selector, leaving the unmerged worktree alone
+    let done = ctx.temp_dir.path().join("test-repo-done");
+    let wip = ctx.temp_dir.path().join("test-repo-wip");
+    fs::write(done.join("feature.txt"), "feature").unwrap();
+    fs::write(wip.join("wip.txt"), "wip").unwrap();
+    for (dir, args) in [
+        (&done, vec!["add", "feature.txt"]),
+        (&done, vec!["commit", "-m", "Add feature"]),
+        (
+            &ctx.repo_dir,
+            vec!["merge", "--no-ff", "-m", "Merge done", "done"],
+        ),
+        (&wip, vec!["add", "wip.txt"]),
+        (&wip, vec!["commit", "-m", "Work in progress"]),
+    ] {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+    }
+    ctx.xlaude(&["delete", "--merged", "--repo", "test-repo"])
+        .write_stdin("y\n")
+        .assert()
+        .success();
+
+    let state = ctx.read_state();
+    let remaining: Vec<_> = state["worktrees"].as_object().unwrap().keys().collect();
+    assert_eq!(remaining, ["test-repo/wip"]);
+
+    ctx.xlaude(&["delete", "wip", "missing"]).assert().code(3);
+}
+
+#[test]
+fn test_delete_force_and_keep_branch() {
+    let ctx = TestContext::new("test-repo");
+    let branches = || {
+        let output = ctx.git(&["branch", "--format=%(refname:short)"]);
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    for name in ["scratch", "keep"] {
+        ctx.xlaude(&["create", name]).assert().success();
+        let worktree = ctx.temp_dir.path().join(format!("test-repo-{name}"));
+        fs::write(worktree.join("idea.txt"), "idea").unwrap();
+        for args in [&["add", "idea.txt"][..], &["commit", "-m", "Unmerged idea"]] {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(&worktree)
+                .output()
+                .unwrap();
+        }
+    }
+
+    // No prompts even with dirty files, a lock and an unmerged branch
+    let scratch = ctx.temp_dir.path().join("test-repo-scratch");
+    fs::write(scratch.join("dirty.txt"), "dirty").unwrap();
+    ctx.xlaude(&["lock", "scratch"]).assert().success();
+    ctx.xlaude(&["delete", "scratch", "--force"])
+        .assert()
+        .success();
+    assert!(!scratch.exists());
+    assert!(!branches().lines().any(|b| b == "scratch"));
+    assert_eq!(ctx.read_state()["trash"][0]["branch"], "scratch");
+
+    ctx.xlaude(&["delete", "keep", "--keep-branch"])
+        .write_stdin("y\n")
+        .assert()
+        .success();
+    assert!(!ctx.temp_dir.path().join("test-repo-keep").exists());
+    assert!(branches().lines().any(|b| b == "keep"));
+}
+
+#[test]
+fn test_move_relocates_worktree() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "feature"]).assert().success();
+
+    let new_path = ctx.temp_dir.path().join("elsewhere/feature");
+    ctx.xlaude(&["move", "feature", new_path.to_str().unwrap()])
+        .assert()
+        .success();
+
+    assert!(!ctx.temp_dir.path().join("test-repo-feature").exists());
+    assert!(new_path.join("README.md").exists());
+    let state = ctx.read_state();
+    assert_eq!(
+        state["worktrees"]["test-repo/feature"]["path"],
+        new_path.to_str().unwrap()
+    );
+    let output = ctx.git(&["worktree", "list"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("elsewhere/feature"));
+
+    // The destination must not exist yet
+    ctx.xlaude(&["move", "feature", ctx.repo_dir.to_str().unwrap()])
+        .assert()
+        .code(4);
+}
+
+#[test]
+fn test_clone_registers_repository() {
+    let ctx = TestContext::new("test-repo");
+    let checkout = ctx.temp_dir.path().join("checkouts/test-repo");
+
+    let output = ctx
+        .xlaude(&[
+            "clone",
+            ctx.repo_dir.to_str().unwrap(),
+            checkout.to_str().unwrap(),
+            "--worktree",
+            "first",
+        ])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("Registered 'test-repo'"), "{stdout}");
+
+    assert!(checkout.join("README.md").exists());
+    assert!(
+        ctx.temp_dir
+            .path()
+            .join("checkouts/test-repo-first")
+            .exists()
+    );
+    let state = ctx.read_state();
+    assert_eq!(
+        state["repos"]["test-repo"]["path"],
+        checkout.to_str().unwrap()
+    );
+    assert_eq!(state["repos"]["test-repo"]["base_branch"], "main");
+    assert!(state["worktrees"]["test-repo/first"].is_object());
+
+    // Cloning a managed repository again is refused
+    ctx.xlaude(&["clone", ctx.repo_dir.to_str().unwrap()])
+        .assert()
+        .code(4);
+}
+
+#[test]
+fn test_create_generated_names() {
+    let ctx = TestContext::new("test-repo");
+    let mut state = ctx.read_state();
+    state["names"] = json!({"pattern": "{animal}", "words": {"animal": ["otter"]}});
+    ctx.write_state(&state);
+
+    // The only word is used once, then numbered
+    ctx.xlaude(&["create"]).assert().success();
+    ctx.xlaude(&["create"]).assert().success();
+    // A prompt names the worktree after what it is for
+    ctx.xlaude(&["create", "--type-text", "Fix the flaky login test"])
+        .assert()
+        .success();
+
+    let state = ctx.read_state();
+    let mut names: Vec<_> = state["worktrees"].as_object().unwrap().keys().collect();
+    names.sort();
+    assert_eq!(
+        names,
+        [
+            "test-repo/fix-flaky-login-test",
+            "test-repo/otter",
+            "test-repo/otter-2"
+        ]
+    );
+}
+
+#[test]
+fn test_create_asks_agent_for_name() {
+    let ctx = TestContext::new("test-repo");
+    let mut state = ctx.read_state();
+    state["names"] = json!({"agent": "sh -c 'echo Thinking...; echo Login-Race'"});
+    ctx.write_state(&state);
+
+    ctx.xlaude(&[
+        "create",
+        "--type-text",
+        "Users are sometimes logged out right after signing in",
+    ])
+    .assert()
+    .success();
+    assert!(ctx.read_state()["worktrees"]["test-repo/login-race"].is_object());
+}
+
+#[test]
+fn test_create_falls_back_when_naming_agent_hangs() {
+    let ctx = TestContext::new("test-repo");
+    let mut state = ctx.read_state();
+    state["names"] = json!({"agent": "sh -c 'sleep 30; echo Too-Late'"});
+    ctx.write_state(&state);
+
+    let started = std::time::Instant::now();
+    ctx.xlaude(&["create", "--type-text", "Fix the flaky login test"])
+        .env("XLAUDE_NAME_AGENT_TIMEOUT", "1")
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("timed out"));
+    assert!(started.elapsed() < std::time::Duration::from_secs(20));
+    assert!(ctx.read_state()["worktrees"]["test-repo/fix-flaky-login-test"].is_object());
+}
+
+#[test]
+fn test_prune_deletes_expired_worktrees() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "spike", "--ttl", "1"])
+        .assert()
+        .success();
+    ctx.xlaude(&["create", "keep", "--ttl", "30"])
+        .assert()
+        .success();
+
+    let mut state = ctx.read_state();
+    let created = (chrono::Utc::now() - chrono::Duration::days(3)).to_rfc3339();
+    for key in ["test-repo/spike", "test-repo/keep"] {
+        state["worktrees"][key]["created_at"] = json!(created);
+    }
+    ctx.write_state(&state);
+
+    let output = ctx.xlaude(&["list"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("TTL expired 2d ago"), "{stdout}");
+    assert!(stdout.contains("1 worktree is past its TTL"), "{stdout}");
+
+    ctx.xlaude(&["prune"]).assert().success();
+    let state = ctx.read_state();
+    let remaining: Vec<_> = state["worktrees"].as_object().unwrap().keys().collect();
+    assert_eq!(remaining, ["test-repo/keep"]);
+}
+
+#[test]
+fn test_list_sorts_by_cached_activity() {
+    let ctx = TestContext::new("test-repo");
+    for name in ["alpha", "beta", "gamma"] {
+        ctx.xlaude(&["create", name]).assert().success();
+    }
+
+    let mut state = ctx.read_state();
+    let in_hours = |hours| (chrono::Utc::now() + chrono::Duration::hours(hours)).to_rfc3339();
+    state["worktrees"]["test-repo/alpha"]["last_activity"] = json!(in_hours(1));
+    state["worktrees"]["test-repo/gamma"]["last_activity"] = json!(in_hours(2));
+    ctx.write_state(&state);
+
+    let output = ctx
+        .xlaude(&["list", "--json", "--sort", "activity"])
+        .assert()
+        .success();
+    let listed: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let names: Vec<_> = listed["worktrees"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|w| w["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["gamma", "alpha", "beta"]);
+
+    let output = ctx
+        .xlaude(&["complete-worktrees", "--format", "simple"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert_eq!(
+        stdout.lines().collect::<Vec<_>>(),
+        ["gamma", "alpha", "beta"]
+    );
+}
+
+#[test]
+fn test_complete_worktrees_json_is_cached_until_state_changes() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "alpha"]).assert().success();
+
+    let complete = || {
+        let output = ctx
+            .xlaude(&["complete-worktrees", "--format", "json"])
+            .assert()
+            .success();
+        serde_json::from_slice::<serde_json::Value>(&output.get_output().stdout).unwrap()
+    };
+    let entries = complete();
+    assert_eq!(entries[0]["key"], "test-repo/alpha");
+    assert_eq!(entries[0]["sessions"], 0);
+    assert!(ctx.config_dir.join("completions.json").exists());
+    fs::write(
+        ctx.temp_dir.path().join("test-repo-alpha/notes.txt"),
+        "draft\n",
+    )
+    .unwrap();
+
+    // Editing the state file invalidates the cache
+    let mut state = ctx.read_state();
+    state["worktrees"]["test-repo/alpha"]["description"] = json!("Parser rewrite");
+    ctx.write_state(&state);
+    assert_eq!(complete()[0]["description"], "Parser rewrite");
+
+    let output = ctx
+        .xlaude(&["complete-worktrees", "--format", "detailed"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(
+        stdout
+            .trim_end()
+            .ends_with("\tno sessions\tParser rewrite\tidle\t?")
+    );
+
+    // Qualified names work wherever a worktree name does
+    ctx.xlaude(&["dir", "test-repo/alpha"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("test-repo-alpha"));
+}
+
+#[test]
+fn test_list_verbose_shows_branch_summary() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "feature"]).assert().success();
+    let worktree_path = ctx.temp_dir.path().join("test-repo-feature");
+
+    fs::write(worktree_path.join("notes.txt"), "one\ntwo\n").unwrap();
+    for args in [&["add", "notes.txt"][..], &["commit", "-m", "Add notes"]] {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(&worktree_path)
+            .output()
+            .unwrap();
+    }
+    fs::write(ctx.repo_dir.join("main.txt"), "main\n").unwrap();
+    ctx.git(&["add", "main.txt"]);
+    ctx.git(&["commit", "-m", "Advance main"]);
+
+    let output = ctx
+        .xlaude(&["list", "--json", "--verbose"])
+        .assert()
+        .success();
+    let listed: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let summary = &listed["worktrees"][0]["summary"];
+    assert_eq!(summary["ahead"], 1);
+    assert_eq!(summary["behind"], 1);
+    assert_eq!(summary["files_changed"], 1);
+    assert_eq!(summary["insertions"], 2);
+    assert_eq!(summary["deletions"], 0);
+    assert_eq!(summary["last_commit_subject"], "Add notes");
+
+    let output = ctx.xlaude(&["list", "-v"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("1 ahead, 1 behind"));
+    assert!(stdout.contains("Last commit: Add notes"));
+
+    let output = ctx.xlaude(&["list", "--json"]).assert().success();
+    let listed: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert!(listed["worktrees"][0].get("summary").is_none());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_list_marks_running_agents() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "busy"]).assert().success();
+    ctx.xlaude(&["create", "idle"]).assert().success();
+    let worktree_path = ctx.temp_dir.path().join("test-repo-busy");
+
+    let mut agent = std::process::Command::new("sleep")
+        .arg("30")
+        .current_dir(&worktree_path)
+        .spawn()
+        .unwrap();
+    let sessions = json!([{
+        "pid": agent.id(),
+        "process_started": process_started(agent.id()),
+        "path": worktree_path,
+        "program": "claude",
+        "started_at": "2025-01-01T00:00:00Z"
+    }]);
+    fs::write(
+        ctx.config_dir.join("sessions.json"),
+        serde_json::to_string(&sessions).unwrap(),
+    )
+    .unwrap();
+
+    let output = ctx.xlaude(&["list", "--json"]).assert().success();
+    let listed: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let running: Vec<_> = listed["worktrees"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|w| (w["name"].as_str().unwrap(), w["running"].as_bool().unwrap()))
+        .collect();
+    assert_eq!(running, [("busy", true), ("idle", false)]);
+
+    agent.kill().unwrap();
+    agent.wait().unwrap();
+    let output = ctx.xlaude(&["list"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(!stdout.contains("Agent:"), "{stdout}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_kill_and_wait_for_tracked_agent() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "busy"]).assert().success();
+    let worktree_path = ctx.temp_dir.path().join("test-repo-busy");
+
+    let mut agent = std::process::Command::new("sleep")
+        .arg("30")
+        .current_dir(&worktree_path)
+        .spawn()
+        .unwrap();
+    let sessions = json!([{
+        "pid": agent.id(),
+        "process_started": process_started(agent.id()),
+        "path": worktree_path,
+        "program": "/usr/bin/claude",
+        "agent": "claude",
+        "started_at": chrono::Utc::now().to_rfc3339()
+    }]);
+    fs::write(
+        ctx.config_dir.join("sessions.json"),
+        serde_json::to_string(&sessions).unwrap(),
+    )
+    .unwrap();
+
+    let output = ctx
+        .xlaude(&["wait", "busy", "--timeout", "1"])
+        .assert()
+        .failure();
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+    assert!(stderr.contains("Timed out"), "{stderr}");
+
+    let output = ctx.xlaude(&["kill", "busy"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(
+        stdout.contains(&format!("Stopped claude (pid {}, up 0m)", agent.id())),
+        "{stdout}"
+    );
+    assert!(!agent.wait().unwrap().success());
+
+    let output = ctx.xlaude(&["wait", "busy"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("No agent is running"), "{stdout}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_kill_skips_reused_pid() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "busy"]).assert().success();
+    let worktree_path = ctx.temp_dir.path().join("test-repo-busy");
+
+    // The recorded agent exited and its pid now belongs to another process
+    let mut other = std::process::Command::new("sleep")
+        .arg("30")
+        .current_dir(&worktree_path)
+        .spawn()
+        .unwrap();
+    let sessions = json!([{
+        "pid": other.id(),
+        "process_started": "Thu Jan  1 00:00:00 1970",
+        "path": worktree_path,
+        "program": "claude",
+        "started_at": "2025-01-01T00:00:00Z"
+    }]);
+    let sessions_path = ctx.config_dir.join("sessions.json");
+    fs::write(&sessions_path, serde_json::to_string(&sessions).unwrap()).unwrap();
+
+    // Reading does not rewrite the registry
+    ctx.xlaude(&["list"]).assert().success();
+    assert!(sessions_path.exists());
+
+    let output = ctx.xlaude(&["kill", "busy"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("No agent is running"), "{stdout}");
+    assert!(other.try_wait().unwrap().is_none());
+    other.kill().unwrap();
+    other.wait().unwrap();
+}
+
+#[test]
+fn test_delete_from_worktree_subdirectory() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "nested"]).assert().success();
+    let worktree_path = ctx.temp_dir.path().join("test-repo-nested");
+    let subdir = worktree_path.join("src/deep");
+    fs::create_dir_all(&subdir).unwrap();
+
+    let output = ctx.xlaude_in_dir(&subdir, &["delete"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(
+        stdout.contains("Your shell was inside the deleted worktree"),
+        "{stdout}"
+    );
+    assert!(!worktree_path.exists());
+    assert!(
+        ctx.read_state()["worktrees"]
+            .as_object()
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_detached_and_switched_worktrees() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "loose"]).assert().success();
+    ctx.xlaude(&["create", "moved"]).assert().success();
+    let loose = ctx.temp_dir.path().join("test-repo-loose");
+    let moved = ctx.temp_dir.path().join("test-repo-moved");
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    git(&loose, &["checkout", "--detach"]);
+    git(&moved, &["switch", "-c", "moved-again"]);
+
+    let output = ctx.xlaude(&["list", "--json"]).assert().success();
+    let listed: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(listed["worktrees"][0]["name"], "loose");
+    assert_eq!(listed["worktrees"][0]["head_state"], "detached");
+
+    let output = ctx.xlaude(&["clean"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(
+        stdout.contains("test-repo/loose is on a detached HEAD"),
+        "{stdout}"
+    );
+    assert_eq!(
+        ctx.read_state()["worktrees"]["test-repo/moved"]["branch"],
+        "moved-again"
+    );
+
+    let output = ctx.xlaude(&["delete", "loose"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(
+        stdout.contains("detached HEAD, branch 'loose' kept"),
+        "{stdout}"
+    );
+    assert!(!git(&ctx.repo_dir, &["branch", "--list", "loose"]).is_empty());
+}
+
+#[test]
+fn test_create_and_delete_from_bare_repository() {
+    let ctx = TestContext::new("test-repo");
+    let bare = ctx.temp_dir.path().join("project.git");
+    std::process::Command::new("git")
+        .args(["clone", "--bare", "-q"])
+        .arg(&ctx.repo_dir)
+        .arg(&bare)
+        .status()
+        .unwrap();
+    std::process::Command::new("git")
+        .args(["remote", "remove", "origin"])
+        .current_dir(&bare)
+        .status()
+        .unwrap();
+
+    ctx.xlaude_in_dir(&bare, &["create", "feat"])
+        .assert()
+        .success();
+    let worktree_path = ctx.temp_dir.path().join("project-feat");
+    assert!(worktree_path.join("README.md").exists());
+    let state = ctx.read_state();
+    assert_eq!(state["worktrees"]["project/feat"]["branch"], "feat");
+
+    ctx.xlaude(&["delete", "feat"]).assert().success();
+    assert!(!worktree_path.exists());
+    let branches = std::process::Command::new("git")
+        .args(["branch", "--list", "feat"])
+        .current_dir(&bare)
+        .output()
+        .unwrap();
+    assert!(branches.stdout.is_empty());
+}
+
+#[test]
+fn test_report_embeds_dashboard_payload() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "feature"]).assert().success();
+    let mut state = ctx.read_state();
+    state["worktrees"]["test-repo/feature"]["description"] = json!("</script><b>notes</b>");
+    ctx.write_state(&state);
+
+    let report = ctx.temp_dir.path().join("report.html");
+    ctx.xlaude(&["report", "--output", report.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("1 worktree"));
+
+    let html = fs::read_to_string(&report).unwrap();
+    let snapshot = html
+        .split("window.XLAUDE_SNAPSHOT = ")
+        .nth(1)
+        .and_then(|rest| rest.split(";</script>").next())
+        .expect("report should embed the payload");
+    let payload: serde_json::Value = serde_json::from_str(snapshot).unwrap();
+    assert_eq!(payload["worktrees"][0]["name"], "feature");
+    assert_eq!(
+        payload["worktrees"][0]["description"],
+        "</script><b>notes</b>"
+    );
+    assert!(!html.contains("</script><b>"));
+    assert!(html.contains("xlaude Dashboard"));
+}
+
+#[test]
+fn test_trash_restores_deleted_worktree() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "scratch"]).assert().success();
+    let scratch = ctx.temp_dir.path().join("test-repo-scratch");
+    fs::write(scratch.join("notes.txt"), "work in progress").unwrap();
+
+    // Force deletion also removes the branch, restoring starts from the saved commit
+    ctx.xlaude(&["delete", "scratch", "--force"])
+        .assert()
+        .success();
+    assert!(!scratch.exists());
+    let state = ctx.read_state();
+    assert_eq!(state["trashed_worktrees"][0]["info"]["name"], "scratch");
+    assert!(state["trashed_worktrees"][0]["patch"].is_string());
+
+    ctx.xlaude(&["trash", "list"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("test-repo/scratch"))
+        .stdout(predicates::str::contains("uncommitted changes saved"));
+
+    ctx.xlaude(&["trash", "restore", "scratch"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Uncommitted changes restored"));
+    assert_eq!(
+        fs::read_to_string(scratch.join("notes.txt")).unwrap(),
+        "work in progress"
+    );
+    let state = ctx.read_state();
+    assert!(state["worktrees"]["test-repo/scratch"].is_object());
+    assert!(state.get("trashed_worktrees").is_none());
+    assert_eq!(
+        fs::read_dir(ctx.config_dir.join("trash")).unwrap().count(),
+        0
+    );
+
+    ctx.xlaude(&["trash", "restore", "scratch"])
+        .assert()
+        .code(3);
+
+    ctx.xlaude(&["delete", "scratch", "--force"])
+        .assert()
+        .success();
+    ctx.xlaude(&["trash", "empty"])
+        .env("XLAUDE_YES", "1")
+        .assert()
+        .success();
+    assert!(ctx.read_state().get("trashed_worktrees").is_none());
+    ctx.xlaude(&["trash", "list"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Trash is empty"));
+}
+
+#[test]
+fn test_snapshot_and_restore_uncommitted_changes() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "scratch"]).assert().success();
+    let scratch = ctx.temp_dir.path().join("test-repo-scratch");
+
+    ctx.xlaude(&["snapshot", "scratch"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("No uncommitted changes"));
+
+    fs::write(scratch.join("notes.txt"), "work in progress").unwrap();
+    ctx.xlaude(&["snapshot", "scratch", "-m", "before refactor"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Snapshot"));
+    // Taking a snapshot leaves the files alone
+    assert!(scratch.join("notes.txt").exists());
+
+    // An agent throws the work away
+    fs::remove_file(scratch.join("notes.txt")).unwrap();
+    fs::write(scratch.join("other.txt"), "unrelated").unwrap();
+
+    ctx.xlaude(&["snapshots", "scratch"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("before refactor"));
+    ctx.xlaude(&["restore-snapshot", "scratch"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Current changes saved"));
+    assert_eq!(
+        fs::read_to_string(scratch.join("notes.txt")).unwrap(),
+        "work in progress"
+    );
+    assert!(!scratch.join("other.txt").exists());
+
+    // What was replaced is the latest snapshot now
+    ctx.xlaude(&["restore-snapshot", "scratch"])
+        .assert()
+        .success();
+    assert!(scratch.join("other.txt").exists());
+    assert!(!scratch.join("notes.txt").exists());
+
+    ctx.xlaude(&["restore-snapshot", "scratch", "--snapshot", "missing"])
+        .assert()
+        .code(3);
+}
+
+#[test]
+fn test_rename_repo_rekeys_and_moves_worktrees() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "one"]).assert().success();
+    ctx.xlaude(&["create", "two"]).assert().success();
+    ctx.xlaude(&["repo", "set-base", "test-repo", "main"])
+        .assert()
+        .success();
+
+    let renamed = ctx.temp_dir.path().join("renamed-repo");
+    fs::rename(&ctx.repo_dir, &renamed).unwrap();
+
+    ctx.xlaude_in_dir(&renamed, &["rename", "--repo", "test-repo", "renamed-repo"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Renamed repository"));
+
+    let state = ctx.read_state();
+    let worktrees = state["worktrees"].as_object().unwrap();
+    assert_eq!(worktrees.len(), 2);
+    for name in ["one", "two"] {
+        let info = &worktrees[&format!("renamed-repo/{name}")];
+        assert_eq!(info["repo_name"], "renamed-repo");
+        let path = ctx.temp_dir.path().join(format!("renamed-repo-{name}"));
+        assert_eq!(info["path"], path.to_str().unwrap());
+        assert!(path.join(".git").exists());
+    }
+    assert!(!ctx.temp_dir.path().join("test-repo-one").exists());
+    assert_eq!(state["repos"]["renamed-repo"]["base_branch"], "main");
+
+    // Git follows both the repository and the worktrees
+    let list = std::process::Command::new("git")
+        .args(["worktree", "list"])
+        .current_dir(&renamed)
+        .output()
+        .unwrap();
+    let list = String::from_utf8_lossy(&list.stdout);
+    assert!(list.contains("renamed-repo-one"));
+    assert!(!list.contains("prunable"));
+
+    ctx.xlaude_in_dir(&renamed, &["rename", "--repo", "test-repo", "other"])
+        .assert()
+        .code(3);
+}
+
+#[test]
+fn test_rename_repo_rolls_back_when_a_move_fails() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "one"]).assert().success();
+    ctx.xlaude(&["create", "two"]).assert().success();
+
+    // Git refuses to move a locked worktree
+    let two = ctx.temp_dir.path().join("test-repo-two");
+    std::process::Command::new("git")
+        .args(["worktree", "lock", two.to_str().unwrap()])
+        .current_dir(&ctx.repo_dir)
+        .output()
+        .unwrap();
+    let before = ctx.read_state();
+
+    ctx.xlaude(&["rename", "--repo", "test-repo", "renamed-repo"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Failed to move worktree 'two'"));
+
+    assert_eq!(ctx.read_state(), before);
+    assert!(ctx.temp_dir.path().join("test-repo-one/.git").exists());
+    assert!(!ctx.temp_dir.path().join("renamed-repo-one").exists());
+    assert!(two.exists());
+}
+
+#[test]
+fn test_clean_merges_duplicate_entries() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "feature"]).assert().success();
+
+    // A second entry for the same directory, as a later auto-add would leave
+    let mut state = ctx.read_state();
+    let mut duplicate = state["worktrees"]["test-repo/feature"].clone();
+    duplicate["name"] = json!("feature-copy");
+    duplicate["created_at"] = json!("2099-01-01T00:00:00Z");
+    duplicate["tags"] = json!(["wip"]);
+    state["worktrees"]["test-repo/feature-copy"] = duplicate;
+    ctx.write_state(&state);
+
+    // Non-interactive runs only report
+    ctx.xlaude(&["clean"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "test-repo/feature, test-repo/feature-copy",
+        ))
+        .stdout(predicates::str::contains("interactively"));
+    assert_eq!(ctx.read_state()["worktrees"].as_object().unwrap().len(), 2);
+
+    ctx.xlaude(&["clean"])
+        .write_stdin("0\n")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Merged 1 duplicate entry"));
+    let state = ctx.read_state();
+    let worktrees = state["worktrees"].as_object().unwrap();
+    assert_eq!(worktrees.len(), 1);
+    assert_eq!(worktrees["test-repo/feature"]["tags"], json!(["wip"]));
+}
+
+#[test]
+fn test_repo_scope_for_clean_list_and_prune() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "feature"]).assert().success();
+    let mut state = ctx.read_state();
+    state["worktrees"]["other-repo/gone"] = json!({
+        "name": "gone",
+        "branch": "gone",
+        "path": "/non/existent/path",
+        "repo_name": "other-repo",
+        "created_at": "2025-01-01T00:00:00Z"
+    });
+    ctx.write_state(&state);
+
+    let output = ctx
+        .xlaude(&["list", "--repo", "test-repo"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("feature"));
+    assert!(!stdout.contains("other-repo"));
+    ctx.xlaude(&["list"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "2 worktrees in 2 repositories: other-repo (1), test-repo (1)",
+        ));
+
+    // Other repositories are left alone
+    ctx.xlaude(&["clean", "--repo", "test-repo"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("All worktrees are valid"));
+    assert!(ctx.read_state()["worktrees"]["other-repo/gone"].is_object());
+
+    ctx.xlaude(&["clean"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "other-repo: 1 checked, 1 invalid",
+        ))
+        .stdout(predicates::str::contains("test-repo: 1 checked, 0 invalid"));
+    assert!(ctx.read_state()["worktrees"]["other-repo/gone"].is_null());
+
+    for command in ["list", "clean", "prune"] {
+        ctx.xlaude(&[command, "--repo", "missing"]).assert().code(3);
+    }
+}
+
+#[test]
+fn test_list_reports_moved_base() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "feature"]).assert().success();
+    let info = &ctx.read_state()["worktrees"]["test-repo/feature"];
+    assert_eq!(info["base_branch"], "main");
+    let head = ctx.git(&["rev-parse", "HEAD"]);
+    assert_eq!(
+        info["base_commit"],
+        String::from_utf8_lossy(&head.stdout).trim()
+    );
+
+    ctx.xlaude(&["list", "-v"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("(up to date)"));
+
+    fs::write(ctx.repo_dir.join("upstream.txt"), "new").unwrap();
+    ctx.git(&["add", "upstream.txt"]);
+    ctx.git(&["commit", "-m", "Upstream change"]);
+
+    ctx.xlaude(&["list", "-v"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "moved 1 commit since, needs rebase",
+        ));
+    let output = ctx.xlaude(&["list", "--json", "-v"]).assert().success();
+    let listed: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(listed["worktrees"][0]["base_moved"], 1);
+}
+
+#[test]
+fn test_rebase_all_reports_each_worktree() {
+    let ctx = TestContext::new("test-repo");
+    for name in ["clean", "dirty", "clash"] {
+        ctx.xlaude(&["create", name]).assert().success();
+    }
+    let commit = |dir: &Path, file: &str, content: &str| {
+        fs::write(dir.join(file), content).unwrap();
+        for args in [&["add", file][..], &["commit", "-m", file]] {
+            let output = std::process::Command::new("git")
+                .args(args)
+                .current_dir(dir)
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+        }
+    };
+    let worktree = |name: &str| ctx.temp_dir.path().join(format!("test-repo-{name}"));
+    commit(&worktree("clash"), "shared.txt", "from the worktree");
+    commit(&ctx.repo_dir, "shared.txt", "from main");
+    fs::write(worktree("dirty").join("notes.txt"), "wip").unwrap();
+
+    let output = ctx.xlaude(&["rebase", "--all"]).assert().code(6);
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout).to_string();
+    assert!(stdout.contains("rebased over 1 commit"));
+    assert!(stdout.contains("skipped: uncommitted changes"));
+    assert!(stdout.contains("conflicts in shared.txt, left unchanged"));
+    assert!(stdout.contains("1 rebased, 1 skipped, 1 with conflicts"));
+    assert!(worktree("clean").join("shared.txt").exists());
+    // The conflicting rebase was aborted
+    assert_eq!(
+        fs::read_to_string(worktree("clash").join("shared.txt")).unwrap(),
+        "from the worktree"
+    );
+
+    let output = ctx
+        .xlaude(&["rebase", "--all", "--autostash"])
+        .assert()
+        .code(6);
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout).to_string();
+    assert!(stdout.contains("1 rebased, 0 skipped, 1 with conflicts"));
+    assert_eq!(
+        fs::read_to_string(worktree("dirty").join("notes.txt")).unwrap(),
+        "wip"
+    );
+    assert!(worktree("dirty").join("shared.txt").exists());
+}